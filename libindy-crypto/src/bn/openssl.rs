@@ -2,7 +2,7 @@ use errors::IndyCryptoError;
 
 use int_traits::IntTraits;
 
-use openssl::bn::{BigNum, BigNumRef, BigNumContext, MSB_MAYBE_ZERO};
+use openssl::bn::{BigNum, BigNumRef, BigNumContext};
 use openssl::hash::{hash2, MessageDigest, Hasher};
 use openssl::error::ErrorStack;
 
@@ -12,65 +12,375 @@ use serde::ser::{Serialize, Serializer, Error as SError};
 #[cfg(feature = "serialization")]
 use serde::de::{Deserialize, Deserializer, Visitor, Error as DError};
 
+use std::cell::Cell;
 use std::error::Error;
 use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ptr;
 use std::cmp::Ord;
 use std::cmp::Ordering;
 
+use utils::entropy;
+
+#[cfg(feature = "leak_detection")]
+use utils::instrumentation::{self, TrackedObject};
+
+/// Attempts of rejection sampling of `BigNumber::rand_range` before giving up.
+const RAND_RANGE_ATTEMPTS: usize = 128;
+
+/// Attempts of `BigNumber::generate_prime_in_range` before giving up: ranges used by the library
+/// contain a prime among a few hundred random candidates.
+const PRIME_IN_RANGE_ATTEMPTS: usize = 100_000;
+
+/// Bound of odd primes prime candidates are sieved by before Miller-Rabin tests.
+const SIEVE_LIMIT: u32 = 2048;
+
+/// Number of consecutive odd candidates tested from one random start of prime search.
+const SIEVE_CANDIDATES: u32 = 1 << 15;
+
+/// Min size of generated primes: smaller candidates could be equal to sieving primes.
+const MIN_PRIME_SIZE: usize = 16;
+
+/// Odd primes below `SIEVE_LIMIT`.
+fn _small_primes() -> Vec<u32> {
+    let mut composite = vec![false; SIEVE_LIMIT as usize];
+    let mut primes = Vec::new();
+
+    let mut n = 3;
+    while n < SIEVE_LIMIT as usize {
+        if !composite[n] {
+            primes.push(n as u32);
+
+            let mut multiple = n * n;
+            while multiple < SIEVE_LIMIT as usize {
+                composite[multiple] = true;
+                multiple += 2 * n;
+            }
+        }
+        n += 2;
+    }
+
+    primes
+}
+
+/// Tolerance of decimal parsing of `BigNumber::from_dec` and `BigNumber` deserialization.
+///
+/// Parsers of different implementations tolerating different inputs let the same proof
+/// be serialized in many ways, so strict parsing is the default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecParsing {
+    /// Accepts only canonical representation produced by `to_dec`:
+    /// optional `-` followed by digits without leading zeros (`-0` is rejected too).
+    Strict,
+    /// Compatibility with tolerant implementations: surrounding whitespace, `+` sign and leading zeros are accepted.
+    Lenient
+}
+
+thread_local! {
+    static DEC_PARSING: Cell<DecParsing> = Cell::new(DecParsing::Strict);
+}
+
+struct DecParsingGuard {
+    prev: DecParsing
+}
+
+impl Drop for DecParsingGuard {
+    fn drop(&mut self) {
+        DEC_PARSING.with(|dec_parsing| dec_parsing.set(self.prev));
+    }
+}
+
+/// Runs `f` parsing decimal representations of big numbers by `BigNumber::from_dec`
+/// and deserializers called from `f` on the current thread with the given mode.
+///
+/// Mode isn't process-wide: parsing outside of `f` and on other threads stays strict,
+/// so lenient parsing is enabled only for artifacts known to come from tolerant implementations.
+///
+/// # Example
+/// ```
+/// use indy_crypto::bn::{with_dec_parsing, BigNumber, DecParsing};
+///
+/// assert!(BigNumber::from_dec("007").is_err());
+/// assert_eq!(BigNumber::from_dec("7").unwrap(),
+///            with_dec_parsing(DecParsing::Lenient, || BigNumber::from_dec("007")).unwrap());
+/// ```
+pub fn with_dec_parsing<F, T>(mode: DecParsing, f: F) -> Result<T, IndyCryptoError>
+    where F: FnOnce() -> Result<T, IndyCryptoError> {
+    let prev = DEC_PARSING.with(|dec_parsing| dec_parsing.replace(mode));
+    let _guard = DecParsingGuard { prev };
+
+    f()
+}
+
 pub struct BigNumberContext {
     openssl_bn_context: BigNumContext
 }
 
 #[derive(Debug)]
 pub struct BigNumber {
-    openssl_bn: BigNum
+    // Number is given back to the active `BigNumberArena` or freed explicitly on drop
+    openssl_bn: ManuallyDrop<BigNum>
+}
+
+impl BigNumberContext {
+    fn wrap(ctx: BigNumContext) -> BigNumberContext {
+        #[cfg(feature = "leak_detection")]
+        instrumentation::track_new(TrackedObject::BigNumberContext);
+        BigNumberContext { openssl_bn_context: ctx }
+    }
+}
+
+#[cfg(feature = "leak_detection")]
+impl Drop for BigNumberContext {
+    fn drop(&mut self) {
+        instrumentation::track_drop(TrackedObject::BigNumberContext);
+    }
+}
+
+/// Max number of freed numbers `BigNumberArena` keeps for reuse, the rest are freed at once.
+const MAX_ARENA_NUMBERS: usize = 4096;
+
+/// Counters of `BigNumberArena`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// Numbers allocated because the arena had no free one.
+    pub numbers_created: usize,
+    /// Numbers drawn from the arena instead of being allocated.
+    pub numbers_reused: usize
+}
+
+struct ArenaState {
+    numbers: Vec<BigNum>,
+    stats: ArenaStats
+}
+
+thread_local! {
+    // Raw pointer has no destructor, so numbers dropped by destructors of other thread locals still reach it.
+    // State is owned by the outermost `BigNumberArena` and borrowed only for a push or pop that never reenters.
+    static ARENA: Cell<*mut ArenaState> = Cell::new(ptr::null_mut());
+}
+
+/// Runs `f` with state of the arena active on the current thread, returns `None` without active arena.
+fn _with_arena_state<F, T>(f: F) -> Option<T> where F: FnOnce(&mut ArenaState) -> T {
+    ARENA.with(|arena| {
+        let state = arena.get();
+        if state.is_null() {
+            None
+        } else {
+            Some(f(unsafe { &mut *state }))
+        }
+    })
+}
+
+/// Pool of `BigNumber` allocations of the current thread.
+///
+/// Proof init and finalize allocate and free thousands of short-lived numbers (results of intermediate operations).
+/// While the arena is alive, numbers dropped on the thread are wiped and kept in the arena instead of being freed,
+/// results of operations reuse their buffers, and all of them are freed at once when the arena is dropped.
+///
+/// Arena created while another one is alive on the thread doesn't own numbers: the outer arena is used.
+/// OpenSSL contexts of operations called without explicit context aren't pooled:
+/// they hold secret intermediates, so they are freed (and wiped by OpenSSL) on return.
+///
+/// # Example
+/// ```
+/// use indy_crypto::bn::{BigNumber, BigNumberArena};
+///
+/// let arena = BigNumberArena::new();
+/// let a = BigNumber::from_u32(3).unwrap();
+/// let n = BigNumber::from_u32(7).unwrap();
+/// a.mod_mul(&a, &n, None).unwrap();
+/// a.mod_mul(&a, &n, None).unwrap();
+/// assert_eq!(1, arena.stats().numbers_created);
+/// assert_eq!(1, arena.stats().numbers_reused);
+/// ```
+pub struct BigNumberArena {
+    owner: bool,
+    // Arena is bound to the thread it's created on
+    _not_send: PhantomData<*const ()>
+}
+
+impl BigNumberArena {
+    pub fn new() -> BigNumberArena {
+        let owner = ARENA.with(|arena| {
+            if !arena.get().is_null() {
+                return false;
+            }
+            arena.set(Box::into_raw(Box::new(ArenaState { numbers: Vec::new(), stats: ArenaStats::default() })));
+            true
+        });
+
+        BigNumberArena { owner, _not_send: PhantomData }
+    }
+
+    /// Returns counters of the arena active on the current thread.
+    pub fn stats(&self) -> ArenaStats {
+        _with_arena_state(|state| state.stats).unwrap_or_default()
+    }
+}
+
+impl Default for BigNumberArena {
+    fn default() -> BigNumberArena {
+        BigNumberArena::new()
+    }
+}
+
+impl Drop for BigNumberArena {
+    fn drop(&mut self) {
+        if self.owner {
+            // Numbers are freed after the arena is detached from the thread
+            let state = ARENA.with(|arena| arena.replace(ptr::null_mut()));
+            if !state.is_null() {
+                drop(unsafe { Box::from_raw(state) });
+            }
+        }
+    }
+}
+
+impl Drop for BigNumber {
+    fn drop(&mut self) {
+        // Any number may be a copy of a secret (master secret, hidden attribute, blinding factor),
+        // so every number is wiped before it's freed or reused
+        self.openssl_bn.clear();
+
+        // Field is `ManuallyDrop` and isn't accessed after, so the number is released only once
+        let bn = unsafe { ptr::read(&*self.openssl_bn) };
+        let unused = ARENA.with(|arena| {
+            let state = arena.get();
+            if !state.is_null() {
+                let state = unsafe { &mut *state };
+                if state.numbers.len() < MAX_ARENA_NUMBERS {
+                    state.numbers.push(bn);
+                    return None;
+                }
+            }
+            Some(bn)
+        });
+        // Without active arena the number is freed here
+        drop(unused);
+
+        #[cfg(feature = "leak_detection")]
+        instrumentation::track_drop(TrackedObject::BigNumber);
+    }
 }
 
 impl BigNumber {
+    fn wrap(bn: BigNum) -> BigNumber {
+        #[cfg(feature = "leak_detection")]
+        instrumentation::track_new(TrackedObject::BigNumber);
+        BigNumber { openssl_bn: ManuallyDrop::new(bn) }
+    }
+
     pub fn new_context() -> Result<BigNumberContext, IndyCryptoError> {
         let ctx = BigNumContext::new()?;
-        Ok(BigNumberContext {
-            openssl_bn_context: ctx
-        })
+        Ok(BigNumberContext::wrap(ctx))
     }
 
     pub fn new() -> Result<BigNumber, IndyCryptoError> {
-        let bn = BigNum::new()?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        let pooled = _with_arena_state(|state| {
+            let bn = state.numbers.pop();
+            match bn {
+                Some(_) => state.stats.numbers_reused += 1,
+                None => state.stats.numbers_created += 1
+            }
+            bn
+        }).unwrap_or(None);
+
+        let bn = match pooled {
+            Some(bn) => bn,
+            None => BigNum::new()?
+        };
+        Ok(BigNumber::wrap(bn))
     }
 
+    /// Generates prime of `size` bits taking entropy from `utils::entropy`.
     pub fn generate_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
-        let mut bn = BigNumber::new()?;
-        BigNumRef::generate_prime(&mut bn.openssl_bn, size as i32, false, None, None)?;
-        Ok(bn)
+        BigNumber::_generate_prime(size, false)
     }
 
+    /// Generates safe prime `2q + 1` of `size + 1` bits taking entropy from `utils::entropy`.
     pub fn generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
-        let mut bn = BigNumber::new()?;
-        BigNumRef::generate_prime(&mut bn.openssl_bn, (size + 1) as i32, true, None, None)?;
-        Ok(bn)
+        BigNumber::_generate_prime(size, true)?.lshift1()?.increment()
+    }
+
+    /// Searches prime `q` of `size` bits (such that `2q + 1` is prime too if `safe`)
+    /// among consecutive odd numbers from random start.
+    ///
+    /// OpenSSL prime generation takes randomness from its own RNG, so candidates are drawn here
+    /// and only tested by OpenSSL: generated primes follow entropy callback, fixed entropy and `with_rng`.
+    fn _generate_prime(size: usize, safe: bool) -> Result<BigNumber, IndyCryptoError> {
+        if size < MIN_PRIME_SIZE {
+            return Err(IndyCryptoError::InvalidParam1(format!("Prime size must be at least {} bits", MIN_PRIME_SIZE)));
+        }
+
+        let small_primes = _small_primes();
+        let mut ctx = BigNumber::new_context()?;
+
+        loop {
+            let mut start = BigNumber::rand(size)?;
+            start.set_bit(size as i32 - 1)?;
+            start.set_bit(0)?;
+
+            let residues = small_primes
+                .iter()
+                .map(|&r| -> Result<u32, IndyCryptoError> {
+                    start.modulus(&BigNumber::from_u32(r as usize)?, Some(&mut ctx))?._to_u32()
+                })
+                .collect::<Result<Vec<u32>, IndyCryptoError>>()?;
+
+            let mut delta = 0u32;
+            while delta < 2 * SIEVE_CANDIDATES {
+                // Candidate `q` or `2q + 1` divisible by small prime is skipped without Miller-Rabin tests
+                let sieved = small_primes
+                    .iter()
+                    .zip(residues.iter())
+                    .all(|(&r, &residue)| {
+                        let q_mod_r = (residue + delta) % r;
+                        q_mod_r != 0 && (!safe || (2 * q_mod_r + 1) % r != 0)
+                    });
+
+                if sieved {
+                    let mut candidate = start.clone()?;
+                    candidate.add_word(delta)?;
+
+                    if candidate.num_bits()? as usize != size {
+                        break;
+                    }
+
+                    if candidate.is_prime(Some(&mut ctx))? &&
+                        (!safe || candidate.lshift1()?.increment()?.is_prime(Some(&mut ctx))?) {
+                        return Ok(candidate);
+                    }
+                }
+
+                delta += 2;
+            }
+        }
+    }
+
+    fn _to_u32(&self) -> Result<u32, IndyCryptoError> {
+        let bytes = self.to_bytes()?;
+        if self.is_negative() || bytes.len() > 4 {
+            return Err(IndyCryptoError::InvalidState(format!("BigNumber {:?} doesn't fit u32", self)));
+        }
+        Ok(bytes.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32))
     }
 
     pub fn generate_prime_in_range(start: &BigNumber, end: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
-        let mut prime;
-        let mut iteration = 0;
         let mut bn_ctx = BigNumber::new_context()?;
         let sub = end.sub(start)?;
 
-        loop {
-            prime = sub.rand_range()?;
-            prime = prime.add(start)?;
+        for iteration in 0..PRIME_IN_RANGE_ATTEMPTS {
+            let prime = sub.rand_range()?.add(start)?;
 
             if prime.is_prime(Some(&mut bn_ctx))? {
                 debug!("Found prime in {} iteration", iteration);
-                break;
+                return Ok(prime);
             }
-            iteration += 1;
         }
 
-        Ok(prime)
+        Err(IndyCryptoError::InvalidState(format!("No prime in range found in {} attempts", PRIME_IN_RANGE_ATTEMPTS)))
     }
 
     pub fn is_prime(&self, ctx: Option<&mut BigNumberContext>) -> Result<bool, IndyCryptoError> {
@@ -107,16 +417,30 @@ impl BigNumber {
         }
     }
 
+    /// Returns random number of at most `size` bits taking entropy from `utils::entropy`.
     pub fn rand(size: usize) -> Result<BigNumber, IndyCryptoError> {
-        let mut bn = BigNumber::new()?;
-        BigNumRef::rand(&mut bn.openssl_bn, size as i32, MSB_MAYBE_ZERO, false)?;
-        Ok(bn)
+        let mut bytes = vec![0u8; (size + 7) / 8];
+        entropy::fill_bytes(&mut bytes)?;
+        BigNumber::from_bytes(&bytes)?.rshift((bytes.len() * 8 - size) as i32)
     }
 
+    /// Returns random number in range [0, self) taking entropy from `utils::entropy`.
     pub fn rand_range(&self) -> Result<BigNumber, IndyCryptoError> {
-        let mut bn = BigNumber::new()?;
-        BigNumRef::rand_range(&self.openssl_bn, &mut bn.openssl_bn)?;
-        Ok(bn)
+        if self.is_negative() || self.num_bits()? == 0 {
+            return Err(IndyCryptoError::InvalidStructure("Range of random number must be positive".to_string()));
+        }
+
+        // Rejection sampling keeps the distribution uniform; less than 2 attempts are expected,
+        // so running out of attempts means that entropy source is broken
+        let size = self.num_bits()? as usize;
+        for _ in 0..RAND_RANGE_ATTEMPTS {
+            let bn = BigNumber::rand(size)?;
+            if bn < *self {
+                return Ok(bn);
+            }
+        }
+
+        Err(IndyCryptoError::InvalidState(format!("No random number in range drawn in {} attempts", RAND_RANGE_ATTEMPTS)))
     }
 
     pub fn num_bits(&self) -> Result<i32, IndyCryptoError> {
@@ -134,30 +458,51 @@ impl BigNumber {
 
     pub fn from_u32(n: usize) -> Result<BigNumber, IndyCryptoError> {
         let bn = BigNum::from_u32(n as u32)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
+    /// Parses decimal representation strictly or with the mode of enclosing `with_dec_parsing`.
     pub fn from_dec(dec: &str) -> Result<BigNumber, IndyCryptoError> {
+        BigNumber::from_dec_with(dec, DEC_PARSING.with(|dec_parsing| dec_parsing.get()))
+    }
+
+    /// Parses decimal representation according to the given mode regardless of enclosing `with_dec_parsing`.
+    pub fn from_dec_with(dec: &str, mode: DecParsing) -> Result<BigNumber, IndyCryptoError> {
+        let dec = match mode {
+            DecParsing::Strict => dec,
+            DecParsing::Lenient => {
+                let trimmed = dec.trim();
+                if trimmed.starts_with('+') {
+                    // Only one sign is allowed, so "+-1" is rejected
+                    if trimmed[1..].starts_with('-') {
+                        return Err(IndyCryptoError::InvalidStructure(format!("Invalid decimal representation of BigNumber: {:?}", trimmed)));
+                    }
+                    &trimmed[1..]
+                } else { trimmed }
+            }
+        };
+
+        let digits = if dec.starts_with('-') { &dec[1..] } else { dec };
+
+        let canonical = (digits == "0" && !dec.starts_with('-')) || !digits.starts_with('0');
+
+        // OpenSSL parses the longest digits prefix and ignores the rest, so the whole input is checked before
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) || (mode == DecParsing::Strict && !canonical) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Invalid decimal representation of BigNumber: {:?}", dec)));
+        }
+
         let bn = BigNum::from_dec_str(dec)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn from_hex(hex: &str) -> Result<BigNumber, IndyCryptoError> {
         let bn = BigNum::from_hex_str(hex)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<BigNumber, IndyCryptoError> {
         let bn = BigNum::from_slice(bytes)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn to_dec(&self) -> Result<String, IndyCryptoError> {
@@ -328,31 +673,30 @@ impl BigNumber {
     }
 
     pub fn set_negative(&self, negative: bool) -> Result<BigNumber, IndyCryptoError> {
-        let mut bn = BigNum::from_slice(&self.openssl_bn.to_vec())?;
+        let mut bn = self.openssl_bn.to_owned()?;
         bn.set_negative(negative);
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn is_negative(&self) -> bool {
         self.openssl_bn.is_negative()
     }
 
+    /// Overwrites the value with zeros in place (`BN_clear`), so secret values don't stay in memory.
+    pub fn clear(&mut self) {
+        self.openssl_bn.clear();
+    }
+
     pub fn increment(&self) -> Result<BigNumber, IndyCryptoError> {
         let mut bn = BigNum::from_slice(&self.openssl_bn.to_vec())?;
         bn.add_word(1)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn decrement(&self) -> Result<BigNumber, IndyCryptoError> {
         let mut bn = BigNum::from_slice(&self.openssl_bn.to_vec())?;
         bn.sub_word(1)?;
-        Ok(BigNumber {
-            openssl_bn: bn
-        })
+        Ok(BigNumber::wrap(bn))
     }
 
     pub fn lshift1(&self) -> Result<BigNumber, IndyCryptoError> {
@@ -401,10 +745,9 @@ impl BigNumber {
         Ok(qr)
     }
 
+    /// Copies the number with `BN_dup`: no intermediate buffer with the value is left in memory.
     pub fn clone(&self) -> Result<BigNumber, IndyCryptoError> {
-        Ok(BigNumber {
-            openssl_bn: BigNum::from_slice(&self.openssl_bn.to_vec()[..])?
-        })
+        Ok(BigNumber::wrap(self.openssl_bn.to_owned()?))
     }
 
     pub fn hash_array(nums: &Vec<Vec<u8>>) -> Result<Vec<u8>, IndyCryptoError> {
@@ -434,7 +777,7 @@ impl PartialOrd for BigNumber {
 
 impl PartialEq for BigNumber {
     fn eq(&self, other: &BigNumber) -> bool {
-        self.openssl_bn == other.openssl_bn
+        *self.openssl_bn == *other.openssl_bn
     }
 }
 
@@ -487,6 +830,18 @@ lazy_static! {
     pub static ref BIGNUMBER_2: BigNumber = BigNumber::from_u32(2).unwrap();
 }
 
+pub(crate) fn init_constants() {
+    ::lazy_static::initialize(&BIGNUMBER_1);
+    ::lazy_static::initialize(&BIGNUMBER_2);
+}
+
+/// Initializes OpenSSL (locking callbacks, error strings) and constants, returns OpenSSL version.
+pub(crate) fn init() -> &'static str {
+    ::openssl::init();
+    init_constants();
+    ::openssl::version::version()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -496,6 +851,45 @@ mod tests {
     const RANGE_LEFT: usize = 592;
     const RANGE_RIGHT: usize = 592;
 
+    #[test]
+    fn clone_works() {
+        let bn = BigNumber::from_dec("-123456789012345678901234567890").unwrap();
+        assert_eq!(bn, bn.clone().unwrap());
+    }
+
+    #[test]
+    fn clear_works() {
+        let mut bn = BigNumber::from_dec("123456789012345678901234567890").unwrap();
+        bn.clear();
+        assert_eq!(BigNumber::from_u32(0).unwrap(), bn);
+    }
+
+    #[test]
+    fn arena_works() {
+        let a = BigNumber::from_u32(3).unwrap();
+        let n = BigNumber::from_u32(7).unwrap();
+
+        let arena = BigNumberArena::new();
+        for _ in 0..10 {
+            assert_eq!(BigNumber::from_u32(6).unwrap(), a.mod_exp(&a, &n, None).unwrap());
+        }
+        assert_eq!(ArenaStats { numbers_created: 1, numbers_reused: 9 }, arena.stats());
+
+        {
+            let nested_arena = BigNumberArena::new();
+            a.mod_mul(&a, &n, None).unwrap();
+            assert_eq!(ArenaStats { numbers_created: 1, numbers_reused: 10 }, nested_arena.stats());
+        }
+        assert_eq!(ArenaStats { numbers_created: 1, numbers_reused: 10 }, arena.stats());
+
+        // Reused numbers are wiped
+        assert_eq!(BigNumber::from_u32(0).unwrap(), BigNumber::new().unwrap());
+
+        drop(arena);
+        a.mod_mul(&a, &n, None).unwrap();
+        assert_eq!(ArenaStats::default(), BigNumberArena::new().stats());
+    }
+
     #[test]
     #[ignore] //TODO check
     fn generate_prime_in_range_works() {
@@ -548,6 +942,23 @@ mod tests {
         assert_eq!(BigNumber::from_u32(7).unwrap(), base.mod_exp(&exp, &modulus, None).unwrap());
     }
 
+    #[test]
+    fn generate_prime_works_for_rng() {
+        let primes_with_seed = |seed: &[u8]| {
+            let mut rng = entropy::SeededRng::new(seed).unwrap();
+            entropy::with_rng(&mut rng, || Ok((BigNumber::generate_prime(128)?, BigNumber::generate_safe_prime(128)?))).unwrap()
+        };
+
+        let (prime, safe_prime) = primes_with_seed(b"seed");
+        assert_eq!(128, prime.num_bits().unwrap());
+        assert!(prime.is_prime(None).unwrap());
+        assert_eq!(129, safe_prime.num_bits().unwrap());
+        assert!(safe_prime.is_safe_prime(None).unwrap());
+
+        assert_eq!((prime, safe_prime), primes_with_seed(b"seed"));
+        assert!(BigNumber::generate_prime(8).is_err());
+    }
+
     #[test]
     #[ignore]
     fn is_safe_prime_works() {
@@ -564,6 +975,22 @@ mod tests {
         assert!(prime.is_safe_prime(None).unwrap());
     }
 
+    #[test]
+    fn rand_works() {
+        for size in [0, 1, 7, 8, 9, 80, 593].iter() {
+            assert!(BigNumber::rand(*size).unwrap().num_bits().unwrap() <= *size as i32);
+        }
+    }
+
+    #[test]
+    fn rand_range_works() {
+        let range = BigNumber::from_u32(1000).unwrap();
+        for _ in 0..100 {
+            assert!(range.rand_range().unwrap() < range);
+        }
+        assert!(BigNumber::from_u32(0).unwrap().rand_range().is_err());
+    }
+
     #[test]
     fn decrement_works() {
         let num = BigNumber::from_u32(1000).unwrap();
@@ -613,6 +1040,42 @@ mod tests {
         assert_eq!("{\"field\":\"1\"}", serialized.unwrap());
     }
 
+    #[test]
+    fn from_dec_with_works_for_strict_parsing() {
+        for dec in ["0", "1", "-1", "1208925819614629174706176", "-1208925819614629174706176"].iter() {
+            assert_eq!(*dec, BigNumber::from_dec_with(dec, DecParsing::Strict).unwrap().to_dec().unwrap());
+        }
+
+        for dec in ["", "-", "01", "-01", "00", "-0", "+1", " 1", "1 ", "1\n", "12abc", "1_000", "0x10"].iter() {
+            assert!(BigNumber::from_dec_with(dec, DecParsing::Strict).is_err(), "{:?}", dec);
+        }
+    }
+
+    #[test]
+    fn from_dec_with_works_for_lenient_parsing() {
+        for &(dec, expected) in [("01", "1"), ("+1", "1"), (" -007\n", "-7"), ("1", "1")].iter() {
+            assert_eq!(expected, BigNumber::from_dec_with(dec, DecParsing::Lenient).unwrap().to_dec().unwrap());
+        }
+
+        for dec in ["", "+", "+-1", "12abc", "1 2", "0x10"].iter() {
+            assert!(BigNumber::from_dec_with(dec, DecParsing::Lenient).is_err(), "{:?}", dec);
+        }
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn deserialize_works_for_non_canonical_value() {
+        assert!(serde_json::from_str::<Test>("{\"field\":\"01\"}").is_err());
+        assert!(serde_json::from_str::<Test>("{\"field\":\"1abc\"}").is_err());
+
+        let test: Test = with_dec_parsing(DecParsing::Lenient, || Ok(serde_json::from_str("{\"field\":\"01\"}")?)).unwrap();
+        assert_eq!(BigNumber::from_u32(1).unwrap(), test.field);
+        assert!(with_dec_parsing(DecParsing::Lenient, || Ok(serde_json::from_str::<Test>("{\"field\":\"1abc\"}")?)).is_err());
+
+        // Mode is reset on return
+        assert!(serde_json::from_str::<Test>("{\"field\":\"01\"}").is_err());
+    }
+
     #[cfg(feature = "serialization")]
     #[test]
     fn deserialize_works() {
@@ -0,0 +1,409 @@
+//! Intent-level facades over Issuer, Prover and Verifier API for integrators that don't need
+//! fine-grained control over the protocol.
+//!
+//! Every actor owns its keys and secrets: `IssuerActor` creates offers and issues credentials,
+//! `HolderActor` accepts offers, stores issued credentials and presents proofs,
+//! `VerifierActor` requests and verifies proofs. Facades support non revocable credentials only
+//! and bind credentials to holder's master secret (`master_secret` non schema attribute).
+//!
+//! # Example
+//! ```
+//! use indy_crypto::cl::actors::{HolderActor, IssuerActor, VerifierActor};
+//! use indy_crypto::cl::issuer::Issuer;
+//! use indy_crypto::cl::verifier::Verifier;
+//!
+//! let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+//! credential_schema_builder.add_attr("age").unwrap();
+//! let credential_schema = credential_schema_builder.finalize().unwrap();
+//!
+//! let issuer = IssuerActor::new(&credential_schema).unwrap();
+//! let mut holder = HolderActor::new("holder").unwrap();
+//! let verifier = VerifierActor::new(issuer.pub_key(), issuer.credential_schema()).unwrap();
+//!
+//! let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+//! credential_values_builder.add_dec_known("age", "25").unwrap();
+//! let offer = issuer.create_offer(credential_values_builder.finalize().unwrap()).unwrap();
+//!
+//! let request = holder.accept_offer(&offer).unwrap();
+//! let issued_credential = issuer.issue(&offer, &request).unwrap();
+//! let credential_index = holder.store_credential(issued_credential).unwrap();
+//!
+//! let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+//! sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+//! let presentation_request = verifier.request(sub_proof_request_builder.finalize().unwrap()).unwrap();
+//!
+//! let proof = holder.present(credential_index, &presentation_request).unwrap();
+//! assert!(verifier.verify(&presentation_request, &proof).unwrap());
+//! ```
+use cl::*;
+use cl::issuer::Issuer;
+use cl::prover::Prover;
+use cl::verifier::Verifier;
+use errors::IndyCryptoError;
+
+use std::collections::BTreeMap;
+
+const MASTER_SECRET: &str = "master_secret";
+
+fn _non_credential_schema() -> Result<NonCredentialSchema, IndyCryptoError> {
+    let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder()?;
+    non_credential_schema_builder.add_attr(MASTER_SECRET)?;
+    non_credential_schema_builder.finalize()
+}
+
+/// Offer of credential with known attribute values created by Issuer.
+#[derive(Debug)]
+pub struct CredentialOffer {
+    credential_schema: CredentialSchema,
+    pub_key: CredentialPublicKey,
+    key_correctness_proof: CredentialKeyCorrectnessProof,
+    values: CredentialValues,
+    nonce: Nonce
+}
+
+impl CredentialOffer {
+    pub fn credential_schema(&self) -> &CredentialSchema {
+        &self.credential_schema
+    }
+
+    pub fn pub_key(&self) -> &CredentialPublicKey {
+        &self.pub_key
+    }
+
+    pub fn values(&self) -> &CredentialValues {
+        &self.values
+    }
+}
+
+/// Request of credential created by Holder that accepted the offer.
+#[derive(Debug)]
+pub struct CredentialRequest {
+    prover_id: String,
+    blinded_credential_secrets: BlindedCredentialSecrets,
+    blinded_credential_secrets_correctness_proof: BlindedCredentialSecretsCorrectnessProof,
+    nonce: Nonce
+}
+
+/// Credential signature issued by Issuer in response to the credential request.
+#[derive(Debug)]
+pub struct IssuedCredential {
+    signature: CredentialSignature,
+    signature_correctness_proof: SignatureCorrectnessProof,
+    request_nonce: Nonce
+}
+
+/// Request of proof created by Verifier.
+#[derive(Debug)]
+pub struct PresentationRequest {
+    sub_proof_request: SubProofRequest,
+    nonce: Nonce
+}
+
+impl PresentationRequest {
+    pub fn sub_proof_request(&self) -> &SubProofRequest {
+        &self.sub_proof_request
+    }
+
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+}
+
+/// Issuer that owns credential keys of one credential schema.
+#[derive(Debug)]
+pub struct IssuerActor {
+    credential_schema: CredentialSchema,
+    non_credential_schema: NonCredentialSchema,
+    pub_key: CredentialPublicKey,
+    priv_key: CredentialPrivateKey,
+    key_correctness_proof: CredentialKeyCorrectnessProof
+}
+
+impl IssuerActor {
+    /// Creates Issuer with new credential keys (without revocation part) for the schema.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    pub fn new(credential_schema: &CredentialSchema) -> Result<IssuerActor, IndyCryptoError> {
+        let non_credential_schema = _non_credential_schema()?;
+
+        let (pub_key, priv_key, key_correctness_proof) =
+            Issuer::new_credential_def(credential_schema, &non_credential_schema, false)?;
+
+        Ok(IssuerActor {
+            credential_schema: credential_schema.clone(),
+            non_credential_schema,
+            pub_key,
+            priv_key,
+            key_correctness_proof
+        })
+    }
+
+    pub fn credential_schema(&self) -> &CredentialSchema {
+        &self.credential_schema
+    }
+
+    /// Returns non credential schema of the Issuer keys (contains master secret only).
+    pub fn non_credential_schema(&self) -> &NonCredentialSchema {
+        &self.non_credential_schema
+    }
+
+    pub fn pub_key(&self) -> &CredentialPublicKey {
+        &self.pub_key
+    }
+
+    /// Creates offer of credential with the given known attribute values.
+    ///
+    /// # Arguments
+    /// * `values` - Known values of credential schema attributes.
+    pub fn create_offer(&self, values: CredentialValues) -> Result<CredentialOffer, IndyCryptoError> {
+        trace!("IssuerActor::create_offer: >>> values: {:?}", values);
+
+        if values.attrs_values.values().any(|value| !value.is_known()) {
+            return Err(IndyCryptoError::InvalidStructure("Offered credential values must be known to Issuer".to_string()));
+        }
+
+        let offer = CredentialOffer {
+            credential_schema: self.credential_schema.clone(),
+            pub_key: self.pub_key.clone()?,
+            key_correctness_proof: self.key_correctness_proof.clone()?,
+            values,
+            nonce: new_nonce()?
+        };
+
+        trace!("IssuerActor::create_offer: <<< offer: {:?}", offer);
+
+        Ok(offer)
+    }
+
+    /// Issues credential for the request of the offer.
+    ///
+    /// # Arguments
+    /// * `offer` - Offer created by this Issuer.
+    /// * `request` - Request created by Holder for the offer.
+    pub fn issue(&self, offer: &CredentialOffer, request: &CredentialRequest) -> Result<IssuedCredential, IndyCryptoError> {
+        trace!("IssuerActor::issue: >>> offer: {:?}, request: {:?}", offer, request);
+
+        if !offer.pub_key.is_same_key(&self.pub_key)? {
+            return Err(IndyCryptoError::InvalidStructure("Offer was created by other Issuer".to_string()));
+        }
+
+        let (signature, signature_correctness_proof) =
+            Issuer::sign_credential(&request.prover_id,
+                                    &request.blinded_credential_secrets,
+                                    &request.blinded_credential_secrets_correctness_proof,
+                                    &offer.nonce,
+                                    &request.nonce,
+                                    &offer.values,
+                                    &self.pub_key,
+                                    &self.priv_key)?;
+
+        let issued_credential = IssuedCredential {
+            signature,
+            signature_correctness_proof,
+            request_nonce: request.nonce.clone()?
+        };
+
+        trace!("IssuerActor::issue: <<< issued_credential: {:?}", issued_credential);
+
+        Ok(issued_credential)
+    }
+}
+
+#[derive(Debug)]
+struct PendingCredential {
+    credential_schema: CredentialSchema,
+    pub_key: CredentialPublicKey,
+    values: CredentialValues,
+    blinding_factors: CredentialSecretsBlindingFactors,
+    nonce: Nonce
+}
+
+#[derive(Debug)]
+struct HeldCredential {
+    credential_schema: CredentialSchema,
+    pub_key: CredentialPublicKey,
+    values: CredentialValues,
+    signature: CredentialSignature
+}
+
+/// Holder that owns master secret and credentials bound to it.
+#[derive(Debug)]
+pub struct HolderActor {
+    prover_id: String,
+    master_secret: MasterSecret,
+    non_credential_schema: NonCredentialSchema,
+    pending: BTreeMap<String /* request nonce */, PendingCredential>,
+    credentials: Vec<HeldCredential>
+}
+
+impl HolderActor {
+    /// Creates Holder with new master secret.
+    ///
+    /// # Arguments
+    /// * `prover_id` - Identifier of the Holder known to Issuers.
+    pub fn new(prover_id: &str) -> Result<HolderActor, IndyCryptoError> {
+        Ok(HolderActor {
+            prover_id: prover_id.to_string(),
+            master_secret: Prover::new_master_secret()?,
+            non_credential_schema: _non_credential_schema()?,
+            pending: BTreeMap::new(),
+            credentials: Vec::new()
+        })
+    }
+
+    /// Accepts credential offer: checks Issuer key and creates request with blinded master secret.
+    ///
+    /// # Arguments
+    /// * `offer` - Credential offer.
+    pub fn accept_offer(&mut self, offer: &CredentialOffer) -> Result<CredentialRequest, IndyCryptoError> {
+        trace!("HolderActor::accept_offer: >>> offer: {:?}", offer);
+
+        let mut values = offer.values.clone()?;
+        values.attrs_values.insert(MASTER_SECRET.to_string(), CredentialValue::Hidden { value: self.master_secret.value()? });
+
+        let (blinded_credential_secrets, blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&offer.pub_key, &offer.key_correctness_proof, &values, &offer.nonce)?;
+
+        let nonce = new_nonce()?;
+
+        self.pending.insert(nonce.to_dec()?, PendingCredential {
+            credential_schema: offer.credential_schema.clone(),
+            pub_key: offer.pub_key.clone()?,
+            values,
+            blinding_factors,
+            nonce: nonce.clone()?
+        });
+
+        let request = CredentialRequest {
+            prover_id: self.prover_id.clone(),
+            blinded_credential_secrets,
+            blinded_credential_secrets_correctness_proof,
+            nonce
+        };
+
+        trace!("HolderActor::accept_offer: <<< request: {:?}", request);
+
+        Ok(request)
+    }
+
+    /// Checks and stores issued credential. Returns index of the credential to be used for presentation.
+    ///
+    /// # Arguments
+    /// * `issued_credential` - Credential issued for the request created by this Holder.
+    pub fn store_credential(&mut self, issued_credential: IssuedCredential) -> Result<usize, IndyCryptoError> {
+        trace!("HolderActor::store_credential: >>> issued_credential: {:?}", issued_credential);
+
+        let pending = self.pending.remove(&issued_credential.request_nonce.to_dec()?)
+            .ok_or(IndyCryptoError::InvalidState("Credential wasn't requested by Holder".to_string()))?;
+
+        let mut signature = issued_credential.signature;
+
+        Prover::process_credential_signature(&mut signature,
+                                             &pending.values,
+                                             &issued_credential.signature_correctness_proof,
+                                             &pending.blinding_factors,
+                                             &pending.pub_key,
+                                             &pending.nonce,
+                                             None,
+                                             None,
+                                             None)?;
+
+        self.credentials.push(HeldCredential {
+            credential_schema: pending.credential_schema,
+            pub_key: pending.pub_key,
+            values: pending.values,
+            signature
+        });
+
+        let credential_index = self.credentials.len() - 1;
+
+        trace!("HolderActor::store_credential: <<< credential_index: {:?}", credential_index);
+
+        Ok(credential_index)
+    }
+
+    /// Creates proof over stored credential for the presentation request.
+    ///
+    /// # Arguments
+    /// * `credential_index` - Index of credential returned by `store_credential`.
+    /// * `request` - Presentation request created by Verifier.
+    pub fn present(&self, credential_index: usize, request: &PresentationRequest) -> Result<Proof, IndyCryptoError> {
+        trace!("HolderActor::present: >>> credential_index: {:?}, request: {:?}", credential_index, request);
+
+        let credential = self.credentials.get(credential_index)
+            .ok_or(IndyCryptoError::InvalidParam1(format!("Credential with index {} not found", credential_index)))?;
+
+        let mut proof_builder = Prover::new_proof_builder()?;
+        proof_builder.add_common_attribute(MASTER_SECRET)?;
+        proof_builder.add_sub_proof_request(&request.sub_proof_request,
+                                            &credential.credential_schema,
+                                            &self.non_credential_schema,
+                                            &credential.signature,
+                                            &credential.values,
+                                            &credential.pub_key,
+                                            None,
+                                            None)?;
+        let proof = proof_builder.finalize(&request.nonce)?;
+
+        trace!("HolderActor::present: <<< proof: {:?}", proof);
+
+        Ok(proof)
+    }
+}
+
+/// Verifier that trusts credentials of one Issuer key.
+#[derive(Debug)]
+pub struct VerifierActor {
+    credential_schema: CredentialSchema,
+    non_credential_schema: NonCredentialSchema,
+    pub_key: CredentialPublicKey
+}
+
+impl VerifierActor {
+    /// Creates Verifier accepting credentials issued with the key.
+    ///
+    /// # Arguments
+    /// * `pub_key` - Credential public key of trusted Issuer.
+    /// * `credential_schema` - Credential schema entity.
+    pub fn new(pub_key: &CredentialPublicKey, credential_schema: &CredentialSchema) -> Result<VerifierActor, IndyCryptoError> {
+        Ok(VerifierActor {
+            credential_schema: credential_schema.clone(),
+            non_credential_schema: _non_credential_schema()?,
+            pub_key: pub_key.clone()?
+        })
+    }
+
+    /// Creates presentation request with fresh nonce.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Attributes and predicates Holder must prove.
+    pub fn request(&self, sub_proof_request: SubProofRequest) -> Result<PresentationRequest, IndyCryptoError> {
+        Ok(PresentationRequest {
+            sub_proof_request,
+            nonce: new_nonce()?
+        })
+    }
+
+    /// Verifies proof presented for the request.
+    ///
+    /// # Arguments
+    /// * `request` - Presentation request created by this Verifier.
+    /// * `proof` - Proof presented by Holder.
+    pub fn verify(&self, request: &PresentationRequest, proof: &Proof) -> Result<bool, IndyCryptoError> {
+        trace!("VerifierActor::verify: >>> request: {:?}, proof: {:?}", request, proof);
+
+        let mut proof_verifier = Verifier::new_proof_verifier()?;
+        proof_verifier.add_sub_proof_request(&request.sub_proof_request,
+                                             &self.credential_schema,
+                                             &self.non_credential_schema,
+                                             &self.pub_key,
+                                             None,
+                                             None)?;
+        let valid = proof_verifier.verify(proof, &request.nonce)?;
+
+        trace!("VerifierActor::verify: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+}
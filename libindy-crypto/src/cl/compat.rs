@@ -0,0 +1,221 @@
+//! Compatibility with artifacts produced and consumed by indy-sdk.
+//!
+//! indy-sdk has a few behaviors that differ from native APIs of this crate:
+//!
+//! * Attribute encoding: raw value is parsed as integer the way Python `int()` does it
+//!   (surrounding whitespace and `_` between digits are allowed), so `" 25"` and `"1_000"`
+//!   are encoded as integers, while `encode_raw_value` hashes them.
+//! * Predicate values: 32-bit, while native predicates accept 64-bit values.
+//! * Nonce: always 80 bits serialized as json string of decimal digits, while native nonces
+//!   can be longer (`new_nonce_with_params`) and can be negative.
+//! * Decimal parsing: big numbers are parsed strictly by default. Artifacts of tolerant implementations
+//!   (leading zeros, `+` sign, whitespace) have to be deserialized within `bn::with_dec_parsing(DecParsing::Lenient, ..)`.
+//! * Challenge hash input: `tau_list || c_list || nonce` only. Proofs that use extensions hashed into
+//!   the challenge (guardianship, snark proofs, sub proof request fingerprints,
+//!   non-revocation timestamps, self-attested attributes, channel binding)
+//!   or other challenge reduction can't be verified by indy-sdk.
+use bn::BigNumber;
+use cl::*;
+use cl::constants::LARGE_NONCE;
+use errors::IndyCryptoError;
+use utils::get_hash_as_int;
+
+/// Size of indy-sdk nonce in bits.
+pub const NONCE_BITS: usize = LARGE_NONCE;
+
+/// Encodes raw attribute value the way indy-sdk does.
+///
+/// # Example
+/// ```
+/// use indy_crypto::cl::compat::encode_raw_value;
+///
+/// assert_eq!("1000", encode_raw_value(" 1_000 ").unwrap().to_dec().unwrap());
+/// assert_eq!(indy_crypto::cl::encode_raw_value("Alex").unwrap(), encode_raw_value("Alex").unwrap());
+/// ```
+pub fn encode_raw_value(raw: &str) -> Result<BigNumber, IndyCryptoError> {
+    match _parse_int(raw) {
+        Some(value) => BigNumber::from_dec(&value.to_string()),
+        None => BigNumber::from_bytes(&BigNumber::hash(raw.as_bytes())?)
+    }
+}
+
+/// Parses 32-bit integer accepting the same literals as Python `int()`.
+fn _parse_int(raw: &str) -> Option<i32> {
+    let trimmed = raw.trim();
+    let (sign, digits) = match trimmed.chars().next() {
+        Some(sign @ '-') | Some(sign @ '+') => (Some(sign), &trimmed[1..]),
+        _ => (None, trimmed)
+    };
+
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") ||
+        !digits.chars().all(|c| c.is_ascii_digit() || c == '_') {
+        return None;
+    }
+
+    let digits: String = digits.chars().filter(|c| *c != '_').collect();
+    let literal = match sign {
+        Some('-') => format!("-{}", digits),
+        _ => digits
+    };
+
+    literal.parse::<i32>().ok()
+}
+
+/// Creates nonce of indy-sdk size.
+pub fn new_nonce() -> Result<Nonce, IndyCryptoError> {
+    ::cl::new_nonce()
+}
+
+/// Checks that nonce can be sent to indy-sdk peers: it isn't longer than `NONCE_BITS` bits.
+pub fn check_nonce(nonce: &Nonce) -> Result<(), IndyCryptoError> {
+    if nonce.num_bits()? > NONCE_BITS as i32 {
+        return Err(IndyCryptoError::InvalidStructure(
+            format!("Nonce has {} bits, indy-sdk nonce has at most {} bits", nonce.num_bits()?, NONCE_BITS)));
+    }
+    Ok(())
+}
+
+/// Parses nonce json produced by indy-sdk: json string of decimal digits of at most `NONCE_BITS` bits.
+pub fn nonce_from_json(nonce_json: &str) -> Result<Nonce, IndyCryptoError> {
+    let nonce_dec: String = ::serde_json::from_str(nonce_json)?;
+
+    if nonce_dec.is_empty() || !nonce_dec.chars().all(|c| c.is_ascii_digit()) {
+        return Err(IndyCryptoError::InvalidStructure(format!("Invalid indy-sdk nonce: {:?}", nonce_dec)));
+    }
+
+    let nonce = BigNumber::from_dec(&nonce_dec)?;
+    check_nonce(&nonce)?;
+    Ok(nonce)
+}
+
+/// Computes proof challenge hash with indy-sdk ordering of hash input.
+///
+/// # Arguments
+/// * `tau_list` - Tau values of all sub proofs.
+/// * `c_list` - C values of all sub proofs.
+/// * `nonce` - Nonce of the proof request.
+pub fn challenge_hash(tau_list: &[Vec<u8>], c_list: &[Vec<u8>], nonce: &Nonce) -> Result<BigNumber, IndyCryptoError> {
+    let mut values: Vec<Vec<u8>> = Vec::with_capacity(tau_list.len() + c_list.len() + 1);
+    values.extend_from_slice(tau_list);
+    values.extend_from_slice(c_list);
+    values.push(nonce.to_bytes()?);
+    get_hash_as_int(&values)
+}
+
+/// Checks that proof doesn't use extensions unknown to indy-sdk, so indy-sdk verifier can verify it.
+pub fn check_proof(proof: &Proof) -> Result<(), IndyCryptoError> {
+    let mut extensions: Vec<&str> = Vec::new();
+
+    if proof.guardianship.is_some() {
+        extensions.push("guardianship");
+    }
+    if !proof.snark_proofs.is_empty() {
+        extensions.push("snark proofs");
+    }
+    if proof.challenge_reduction != ChallengeReduction::V1Full {
+        extensions.push("challenge reduction");
+    }
+    if proof.proofs.iter().any(|sub_proof| !sub_proof.sub_proof_request_fingerprint.is_empty()) {
+        extensions.push("sub proof request fingerprints");
+    }
+    if !proof.self_attested_attrs.is_empty() {
+        extensions.push("self-attested attributes");
+    }
+    if proof.proofs.iter().any(|sub_proof| sub_proof.non_revoc_timestamp.is_some()) {
+        extensions.push("non-revocation timestamps");
+    }
+    if proof.proofs.iter().any(|sub_proof| !sub_proof.attr_non_revoc_proofs.is_empty()) {
+        extensions.push("attribute non-revocation proofs");
+    }
+    if proof.proofs.iter().any(|sub_proof| !sub_proof.committed_attr_proofs.is_empty()) {
+        extensions.push("committed attributes");
+    }
+    if proof.proofs.iter().any(|sub_proof| sub_proof.primary_proof.ge_proofs.iter()
+        .any(|ge_proof| ge_proof.predicate.value < ::std::i32::MIN as i64 || ge_proof.predicate.value > ::std::i32::MAX as i64)) {
+        extensions.push("64-bit predicate values");
+    }
+    if proof.proofs.iter().any(|sub_proof| !sub_proof.primary_proof.eq_proof.revealed_raw_attrs.is_empty()) {
+        extensions.push("raw attribute values");
+    }
+    if proof.is_compact() {
+        extensions.push("compact form");
+    }
+
+    if !extensions.is_empty() {
+        return Err(IndyCryptoError::InvalidStructure(format!("Proof uses extensions unknown to indy-sdk: {}", extensions.join(", "))));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cl::prover::mocks;
+
+    #[test]
+    fn encode_raw_value_works() {
+        assert_eq!("25", encode_raw_value("25").unwrap().to_dec().unwrap());
+        assert_eq!("-25", encode_raw_value(" -25\n").unwrap().to_dec().unwrap());
+        assert_eq!("1000", encode_raw_value("+1_000").unwrap().to_dec().unwrap());
+        assert_eq!("-2147483648", encode_raw_value("-2147483648").unwrap().to_dec().unwrap());
+    }
+
+    #[test]
+    fn encode_raw_value_works_for_hashed_values() {
+        for raw in ["Alex", "", "2147483648", "1__000", "_1", "1_", "- 1", "0x10", "1.0"].iter() {
+            assert_eq!(BigNumber::from_bytes(&BigNumber::hash(raw.as_bytes()).unwrap()).unwrap(), encode_raw_value(raw).unwrap());
+        }
+    }
+
+    #[test]
+    fn encode_raw_value_differs_from_native_encoding() {
+        assert_ne!(::cl::encode_raw_value(" 25").unwrap(), encode_raw_value(" 25").unwrap());
+        assert_eq!(::cl::encode_raw_value("Alex").unwrap(), encode_raw_value("Alex").unwrap());
+    }
+
+    #[test]
+    fn nonce_from_json_works() {
+        let nonce = new_nonce().unwrap();
+        check_nonce(&nonce).unwrap();
+
+        let nonce_json = format!("\"{}\"", nonce.to_dec().unwrap());
+        assert_eq!(nonce, nonce_from_json(&nonce_json).unwrap());
+    }
+
+    #[test]
+    fn nonce_from_json_works_for_invalid_nonce() {
+        for nonce_json in [r#""-1""#, r#""+1""#, r#""""#, r#""0x10""#, "1", r#""1208925819614629174706176""#].iter() {
+            assert!(nonce_from_json(nonce_json).is_err(), "{}", nonce_json);
+        }
+    }
+
+    #[test]
+    fn challenge_hash_works() {
+        let tau_list = vec![vec![1u8, 2], vec![3]];
+        let c_list = vec![vec![4u8]];
+        let nonce = mocks::proof_request_nonce();
+
+        let mut values = tau_list.clone();
+        values.extend(c_list.clone());
+        values.extend(session_values(&nonce, None).unwrap());
+
+        assert_eq!(get_hash_as_int(&values).unwrap(), challenge_hash(&tau_list, &c_list, &nonce).unwrap());
+        assert_ne!(get_hash_as_int(&values).unwrap(), challenge_hash(&c_list, &tau_list, &nonce).unwrap());
+    }
+
+    #[test]
+    fn check_proof_works() {
+        check_proof(&mocks::proof()).unwrap();
+
+        let mut proof = mocks::proof();
+        proof.challenge_reduction = ChallengeReduction::V2ReducedModOrder;
+        assert!(check_proof(&proof).is_err());
+
+        let mut proof = mocks::proof();
+        proof.proofs[0].sub_proof_request_fingerprint = mocks::sub_proof_request().fingerprint().unwrap();
+        assert!(check_proof(&proof).is_err());
+
+        assert!(check_proof(&mocks::proof().compact()).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use bn::{BigNumber, BIGNUMBER_2};
+use pair::DerivationDomain;
 
 pub const LARGE_MASTER_SECRET: usize = 256;
 pub const LARGE_E_START: usize = 596;
@@ -20,7 +21,20 @@ pub const ITERATION: usize = 4;
   FIXME sync the paper and remove this comment
 */
 pub const LARGE_NONCE: usize = 80;
+pub const MAX_NONCE_BITS: usize = 4096;
 pub const LARGE_ALPHATILDE: usize = 2787;
+pub const KEY_USAGE_CONSTRAINTS_EXPONENT: usize = 65537;
+pub const KEY_ROTATION_EXPONENT: usize = 65537;
+pub const KEY_ROTATION_DOMAIN: &[u8] = b"key_rotation";
+pub const MASTER_SECRET_ROTATION_DOMAIN: &[u8] = b"master_secret_rotation";
+pub const CREDENTIAL_BLINDING_DOMAIN: &[u8] = b"credential_blinding";
+pub const CREDENTIAL_SIGNATURE_DOMAIN: &[u8] = b"credential_signature";
+pub const NONCE_CONTEXT_DOMAIN: &[u8] = b"nonce_context";
+pub const SUB_PROOF_REQUEST_FINGERPRINT_DOMAIN: &[u8] = b"sub_proof_request";
+pub const CREDENTIAL_PUBLIC_KEY_FINGERPRINT_DOMAIN: &[u8] = b"credential_public_key";
+pub const ISSUER_KEY_PAIR_EXPORT_VERSION: u8 = 1;
+pub const REVOCATION_REGISTRY_GAMMA_DOMAIN: DerivationDomain = DerivationDomain(b"revocation_registry_gamma");
+pub const HOLDER_BINDING_DOMAIN: DerivationDomain = DerivationDomain(b"holder_binding");
 
 // Constants that are used throughout the CL signatures code, so avoiding recomputation.
 lazy_static! {
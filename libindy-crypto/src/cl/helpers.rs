@@ -1,11 +1,13 @@
-use bn::{BigNumber, BIGNUMBER_1};
+use bn::{BigNumber, BIGNUMBER_1, BIGNUMBER_2};
 use cl::*;
 use errors::IndyCryptoError;
-use pair::GroupOrderElement;
+use pair::{GroupOrderElement, PointG1};
 use super::constants::*;
+use utils::get_hash_as_int;
 
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::thread;
 
 #[cfg(test)]
 use std::cell::RefCell;
@@ -115,24 +117,28 @@ pub fn encode_attribute(attribute: &str, byte_order: ByteOrder) -> Result<BigNum
 }
 
 #[cfg(test)]
-pub fn generate_v_prime_prime() -> Result<BigNumber, IndyCryptoError> {
+pub fn generate_v_prime_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
     if MockHelper::is_injected() {
         return BigNumber::from_dec("6620937836014079781509458870800001917950459774302786434315639456568768602266735503527631640833663968617512880802104566048179854406925811731340920442625764155409951969854303612644125623549271204625894424804352003689903192473464433927658013251120302922648839652919662117216521257876025436906282750361355336367533874548955283776610021309110505377492806210342214471251451681722267655419075635703240258044336607001296052867746675049720589092355650996711033859489737240617860392914314205277920274997312351322125481593636904917159990500837822414761512231315313922792934655437808723096823124948039695324591344458785345326611693414625458359651738188933757751726392220092781991665483583988703321457480411992304516676385323318285847376271589157730040526123521479652961899368891914982347831632139045838008837541334927738208491424027");
     }
-    _generate_v_prime_prime()
+    _generate_v_prime_prime(size)
 }
 
 #[cfg(not(test))]
-pub fn generate_v_prime_prime() -> Result<BigNumber, IndyCryptoError> {
-    _generate_v_prime_prime()
+pub fn generate_v_prime_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+    _generate_v_prime_prime(size)
 }
 
-pub fn _generate_v_prime_prime() -> Result<BigNumber, IndyCryptoError> {
-    trace!("Helpers::generate_v_prime_prime: >>>");
+pub fn _generate_v_prime_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
+    trace!("Helpers::generate_v_prime_prime: >>> size: {:?}", size);
 
-    let a = bn_rand(LARGE_VPRIME_PRIME)?;
+    let a = bn_rand(size)?;
 
-    let v_prime_prime = bitwise_or_big_int(&a, &LARGE_VPRIME_PRIME_VALUE)?;
+    let v_prime_prime = if size == LARGE_VPRIME_PRIME {
+        bitwise_or_big_int(&a, &LARGE_VPRIME_PRIME_VALUE)?
+    } else {
+        bitwise_or_big_int(&a, &BIGNUMBER_2.exp(&BigNumber::from_u32(size - 1)?, None)?)?
+    };
 
     trace!("Helpers::generate_v_prime_prime: <<< v_prime_prime: {:?}", secret!(&v_prime_prime));
 
@@ -175,11 +181,6 @@ pub fn generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
     _generate_safe_prime(size)
 }
 
-#[cfg(not(test))]
-pub fn generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
-    _generate_safe_prime(size)
-}
-
 pub fn _generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
     trace!("Helpers::generate_safe_prime: >>> size: {:?}", size);
 
@@ -190,6 +191,78 @@ pub fn _generate_safe_prime(size: usize) -> Result<BigNumber, IndyCryptoError> {
     Ok(safe_prime)
 }
 
+#[cfg(test)]
+pub fn generate_safe_primes(size: usize, count: usize) -> Result<Vec<BigNumber>, IndyCryptoError> {
+    if MockHelper::is_injected() {
+        return (0..count).map(|_| generate_safe_prime(size)).collect();
+    }
+    _generate_safe_primes(size, count)
+}
+
+#[cfg(not(test))]
+pub fn generate_safe_primes(size: usize, count: usize) -> Result<Vec<BigNumber>, IndyCryptoError> {
+    _generate_safe_primes(size, count)
+}
+
+/// Generates `count` safe primes of `size + 1` bits searching every prime in separate thread.
+pub fn _generate_safe_primes(size: usize, count: usize) -> Result<Vec<BigNumber>, IndyCryptoError> {
+    trace!("Helpers::generate_safe_primes: >>> size: {:?}, count: {:?}", size, count);
+
+    let handles: Vec<_> = (0..count)
+        .map(|_| thread::spawn(move || _generate_safe_prime(size)))
+        .collect();
+
+    let safe_primes = handles
+        .into_iter()
+        .map(|handle| handle.join()
+            .map_err(|_| IndyCryptoError::InvalidState("Safe prime generation thread panicked".to_string()))?)
+        .collect::<Result<Vec<BigNumber>, IndyCryptoError>>()?;
+
+    trace!("Helpers::generate_safe_primes: <<< safe_primes: {:?}", secret!(&safe_primes));
+
+    Ok(safe_primes)
+}
+
+/// Generates safe prime `2q + 1` of `size + 1` bits (same as `generate_safe_prime`) from primes `q` of `size` bits
+/// reporting every tested candidate and checking cancellation between candidates.
+pub fn generate_safe_prime_with_progress<F>(size: usize,
+                                            progress: &mut KeyGenProgress,
+                                            on_progress: &mut F,
+                                            cancellation: &CancellationToken) -> Result<BigNumber, IndyCryptoError>
+    where F: FnMut(&KeyGenProgress) {
+    trace!("Helpers::generate_safe_prime_with_progress: >>> size: {:?}, progress: {:?}", size, progress);
+
+    let mut ctx = BigNumber::new_context()?;
+    let three = BigNumber::from_u32(3)?;
+    let two = BigNumber::from_u32(2)?;
+
+    let safe_prime = loop {
+        if cancellation.is_cancelled() {
+            return Err(IndyCryptoError::InvalidState("Key generation is cancelled".to_string()));
+        }
+
+        let q = BigNumber::generate_prime(size)?;
+        let p = q.lshift1()?.increment()?;
+
+        // a safe prime is congruent to 2 mod 3, it is cheaper to check it first
+        let found = p.modulus(&three, Some(&mut ctx))? == two && p.is_prime(Some(&mut ctx))?;
+
+        progress.candidates_tested += 1;
+        if found {
+            progress.primes_found += 1;
+        }
+        on_progress(progress);
+
+        if found {
+            break p;
+        }
+    };
+
+    trace!("Helpers::generate_safe_prime_with_progress: <<< safe_prime: {:?}, progress: {:?}", secret!(&safe_prime), progress);
+
+    Ok(safe_prime)
+}
+
 #[cfg(test)]
 pub fn gen_x(p: &BigNumber, q: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
     if MockHelper::is_injected() {
@@ -276,7 +349,8 @@ pub fn transform_u32_to_array_of_u8(x: u32) -> Vec<u8> {
 pub fn get_mtilde(unrevealed_attrs: &HashSet<String>, mtilde: &mut HashMap<String, BigNumber>) -> Result<(), IndyCryptoError> {
     trace!("Helpers::get_mtilde: >>> unrevealed_attrs: {:?}", unrevealed_attrs);
 
-    for attr in unrevealed_attrs {
+    // Sorted order keeps transcripts reproducible with `utils::entropy::with_rng`
+    for attr in unrevealed_attrs.iter().collect::<BTreeSet<&String>>() {
         if !mtilde.contains_key(attr) {
             mtilde.insert(attr.clone(), bn_rand(LARGE_MVECT)?);
         }
@@ -325,6 +399,73 @@ pub fn calc_teq(p_pub_key: &CredentialPrimaryPublicKey,
     Ok(result)
 }
 
+/// Batch of modular exponentiations over the same modulus.
+///
+/// Exponentiations are gathered first and then executed at once with shared context,
+/// optionally split across several threads.
+pub struct ModExpBatch<'a> {
+    modulus: &'a BigNumber,
+    ops: Vec<(&'a BigNumber, &'a BigNumber)>
+}
+
+impl<'a> ModExpBatch<'a> {
+    pub fn new(modulus: &'a BigNumber) -> ModExpBatch<'a> {
+        ModExpBatch { modulus, ops: Vec::new() }
+    }
+
+    /// Adds `base^exp mod modulus` to the batch. Results are returned by `execute` in the same order.
+    pub fn push(&mut self, base: &'a BigNumber, exp: &'a BigNumber) {
+        self.ops.push((base, exp));
+    }
+
+    /// Executes all gathered exponentiations using up to `threads` threads.
+    pub fn execute(&self, threads: usize) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        trace!("Helpers::ModExpBatch::execute: >>> ops: {:?}, threads: {:?}", self.ops.len(), threads);
+
+        let res = if threads <= 1 || self.ops.len() <= 1 {
+            ModExpBatch::_execute_chunk(&self.ops, self.modulus)?
+        } else {
+            let chunk_size = (self.ops.len() + threads - 1) / threads;
+
+            // Spawned threads can't borrow operands, so every chunk is moved to its thread with own copies
+            let handles = self.ops
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let modulus = self.modulus.clone()?;
+                    let ops = chunk
+                        .iter()
+                        .map(|&(base, exp)| Ok((base.clone()?, exp.clone()?)))
+                        .collect::<Result<Vec<(BigNumber, BigNumber)>, IndyCryptoError>>()?;
+
+                    Ok(thread::spawn(move || {
+                        let ops: Vec<(&BigNumber, &BigNumber)> = ops.iter().map(|&(ref base, ref exp)| (base, exp)).collect();
+                        ModExpBatch::_execute_chunk(&ops, &modulus)
+                    }))
+                })
+                .collect::<Result<Vec<_>, IndyCryptoError>>()?;
+
+            let chunks = handles
+                .into_iter()
+                .map(|handle| handle.join()
+                    .map_err(|_| IndyCryptoError::InvalidState("Modular exponentiation thread panicked".to_string()))?)
+                .collect::<Result<Vec<Vec<BigNumber>>, IndyCryptoError>>()?;
+
+            chunks.into_iter().flat_map(|chunk| chunk.into_iter()).collect()
+        };
+
+        trace!("Helpers::ModExpBatch::execute: <<< res: {:?}", res.len());
+
+        Ok(res)
+    }
+
+    fn _execute_chunk(ops: &[(&BigNumber, &BigNumber)], modulus: &BigNumber) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        let mut ctx = BigNumber::new_context()?;
+        ops.iter()
+            .map(|&(base, exp)| base.mod_exp(exp, modulus, Some(&mut ctx)))
+            .collect()
+    }
+}
+
 pub fn calc_tge(p_pub_key: &CredentialPrimaryPublicKey,
                 u: &HashMap<String, BigNumber>,
                 r: &HashMap<String, BigNumber>,
@@ -388,47 +529,56 @@ pub fn calc_tge(p_pub_key: &CredentialPrimaryPublicKey,
     Ok(tau_list)
 }
 
-fn largest_square_less_than(delta: usize) -> usize {
-    (delta as f64).sqrt().floor() as usize
+/// Returns floor of square root of `delta`: f64 estimate corrected to be exact for any 64-bit value.
+fn largest_square_less_than(delta: u64) -> u64 {
+    // Estimate of the root of values close to u64::MAX is 2^32, its square overflows u64
+    let mut root = (delta as f64).sqrt() as u64;
+    while root.checked_mul(root).map(|square| square > delta).unwrap_or(true) {
+        root -= 1;
+    }
+    while (root + 1).checked_mul(root + 1).map(|square| square <= delta).unwrap_or(false) {
+        root += 1;
+    }
+    root
 }
 
 //Express the natural number `delta` as a sum of four integer squares,
 // i.e `delta = a^2 + b^2 + c^2 + d^2` using Lagrange's four-square theorem
-pub fn four_squares(delta: i32) -> Result<HashMap<String, BigNumber>, IndyCryptoError> {
+pub fn four_squares(delta: u64) -> Result<HashMap<String, BigNumber>, IndyCryptoError> {
     trace!("Helpers::four_squares: >>> delta: {:?}", delta);
 
-    if delta < 0 {
-        return Err(IndyCryptoError::InvalidStructure(format!("Cannot express a negative number as sum of four squares {} ", delta)));
-    }
-
-    let d = delta as usize;
-    let mut roots: [usize; 4] = [largest_square_less_than(d), 0, 0, 0];
+    // Every root is at most the root of the remainder it is subtracted from,
+    // so squares and remainders of any 64-bit delta don't overflow u64
+    let mut roots: [u64; 4] = [largest_square_less_than(delta), 0, 0, 0];
 
     'outer: for i in (1..roots[0] + 1).rev() {
         roots[0] = i;
-        if d == roots[0].pow(2) {
+        let rem0 = delta - i * i;
+        if rem0 == 0 {
             roots[1] = 0;
             roots[2] = 0;
             roots[3] = 0;
             break 'outer;
         }
-        roots[1] = largest_square_less_than(d - roots[0].pow(2));
+        roots[1] = largest_square_less_than(rem0);
         for j in (1..roots[1] + 1).rev() {
             roots[1] = j;
-            if d == roots[0].pow(2) + roots[1].pow(2) {
+            let rem1 = rem0 - j * j;
+            if rem1 == 0 {
                 roots[2] = 0;
                 roots[3] = 0;
                 break 'outer;
             }
-            roots[2] = largest_square_less_than(d - roots[0].pow(2) - roots[1].pow(2));
+            roots[2] = largest_square_less_than(rem1);
             for k in (1..roots[2] + 1).rev() {
                 roots[2] = k;
-                if d == roots[0].pow(2) + roots[1].pow(2) + roots[2].pow(2) {
+                let rem2 = rem1 - k * k;
+                if rem2 == 0 {
                     roots[3] = 0;
                     break 'outer;
                 }
-                roots[3] = largest_square_less_than(d - roots[0].pow(2) - roots[1].pow(2) - roots[2].pow(2));
-                if d == roots[0].pow(2) + roots[1].pow(2) + roots[2].pow(2) + roots[3].pow(2) {
+                roots[3] = largest_square_less_than(rem2);
+                if rem2 == roots[3] * roots[3] {
                     break 'outer;
                 }
             }
@@ -455,6 +605,34 @@ pub fn bignum_to_group_element(num: &BigNumber) -> Result<GroupOrderElement, Ind
     Ok(GroupOrderElement::from_bytes(&num.to_bytes()?)?)
 }
 
+pub fn bignum_to_group_element_mod_order(num: &BigNumber) -> Result<GroupOrderElement, IndyCryptoError> {
+    let order = BigNumber::from_bytes(&GroupOrderElement::order_bytes()?)?;
+    bignum_to_group_element(&num.modulus(&order, None)?)
+}
+
+/// Commits to `value` with independent bases of holder binding domain: `g * value + h * blinding_factor`.
+pub fn get_holder_binding_commitment(value: &GroupOrderElement, blinding_factor: &GroupOrderElement) -> Result<PointG1, IndyCryptoError> {
+    let g = PointG1::derive_base(&HOLDER_BINDING_DOMAIN, b"g")?;
+    let h = PointG1::derive_base(&HOLDER_BINDING_DOMAIN, b"h")?;
+    g.mul(value)?.add(&h.mul(blinding_factor)?)
+}
+
+pub fn get_holder_binding_challenge(attr_name: &str,
+                                    committed_attr: &BigNumber,
+                                    holder_binding_commitment: &PointG1,
+                                    committed_attr_tau: &BigNumber,
+                                    holder_binding_tau: &PointG1,
+                                    nonce: &Nonce) -> Result<BigNumber, IndyCryptoError> {
+    get_hash_as_int(&vec![
+        attr_name.as_bytes().to_vec(),
+        committed_attr.to_bytes()?,
+        holder_binding_commitment.to_bytes()?,
+        committed_attr_tau.to_bytes()?,
+        holder_binding_tau.to_bytes()?,
+        nonce.to_bytes()?
+    ])
+}
+
 pub fn create_tau_list_expected_values(r_pub_key: &CredentialRevocationPublicKey,
                                        rev_reg: &RevocationRegistry,
                                        rev_acc_pub_key: &RevocationKeyPublic,
@@ -495,8 +673,17 @@ pub fn create_tau_list_values(r_pub_key: &CredentialRevocationPublicKey,
                               rev_reg: &RevocationRegistry,
                               params: &NonRevocProofXList,
                               proof_c: &NonRevocProofCList) -> Result<NonRevocProofTauList, IndyCryptoError> {
-    trace!("Helpers::create_tau_list_values: >>> r_pub_key: {:?}, rev_reg: {:?}, params: {:?}, proof_c: {:?}",
-           r_pub_key, rev_reg, params, proof_c);
+    let pairings = NonRevocProofPairings::new(r_pub_key, rev_reg)?;
+    create_tau_list_values_with_pairings(r_pub_key, &pairings, params, proof_c)
+}
+
+/// Creates non-revocation tau list using precomputed pairings, so only pairings depending on the proof are computed.
+pub fn create_tau_list_values_with_pairings(r_pub_key: &CredentialRevocationPublicKey,
+                                            pairings: &NonRevocProofPairings,
+                                            params: &NonRevocProofXList,
+                                            proof_c: &NonRevocProofCList) -> Result<NonRevocProofTauList, IndyCryptoError> {
+    trace!("Helpers::create_tau_list_values_with_pairings: >>> r_pub_key: {:?}, pairings: {:?}, params: {:?}, proof_c: {:?}",
+           r_pub_key, pairings, params, proof_c);
 
     let t1 = r_pub_key.h.mul(&params.rho)?.add(&r_pub_key.htilde.mul(&params.o)?)?;
     let mut t2 = proof_c.e.mul(&params.c)?
@@ -506,14 +693,14 @@ pub fn create_tau_list_values(r_pub_key: &CredentialRevocationPublicKey,
         t2 = PointG1::new_inf()?;
     }
     let t3 = Pair::pair(&proof_c.a, &r_pub_key.h_cap)?.pow(&params.c)?
-        .mul(&Pair::pair(&r_pub_key.htilde, &r_pub_key.h_cap)?.pow(&params.r)?)?
-        .mul(&Pair::pair(&r_pub_key.htilde, &r_pub_key.y)?.pow(&params.rho)?
-            .mul(&Pair::pair(&r_pub_key.htilde, &r_pub_key.h_cap)?.pow(&params.m)?)?
-            .mul(&Pair::pair(&r_pub_key.h1, &r_pub_key.h_cap)?.pow(&params.m2)?)?
-            .mul(&Pair::pair(&r_pub_key.h2, &r_pub_key.h_cap)?.pow(&params.s)?)?.inverse()?)?;
-    let t4 = Pair::pair(&r_pub_key.htilde, &rev_reg.accum)?
+        .mul(&pairings.htilde_h_cap.pow(&params.r)?)?
+        .mul(&pairings.htilde_y.pow(&params.rho)?
+            .mul(&pairings.htilde_h_cap.pow(&params.m)?)?
+            .mul(&pairings.h1_h_cap.pow(&params.m2)?)?
+            .mul(&pairings.h2_h_cap.pow(&params.s)?)?.inverse()?)?;
+    let t4 = pairings.htilde_accum
         .pow(&params.r)?
-        .mul(&Pair::pair(&r_pub_key.g.neg()?, &r_pub_key.h_cap)?.pow(&params.r_prime)?)?;
+        .mul(&pairings.neg_g_h_cap.pow(&params.r_prime)?)?;
     let t5 = r_pub_key.g.mul(&params.r)?.add(&r_pub_key.htilde.mul(&params.o_prime)?)?;
     let mut t6 = proof_c.d.mul(&params.r_prime_prime)?
         .add(&r_pub_key.g.mul(&params.m_prime.mod_neg()?)?)?
@@ -522,10 +709,10 @@ pub fn create_tau_list_values(r_pub_key: &CredentialRevocationPublicKey,
         t6 = PointG1::new_inf()?;
     }
     let t7 = Pair::pair(&r_pub_key.pk.add(&proof_c.g)?, &r_pub_key.h_cap)?.pow(&params.r_prime_prime)?
-        .mul(&Pair::pair(&r_pub_key.htilde, &r_pub_key.h_cap)?.pow(&params.m_prime.mod_neg()?)?)?
+        .mul(&pairings.htilde_h_cap.pow(&params.m_prime.mod_neg()?)?)?
         .mul(&Pair::pair(&r_pub_key.htilde, &proof_c.s)?.pow(&params.r)?)?;
-    let t8 = Pair::pair(&r_pub_key.htilde, &r_pub_key.u)?.pow(&params.r)?
-        .mul(&Pair::pair(&r_pub_key.g.neg()?, &r_pub_key.h_cap)?.pow(&params.r_prime_prime_prime)?)?;
+    let t8 = pairings.htilde_u.pow(&params.r)?
+        .mul(&pairings.neg_g_h_cap.pow(&params.r_prime_prime_prime)?)?;
 
     let non_revoc_proof_tau_list = NonRevocProofTauList {
         t1,
@@ -538,7 +725,7 @@ pub fn create_tau_list_values(r_pub_key: &CredentialRevocationPublicKey,
         t8
     };
 
-    trace!("Helpers::create_tau_list_values: <<< non_revoc_proof_tau_list: {:?}", non_revoc_proof_tau_list);
+    trace!("Helpers::create_tau_list_values_with_pairings: <<< non_revoc_proof_tau_list: {:?}", non_revoc_proof_tau_list);
 
     Ok(non_revoc_proof_tau_list)
 }
@@ -560,7 +747,31 @@ mod tests {
         MockHelper::inject();
 
         let result = BigNumber::from_dec("6620937836014079781509458870800001917950459774302786434315639456568768602266735503527631640833663968617512880802104566048179854406925811731340920442625764155409951969854303612644125623549271204625894424804352003689903192473464433927658013251120302922648839652919662117216521257876025436906282750361355336367533874548955283776610021309110505377492806210342214471251451681722267655419075635703240258044336607001296052867746675049720589092355650996711033859489737240617860392914314205277920274997312351322125481593636904917159990500837822414761512231315313922792934655437808723096823124948039695324591344458785345326611693414625458359651738188933757751726392220092781991665483583988703321457480411992304516676385323318285847376271589157730040526123521479652961899368891914982347831632139045838008837541334927738208491424027").unwrap();
-        assert_eq!(generate_v_prime_prime().unwrap(), result);
+        assert_eq!(generate_v_prime_prime(LARGE_VPRIME_PRIME).unwrap(), result);
+    }
+
+    #[test]
+    fn generate_safe_primes_works() {
+        let safe_primes = generate_safe_primes(128, 3).unwrap();
+
+        assert_eq!(3, safe_primes.len());
+        for safe_prime in safe_primes.iter() {
+            assert!(safe_prime.is_safe_prime(None).unwrap());
+            assert_eq!(129, safe_prime.num_bits().unwrap());
+        }
+    }
+
+    #[test]
+    fn generate_safe_prime_with_progress_works() {
+        let mut progress = KeyGenProgress::default();
+        let mut reported = 0;
+
+        let safe_prime = generate_safe_prime_with_progress(128, &mut progress, &mut |_| reported += 1, &CancellationToken::new()).unwrap();
+
+        assert!(safe_prime.is_safe_prime(None).unwrap());
+        assert_eq!(129, safe_prime.num_bits().unwrap());
+        assert_eq!(1, progress.primes_found);
+        assert_eq!(reported, progress.candidates_tested);
     }
 
     #[test]
@@ -573,7 +784,7 @@ mod tests {
 
     #[test]
     fn four_squares_works() {
-        let res = four_squares(107 as i32);
+        let res = four_squares(107);
         let res_data = res.unwrap();
 
         assert_eq!("9".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
@@ -581,7 +792,7 @@ mod tests {
         assert_eq!("1".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
         assert_eq!("0".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
 
-        let res = four_squares(112 as i32);
+        let res = four_squares(112);
         let res_data = res.unwrap();
 
         assert_eq!("10".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
@@ -590,7 +801,7 @@ mod tests {
         assert_eq!("2".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
 
 
-        let res = four_squares(253 as i32);
+        let res = four_squares(253);
         let res_data = res.unwrap();
 
         assert_eq!("14".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
@@ -598,7 +809,7 @@ mod tests {
         assert_eq!("2".to_string(), res_data.get("2").unwrap().to_dec().unwrap());
         assert_eq!("2".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
 
-        let res = four_squares(1506099439 as i32);
+        let res = four_squares(1506099439);
         let res_data = res.unwrap();
 
         assert_eq!("38807".to_string(), res_data.get("0").unwrap().to_dec().unwrap());
@@ -607,6 +818,19 @@ mod tests {
         assert_eq!("11".to_string(), res_data.get("3").unwrap().to_dec().unwrap());
     }
 
+    #[test]
+    fn four_squares_works_for_64_bit_delta() {
+        for delta in [::std::u64::MAX, ::std::u64::MAX - 1, (1u64 << 63) + 7, 4u64.pow(30) * 7].iter() {
+            let res = four_squares(*delta).unwrap();
+
+            let sum = (0..4).fold(BigNumber::from_u32(0).unwrap(), |sum, i| {
+                let root = res.get(&i.to_string()).unwrap();
+                sum.add(&root.mul(root, None).unwrap()).unwrap()
+            });
+            assert_eq!(BigNumber::from_dec(&delta.to_string()).unwrap(), sum);
+        }
+    }
+
     #[test]
     fn transform_u32_to_array_of_u8_works() {
         let int = 0x74BA7445;
@@ -691,4 +915,20 @@ mod tests {
         23513431038543455953776634428397886712772493416769127589401268192936635379602026733723945633\
         53933943790374230983129060596346889726181201177754774157687114812348019929279", res.unwrap().to_dec().unwrap());
     }
+
+    #[test]
+    fn create_tau_list_values_with_pairings_works() {
+        let r_pub_key = issuer::mocks::credential_revocation_public_key();
+        let rev_reg = issuer::mocks::revocation_registry();
+        let init_proof = prover::mocks::init_non_revocation_proof();
+
+        let other_rev_reg = RevocationRegistry { accum: PointG2::new().unwrap() };
+        let pairings = NonRevocProofPairings::new(&r_pub_key, &other_rev_reg).unwrap()
+            .for_rev_reg(&rev_reg).unwrap();
+
+        let expected = create_tau_list_values(&r_pub_key, &rev_reg, &init_proof.tau_list_params, &init_proof.c_list).unwrap();
+        let res = create_tau_list_values_with_pairings(&r_pub_key, &pairings, &init_proof.tau_list_params, &init_proof.c_list).unwrap();
+
+        assert_eq!(expected.as_slice().unwrap(), res.as_slice().unwrap());
+    }
 }
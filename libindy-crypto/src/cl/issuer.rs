@@ -1,13 +1,15 @@
-use bn::BigNumber;
+use bn::{BigNumber, BIGNUMBER_1};
 use cl::*;
 use errors::IndyCryptoError;
 use pair::*;
 use cl::constants::*;
+use cl::verifier::ProofVerifier;
 use cl::helpers::*;
+use utils::clock::{Clock, SystemClock, Timestamp};
 use utils::commitment::*;
 use utils::get_hash_as_int;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Trust source that provides credentials to prover.
 pub struct Issuer {}
@@ -36,6 +38,22 @@ impl Issuer {
         NonCredentialSchemaBuilder::new()
     }
 
+    /// Creates random nonce for credential offer or issuance.
+    ///
+    /// # Arguments
+    /// * `bit_length` - Nonce length in bits (80 to 4096).
+    /// * `context` - (Optional) Context string mixed into the nonce, e.g. credential definition id.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let _credential_nonce = Issuer::new_nonce(80, Some("credential_offer")).unwrap();
+    /// ```
+    pub fn new_nonce(bit_length: usize, context: Option<&str>) -> Result<Nonce, IndyCryptoError> {
+        new_nonce_with_params(bit_length, context)
+    }
+
     /// Creates and returns credential definition (public and private keys, correctness proof) entities.
     ///
     /// # Arguments
@@ -62,10 +80,100 @@ impl Issuer {
                               support_revocation: bool) -> Result<(CredentialPublicKey,
                                                                    CredentialPrivateKey,
                                                                    CredentialKeyCorrectnessProof), IndyCryptoError> {
-        trace!("Issuer::new_credential_def: >>> credential_schema: {:?}, support_revocation: {:?}", credential_schema, support_revocation);
+        Issuer::new_credential_def_with_config(credential_schema,
+                                               non_credential_schema,
+                                               support_revocation,
+                                               &CredentialKeyConfig::default())
+    }
+
+    /// Creates and returns credential definition with primary key of the given configuration (e.g. larger modulus).
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `support_revocation` - If true non revocation part of keys will be generated.
+    /// * `config` - Sizes of primary key and signature parameters.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::CredentialKeyConfig;
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let config = CredentialKeyConfig::new(3072).unwrap();
+    /// assert_eq!(3072, config.modulus_size());
+    /// assert!(CredentialKeyConfig::new(1024).is_err());
+    /// ```
+    pub fn new_credential_def_with_config(credential_schema: &CredentialSchema,
+                                          non_credential_schema: &NonCredentialSchema,
+                                          support_revocation: bool,
+                                          config: &CredentialKeyConfig) -> Result<(CredentialPublicKey,
+                                                                                   CredentialPrivateKey,
+                                                                                   CredentialKeyCorrectnessProof), IndyCryptoError> {
+        trace!("Issuer::new_credential_def: >>> credential_schema: {:?}, support_revocation: {:?}, config: {:?}",
+               credential_schema, support_revocation, config);
+
+        let res = Issuer::_new_credential_def(credential_schema, non_credential_schema, support_revocation, config, |size| {
+            let mut primes = generate_safe_primes(size, 2)?;
+            let q_safe = primes.pop().ok_or(IndyCryptoError::InvalidState("Safe prime not generated".to_string()))?;
+            let p_safe = primes.pop().ok_or(IndyCryptoError::InvalidState("Safe prime not generated".to_string()))?;
+            Ok((p_safe, q_safe))
+        })?;
+
+        trace!("Issuer::new_credential_def: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
+               res.0, secret!(&res.1), res.2);
+
+        Ok(res)
+    }
+
+    /// Creates and returns credential definition reporting progress of safe primes generation.
+    /// Generation can be aborted from other thread with cancellation token (`InvalidState` error is returned).
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `support_revocation` - If true non revocation part of keys will be generated.
+    /// * `config` - Sizes of primary key and signature parameters.
+    /// * `on_progress` - Callback called after every tested safe prime candidate.
+    /// * `cancellation` - Cancellation token.
+    pub fn new_credential_def_with_progress<F>(credential_schema: &CredentialSchema,
+                                               non_credential_schema: &NonCredentialSchema,
+                                               support_revocation: bool,
+                                               config: &CredentialKeyConfig,
+                                               mut on_progress: F,
+                                               cancellation: &CancellationToken) -> Result<(CredentialPublicKey,
+                                                                                            CredentialPrivateKey,
+                                                                                            CredentialKeyCorrectnessProof), IndyCryptoError>
+        where F: FnMut(&KeyGenProgress) {
+        trace!("Issuer::new_credential_def_with_progress: >>> credential_schema: {:?}, support_revocation: {:?}, config: {:?}",
+               credential_schema, support_revocation, config);
+
+        let mut progress = KeyGenProgress::default();
+
+        let res = Issuer::_new_credential_def(credential_schema, non_credential_schema, support_revocation, config,
+                                              |size| {
+                                                  let p_safe = generate_safe_prime_with_progress(size, &mut progress, &mut on_progress, cancellation)?;
+                                                  let q_safe = generate_safe_prime_with_progress(size, &mut progress, &mut on_progress, cancellation)?;
+                                                  Ok((p_safe, q_safe))
+                                              })?;
+
+        trace!("Issuer::new_credential_def_with_progress: <<< cred_pub_key: {:?}, cred_priv_key: {:?}, cred_key_correctness_proof: {:?}",
+               res.0, secret!(&res.1), res.2);
+
+        Ok(res)
+    }
+
+    fn _new_credential_def<G>(credential_schema: &CredentialSchema,
+                              non_credential_schema: &NonCredentialSchema,
+                              support_revocation: bool,
+                              config: &CredentialKeyConfig,
+                              generate_safe_primes: G) -> Result<(CredentialPublicKey,
+                                                                 CredentialPrivateKey,
+                                                                 CredentialKeyCorrectnessProof), IndyCryptoError>
+        where G: FnOnce(usize) -> Result<(BigNumber, BigNumber), IndyCryptoError> {
+        config.validate()?;
 
         let (p_pub_key, p_priv_key, p_key_meta) =
-            Issuer::_new_credential_primary_keys(credential_schema, non_credential_schema)?;
+            Issuer::_new_credential_primary_keys(credential_schema, non_credential_schema, config, generate_safe_primes)?;
 
         let (r_pub_key, r_priv_key) = if support_revocation {
             Issuer::_new_credential_revocation_keys()
@@ -74,7 +182,7 @@ impl Issuer {
             (None, None)
         };
 
-        let cred_pub_key = CredentialPublicKey { p_key: p_pub_key, r_key: r_pub_key };
+        let cred_pub_key = CredentialPublicKey { p_key: p_pub_key, r_key: r_pub_key, usage_constraints: None };
         let cred_priv_key = CredentialPrivateKey { p_key: p_priv_key, r_key: r_priv_key };
         let cred_key_correctness_proof =
             Issuer::_new_credential_key_correctness_proof(&cred_pub_key.p_key,
@@ -87,6 +195,150 @@ impl Issuer {
         Ok((cred_pub_key, cred_priv_key, cred_key_correctness_proof))
     }
 
+    /// Creates and returns credential key usage constraints entity builder.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+    /// constraints_builder.set_max_attrs(10).unwrap();
+    /// constraints_builder.set_revocable_only(true).unwrap();
+    /// let _constraints = constraints_builder.finalize().unwrap();
+    /// ```
+    pub fn new_key_usage_constraints_builder() -> Result<CredentialKeyUsageConstraintsBuilder, IndyCryptoError> {
+        CredentialKeyUsageConstraintsBuilder::new()
+    }
+
+    /// Signs usage constraints with credential private key and embeds them into credential public key.
+    /// Must be called before credential public key is published.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_priv_key` - Credential private key.
+    /// * `constraints` - Credential key usage constraints.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (mut cred_pub_key, cred_priv_key, _cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+    ///
+    /// let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+    /// constraints_builder.set_max_attrs(10).unwrap();
+    /// let constraints = constraints_builder.finalize().unwrap();
+    ///
+    /// Issuer::add_key_usage_constraints(&mut cred_pub_key, &cred_priv_key, &constraints).unwrap();
+    /// assert_eq!(Some(&constraints), cred_pub_key.usage_constraints().unwrap());
+    /// ```
+    pub fn add_key_usage_constraints(credential_pub_key: &mut CredentialPublicKey,
+                                     credential_priv_key: &CredentialPrivateKey,
+                                     constraints: &CredentialKeyUsageConstraints) -> Result<(), IndyCryptoError> {
+        trace!("Issuer::add_key_usage_constraints: >>> credential_pub_key: {:?}, credential_priv_key: {:?}, constraints: {:?}",
+               credential_pub_key, secret!(credential_priv_key), constraints);
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let n = &credential_pub_key.p_key.n;
+        let p_q = credential_priv_key.p_key.p.mul(&credential_priv_key.p_key.q, Some(&mut ctx))?;
+
+        let e = BigNumber::from_u32(KEY_USAGE_CONSTRAINTS_EXPONENT)?;
+        let d = e.inverse(&p_q, Some(&mut ctx))?;
+
+        let signature = constraints.hash(n, &mut ctx)?.mod_exp(&d, n, Some(&mut ctx))?;
+
+        credential_pub_key.usage_constraints = Some(SignedCredentialKeyUsageConstraints {
+            constraints: constraints.clone(),
+            signature
+        });
+
+        trace!("Issuer::add_key_usage_constraints: <<<");
+
+        Ok(())
+    }
+
+    /// Signs statement linking predecessor credential key to the successor key with predecessor private key.
+    ///
+    /// # Arguments
+    /// * `old_pub_key` - Predecessor credential public key.
+    /// * `old_priv_key` - Predecessor credential private key.
+    /// * `new_pub_key` - Successor credential public key.
+    /// * `rotated_at` - Time of the rotation; verifiers accept predecessor key for a grace period after it.
+    pub fn new_key_rotation_statement(old_pub_key: &CredentialPublicKey,
+                                      old_priv_key: &CredentialPrivateKey,
+                                      new_pub_key: &CredentialPublicKey,
+                                      rotated_at: Timestamp) -> Result<KeyRotationStatement, IndyCryptoError> {
+        trace!("Issuer::new_key_rotation_statement: >>> old_pub_key: {:?}, old_priv_key: {:?}, new_pub_key: {:?}, rotated_at: {:?}",
+               old_pub_key, secret!(old_priv_key), new_pub_key, rotated_at);
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let old_key_fingerprint = old_pub_key.fingerprint()?;
+        let new_key_fingerprint = new_pub_key.fingerprint()?;
+
+        if old_key_fingerprint == new_key_fingerprint {
+            return Err(IndyCryptoError::InvalidParam3("Successor key is the same as predecessor key".to_string()));
+        }
+
+        let n = &old_pub_key.p_key.n;
+        let p_q = old_priv_key.p_key.p.mul(&old_priv_key.p_key.q, Some(&mut ctx))?;
+
+        let e = BigNumber::from_u32(KEY_ROTATION_EXPONENT)?;
+        let d = e.inverse(&p_q, Some(&mut ctx))?;
+
+        let signature = KeyRotationStatement::hash(&old_key_fingerprint, &new_key_fingerprint, rotated_at, n, &mut ctx)?
+            .mod_exp(&d, n, Some(&mut ctx))?;
+
+        let statement = KeyRotationStatement { old_key_fingerprint, new_key_fingerprint, rotated_at, signature };
+
+        if !statement.verify(old_pub_key, new_pub_key)? {
+            return Err(IndyCryptoError::InvalidStructure("Predecessor private key doesn't correspond to public key".to_string()));
+        }
+
+        trace!("Issuer::new_key_rotation_statement: <<< statement: {:?}", statement);
+
+        Ok(statement)
+    }
+
+    /// Creates successor credential definition and statement linking it to the predecessor one.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema entity.
+    /// * `non_credential_schema` - Non credential schema entity.
+    /// * `support_revocation` - If true non revocation part of credential keys will be generated.
+    /// * `old_pub_key` - Predecessor credential public key.
+    /// * `old_priv_key` - Predecessor credential private key.
+    /// * `rotated_at` - Time of the rotation.
+    pub fn new_successor_credential_def(credential_schema: &CredentialSchema,
+                                        non_credential_schema: &NonCredentialSchema,
+                                        support_revocation: bool,
+                                        old_pub_key: &CredentialPublicKey,
+                                        old_priv_key: &CredentialPrivateKey,
+                                        rotated_at: Timestamp) -> Result<(CredentialPublicKey,
+                                                                          CredentialPrivateKey,
+                                                                          CredentialKeyCorrectnessProof,
+                                                                          KeyRotationStatement), IndyCryptoError> {
+        trace!("Issuer::new_successor_credential_def: >>> credential_schema: {:?}, non_credential_schema: {:?}, support_revocation: {:?}, \
+                old_pub_key: {:?}, rotated_at: {:?}", credential_schema, non_credential_schema, support_revocation, old_pub_key, rotated_at);
+
+        let (new_pub_key, new_priv_key, key_correctness_proof) =
+            Issuer::new_credential_def(credential_schema, non_credential_schema, support_revocation)?;
+
+        let statement = Issuer::new_key_rotation_statement(old_pub_key, old_priv_key, &new_pub_key, rotated_at)?;
+
+        trace!("Issuer::new_successor_credential_def: <<< new_pub_key: {:?}, statement: {:?}", new_pub_key, statement);
+
+        Ok((new_pub_key, new_priv_key, key_correctness_proof, statement))
+    }
+
     /// Creates and returns revocation registry definition (public and private keys, accumulator and tails generator) entities.
     ///
     /// # Arguments
@@ -126,7 +378,80 @@ impl Issuer {
             .as_ref()
             .ok_or(IndyCryptoError::InvalidStructure(format!("There are not revocation keys in the credential public key.")))?;
 
-        let (rev_key_pub, rev_key_priv) = Issuer::_new_revocation_registry_keys(cred_rev_pub_key, max_cred_num)?;
+        let gamma = GroupOrderElement::new()?;
+
+        let res = Issuer::_new_revocation_registry_def(cred_rev_pub_key, max_cred_num, issuance_by_default, gamma)?;
+
+        trace!("Issuer::new_revocation_registry_def: <<< rev_key_pub: {:?}, rev_key_priv: {:?}, rev_reg: {:?}, rev_tails_generator: {:?}",
+               res.0, secret!(&res.1), res.2, res.3);
+
+        Ok(res)
+    }
+
+    /// Creates reproducible revocation registry definition from secret seed.
+    ///
+    /// The same seed, credential public key and max credential number always give the same
+    /// revocation keys, registry and tails, so the tails can be regenerated instead of stored.
+    /// Revocation private key is derived from the seed: the seed must be kept as secret as the key.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    /// * `max_cred_num` - Max credential number in generated registry.
+    /// * `issuance_by_default` - Type of issuance.
+    /// * `seed` - Secret seed of revocation private key.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::pair::SecretSeed;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (cred_pub_key, _cred_priv_key, _cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+    ///
+    /// let seed = SecretSeed::generate().unwrap();
+    /// let (_rev_key_pub, _rev_key_priv, _rev_reg, _rev_tails_generator) = Issuer::new_revocation_registry_def_from_seed(&cred_pub_key, 5, false, &seed).unwrap();
+    /// ```
+    pub fn new_revocation_registry_def_from_seed(credential_pub_key: &CredentialPublicKey,
+                                                 max_cred_num: u32,
+                                                 issuance_by_default: bool,
+                                                 seed: &SecretSeed) -> Result<(RevocationKeyPublic,
+                                                                               RevocationKeyPrivate,
+                                                                               RevocationRegistry,
+                                                                               RevocationTailsGenerator), IndyCryptoError> {
+        trace!("Issuer::new_revocation_registry_def_from_seed: >>> credential_pub_key: {:?}, max_cred_num: {:?}, issuance_by_default: {:?}, seed: {:?}",
+               credential_pub_key, max_cred_num, issuance_by_default, seed);
+
+        let cred_rev_pub_key: &CredentialRevocationPublicKey = credential_pub_key.r_key
+            .as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure("There are not revocation keys in the credential public key.".to_string()))?;
+
+        let mut info = credential_pub_key.fingerprint()?;
+        info.extend_from_slice(&transform_u32_to_array_of_u8(max_cred_num));
+        let gamma = GroupOrderElement::new_from_domain_seed(&REVOCATION_REGISTRY_GAMMA_DOMAIN, seed, &info)?;
+
+        let res = Issuer::_new_revocation_registry_def(cred_rev_pub_key, max_cred_num, issuance_by_default, gamma)?;
+
+        trace!("Issuer::new_revocation_registry_def_from_seed: <<< rev_key_pub: {:?}, rev_key_priv: {:?}, rev_reg: {:?}, rev_tails_generator: {:?}",
+               res.0, secret!(&res.1), res.2, res.3);
+
+        Ok(res)
+    }
+
+    fn _new_revocation_registry_def(cred_rev_pub_key: &CredentialRevocationPublicKey,
+                                    max_cred_num: u32,
+                                    issuance_by_default: bool,
+                                    gamma: GroupOrderElement) -> Result<(RevocationKeyPublic,
+                                                                         RevocationKeyPrivate,
+                                                                         RevocationRegistry,
+                                                                         RevocationTailsGenerator), IndyCryptoError> {
+        let (rev_key_pub, rev_key_priv) = Issuer::_new_revocation_registry_keys(cred_rev_pub_key, max_cred_num, gamma)?;
 
         let rev_reg = Issuer::_new_revocation_registry(cred_rev_pub_key,
                                                        &rev_key_priv,
@@ -138,9 +463,6 @@ impl Issuer {
             rev_key_priv.gamma.clone(),
             cred_rev_pub_key.g_dash.clone());
 
-        trace!("Issuer::new_revocation_registry_def: <<< rev_key_pub: {:?}, rev_key_priv: {:?}, rev_reg: {:?}, rev_tails_generator: {:?}",
-               rev_key_pub, secret!(&rev_key_priv), rev_reg, rev_tails_generator);
-
         Ok((rev_key_pub, rev_key_priv, rev_reg, rev_tails_generator))
     }
 
@@ -222,6 +544,75 @@ impl Issuer {
                            credential_values: &CredentialValues,
                            credential_pub_key: &CredentialPublicKey,
                            credential_priv_key: &CredentialPrivateKey) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> {
+        Issuer::sign_credential_at(prover_id,
+                                   blinded_credential_secrets,
+                                   blinded_credential_secrets_correctness_proof,
+                                   credential_nonce,
+                                   credential_issuance_nonce,
+                                   credential_values,
+                                   credential_pub_key,
+                                   credential_priv_key,
+                                   &SystemClock::new())
+    }
+
+    /// Signs credential values with primary keys only bound to the nonces of credential issuance session.
+    ///
+    /// Same as `sign_credential` with `credential_nonce` and `credential_issuance_nonce` derived from
+    /// both offer and request nonces, so blinded credential secrets created in other issuance session are rejected.
+    ///
+    /// # Arguments
+    /// * `prover_id` - Prover identifier.
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
+    /// * `nonces` - Nonces of credential issuance session.
+    /// * `credential_values` - Credential values to be signed.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_priv_key` - Credential private key.
+    pub fn sign_credential_with_nonces(prover_id: &str,
+                                       blinded_credential_secrets: &BlindedCredentialSecrets,
+                                       blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                       nonces: &CredentialIssuanceNonces,
+                                       credential_values: &CredentialValues,
+                                       credential_pub_key: &CredentialPublicKey,
+                                       credential_priv_key: &CredentialPrivateKey) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> {
+        trace!("Issuer::sign_credential_with_nonces: >>> nonces: {:?}", nonces);
+
+        let res = Issuer::sign_credential(prover_id,
+                                          blinded_credential_secrets,
+                                          blinded_credential_secrets_correctness_proof,
+                                          &nonces.blinding_nonce()?,
+                                          &nonces.signature_nonce()?,
+                                          credential_values,
+                                          credential_pub_key,
+                                          credential_priv_key)?;
+
+        trace!("Issuer::sign_credential_with_nonces: <<<");
+
+        Ok(res)
+    }
+
+    /// Signs credential values with primary keys only
+    /// checking usage constraints of credential keys (validity period) at the current time of the clock.
+    ///
+    /// # Arguments
+    /// * `prover_id` - Prover identifier.
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
+    /// * `credential_nonce` - Nonce used for verification of blinded_credential_secrets_correctness_proof.
+    /// * `credential_issuance_nonce` - Nonce used for creation of signature_correctness_proof.
+    /// * `credential_values` - Credential values to be signed.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_priv_key` - Credential private key.
+    /// * `clock` - Source of the current time.
+    pub fn sign_credential_at<C>(prover_id: &str,
+                                 blinded_credential_secrets: &BlindedCredentialSecrets,
+                                 blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                 credential_nonce: &Nonce,
+                                 credential_issuance_nonce: &Nonce,
+                                 credential_values: &CredentialValues,
+                                 credential_pub_key: &CredentialPublicKey,
+                                 credential_priv_key: &CredentialPrivateKey,
+                                 clock: &C) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> where C: Clock {
         trace!("Issuer::sign_credential: >>> prover_id: {:?}\n \
                                              blinded_credential_secrets: {:?}\n \
                                              blinded_credential_secrets_correctness_proof: {:?}\n \
@@ -239,21 +630,98 @@ impl Issuer {
                                             credential_pub_key,
                                             secret!(credential_priv_key));
 
+        // In the anoncreds whitepaper, `credential context` is denoted by `m2`
+        let cred_context = Issuer::_gen_credential_context(prover_id, None)?;
+
+        let (cred_signature, signature_correctness_proof) = Issuer::_sign_credential(&cred_context,
+                                                                                     blinded_credential_secrets,
+                                                                                     blinded_credential_secrets_correctness_proof,
+                                                                                     credential_nonce,
+                                                                                     credential_issuance_nonce,
+                                                                                     credential_values,
+                                                                                     credential_pub_key,
+                                                                                     credential_priv_key,
+                                                                                     clock)?;
+
+        trace!("Issuer::sign_credential: <<< cred_signature: {:?}, signature_correctness_proof: {:?}",
+               secret!(&cred_signature), signature_correctness_proof);
+
+        Ok((cred_signature, signature_correctness_proof))
+    }
+
+    /// Signs extension credential: credential with attributes added to the schema after the base credential was issued.
+    ///
+    /// Extension credential is signed with credential context (`m2`) of the base credential,
+    /// so Prover presents both as one credential (see `ProofBuilder::add_extension_sub_proof_request`)
+    /// without re-issuance of the base credential.
+    ///
+    /// # Arguments
+    /// * `prover_id` - Prover identifier the base credential was issued for.
+    /// * `base_rev_idx` - (Optional) Index of the base credential in revocation registry.
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
+    /// * `credential_nonce` - Nonce used for verification of blinded_credential_secrets_correctness_proof.
+    /// * `credential_issuance_nonce` - Nonce used for creation of signature_correctness_proof.
+    /// * `credential_values` - Values of added attributes and the hidden ones (e.g. master secret) to be signed.
+    /// * `credential_pub_key` - Credential public key of extension schema.
+    /// * `credential_priv_key` - Credential private key of extension schema.
+    /// * `clock` - Source of the current time usage constraints of credential keys are checked at.
+    pub fn sign_extension_credential<C>(prover_id: &str,
+                                        base_rev_idx: Option<u32>,
+                                        blinded_credential_secrets: &BlindedCredentialSecrets,
+                                        blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                        credential_nonce: &Nonce,
+                                        credential_issuance_nonce: &Nonce,
+                                        credential_values: &CredentialValues,
+                                        credential_pub_key: &CredentialPublicKey,
+                                        credential_priv_key: &CredentialPrivateKey,
+                                        clock: &C) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> where C: Clock {
+        trace!("Issuer::sign_extension_credential: >>> prover_id: {:?}, base_rev_idx: {:?}, credential_values: {:?}, credential_pub_key: {:?}",
+               prover_id, secret!(base_rev_idx), secret!(credential_values), credential_pub_key);
+
+        let cred_context = Issuer::_gen_credential_context(prover_id, base_rev_idx)?;
+
+        let (cred_signature, signature_correctness_proof) = Issuer::_sign_credential(&cred_context,
+                                                                                     blinded_credential_secrets,
+                                                                                     blinded_credential_secrets_correctness_proof,
+                                                                                     credential_nonce,
+                                                                                     credential_issuance_nonce,
+                                                                                     credential_values,
+                                                                                     credential_pub_key,
+                                                                                     credential_priv_key,
+                                                                                     clock)?;
+
+        trace!("Issuer::sign_extension_credential: <<< cred_signature: {:?}, signature_correctness_proof: {:?}",
+               secret!(&cred_signature), signature_correctness_proof);
+
+        Ok((cred_signature, signature_correctness_proof))
+    }
+
+    fn _sign_credential<C>(cred_context: &BigNumber,
+                           blinded_credential_secrets: &BlindedCredentialSecrets,
+                           blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                           credential_nonce: &Nonce,
+                           credential_issuance_nonce: &Nonce,
+                           credential_values: &CredentialValues,
+                           credential_pub_key: &CredentialPublicKey,
+                           credential_priv_key: &CredentialPrivateKey,
+                           clock: &C) -> Result<(CredentialSignature, SignatureCorrectnessProof), IndyCryptoError> where C: Clock {
+        Issuer::_check_key_usage_constraints(credential_pub_key, credential_values, false, clock)?;
+
+        Issuer::validate_blinded_credential_secrets(blinded_credential_secrets, credential_pub_key)?;
+
         Issuer::_check_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
                                                                blinded_credential_secrets_correctness_proof,
                                                                credential_nonce,
                                                                &credential_pub_key.p_key)?;
 
-        // In the anoncreds whitepaper, `credential context` is denoted by `m2`
-        let cred_context = Issuer::_gen_credential_context(prover_id, None)?;
-
-        let (p_cred, q) = Issuer::_new_primary_credential(&cred_context,
+        let (p_cred, q) = Issuer::_new_primary_credential(cred_context,
                                                           credential_pub_key,
                                                           credential_priv_key,
                                                           blinded_credential_secrets,
                                                           credential_values)?;
 
-        let cred_signature = CredentialSignature { p_credential: p_cred, r_credential: None };
+        let cred_signature = CredentialSignature { p_credential: p_cred, r_credential: None, attr_r_credentials: BTreeMap::new() };
 
         let signature_correctness_proof = Issuer::_new_signature_correctness_proof(&credential_pub_key.p_key,
                                                                                    &credential_priv_key.p_key,
@@ -261,10 +729,6 @@ impl Issuer {
                                                                                    &q,
                                                                                    credential_issuance_nonce)?;
 
-
-        trace!("Issuer::sign_credential: <<< cred_signature: {:?}, signature_correctness_proof: {:?}",
-               secret!(&cred_signature), signature_correctness_proof);
-
         Ok((cred_signature, signature_correctness_proof))
     }
 
@@ -352,12 +816,68 @@ impl Issuer {
                                            rev_tails_accessor: &RTA)
                                            -> Result<(CredentialSignature, SignatureCorrectnessProof, Option<RevocationRegistryDelta>),
                                                IndyCryptoError> where RTA: RevocationTailsAccessor {
+        Issuer::sign_credential_with_revoc_at(prover_id,
+                                              blinded_credential_secrets,
+                                              blinded_credential_secrets_correctness_proof,
+                                              credential_nonce,
+                                              credential_issuance_nonce,
+                                              credential_values,
+                                              credential_pub_key,
+                                              credential_priv_key,
+                                              rev_idx,
+                                              max_cred_num,
+                                              issuance_by_default,
+                                              rev_reg,
+                                              rev_key_priv,
+                                              rev_tails_accessor,
+                                              &SystemClock::new())
+    }
+
+    /// Signs credential values with both primary and revocation keys
+    /// checking usage constraints of credential keys (validity period) at the current time of the clock.
+    ///
+    /// # Arguments
+    /// * `prover_id` - Prover identifier.
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
+    /// * `credential_nonce` - Nonce used for verification of blinded_credential_secrets_correctness_proof.
+    /// * `credential_issuance_nonce` - Nonce used for creation of signature_correctness_proof.
+    /// * `credential_values` - Credential values to be signed.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_priv_key` - Credential private key.
+    /// * `rev_idx` - User index in revocation accumulator. Required for non-revocation credential_signature part generation.
+    /// * `max_cred_num` - Max credential number in generated registry.
+    /// * `rev_reg` - Revocation registry.
+    /// * `rev_key_priv` - Revocation registry private key.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    /// * `clock` - Source of the current time.
+    pub fn sign_credential_with_revoc_at<RTA, C>(prover_id: &str,
+                                                 blinded_credential_secrets: &BlindedCredentialSecrets,
+                                                 blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                                 credential_nonce: &Nonce,
+                                                 credential_issuance_nonce: &Nonce,
+                                                 credential_values: &CredentialValues,
+                                                 credential_pub_key: &CredentialPublicKey,
+                                                 credential_priv_key: &CredentialPrivateKey,
+                                                 rev_idx: u32,
+                                                 max_cred_num: u32,
+                                                 issuance_by_default: bool,
+                                                 rev_reg: &mut RevocationRegistry,
+                                                 rev_key_priv: &RevocationKeyPrivate,
+                                                 rev_tails_accessor: &RTA,
+                                                 clock: &C)
+                                                 -> Result<(CredentialSignature, SignatureCorrectnessProof, Option<RevocationRegistryDelta>),
+                                                     IndyCryptoError> where RTA: RevocationTailsAccessor, C: Clock {
         trace!("Issuer::sign_credential: >>> prover_id: {:?}, blinded_credential_secrets: {:?}, blinded_credential_secrets_correctness_proof: {:?},\
         credential_nonce: {:?}, credential_issuance_nonce: {:?}, credential_values: {:?}, credential_pub_key: {:?}, credential_priv_key: {:?}, \
         rev_idx: {:?}, max_cred_num: {:?}, rev_reg: {:?}, rev_key_priv: {:?}",
                prover_id, blinded_credential_secrets, blinded_credential_secrets_correctness_proof, credential_nonce, secret!(credential_values), credential_issuance_nonce,
                credential_pub_key, secret!(credential_priv_key), secret!(rev_idx), max_cred_num, rev_reg, secret!(rev_key_priv));
 
+        Issuer::_check_key_usage_constraints(credential_pub_key, credential_values, true, clock)?;
+
+        Issuer::validate_blinded_credential_secrets(blinded_credential_secrets, credential_pub_key)?;
+
         Issuer::_check_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
                                                                     blinded_credential_secrets_correctness_proof,
                                                                     credential_nonce,
@@ -383,7 +903,7 @@ impl Issuer {
                                                                              rev_key_priv,
                                                                              rev_tails_accessor)?;
 
-        let cred_signature = CredentialSignature { p_credential: p_cred, r_credential: Some(r_cred) };
+        let cred_signature = CredentialSignature { p_credential: p_cred, r_credential: Some(r_cred), attr_r_credentials: BTreeMap::new() };
 
         let signature_correctness_proof = Issuer::_new_signature_correctness_proof(&credential_pub_key.p_key,
                                                                                    &credential_priv_key.p_key,
@@ -398,6 +918,131 @@ impl Issuer {
         Ok((cred_signature, signature_correctness_proof, rev_reg_delta))
     }
 
+    /// Signs a single credential attribute with revocation keys,
+    /// so this attribute can be revoked independently of the rest of the credential.
+    ///
+    /// Attribute signature is added to the given credential signature and shares credential context with it.
+    ///
+    /// # Arguments
+    /// * `attr` - Name of the attribute that gets its own revocation index.
+    /// * `credential_signature` - Credential signature created by `sign_credential` or `sign_credential_with_revoc`.
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `credential_values` - Credential values signed in credential signature.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `credential_priv_key` - Credential private key.
+    /// * `rev_idx` - Index of the attribute in revocation accumulator.
+    /// * `max_cred_num` - Max credential number in generated registry.
+    /// * `issuance_by_default` - Type of issuance.
+    /// * `rev_reg` - Revocation registry the attribute is registered in.
+    /// * `rev_key_priv` - Revocation registry private key.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::{new_nonce, SimpleTailsAccessor};
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::cl::prover::Prover;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("category").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (cred_pub_key, cred_priv_key, cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+    ///
+    /// let max_cred_num = 5;
+    /// let (_rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) = Issuer::new_revocation_registry_def(&cred_pub_key, max_cred_num, false).unwrap();
+    ///
+    /// let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+    ///
+    /// let master_secret = Prover::new_master_secret().unwrap();
+    ///
+    /// let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    /// credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap());
+    /// credential_values_builder.add_dec_known("category", "3").unwrap();
+    /// let cred_values = credential_values_builder.finalize().unwrap();
+    ///
+    /// let credential_nonce = new_nonce().unwrap();
+    ///
+    /// let (blinded_credential_secrets, _credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+    ///     Prover::blind_credential_secrets(&cred_pub_key, &cred_key_correctness_proof, &cred_values, &credential_nonce).unwrap();
+    ///
+    /// let credential_issuance_nonce = new_nonce().unwrap();
+    ///
+    /// let (mut cred_signature, _signature_correctness_proof) =
+    ///     Issuer::sign_credential("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW",
+    ///                             &blinded_credential_secrets,
+    ///                             &blinded_credential_secrets_correctness_proof,
+    ///                             &credential_nonce,
+    ///                             &credential_issuance_nonce,
+    ///                             &cred_values,
+    ///                             &cred_pub_key,
+    ///                             &cred_priv_key).unwrap();
+    ///
+    /// let _rev_reg_delta = Issuer::sign_attribute_with_revoc("category",
+    ///                                                        &mut cred_signature,
+    ///                                                        &blinded_credential_secrets,
+    ///                                                        &cred_values,
+    ///                                                        &cred_pub_key,
+    ///                                                        &cred_priv_key,
+    ///                                                        1,
+    ///                                                        max_cred_num,
+    ///                                                        false,
+    ///                                                        &mut rev_reg,
+    ///                                                        &rev_key_priv,
+    ///                                                        &simple_tail_accessor).unwrap();
+    /// assert_eq!(Some(1), cred_signature.extract_attr_index("category"));
+    /// ```
+    pub fn sign_attribute_with_revoc<RTA>(attr: &str,
+                                          credential_signature: &mut CredentialSignature,
+                                          blinded_credential_secrets: &BlindedCredentialSecrets,
+                                          credential_values: &CredentialValues,
+                                          credential_pub_key: &CredentialPublicKey,
+                                          credential_priv_key: &CredentialPrivateKey,
+                                          rev_idx: u32,
+                                          max_cred_num: u32,
+                                          issuance_by_default: bool,
+                                          rev_reg: &mut RevocationRegistry,
+                                          rev_key_priv: &RevocationKeyPrivate,
+                                          rev_tails_accessor: &RTA) -> Result<Option<RevocationRegistryDelta>, IndyCryptoError>
+        where RTA: RevocationTailsAccessor {
+        trace!("Issuer::sign_attribute_with_revoc: >>> attr: {:?}, credential_signature: {:?}, blinded_credential_secrets: {:?}, \
+        credential_values: {:?}, credential_pub_key: {:?}, credential_priv_key: {:?}, rev_idx: {:?}, max_cred_num: {:?}, \
+        issuance_by_default: {:?}, rev_reg: {:?}, rev_key_priv: {:?}",
+               attr, secret!(&credential_signature), blinded_credential_secrets, secret!(credential_values), credential_pub_key,
+               secret!(credential_priv_key), secret!(rev_idx), max_cred_num, issuance_by_default, rev_reg, secret!(rev_key_priv));
+
+        if !credential_values.attrs_values.contains_key(attr) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute '{}'", attr)));
+        }
+
+        if credential_signature.attr_r_credentials.contains_key(attr) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Attribute '{}' is already signed with revocation keys", attr)));
+        }
+
+        Issuer::validate_blinded_credential_secrets(blinded_credential_secrets, credential_pub_key)?;
+
+        let (r_cred, rev_reg_delta) = Issuer::_new_non_revocation_credential(rev_idx,
+                                                                             &credential_signature.p_credential.m_2,
+                                                                             blinded_credential_secrets,
+                                                                             credential_pub_key,
+                                                                             credential_priv_key,
+                                                                             max_cred_num,
+                                                                             issuance_by_default,
+                                                                             rev_reg,
+                                                                             rev_key_priv,
+                                                                             rev_tails_accessor)?;
+
+        credential_signature.attr_r_credentials.insert(attr.to_owned(), r_cred);
+
+        trace!("Issuer::sign_attribute_with_revoc: <<< rev_reg_delta: {:?}", rev_reg_delta);
+
+        Ok(rev_reg_delta)
+    }
+
     /// Revokes a credential by a rev_idx in a given revocation registry.
     ///
     /// # Arguments
@@ -468,8 +1113,9 @@ impl Issuer {
 
         let index = Issuer::_get_index(max_cred_num, rev_idx);
 
-        rev_tails_accessor.access_tail(index, &mut |tail| {
-            rev_reg.accum = rev_reg.accum.sub(tail).unwrap();
+        access_tail_with(rev_tails_accessor, index, |tail| {
+            rev_reg.accum = rev_reg.accum.sub(tail)?;
+            Ok(())
         })?;
 
         let rev_reg_delta = RevocationRegistryDelta {
@@ -484,6 +1130,80 @@ impl Issuer {
         Ok(rev_reg_delta)
     }
 
+    /// Revokes a set of credentials by rev_idxs in a given revocation registry
+    /// with a single accumulator update.
+    ///
+    /// # Arguments
+    /// * `rev_reg` - Revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_idxs` - Indices of the users in the revocation registry.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::SimpleTailsAccessor;
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use std::collections::HashSet;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("name").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (cred_pub_key, _cred_priv_key, _cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+    ///
+    /// let max_cred_num = 5;
+    /// let (_rev_key_pub, _rev_key_priv, mut rev_reg, mut rev_tails_generator) = Issuer::new_revocation_registry_def(&cred_pub_key, max_cred_num, true).unwrap();
+    ///
+    /// let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+    ///
+    /// let rev_idxs: HashSet<u32> = [1, 3, 4].iter().cloned().collect();
+    /// Issuer::revoke_credentials(&mut rev_reg, max_cred_num, &rev_idxs, &simple_tail_accessor).unwrap();
+    /// ```
+    pub fn revoke_credentials<RTA>(rev_reg: &mut RevocationRegistry,
+                                   max_cred_num: u32,
+                                   rev_idxs: &HashSet<u32>,
+                                   rev_tails_accessor: &RTA) -> Result<RevocationRegistryDelta, IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Issuer::revoke_credentials: >>> rev_reg: {:?}, max_cred_num: {:?}, rev_idxs: {:?}", rev_reg, max_cred_num, secret!(rev_idxs));
+
+        if rev_idxs.is_empty() {
+            return Err(IndyCryptoError::InvalidParam3("Set of revocation indices is empty".to_string()));
+        }
+
+        if let Some(rev_idx) = rev_idxs.iter().find(|rev_idx| **rev_idx == 0 || **rev_idx > max_cred_num) {
+            return Err(IndyCryptoError::InvalidParam3(format!("Revocation index {} is out of registry range", rev_idx)));
+        }
+
+        let prev_accum = rev_reg.accum;
+
+        let mut tails_sum = PointG2::new_inf()?;
+
+        for rev_idx in rev_idxs {
+            let index = Issuer::_get_index(max_cred_num, *rev_idx);
+
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                tails_sum = tails_sum.add(tail)?;
+                Ok(())
+            })?;
+        }
+
+        rev_reg.accum = rev_reg.accum.sub(&tails_sum)?;
+
+        let rev_reg_delta = RevocationRegistryDelta {
+            prev_accum: Some(prev_accum),
+            accum: rev_reg.accum,
+            issued: HashSet::new(),
+            revoked: rev_idxs.clone()
+        };
+
+        trace!("Issuer::revoke_credentials: <<< rev_reg_delta: {:?}", rev_reg_delta);
+
+        Ok(rev_reg_delta)
+    }
+
     /// Recovery a credential by a rev_idx in a given revocation registry
     ///
     /// # Arguments
@@ -556,8 +1276,9 @@ impl Issuer {
 
         let index = Issuer::_get_index(max_cred_num, rev_idx);
 
-        rev_tails_accessor.access_tail(index, &mut |tail| {
-            rev_reg.accum = rev_reg.accum.add(tail).unwrap();
+        access_tail_with(rev_tails_accessor, index, |tail| {
+            rev_reg.accum = rev_reg.accum.add(tail)?;
+            Ok(())
         })?;
 
         let rev_reg_delta = RevocationRegistryDelta {
@@ -572,11 +1293,269 @@ impl Issuer {
         Ok(rev_reg_delta)
     }
 
-    fn _new_credential_primary_keys(credential_schema: &CredentialSchema,
-                                    non_credential_schema: &NonCredentialSchema) ->
+    /// Rotates revocation registry into a new accumulator epoch.
+    ///
+    /// Accumulator is recomputed from scratch over `issued` indices, all other indices are revoked.
+    /// Returned transition must be published, so verifiers can follow the registry
+    /// and provers can migrate their witnesses with `Witness::migrate_to_epoch`.
+    ///
+    /// # Arguments
+    /// * `rev_reg` - Revocation registry.
+    /// * `epoch` - Number of the new epoch.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `issued` - Indices carried over to the new epoch.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    pub fn new_revocation_registry_epoch<RTA>(rev_reg: &mut RevocationRegistry,
+                                              epoch: u32,
+                                              max_cred_num: u32,
+                                              issued: &HashSet<u32>,
+                                              rev_tails_accessor: &RTA) -> Result<RevocationRegistryEpochTransition, IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Issuer::new_revocation_registry_epoch: >>> rev_reg: {:?}, epoch: {:?}, max_cred_num: {:?}, issued: {:?}",
+               rev_reg, epoch, max_cred_num, secret!(issued));
+
+        if let Some(rev_idx) = issued.iter().find(|rev_idx| **rev_idx == 0 || **rev_idx > max_cred_num) {
+            return Err(IndyCryptoError::InvalidParam4(format!("Revocation index {} is out of registry range", rev_idx)));
+        }
+
+        let prev_accum = rev_reg.accum;
+
+        let mut accum = Accumulator::new_inf()?;
+
+        for rev_idx in issued {
+            let index = Issuer::_get_index(max_cred_num, *rev_idx);
+
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                accum = accum.add(tail)?;
+                Ok(())
+            })?;
+        }
+
+        rev_reg.accum = accum;
+
+        let transition = RevocationRegistryEpochTransition {
+            epoch,
+            prev_accum,
+            accum,
+            issued: issued.clone()
+        };
+
+        trace!("Issuer::new_revocation_registry_epoch: <<< transition: {:?}", transition);
+
+        Ok(transition)
+    }
+
+    /// Verifies correctness proof of blinded credential secrets created by Prover.
+    ///
+    /// Proof shows that Prover knows master secret (and other hidden attributes) and `v_prime`
+    /// blinded in `u`, so Issuer can refuse to sign blinded values it can't validate.
+    /// `sign_credential` performs the same check and fails for invalid proof.
+    ///
+    /// # Arguments
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `blinded_credential_secrets_correctness_proof` - Blinded credential secrets correctness proof.
+    /// * `credential_nonce` - Nonce used by Prover for creation of blinded_credential_secrets_correctness_proof.
+    /// * `credential_pub_key` - Credential public key.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::new_nonce;
+    /// use indy_crypto::cl::issuer::Issuer;
+    /// use indy_crypto::cl::prover::Prover;
+    ///
+    /// let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    /// credential_schema_builder.add_attr("sex").unwrap();
+    /// let credential_schema = credential_schema_builder.finalize().unwrap();
+    ///
+    /// let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    /// non_credential_schema_builder.add_attr("master_secret").unwrap();
+    /// let non_credential_schema = non_credential_schema_builder.finalize().unwrap();
+    ///
+    /// let (credential_pub_key, _credential_priv_key, cred_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+    ///
+    /// let master_secret = Prover::new_master_secret().unwrap();
+    ///
+    /// let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    /// credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+    /// credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+    /// let credential_values = credential_values_builder.finalize().unwrap();
+    ///
+    /// let credential_nonce = new_nonce().unwrap();
+    /// let (blinded_credential_secrets, _, blinded_credential_secrets_correctness_proof) =
+    ///      Prover::blind_credential_secrets(&credential_pub_key, &cred_key_correctness_proof, &credential_values, &credential_nonce).unwrap();
+    ///
+    /// assert!(Issuer::verify_blinded_credential_secrets_correctness_proof(&blinded_credential_secrets,
+    ///                                                                     &blinded_credential_secrets_correctness_proof,
+    ///                                                                     &credential_nonce,
+    ///                                                                     &credential_pub_key).unwrap());
+    /// ```
+    pub fn verify_blinded_credential_secrets_correctness_proof(blinded_credential_secrets: &BlindedCredentialSecrets,
+                                                               blinded_credential_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                                               credential_nonce: &Nonce,
+                                                               credential_pub_key: &CredentialPublicKey) -> Result<bool, IndyCryptoError> {
+        trace!("Issuer::verify_blinded_credential_secrets_correctness_proof: >>> blinded_credential_secrets: {:?}, \
+                blinded_credential_secrets_correctness_proof: {:?}, credential_nonce: {:?}, credential_pub_key: {:?}",
+               blinded_credential_secrets, blinded_credential_secrets_correctness_proof, credential_nonce, credential_pub_key);
+
+        let valid = Issuer::_verify_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
+                                                                                blinded_credential_secrets_correctness_proof,
+                                                                                credential_nonce,
+                                                                                &credential_pub_key.p_key)?;
+
+        trace!("Issuer::verify_blinded_credential_secrets_correctness_proof: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Validates blinded credential secrets received from Prover.
+    ///
+    /// Checks that `u` is in range `[1, n)` and invertible modulo `n` and that `ur` (if present)
+    /// isn't infinity, lies on the curve and belongs to the group, so malformed values are rejected
+    /// before signing. Signing functions perform the same checks.
+    ///
+    /// # Arguments
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `credential_pub_key` - Credential public key.
+    pub fn validate_blinded_credential_secrets(blinded_credential_secrets: &BlindedCredentialSecrets,
+                                               credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        trace!("Issuer::validate_blinded_credential_secrets: >>> blinded_credential_secrets: {:?}, credential_pub_key: {:?}",
+               blinded_credential_secrets, credential_pub_key);
+
+        let n = &credential_pub_key.p_key.n;
+        let u = &blinded_credential_secrets.u;
+
+        if u.is_negative() || *u < *BIGNUMBER_1 || *u >= *n {
+            return Err(IndyCryptoError::InvalidStructure("Blinded credential secrets u is out of range [1, n)".to_string()));
+        }
+
+        u.inverse(n, None)
+            .map_err(|_| IndyCryptoError::InvalidStructure("Blinded credential secrets u isn't invertible modulo n".to_string()))?;
+
+        if let Some(ref ur) = blinded_credential_secrets.ur {
+            if credential_pub_key.r_key.is_none() {
+                return Err(IndyCryptoError::InvalidStructure("Blinded credential secrets ur is present, but credential public key has no revocation part".to_string()));
+            }
+
+            if ur.is_inf()? {
+                return Err(IndyCryptoError::InvalidStructure("Blinded credential secrets ur is infinity".to_string()));
+            }
+
+            if !ur.is_valid()? {
+                return Err(IndyCryptoError::InvalidStructure("Blinded credential secrets ur isn't a point of the group".to_string()));
+            }
+        }
+
+        trace!("Issuer::validate_blinded_credential_secrets: <<<");
+
+        Ok(())
+    }
+
+    /// Verifies that committed attribute of blinded credential secrets has the value of holder binding commitment.
+    ///
+    /// Every issuer that verified the proof for the same holder binding commitment signs the same value,
+    /// so credentials of different issuers are provably about the same subject.
+    ///
+    /// # Arguments
+    /// * `blinded_credential_secrets` - Blinded credential secrets generated by Prover.
+    /// * `attr_name` - Name of committed attribute.
+    /// * `holder_binding_commitment` - Holder binding commitment.
+    /// * `holder_binding_proof` - Holder binding proof.
+    /// * `nonce` - Nonce sent to Prover.
+    /// * `credential_pub_key` - Credential public key.
+    pub fn verify_holder_binding_proof(blinded_credential_secrets: &BlindedCredentialSecrets,
+                                       attr_name: &str,
+                                       holder_binding_commitment: &HolderBindingCommitment,
+                                       holder_binding_proof: &HolderBindingProof,
+                                       nonce: &Nonce,
+                                       credential_pub_key: &CredentialPublicKey) -> Result<bool, IndyCryptoError> {
+        trace!("Issuer::verify_holder_binding_proof: >>> blinded_credential_secrets: {:?}, attr_name: {:?}, \
+                holder_binding_commitment: {:?}, holder_binding_proof: {:?}, nonce: {:?}, credential_pub_key: {:?}",
+               blinded_credential_secrets, attr_name, holder_binding_commitment, holder_binding_proof, nonce, credential_pub_key);
+
+        let p_pub_key = &credential_pub_key.p_key;
+
+        let committed_attr = blinded_credential_secrets.committed_attributes
+            .get(attr_name)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in committed_attributes", attr_name)))?;
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let committed_attr_tau = committed_attr.inverse(&p_pub_key.n, Some(&mut ctx))?
+            .mod_exp(&holder_binding_proof.c, &p_pub_key.n, Some(&mut ctx))?
+            .mod_mul(&get_pedersen_commitment(&p_pub_key.z, &holder_binding_proof.m_cap,
+                                              &p_pub_key.s, &holder_binding_proof.r_cap,
+                                              &p_pub_key.n, &mut ctx)?,
+                     &p_pub_key.n, Some(&mut ctx))?;
+
+        let holder_binding_tau = get_holder_binding_commitment(&bignum_to_group_element_mod_order(&holder_binding_proof.m_cap)?,
+                                                               &holder_binding_proof.rho_cap)?
+            .sub(&holder_binding_commitment.commitment.mul(&bignum_to_group_element_mod_order(&holder_binding_proof.c)?)?)?;
+
+        let c = get_holder_binding_challenge(attr_name,
+                                             committed_attr,
+                                             &holder_binding_commitment.commitment,
+                                             &committed_attr_tau,
+                                             &holder_binding_tau,
+                                             nonce)?;
+
+        let valid = c == holder_binding_proof.c;
+
+        trace!("Issuer::verify_holder_binding_proof: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Verifies proof of master secret rotation created by Prover.
+    ///
+    /// Successful verification means that the holder of the credential bound to the old master secret
+    /// created the new blinded credential secrets, so credentials can be re-issued for them
+    /// without identifying the holder from scratch. Blinded credential secrets still have to be
+    /// signed with `sign_credential` that checks their correctness proof.
+    ///
+    /// # Arguments
+    /// * `proof_verifier` - Proof verifier configured with sub proof request over the credential bound to the old master secret.
+    /// * `rotation_proof` - Master secret rotation proof.
+    /// * `blinded_credential_secrets` - Blinded credential secrets containing the new master secret.
+    /// * `nonce` - Nonce used by Prover for creation of rotation proof.
+    pub fn verify_master_secret_rotation_proof(proof_verifier: &ProofVerifier,
+                                               rotation_proof: &MasterSecretRotationProof,
+                                               blinded_credential_secrets: &BlindedCredentialSecrets,
+                                               nonce: &Nonce) -> Result<bool, IndyCryptoError> {
+        trace!("Issuer::verify_master_secret_rotation_proof: >>> rotation_proof: {:?}, blinded_credential_secrets: {:?}, nonce: {:?}",
+               rotation_proof, blinded_credential_secrets, nonce);
+
+        let rotation_nonce = blinded_credential_secrets.rotation_nonce(nonce)?;
+        let valid = proof_verifier.verify(&rotation_proof.proof, &rotation_nonce)?;
+
+        trace!("Issuer::verify_master_secret_rotation_proof: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    fn _check_key_usage_constraints<C>(credential_pub_key: &CredentialPublicKey,
+                                       credential_values: &CredentialValues,
+                                       revocable: bool,
+                                       clock: &C) -> Result<(), IndyCryptoError> where C: Clock {
+        trace!("Issuer::_check_key_usage_constraints: >>> credential_pub_key: {:?}, revocable: {:?}", credential_pub_key, revocable);
+
+        if let Some(constraints) = credential_pub_key.usage_constraints()? {
+            constraints.check_attrs(credential_values.attrs_values.len())?;
+            constraints.check_revocable(revocable)?;
+            constraints.check_validity(clock.now()?)?;
+        }
+
+        trace!("Issuer::_check_key_usage_constraints: <<<");
+
+        Ok(())
+    }
+
+    fn _new_credential_primary_keys<G>(credential_schema: &CredentialSchema,
+                                       non_credential_schema: &NonCredentialSchema,
+                                       config: &CredentialKeyConfig,
+                                       generate_safe_primes: G) ->
                                                                           Result<(CredentialPrimaryPublicKey,
                                                                                   CredentialPrimaryPrivateKey,
-                                                                                  CredentialPrimaryPublicKeyMetadata), IndyCryptoError> {
+                                                                                  CredentialPrimaryPublicKeyMetadata), IndyCryptoError>
+        where G: FnOnce(usize) -> Result<(BigNumber, BigNumber), IndyCryptoError> {
         trace!("Issuer::_new_credential_primary_keys: >>> credential_schema: {:?}", credential_schema);
 
         let mut ctx = BigNumber::new_context()?;
@@ -585,8 +1564,7 @@ impl Issuer {
             return Err(IndyCryptoError::InvalidStructure(format!("List of attributes is empty")));
         }
 
-        let p_safe = generate_safe_prime(LARGE_PRIME)?;
-        let q_safe = generate_safe_prime(LARGE_PRIME)?;
+        let (p_safe, q_safe) = generate_safe_primes(config.large_prime)?;
 
         let p = p_safe.rshift1()?;
         let q = q_safe.rshift1()?;
@@ -613,7 +1591,7 @@ impl Issuer {
 
         let rctxt = s.mod_exp(&gen_x(&p, &q)?, &n, Some(&mut ctx))?;
 
-        let cred_pr_pub_key = CredentialPrimaryPublicKey { n, s, rctxt, r, z };
+        let cred_pr_pub_key = CredentialPrimaryPublicKey { n, s, rctxt, r, z, config: *config };
         let cred_pr_priv_key = CredentialPrimaryPrivateKey { p, q };
         let cred_pr_pub_key_metadata = CredentialPrimaryPublicKeyMetadata { xz, xr };
 
@@ -623,8 +1601,8 @@ impl Issuer {
         Ok((cred_pr_pub_key, cred_pr_priv_key, cred_pr_pub_key_metadata))
     }
 
-    fn _new_credential_revocation_keys() -> Result<(CredentialRevocationPublicKey,
-                                                    CredentialRevocationPrivateKey), IndyCryptoError> {
+    pub(crate) fn _new_credential_revocation_keys() -> Result<(CredentialRevocationPublicKey,
+                                                               CredentialRevocationPrivateKey), IndyCryptoError> {
         trace!("Issuer::_new_credential_revocation_keys: >>>");
 
         let h = PointG1::new()?;
@@ -734,12 +1712,11 @@ impl Issuer {
         Ok(rev_reg)
     }
 
-    fn _new_revocation_registry_keys(cred_rev_pub_key: &CredentialRevocationPublicKey,
-                                     max_cred_num: u32) -> Result<(RevocationKeyPublic, RevocationKeyPrivate), IndyCryptoError> {
-        trace!("Issuer::_new_revocation_registry_keys: >>> cred_rev_pub_key: {:?}, max_cred_num: {:?}",
-               cred_rev_pub_key, max_cred_num);
-
-        let gamma = GroupOrderElement::new()?;
+    pub(crate) fn _new_revocation_registry_keys(cred_rev_pub_key: &CredentialRevocationPublicKey,
+                                                max_cred_num: u32,
+                                                gamma: GroupOrderElement) -> Result<(RevocationKeyPublic, RevocationKeyPrivate), IndyCryptoError> {
+        trace!("Issuer::_new_revocation_registry_keys: >>> cred_rev_pub_key: {:?}, max_cred_num: {:?}, gamma: {:?}",
+               cred_rev_pub_key, max_cred_num, secret!(&gamma));
 
         let mut z = Pair::pair(&cred_rev_pub_key.g, &cred_rev_pub_key.g_dash)?;
         let mut pow = GroupOrderElement::from_bytes(&transform_u32_to_array_of_u8(max_cred_num + 1))?;
@@ -761,6 +1738,27 @@ impl Issuer {
         trace!("Issuer::_check_blinded_credential_secrets_correctness_proof: >>> blinded_cred_secrets: {:?}, blinded_cred_secrets_correctness_proof: {:?},\
          nonce: {:?}, cred_pr_pub_key: {:?}", blinded_cred_secrets, blinded_cred_secrets_correctness_proof, nonce, cred_pr_pub_key);
 
+        let valid = Issuer::_verify_blinded_credential_secrets_correctness_proof(blinded_cred_secrets,
+                                                                                blinded_cred_secrets_correctness_proof,
+                                                                                nonce,
+                                                                                cred_pr_pub_key)?;
+
+        if !valid {
+            return Err(IndyCryptoError::InvalidStructure("Invalid BlindedCredentialSecrets correctness proof".to_string()));
+        }
+
+        trace!("Issuer::_check_blinded_credential_secrets_correctness_proof: <<<");
+
+        Ok(())
+    }
+
+    fn _verify_blinded_credential_secrets_correctness_proof(blinded_cred_secrets: &BlindedCredentialSecrets,
+                                                            blinded_cred_secrets_correctness_proof: &BlindedCredentialSecretsCorrectnessProof,
+                                                            nonce: &Nonce,
+                                                            cred_pr_pub_key: &CredentialPrimaryPublicKey) -> Result<bool, IndyCryptoError> {
+        trace!("Issuer::_verify_blinded_credential_secrets_correctness_proof: >>> blinded_cred_secrets: {:?}, blinded_cred_secrets_correctness_proof: {:?},\
+         nonce: {:?}, cred_pr_pub_key: {:?}", blinded_cred_secrets, blinded_cred_secrets_correctness_proof, nonce, cred_pr_pub_key);
+
         let mut values: Vec<u8> = Vec::new();
         let mut ctx = BigNumber::new_context()?;
 
@@ -778,17 +1776,24 @@ impl Issuer {
                                                   let pk_r = cred_pr_pub_key.r
                                                                     .get(&attr.clone())
                                                                     .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in cred_pr_pub_key.r", attr)))?;
-                                                  let m_cap = &blinded_cred_secrets_correctness_proof.m_caps[attr];
-                                                  acc?.mod_mul(&pk_r.mod_exp(&m_cap, &cred_pr_pub_key.n, Some(&mut ctx))?,
+                                                  let m_cap = blinded_cred_secrets_correctness_proof.m_caps
+                                                                    .get(attr)
+                                                                    .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in m_caps", attr)))?;
+                                                  acc?.mod_mul(&pk_r.mod_exp(m_cap, &cred_pr_pub_key.n, Some(&mut ctx))?,
                                                                &cred_pr_pub_key.n, Some(&mut ctx))
                                               })?;
 
         for (key, value) in &blinded_cred_secrets.committed_attributes {
-            let m_cap = &blinded_cred_secrets_correctness_proof.m_caps[key];
+            let m_cap = blinded_cred_secrets_correctness_proof.m_caps
+                .get(key)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in m_caps", key)))?;
+            let r_cap = blinded_cred_secrets_correctness_proof.r_caps
+                .get(key)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in r_caps", key)))?;
             let comm_att_cap = value.inverse(&cred_pr_pub_key.n, Some(&mut ctx))?
                                     .mod_exp(&blinded_cred_secrets_correctness_proof.c, &cred_pr_pub_key.n, Some(&mut ctx))?
-                                    .mod_mul(&get_pedersen_commitment(&cred_pr_pub_key.z, &m_cap,
-                                                                      &cred_pr_pub_key.s, &blinded_cred_secrets_correctness_proof.r_caps[key],
+                                    .mod_mul(&get_pedersen_commitment(&cred_pr_pub_key.z, m_cap,
+                                                                      &cred_pr_pub_key.s, r_cap,
                                                                       &cred_pr_pub_key.n, &mut ctx)?,
                                              &cred_pr_pub_key.n, Some(&mut ctx))?;
 
@@ -805,13 +1810,9 @@ impl Issuer {
 
         let valid = blinded_cred_secrets_correctness_proof.c.eq(&c);
 
-        if !valid {
-            return Err(IndyCryptoError::InvalidStructure(format!("Invalid BlindedCredentialSecrets correctness proof")));
-        }
+        trace!("Issuer::_verify_blinded_credential_secrets_correctness_proof: <<< valid: {:?}", valid);
 
-        trace!("Issuer::_check_blinded_credential_secrets_correctness_proof: <<<");
-
-        Ok(())
+        Ok(valid)
     }
 
     // In the anoncreds whitepaper, `credential context` is denoted by `m2`
@@ -842,9 +1843,11 @@ impl Issuer {
         trace!("Issuer::_new_primary_credential: >>> credential_context: {:?}, cred_pub_key: {:?}, cred_priv_key: {:?}, blinded_ms: {:?},\
          cred_values: {:?}", secret!(credential_context), cred_pub_key, secret!(cred_priv_key), blinded_credential_secrets, secret!(cred_values));
 
-        let v = generate_v_prime_prime()?;
+        let config = &cred_pub_key.p_key.config;
 
-        let e = generate_prime_in_range(&LARGE_E_START_VALUE, &LARGE_E_END_RANGE_VALUE)?;
+        let v = generate_v_prime_prime(config.large_vprime_prime)?;
+
+        let e = generate_prime_in_range(&config.e_start_value()?, &config.e_end_range_value()?)?;
         let (a, q) = Issuer::_sign_primary_credential(cred_pub_key, cred_priv_key, &credential_context, &cred_values, &v, blinded_credential_secrets, &e)?;
 
         let pr_cred_sig = PrimaryCredentialSignature { m_2: credential_context.clone()?, a, e, v };
@@ -940,7 +1943,7 @@ impl Issuer {
         Ok(signature_correctness_proof)
     }
 
-    fn _get_index(max_cred_num: u32, rev_idx: u32) -> u32 {
+    pub(crate) fn _get_index(max_cred_num: u32, rev_idx: u32) -> u32 {
         max_cred_num + 1 - rev_idx
     }
 
@@ -982,22 +1985,21 @@ impl Issuer {
             r_pub_key.g.mul(&pow)?
         };
 
+        let gamma_i = rev_key_priv.gamma
+            .pow_mod(&GroupOrderElement::from_bytes(&transform_u32_to_array_of_u8(rev_idx))?)?;
+
+        // 1 / (x + c) and 1 / (sk + gamma ^ i) with one inversion
+        let inverses = GroupOrderElement::batch_inverse(&[r_priv_key.x.add_mod(&c)?, r_priv_key.sk.add_mod(&gamma_i)?])?;
+
         let sigma =
             r_pub_key.h0.add(&r_pub_key.h1.mul(&m2)?)?
                 .add(&ur)?
                 .add(&g_i)?
                 .add(&r_pub_key.h2.mul(&vr_prime_prime)?)?
-                .mul(&r_priv_key.x.add_mod(&c)?.inverse()?)?;
-
+                .mul(&inverses[0])?;
 
-        let sigma_i = r_pub_key.g_dash
-            .mul(&r_priv_key.sk
-                .add_mod(&rev_key_priv.gamma
-                    .pow_mod(&GroupOrderElement::from_bytes(&transform_u32_to_array_of_u8(rev_idx))?)?)?
-                .inverse()?)?;
-        let u_i = r_pub_key.u
-            .mul(&rev_key_priv.gamma
-                .pow_mod(&GroupOrderElement::from_bytes(&transform_u32_to_array_of_u8(rev_idx))?)?)?;
+        let sigma_i = r_pub_key.g_dash.mul(&inverses[1])?;
+        let u_i = r_pub_key.u.mul(&gamma_i)?;
 
         let index = Issuer::_get_index(max_cred_num, rev_idx);
 
@@ -1006,8 +2008,9 @@ impl Issuer {
         } else {
             let prev_acc = rev_reg.accum.clone();
 
-            rev_tails_accessor.access_tail(index, &mut |tail| {
-                rev_reg.accum = rev_reg.accum.add(tail).unwrap();
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                rev_reg.accum = rev_reg.accum.add(tail)?;
+                Ok(())
             })?;
 
             Some(RevocationRegistryDelta {
@@ -1109,7 +2112,7 @@ mod tests {
 
     #[test]
     fn issuer_new_credential_works_for_empty_attributes() {
-        let cred_attrs = CredentialSchema { attrs: BTreeSet::new() };
+        let cred_attrs = CredentialSchema { attrs: BTreeSet::new(), attr_types: BTreeMap::new() };
         let non_cred_attrs = NonCredentialSchema { attrs: BTreeSet::new() };
         let res = Issuer::new_credential_def(&cred_attrs, &non_cred_attrs, false);
         assert!(res.is_err())
@@ -1123,6 +2126,22 @@ mod tests {
         Issuer::new_revocation_registry_def(&pub_key, 100, false).unwrap();
     }
 
+    #[test]
+    fn issuer_new_revocation_registry_def_from_seed_works() {
+        MockHelper::inject();
+
+        let (pub_key, _, _) = Issuer::new_credential_def(&mocks::credential_schema(), &mocks::non_credential_schema(), true).unwrap();
+        let seed = SecretSeed::new(&[1u8; 32]).unwrap();
+
+        let (rev_key_pub, _, rev_reg, _) = Issuer::new_revocation_registry_def_from_seed(&pub_key, 5, true, &seed).unwrap();
+        let (rev_key_pub_2, _, rev_reg_2, _) = Issuer::new_revocation_registry_def_from_seed(&pub_key, 5, true, &seed).unwrap();
+        assert_eq!(rev_key_pub.z, rev_key_pub_2.z);
+        assert_eq!(rev_reg.accum, rev_reg_2.accum);
+
+        let (rev_key_pub_3, _, _, _) = Issuer::new_revocation_registry_def_from_seed(&pub_key, 6, true, &seed).unwrap();
+        assert_ne!(rev_key_pub.z, rev_key_pub_3.z);
+    }
+
     #[test]
     fn sign_primary_credential_works() {
         MockHelper::inject();
@@ -1288,7 +2307,8 @@ pub mod mocks {
     pub fn credential_public_key() -> CredentialPublicKey {
         CredentialPublicKey {
             p_key: credential_primary_public_key(),
-            r_key: Some(credential_revocation_public_key())
+            r_key: Some(credential_revocation_public_key()),
+            usage_constraints: None
         }
     }
 
@@ -1332,6 +2352,7 @@ pub mod mocks {
             ],
             rctxt: BigNumber::from_dec("22367649113891905664593367589756927154620026002870686791425116899113166102463385255777947612590272326902876607965930393299017708388456014672833098517510402725906562714517383519224241769370097436360213271801024664973101516459676759121006263327545857171301256844849290876113986609209526369774492299815377779730250971480247123999361231894462657785201833140206882164481738440445907028661962175780038926095996356731476561447556285865588500666880748440388241988576483428813710093676464103155200711556185738545216528962065908814210434956734336781475483267248489836659903340870985489551641891702996597499832133432061498821350").unwrap(),
             z: BigNumber::from_dec("20971049306556516416548411855462653126934915528788169742105904685171526036021814020308366595378985697473160298612279628754632434933759095053014742445453246869014501318132129164954281672366894792411718693685773560773966579052996993259737028689495198784560422879504530423473348349585086897461177376910543665826129373202987768115430007889968052288637875214108680986123834214768628273585410552488075439001161273207000954506399869209972102566538554006252214727260705838993631349254893430895487478655362331032373744785458381443406082435300178682616238581378757588795672662888045672364001684986862571709608524646032002755410").unwrap(),
+            config: CredentialKeyConfig::default()
         }
     }
 
@@ -1344,7 +2365,8 @@ pub mod mocks {
 
     pub fn credential_schema() -> CredentialSchema {
         CredentialSchema {
-            attrs: btreeset!["name".to_string(), "age".to_string(), "height".to_string(), "sex".to_string()]
+            attrs: btreeset!["name".to_string(), "age".to_string(), "height".to_string(), "sex".to_string()],
+            attr_types: BTreeMap::new()
         }
     }
 
@@ -1362,14 +2384,16 @@ pub mod mocks {
                 "master_secret".to_string() => CredentialValue::Hidden { value: prover_mocks::master_secret().value().unwrap() },
                 "name".to_string() => CredentialValue::Known { value: BigNumber::from_dec("66682250590915135919393234675423675079281389286836524491448775067034910960723").unwrap() },
                 "sex".to_string() => CredentialValue::Known { value: BigNumber::from_dec("59607158875075502079861259255950808097316057515161310607657216396491477298979").unwrap() }
-            ]
+            ],
+            raw_values: BTreeMap::new()
         }
     }
 
     pub fn credential() -> CredentialSignature {
         CredentialSignature {
             p_credential: primary_credential(),
-            r_credential: Some(revocation_credential())
+            r_credential: Some(revocation_credential()),
+            attr_r_credentials: BTreeMap::new()
         }
     }
 
@@ -1,17 +1,32 @@
 mod constants;
 #[macro_use]
 mod helpers;
+pub mod actors;
+#[cfg(feature = "compat-indy-sdk")]
+pub mod compat;
 pub mod issuer;
+pub mod presentation_log;
 pub mod prover;
+pub mod registry_creation;
+pub mod snark;
+pub mod tails;
 pub mod verifier;
+pub mod witness_service;
 
-use bn::BigNumber;
+use bn::{BigNumber, BigNumberContext, BIGNUMBER_2};
 use errors::IndyCryptoError;
 use pair::*;
+use self::snark::{AttributeCommitment, SnarkProof};
+use utils::aead;
+use utils::clock::{Clock, Timestamp};
+use utils::{get_hash_as_int, u64_to_be_bytes};
 
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet, BTreeSet, BTreeMap};
+use std::fmt;
 use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
 /// Creates random nonce
 ///
@@ -25,21 +40,215 @@ pub fn new_nonce() -> Result<Nonce, IndyCryptoError> {
     Ok(helpers::bn_rand(constants::LARGE_NONCE)?)
 }
 
+/// Creates random nonce of `bit_length` bits with optional context mixed into the value.
+///
+/// Random value is expanded with SHA-256 over the context, so nonces created for different contexts
+/// (e.g. credential offer and proof request) are domain separated and still uniformly distributed.
+fn new_nonce_with_params(bit_length: usize, context: Option<&str>) -> Result<Nonce, IndyCryptoError> {
+    if bit_length < constants::LARGE_NONCE || bit_length > constants::MAX_NONCE_BITS {
+        return Err(IndyCryptoError::InvalidParam1(
+            format!("Nonce bit length must be in range [{}, {}]", constants::LARGE_NONCE, constants::MAX_NONCE_BITS)));
+    }
+
+    let nonce = helpers::bn_rand(bit_length)?;
+
+    let context = match context {
+        Some(context) => context,
+        None => return Ok(nonce)
+    };
+
+    let seed = nonce.to_bytes()?;
+    let bytes_len = (bit_length + 7) / 8;
+    let mut bytes = Vec::with_capacity(bytes_len);
+    let mut counter: u32 = 0;
+    while bytes.len() < bytes_len {
+        bytes.extend_from_slice(&BigNumber::hash_array(&vec![
+            constants::NONCE_CONTEXT_DOMAIN.to_vec(),
+            u64_to_be_bytes(context.len() as u64),
+            context.as_bytes().to_vec(),
+            seed.clone(),
+            helpers::transform_u32_to_array_of_u8(counter)
+        ])?);
+        counter += 1;
+    }
+    bytes.truncate(bytes_len);
+
+    BigNumber::from_bytes(&bytes)?.rshift((bytes_len * 8 - bit_length) as i32)
+}
+
+/// Encodes raw attribute value with the standard Indy encoding:
+/// 32-bit integers are encoded as themselves, any other value as SHA-256 hash of its UTF-8 bytes.
+///
+/// Issuers and verifiers must use the same encoding, so revealed values can be compared with raw ones.
+///
+/// # Example
+/// ```
+/// use indy_crypto::cl::encode_raw_value;
+///
+/// assert_eq!("25", encode_raw_value("25").unwrap().to_dec().unwrap());
+/// assert_eq!("99262857098057710338306967609588410025648622308394250666849665532448612202874",
+///            encode_raw_value("Alex").unwrap().to_dec().unwrap());
+/// ```
+pub fn encode_raw_value(raw: &str) -> Result<BigNumber, IndyCryptoError> {
+    match raw.parse::<i32>() {
+        Ok(value) => BigNumber::from_dec(&value.to_string()),
+        Err(_) => BigNumber::from_bytes(&BigNumber::hash(raw.as_bytes())?)
+    }
+}
+
+/// Separator of namespace and attribute name in namespaced attribute name (e.g. `address.city`).
+pub const ATTR_NAMESPACE_SEPARATOR: char = '.';
+
+/// Returns name of attribute `attr` in logical schema `namespace`, e.g. `core.name`.
+///
+/// Namespaced attributes let one credential carry structured data of several logical schemas
+/// instead of many tiny credentials. Namespaced attribute is an ordinary attribute for proofs.
+///
+/// # Example
+/// ```
+/// use indy_crypto::cl::{namespaced_attr, split_namespaced_attr};
+///
+/// assert_eq!("address.city", namespaced_attr("address", "city").unwrap());
+/// assert_eq!(Some(("address", "city")), split_namespaced_attr("address.city"));
+/// ```
+pub fn namespaced_attr(namespace: &str, attr: &str) -> Result<String, IndyCryptoError> {
+    if namespace.is_empty() || namespace.contains(ATTR_NAMESPACE_SEPARATOR) {
+        return Err(IndyCryptoError::InvalidParam1(format!("Invalid attribute namespace: {:?}", namespace)));
+    }
+    if attr.is_empty() {
+        return Err(IndyCryptoError::InvalidParam2("Attribute name is empty".to_string()));
+    }
+    Ok(format!("{}{}{}", namespace, ATTR_NAMESPACE_SEPARATOR, attr))
+}
+
+/// Splits namespaced attribute name into namespace and attribute name.
+/// Returns `None` if attribute isn't namespaced.
+pub fn split_namespaced_attr(attr: &str) -> Option<(&str, &str)> {
+    match attr.find(ATTR_NAMESPACE_SEPARATOR) {
+        Some(idx) if idx > 0 && idx + 1 < attr.len() => Some((&attr[..idx], &attr[idx + 1..])),
+        _ => None
+    }
+}
+
+pub(crate) fn init_constants() {
+    ::lazy_static::initialize(&constants::LARGE_E_START_VALUE);
+    ::lazy_static::initialize(&constants::LARGE_E_END_RANGE_VALUE);
+    ::lazy_static::initialize(&constants::LARGE_VPRIME_PRIME_VALUE);
+}
+
+/// Type of attribute value declared in `Credential Schema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AttributeType {
+    /// Encoded with `encode_raw_value`.
+    String,
+    /// 64-bit integer encoded as itself.
+    ///
+    /// Unlike `encode_raw_value` that encodes only 32-bit integers as themselves,
+    /// so values out of 32-bit range are encoded differently than untyped ones.
+    Integer,
+    /// Date in `YYYY-MM-DD` format encoded as integer `YYYYMMDD`, so encoded dates keep their order.
+    Date,
+    /// `true` or `false` encoded as 1 or 0.
+    Boolean
+}
+
+impl AttributeType {
+    /// Returns true if predicates can be applied to values of the type.
+    pub fn is_numeric(&self) -> bool {
+        match *self {
+            AttributeType::Integer | AttributeType::Date => true,
+            AttributeType::String | AttributeType::Boolean => false
+        }
+    }
+
+    /// Validates raw value of the type and encodes it.
+    pub fn encode(&self, raw: &str) -> Result<BigNumber, IndyCryptoError> {
+        let invalid = || IndyCryptoError::InvalidStructure(format!("Invalid {:?} attribute value: {}", self, raw));
+
+        match *self {
+            AttributeType::String => encode_raw_value(raw),
+            AttributeType::Integer => {
+                let value = raw.parse::<i64>().map_err(|_| invalid())?;
+                BigNumber::from_dec(&value.to_string())
+            }
+            AttributeType::Date => {
+                let parts = raw.split('-').collect::<Vec<&str>>();
+                if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 ||
+                    !parts.iter().all(|part| part.bytes().all(|b| b.is_ascii_digit())) {
+                    return Err(invalid());
+                }
+                let (year, month, day) = (parts[0].parse::<u32>().map_err(|_| invalid())?,
+                                          parts[1].parse::<u32>().map_err(|_| invalid())?,
+                                          parts[2].parse::<u32>().map_err(|_| invalid())?);
+                let days_in_month = match month {
+                    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+                    4 | 6 | 9 | 11 => 30,
+                    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+                    2 => 28,
+                    _ => return Err(invalid())
+                };
+                if day < 1 || day > days_in_month {
+                    return Err(invalid());
+                }
+                BigNumber::from_u32((year * 10000 + month * 100 + day) as usize)
+            }
+            AttributeType::Boolean => match raw {
+                "true" => BigNumber::from_u32(1),
+                "false" => BigNumber::from_u32(0),
+                _ => Err(invalid())
+            }
+        }
+    }
+}
+
 /// A list of attributes a Credential is based on.
 #[derive(Debug, Clone)]
 pub struct CredentialSchema {
     attrs: BTreeSet<String>, /* attr names */
+    attr_types: BTreeMap<String, AttributeType>, /* types of typed attrs */
+}
+
+impl CredentialSchema {
+    /// Returns type of the attribute or `None` if the attribute is untyped or absent.
+    pub fn attr_type(&self, attr: &str) -> Option<AttributeType> {
+        self.attr_types.get(attr).cloned()
+    }
+
+    /// Returns namespaces of namespaced attributes of the schema.
+    pub fn namespaces(&self) -> BTreeSet<String> {
+        self.attrs.iter()
+            .filter_map(|attr| split_namespaced_attr(attr))
+            .map(|(namespace, _)| namespace.to_owned())
+            .collect()
+    }
+
+    /// Returns (namespaced) names of attributes of the schema in `namespace`.
+    pub fn namespace_attrs(&self, namespace: &str) -> BTreeSet<String> {
+        self.attrs.iter()
+            .filter(|attr| split_namespaced_attr(attr).map(|(ns, _)| ns == namespace).unwrap_or(false))
+            .cloned()
+            .collect()
+    }
+
+    fn check_predicate_attr(&self, attr: &str) -> Result<(), IndyCryptoError> {
+        match self.attr_type(attr) {
+            Some(attr_type) if !attr_type.is_numeric() =>
+                Err(IndyCryptoError::InvalidStructure(format!("Predicate can't be applied to {:?} attribute '{}'", attr_type, attr))),
+            _ => Ok(())
+        }
+    }
 }
 
 /// A Builder of `Credential Schema`.
 #[derive(Debug)]
 pub struct CredentialSchemaBuilder {
     attrs: BTreeSet<String>, /* attr names */
+    attr_types: BTreeMap<String, AttributeType>,
 }
 
 impl CredentialSchemaBuilder {
     pub fn new() -> Result<CredentialSchemaBuilder, IndyCryptoError> {
-        Ok(CredentialSchemaBuilder { attrs: BTreeSet::new() })
+        Ok(CredentialSchemaBuilder { attrs: BTreeSet::new(), attr_types: BTreeMap::new() })
     }
 
     pub fn add_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
@@ -47,8 +256,22 @@ impl CredentialSchemaBuilder {
         Ok(())
     }
 
+    /// Adds attribute with declared value type.
+    /// Predicates can be applied to numeric typed attributes only.
+    pub fn add_typed_attr(&mut self, attr: &str, attr_type: AttributeType) -> Result<(), IndyCryptoError> {
+        self.attrs.insert(attr.to_owned());
+        self.attr_types.insert(attr.to_owned(), attr_type);
+        Ok(())
+    }
+
+    /// Adds attribute `attr` of logical schema `namespace` (named `namespace.attr`, see `namespaced_attr`).
+    pub fn add_namespaced_attr(&mut self, namespace: &str, attr: &str) -> Result<(), IndyCryptoError> {
+        self.attrs.insert(namespaced_attr(namespace, attr)?);
+        Ok(())
+    }
+
     pub fn finalize(self) -> Result<CredentialSchema, IndyCryptoError> {
-        Ok(CredentialSchema { attrs: self.attrs })
+        Ok(CredentialSchema { attrs: self.attrs, attr_types: self.attr_types })
     }
 }
 
@@ -81,12 +304,13 @@ impl NonCredentialSchemaBuilder {
 
 /// The m value for attributes,
 /// commitments also store a blinding factor
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub enum CredentialValue {
     Known { value: BigNumber }, //Issuer and Prover know these
     Hidden { value: BigNumber }, //Only known to Prover who binds these into the U factor
     Commitment {
         value: BigNumber,
+        #[serde(alias = "blindingFactor")]
         blinding_factor: BigNumber,
     }, //Only known to Prover, not included in the credential, used for proving knowledge during issuance
 }
@@ -140,29 +364,151 @@ impl CredentialValue {
     }
 }
 
+/// Hidden values and commitment openings are known to Prover only and never printed.
+impl fmt::Debug for CredentialValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CredentialValue::Known { ref value } => write!(f, "Known {{ value: {:?} }}", value),
+            CredentialValue::Hidden { .. } => write!(f, "Hidden {{ value: <redacted> }}"),
+            CredentialValue::Commitment { .. } => write!(f, "Commitment {{ value: <redacted>, blinding_factor: <redacted> }}"),
+        }
+    }
+}
+
 /// Values of attributes from `Claim Schema` (must be integers).
-#[derive(Debug)]
+#[derive(Deserialize, Serialize)]
 pub struct CredentialValues {
+    #[serde(alias = "attrsValues")]
     attrs_values: BTreeMap<String, CredentialValue>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    raw_values: BTreeMap<String, String>,
 }
 
 impl CredentialValues {
     pub fn clone(&self) -> Result<CredentialValues, IndyCryptoError> {
         Ok(CredentialValues {
-            attrs_values: clone_credential_value_map(&self.attrs_values)?
+            attrs_values: clone_credential_value_map(&self.attrs_values)?,
+            raw_values: self.raw_values.clone()
         })
     }
+
+    /// Returns raw value of attribute added with `CredentialValuesBuilder::add_raw_value`.
+    pub fn raw_value(&self, attr: &str) -> Option<&str> {
+        self.raw_values.get(attr).map(String::as_str)
+    }
+
+    /// Returns copy of credential values with values of the given attributes replaced.
+    ///
+    /// Updated attributes keep their kind (known, hidden or commitment with the same blinding factor),
+    /// so re-issued credential corresponds to the same credential schema.
+    ///
+    /// # Arguments
+    /// * `updates` - New encoded values of existing attributes.
+    pub fn with_updates(&self, updates: &BTreeMap<String, BigNumber>) -> Result<CredentialValues, IndyCryptoError> {
+        let mut attrs_values = clone_credential_value_map(&self.attrs_values)?;
+
+        for (attr, value) in updates.iter() {
+            let credential_value = attrs_values.get_mut(attr)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Attribute '{}' not found in credential values", attr)))?;
+
+            *credential_value = match *credential_value {
+                CredentialValue::Known { .. } => CredentialValue::Known { value: value.clone()? },
+                CredentialValue::Hidden { .. } => CredentialValue::Hidden { value: value.clone()? },
+                CredentialValue::Commitment { ref blinding_factor, .. } => CredentialValue::Commitment {
+                    value: value.clone()?,
+                    blinding_factor: blinding_factor.clone()?,
+                },
+            };
+        }
+
+        let raw_values = self.raw_values.iter()
+            .filter(|&(attr, _)| !updates.contains_key(attr))
+            .map(|(attr, raw)| (attr.clone(), raw.clone()))
+            .collect();
+
+        Ok(CredentialValues { attrs_values, raw_values })
+    }
+
+    /// Returns copy of credential values with hidden `master_secret` attribute taken from master secret store.
+    ///
+    /// # Arguments
+    /// * `master_secret_store` - Master secret store.
+    /// * `master_secret_id` - Id of the master secret in the store.
+    pub fn with_master_secret<MSS>(&self, master_secret_store: &MSS, master_secret_id: &str) -> Result<CredentialValues, IndyCryptoError>
+        where MSS: MasterSecretStore {
+        let master_secret = master_secret_store.get(master_secret_id)?
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Master secret '{}' not found in master secret store", master_secret_id)))?;
+        self._with_master_secret(&master_secret)
+    }
+
+    fn _with_master_secret(&self, master_secret: &MasterSecret) -> Result<CredentialValues, IndyCryptoError> {
+        let mut attrs_values = clone_credential_value_map(&self.attrs_values)?;
+
+        match attrs_values.get("master_secret") {
+            Some(&CredentialValue::Hidden { ref value }) if *value == master_secret.ms => {}
+            Some(_) => return Err(IndyCryptoError::InvalidStructure("Credential values contain other master secret".to_string())),
+            None => {}
+        }
+
+        attrs_values.insert("master_secret".to_string(), CredentialValue::Hidden { value: master_secret.value()? });
+
+        Ok(CredentialValues { attrs_values, raw_values: self.raw_values.clone() })
+    }
+
+    /// Checks that encoded values correspond to raw values of the attributes.
+    ///
+    /// # Arguments
+    /// * `raw_values` - Raw values of attributes to check.
+    /// * `encode` - Encoding used to create credential values from raw values.
+    pub fn check_encoding<F>(&self, raw_values: &BTreeMap<String, String>, encode: F) -> Result<(), IndyCryptoError>
+        where F: Fn(&str) -> Result<BigNumber, IndyCryptoError> {
+        for (attr, raw_value) in raw_values.iter() {
+            let credential_value = self.attrs_values.get(attr)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Attribute '{}' not found in credential values", attr)))?;
+
+            if *credential_value.value() != encode(raw_value)? {
+                return Err(IndyCryptoError::InvalidStructure(format!("Value of attribute '{}' doesn't correspond to raw value", attr)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints names of attributes and redacted hidden values only (see `CredentialValue`),
+/// so credential values can be traced without leaking master secret.
+impl fmt::Debug for CredentialValues {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialValues {{ attrs_values: {:?}, raw_values: {:?} }}", self.attrs_values, self.raw_values.keys().collect::<BTreeSet<_>>())
+    }
 }
 
 /// A Builder of `Credential Values`.
 #[derive(Debug)]
 pub struct CredentialValuesBuilder {
     attrs_values: BTreeMap<String, CredentialValue>, /* attr_name -> int representation of value */
+    attr_types: BTreeMap<String, AttributeType>,
+    raw_values: BTreeMap<String, String>,
 }
 
 impl CredentialValuesBuilder {
     pub fn new() -> Result<CredentialValuesBuilder, IndyCryptoError> {
-        Ok(CredentialValuesBuilder { attrs_values: BTreeMap::new() })
+        Ok(CredentialValuesBuilder { attrs_values: BTreeMap::new(), attr_types: BTreeMap::new(), raw_values: BTreeMap::new() })
+    }
+
+    /// Creates builder that validates and encodes raw values of typed attributes according to their types.
+    ///
+    /// # Arguments
+    /// * `credential_schema` - Credential schema.
+    pub fn new_for_schema(credential_schema: &CredentialSchema) -> Result<CredentialValuesBuilder, IndyCryptoError> {
+        Ok(CredentialValuesBuilder { attrs_values: BTreeMap::new(), attr_types: credential_schema.attr_types.clone(), raw_values: BTreeMap::new() })
+    }
+
+    fn _encode_raw(&self, attr: &str, raw: &str) -> Result<BigNumber, IndyCryptoError> {
+        match self.attr_types.get(attr) {
+            Some(attr_type) => attr_type.encode(raw),
+            None => encode_raw_value(raw)
+        }
     }
 
     pub fn add_dec_known(&mut self, attr: &str, value: &str) -> Result<(), IndyCryptoError> {
@@ -197,6 +543,50 @@ impl CredentialValuesBuilder {
         Ok(())
     }
 
+    /// Adds known attribute value encoded from raw value with `encode_raw_value`
+    /// or according to the attribute type if builder is created for typed schema.
+    pub fn add_raw_known(&mut self, attr: &str, raw: &str) -> Result<(), IndyCryptoError> {
+        let value = self._encode_raw(attr, raw)?;
+        self.attrs_values.insert(
+            attr.to_owned(),
+            CredentialValue::Known { value },
+        );
+        Ok(())
+    }
+
+    /// Adds hidden attribute value encoded from raw value with `encode_raw_value`
+    /// or according to the attribute type if builder is created for typed schema.
+    pub fn add_raw_hidden(&mut self, attr: &str, raw: &str) -> Result<(), IndyCryptoError> {
+        let value = self._encode_raw(attr, raw)?;
+        self.attrs_values.insert(
+            attr.to_owned(),
+            CredentialValue::Hidden { value },
+        );
+        Ok(())
+    }
+
+    /// Adds known attribute value encoded from raw value with `encode_raw_value` and records the raw value,
+    /// so proofs revealing the attribute carry the raw value checked by verifier against the encoded one.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::CredentialValuesBuilder;
+    ///
+    /// let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+    /// credential_values_builder.add_raw_value("name", "Alex").unwrap();
+    /// let credential_values = credential_values_builder.finalize().unwrap();
+    ///
+    /// assert_eq!(Some("Alex"), credential_values.raw_value("name"));
+    /// ```
+    pub fn add_raw_value(&mut self, attr: &str, raw: &str) -> Result<(), IndyCryptoError> {
+        self.attrs_values.insert(
+            attr.to_owned(),
+            CredentialValue::Known { value: encode_raw_value(raw)? },
+        );
+        self.raw_values.insert(attr.to_owned(), raw.to_owned());
+        Ok(())
+    }
+
     pub fn add_value_known(
         &mut self,
         attr: &str,
@@ -238,7 +628,17 @@ impl CredentialValuesBuilder {
     }
 
     pub fn finalize(self) -> Result<CredentialValues, IndyCryptoError> {
-        Ok(CredentialValues { attrs_values: self.attrs_values })
+        let mut raw_values = BTreeMap::new();
+
+        // Raw value is kept only while the attribute isn't overwritten by other value
+        for (attr, raw) in self.raw_values {
+            match self.attrs_values.get(&attr) {
+                Some(&CredentialValue::Known { ref value }) if *value == encode_raw_value(&raw)? => { raw_values.insert(attr, raw); }
+                _ => {}
+            }
+        }
+
+        Ok(CredentialValues { attrs_values: self.attrs_values, raw_values })
     }
 }
 
@@ -248,18 +648,83 @@ impl CredentialValuesBuilder {
 /// Issuer keys have global identifier that must be known to all parties.
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct CredentialPublicKey {
+    #[serde(alias = "pKey")]
     p_key: CredentialPrimaryPublicKey,
+    #[serde(alias = "rKey")]
     r_key: Option<CredentialRevocationPublicKey>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "usageConstraints")]
+    usage_constraints: Option<SignedCredentialKeyUsageConstraints>,
 }
 
 impl CredentialPublicKey {
     pub fn clone(&self) -> Result<CredentialPublicKey, IndyCryptoError> {
         Ok(CredentialPublicKey {
             p_key: self.p_key.clone()?,
-            r_key: self.r_key.clone()
+            r_key: self.r_key.clone(),
+            usage_constraints: match self.usage_constraints {
+                Some(ref usage_constraints) => Some(usage_constraints.clone()?),
+                None => None
+            }
         })
     }
 
+    /// Returns usage constraints embedded into the key by Issuer.
+    /// Fails if constraints signature doesn't match the key.
+    pub fn usage_constraints(&self) -> Result<Option<&CredentialKeyUsageConstraints>, IndyCryptoError> {
+        match self.usage_constraints {
+            Some(ref usage_constraints) => {
+                if !usage_constraints.verify(&self.p_key.n)? {
+                    return Err(IndyCryptoError::InvalidStructure("Invalid signature of credential key usage constraints".to_string()));
+                }
+                Ok(Some(&usage_constraints.constraints))
+            }
+            None => Ok(None)
+        }
+    }
+
+    /// Returns SHA-256 fingerprint of the key: hash of canonical json of primary key (n, s, z, r, rctxt)
+    /// and revocation key. Usage constraints aren't included, so constraints can be added without changing the fingerprint.
+    pub fn fingerprint(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        let r_key = match self.r_key {
+            Some(ref r_key) => ::utils::json::to_canonical_json(r_key)?.into_bytes(),
+            None => Vec::new()
+        };
+
+        let values: Vec<Vec<u8>> = vec![
+            constants::CREDENTIAL_PUBLIC_KEY_FINGERPRINT_DOMAIN.to_vec(),
+            ::utils::json::to_canonical_json(&self.p_key)?.into_bytes(),
+            r_key
+        ];
+        BigNumber::hash_array(&values)
+    }
+
+    /// Returns identifier of the key: hex encoded fingerprint.
+    pub fn key_id(&self) -> Result<String, IndyCryptoError> {
+        Ok(self.fingerprint()?.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Serializes credential public key as json directly to writer (e.g. socket or file) without building intermediate string.
+    pub fn write_to<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), IndyCryptoError> {
+        ::utils::json::to_writer(self, writer)
+    }
+
+    /// Deserializes credential public key from json read directly from reader.
+    pub fn read_from<R: ::std::io::Read>(reader: &mut R) -> Result<CredentialPublicKey, IndyCryptoError> {
+        ::utils::json::from_reader(reader)
+    }
+
+    /// Checks that the key has the given fingerprint.
+    pub fn has_fingerprint(&self, fingerprint: &[u8]) -> Result<bool, IndyCryptoError> {
+        Ok(self.fingerprint()? == fingerprint)
+    }
+
+    /// Checks that both entities contain the same key regardless of usage constraints.
+    pub fn is_same_key(&self, other: &CredentialPublicKey) -> Result<bool, IndyCryptoError> {
+        Ok(self.fingerprint()? == other.fingerprint()?)
+    }
+
     pub fn get_primary_key(&self) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
         Ok(self.p_key.clone()?)
     }
@@ -271,180 +736,1245 @@ impl CredentialPublicKey {
     pub fn build_from_parts(p_key: &CredentialPrimaryPublicKey, r_key: Option<&CredentialRevocationPublicKey>) -> Result<CredentialPublicKey, IndyCryptoError> {
         Ok(CredentialPublicKey {
             p_key: p_key.clone()?,
-            r_key: r_key.map(|key| key.clone())
+            r_key: r_key.map(|key| key.clone()),
+            usage_constraints: None
         })
     }
 }
 
 /// `Issuer Private Key`: contains 2 internal parts.
 /// One for signing primary credentials and second for signing non-revocation credentials.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CredentialPrivateKey {
+    #[serde(alias = "pKey")]
     p_key: CredentialPrimaryPrivateKey,
+    #[serde(alias = "rKey")]
     r_key: Option<CredentialRevocationPrivateKey>,
 }
 
-/// Issuer's "Public Key" is used to verify the Issuer's signature over the Credential's attributes' values (primary credential).
-#[derive(Debug, PartialEq, Serialize)]
-pub struct CredentialPrimaryPublicKey {
-    n: BigNumber,
-    s: BigNumber,
-    r: HashMap<String /* attr_name */, BigNumber>,
-    rctxt: BigNumber,
-    z: BigNumber
-}
-
-impl CredentialPrimaryPublicKey {
-    pub fn clone(&self) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
-        Ok(CredentialPrimaryPublicKey {
-            n: self.n.clone()?,
-            s: self.s.clone()?,
-            r: clone_bignum_map(&self.r)?,
-            rctxt: self.rctxt.clone()?,
-            z: self.z.clone()?
-        })
+impl fmt::Debug for CredentialPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialPrivateKey {{ p_key: {:?}, r_key: {:?} }}", self.p_key, self.r_key)
     }
 }
 
-impl <'a> ::serde::de::Deserialize<'a> for CredentialPrimaryPublicKey {
-    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
-        #[derive(Deserialize)]
-        struct CredentialPrimaryPublicKeyV1 {
-            n: BigNumber,
-            s: BigNumber,
-            r: HashMap<String /* attr_name */, BigNumber>,
-            rctxt: BigNumber,
-            #[serde(default)]
-            rms: BigNumber,
-            z: BigNumber
-        }
+impl CredentialPrivateKey {
+    /// Builds private key from primary and revocation parts, e.g. imported from separate custodians.
+    pub fn from_parts(p_key: CredentialPrimaryPrivateKey, r_key: Option<CredentialRevocationPrivateKey>) -> CredentialPrivateKey {
+        CredentialPrivateKey { p_key, r_key }
+    }
 
-        let mut helper = CredentialPrimaryPublicKeyV1::deserialize(deserializer)?;
-        if helper.rms != BigNumber::default() {
-            helper.r.insert("master_secret".to_string(), helper.rms);
-        }
-        Ok(CredentialPrimaryPublicKey {
-            n: helper.n,
-            s: helper.s,
-            rctxt: helper.rctxt,
-            z: helper.z,
-            r: helper.r
-        })
+    /// Splits private key into primary and revocation parts, so they can be exported and kept separately.
+    pub fn into_parts(self) -> (CredentialPrimaryPrivateKey, Option<CredentialRevocationPrivateKey>) {
+        (self.p_key, self.r_key)
     }
 }
 
-/// Issuer's "Private Key" used for signing Credential's attributes' values (primary credential)
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
-pub struct CredentialPrimaryPrivateKey {
-    p: BigNumber,
-    q: BigNumber
+/// Issuer's credential key pair (primary and revocation keys) that can be exported as password encrypted backup.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IssuerKeyPair {
+    #[serde(alias = "pubKey")]
+    pub_key: CredentialPublicKey,
+    #[serde(alias = "privKey")]
+    priv_key: CredentialPrivateKey
 }
 
-/// `Primary Public Key Metadata` required for building of Proof Correctness of `Issuer Public Key`
-#[derive(Debug)]
-pub struct CredentialPrimaryPublicKeyMetadata {
-    xz: BigNumber,
-    xr: HashMap<String, BigNumber>
-}
+impl IssuerKeyPair {
+    /// Creates key pair checking that private key corresponds to public key.
+    ///
+    /// # Arguments
+    /// * `pub_key` - Credential public key.
+    /// * `priv_key` - Credential private key.
+    pub fn new(pub_key: CredentialPublicKey, priv_key: CredentialPrivateKey) -> Result<IssuerKeyPair, IndyCryptoError> {
+        let key_pair = IssuerKeyPair { pub_key, priv_key };
+        key_pair._check_keys()?;
+        Ok(key_pair)
+    }
 
-/// Proof of `Issuer Public Key` correctness
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
-pub struct CredentialKeyCorrectnessProof {
-    c: BigNumber,
-    xz_cap: BigNumber,
-    xr_cap: Vec<(String, BigNumber)>,
-}
+    pub fn pub_key(&self) -> &CredentialPublicKey {
+        &self.pub_key
+    }
 
-/// `Revocation Public Key` is used to verify that credential was'nt revoked by Issuer.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
-pub struct CredentialRevocationPublicKey {
-    g: PointG1,
-    g_dash: PointG2,
-    h: PointG1,
-    h0: PointG1,
-    h1: PointG1,
-    h2: PointG1,
-    htilde: PointG1,
-    h_cap: PointG2,
-    u: PointG2,
-    pk: PointG1,
-    y: PointG2,
-}
+    pub fn priv_key(&self) -> &CredentialPrivateKey {
+        &self.priv_key
+    }
 
-/// `Revocation Private Key` is used for signing Credential.
-#[derive(Debug, Deserialize, Serialize)]
-pub struct CredentialRevocationPrivateKey {
-    x: GroupOrderElement,
-    sk: GroupOrderElement
+    pub fn into_parts(self) -> (CredentialPublicKey, CredentialPrivateKey) {
+        (self.pub_key, self.priv_key)
+    }
+
+    /// Exports key pair as authenticated blob encrypted with AES-256-GCM under the key derived from password:
+    /// `version (1 byte) | salt | iv | tag | ciphertext`.
+    ///
+    /// # Arguments
+    /// * `password` - Password to derive encryption key from.
+    pub fn export(&self, password: &str) -> Result<Vec<u8>, IndyCryptoError> {
+        trace!("IssuerKeyPair::export: >>> pub_key: {:?}", self.pub_key);
+
+        let blob = _export_encrypted(self, password)?;
+
+        trace!("IssuerKeyPair::export: <<< blob: {:?} bytes", blob.len());
+
+        Ok(blob)
+    }
+
+    /// Imports key pair exported by `export`. Fails if password is wrong or blob was modified.
+    ///
+    /// # Arguments
+    /// * `blob` - Exported key pair.
+    /// * `password` - Password key pair was exported with.
+    pub fn import(blob: &[u8], password: &str) -> Result<IssuerKeyPair, IndyCryptoError> {
+        trace!("IssuerKeyPair::import: >>> blob: {:?} bytes", blob.len());
+
+        let key_pair: IssuerKeyPair = _import_encrypted(blob, password)?;
+        key_pair._check_keys()?;
+
+        trace!("IssuerKeyPair::import: <<< pub_key: {:?}", key_pair.pub_key);
+
+        Ok(key_pair)
+    }
+
+    fn _check_keys(&self) -> Result<(), IndyCryptoError> {
+        self.priv_key.p_key._check(&self.pub_key)?;
+
+        match (self.pub_key.r_key.as_ref(), self.priv_key.r_key.as_ref()) {
+            (Some(_), Some(r_priv_key)) => r_priv_key._check(&self.pub_key),
+            (None, None) => Ok(()),
+            _ => Err(IndyCryptoError::InvalidStructure("Revocation part must be present in both keys or in none".to_string()))
+        }
+    }
 }
 
-pub type Accumulator = PointG2;
+/// Encrypts serialized entity with AES-256-GCM under the key derived from password:
+/// `version (1 byte) | salt | iv | tag | ciphertext`.
+fn _export_encrypted<T: ::serde::Serialize>(entity: &T, password: &str) -> Result<Vec<u8>, IndyCryptoError> {
+    let salt = aead::gen_salt()?;
+    let iv = aead::gen_iv()?;
+    let (ciphertext, tag) = aead::encrypt(&aead::derive_key(password.as_bytes(), &salt)?, &iv, &::serde_json::to_vec(entity)?)?;
 
-/// `Revocation Registry` contains accumulator.
-/// Must be published by Issuer on a tamper-evident and highly available storage
-/// Used by prover to prove that a credential hasn't revoked by the issuer
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct RevocationRegistry {
-    accum: Accumulator
+    let mut blob = vec![constants::ISSUER_KEY_PAIR_EXPORT_VERSION];
+    blob.extend_from_slice(&salt);
+    blob.extend_from_slice(&iv);
+    blob.extend_from_slice(&tag);
+    blob.extend_from_slice(&ciphertext);
+
+    Ok(blob)
 }
 
-impl From<RevocationRegistryDelta> for RevocationRegistry {
-    fn from(rev_reg_delta: RevocationRegistryDelta) -> RevocationRegistry {
-        RevocationRegistry { accum: rev_reg_delta.accum }
+fn _import_encrypted<T: ::serde::de::DeserializeOwned>(blob: &[u8], password: &str) -> Result<T, IndyCryptoError> {
+    let header_len = 1 + aead::SALT_LEN + aead::IV_LEN + aead::TAG_LEN;
+
+    if blob.len() <= header_len {
+        return Err(IndyCryptoError::InvalidStructure("Exported key is too short".to_string()));
     }
-}
 
-/// `Revocation Registry Delta` contains Accumulator changes.
-/// Must be applied to `Revocation Registry`
-#[derive(Debug, Clone, Deserialize, Serialize)]
+    if blob[0] != constants::ISSUER_KEY_PAIR_EXPORT_VERSION {
+        return Err(IndyCryptoError::InvalidStructure(format!("Unsupported version of exported key: {}", blob[0])));
+    }
+
+    let (salt, rest) = blob[1..].split_at(aead::SALT_LEN);
+    let (iv, rest) = rest.split_at(aead::IV_LEN);
+    let (tag, ciphertext) = rest.split_at(aead::TAG_LEN);
+
+    let bytes = aead::decrypt(&aead::derive_key(password.as_bytes(), salt)?, iv, ciphertext, tag)?;
+
+    Ok(::serde_json::from_slice(&bytes)?)
+}
+
+/// Constraints on usage of `Credential Keys` Issuer embeds into `Credential Public Key`
+/// to bound the damage of stolen or misconfigured keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CredentialKeyUsageConstraints {
+    #[serde(alias = "maxAttrs")]
+    max_attrs: Option<u32>,
+    #[serde(alias = "revocableOnly")]
+    revocable_only: bool,
+    #[serde(alias = "validFrom")]
+    valid_from: Option<u64>,
+    #[serde(alias = "validTo")]
+    valid_to: Option<u64>
+}
+
+impl CredentialKeyUsageConstraints {
+    pub fn max_attrs(&self) -> Option<u32> {
+        self.max_attrs
+    }
+
+    pub fn revocable_only(&self) -> bool {
+        self.revocable_only
+    }
+
+    pub fn valid_from(&self) -> Option<u64> {
+        self.valid_from
+    }
+
+    pub fn valid_to(&self) -> Option<u64> {
+        self.valid_to
+    }
+
+    /// Checks that keys can be used at given time (seconds since the epoch).
+    pub fn check_validity(&self, time: u64) -> Result<(), IndyCryptoError> {
+        if self.valid_from.map(|valid_from| time < valid_from).unwrap_or(false) ||
+            self.valid_to.map(|valid_to| time > valid_to).unwrap_or(false) {
+            return Err(IndyCryptoError::InvalidState(format!("Credential keys are not valid at {}", time)));
+        }
+        Ok(())
+    }
+
+    fn check_attrs(&self, attrs_count: usize) -> Result<(), IndyCryptoError> {
+        if self.max_attrs.map(|max_attrs| attrs_count > max_attrs as usize).unwrap_or(false) {
+            return Err(IndyCryptoError::InvalidState(format!("Credential keys allow to sign {:?} attributes at most", self.max_attrs)));
+        }
+        Ok(())
+    }
+
+    fn check_revocable(&self, revocable: bool) -> Result<(), IndyCryptoError> {
+        if self.revocable_only && !revocable {
+            return Err(IndyCryptoError::InvalidState("Credential keys allow to sign revocable credentials only".to_string()));
+        }
+        Ok(())
+    }
+
+    fn hash(&self, n: &BigNumber, ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+        // Squared to get an element of QR(n)
+        BigNumber::from_bytes(&BigNumber::hash(&::serde_json::to_vec(self)?)?)?
+            .mod_exp(&BIGNUMBER_2, n, Some(ctx))
+    }
+}
+
+/// A Builder of `Credential Key Usage Constraints`.
+#[derive(Debug)]
+pub struct CredentialKeyUsageConstraintsBuilder {
+    value: CredentialKeyUsageConstraints
+}
+
+impl CredentialKeyUsageConstraintsBuilder {
+    pub fn new() -> Result<CredentialKeyUsageConstraintsBuilder, IndyCryptoError> {
+        Ok(CredentialKeyUsageConstraintsBuilder { value: CredentialKeyUsageConstraints::default() })
+    }
+
+    pub fn set_max_attrs(&mut self, max_attrs: u32) -> Result<(), IndyCryptoError> {
+        self.value.max_attrs = Some(max_attrs);
+        Ok(())
+    }
+
+    pub fn set_revocable_only(&mut self, revocable_only: bool) -> Result<(), IndyCryptoError> {
+        self.value.revocable_only = revocable_only;
+        Ok(())
+    }
+
+    pub fn set_validity_period(&mut self, valid_from: Option<u64>, valid_to: Option<u64>) -> Result<(), IndyCryptoError> {
+        if let (Some(valid_from), Some(valid_to)) = (valid_from, valid_to) {
+            if valid_from > valid_to {
+                return Err(IndyCryptoError::InvalidStructure(format!("Invalid validity period: {} is greater than {}", valid_from, valid_to)));
+            }
+        }
+        self.value.valid_from = valid_from;
+        self.value.valid_to = valid_to;
+        Ok(())
+    }
+
+    pub fn finalize(self) -> Result<CredentialKeyUsageConstraints, IndyCryptoError> {
+        Ok(self.value)
+    }
+}
+
+/// `Credential Key Usage Constraints` signed with Issuer's primary private key.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SignedCredentialKeyUsageConstraints {
+    constraints: CredentialKeyUsageConstraints,
+    signature: BigNumber
+}
+
+impl SignedCredentialKeyUsageConstraints {
+    pub fn clone(&self) -> Result<SignedCredentialKeyUsageConstraints, IndyCryptoError> {
+        Ok(SignedCredentialKeyUsageConstraints {
+            constraints: self.constraints.clone(),
+            signature: self.signature.clone()?
+        })
+    }
+
+    fn verify(&self, n: &BigNumber) -> Result<bool, IndyCryptoError> {
+        let mut ctx = BigNumber::new_context()?;
+        let e = BigNumber::from_u32(constants::KEY_USAGE_CONSTRAINTS_EXPONENT)?;
+        Ok(self.signature.mod_exp(&e, n, Some(&mut ctx))? == self.constraints.hash(n, &mut ctx)?)
+    }
+}
+
+/// Statement signed with the predecessor `Credential Private Key` that links it to the successor key.
+/// Verifiers that trust the predecessor key can accept the successor key without out of band checks.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KeyRotationStatement {
+    #[serde(alias = "oldKeyFingerprint")]
+    old_key_fingerprint: Vec<u8>,
+    #[serde(alias = "newKeyFingerprint")]
+    new_key_fingerprint: Vec<u8>,
+    #[serde(alias = "rotatedAt")]
+    rotated_at: Timestamp,
+    signature: BigNumber
+}
+
+impl KeyRotationStatement {
+    pub fn old_key_fingerprint(&self) -> &[u8] {
+        &self.old_key_fingerprint
+    }
+
+    pub fn new_key_fingerprint(&self) -> &[u8] {
+        &self.new_key_fingerprint
+    }
+
+    pub fn rotated_at(&self) -> Timestamp {
+        self.rotated_at
+    }
+
+    /// Checks that statement links the given keys and is signed with the predecessor key.
+    ///
+    /// # Arguments
+    /// * `old_pub_key` - Predecessor credential public key.
+    /// * `new_pub_key` - Successor credential public key.
+    pub fn verify(&self, old_pub_key: &CredentialPublicKey, new_pub_key: &CredentialPublicKey) -> Result<bool, IndyCryptoError> {
+        if !old_pub_key.has_fingerprint(&self.old_key_fingerprint)? || !new_pub_key.has_fingerprint(&self.new_key_fingerprint)? {
+            return Ok(false);
+        }
+
+        let mut ctx = BigNumber::new_context()?;
+        let n = &old_pub_key.p_key.n;
+        let e = BigNumber::from_u32(constants::KEY_ROTATION_EXPONENT)?;
+        let hash = KeyRotationStatement::hash(&self.old_key_fingerprint, &self.new_key_fingerprint, self.rotated_at, n, &mut ctx)?;
+
+        Ok(self.signature.mod_exp(&e, n, Some(&mut ctx))? == hash)
+    }
+
+    fn hash(old_key_fingerprint: &[u8],
+            new_key_fingerprint: &[u8],
+            rotated_at: Timestamp,
+            n: &BigNumber,
+            ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError> {
+        let values: Vec<Vec<u8>> = vec![
+            constants::KEY_ROTATION_DOMAIN.to_vec(),
+            old_key_fingerprint.to_vec(),
+            new_key_fingerprint.to_vec(),
+            u64_to_be_bytes(rotated_at.as_secs())
+        ];
+        // Squared to get an element of QR(n)
+        BigNumber::from_bytes(&BigNumber::hash_array(&values)?)?
+            .mod_exp(&BIGNUMBER_2, n, Some(ctx))
+    }
+}
+
+/// Sizes (in bits) of primary key and credential signature parameters.
+///
+/// Default configuration corresponds to 2048-bit modulus and `cl::constants`.
+/// Configuration is recorded in credential public key, so Prover and Verifier use the same sizes as Issuer.
+/// Sizes of blinding values used in proofs are derived from the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CredentialKeyConfig {
+    #[serde(alias = "largePrime")]
+    large_prime: usize,
+    #[serde(alias = "largeEStart")]
+    large_e_start: usize,
+    #[serde(alias = "largeEEndRange")]
+    large_e_end_range: usize,
+    #[serde(alias = "largeVprime")]
+    large_vprime: usize,
+    #[serde(alias = "largeVprimePrime")]
+    large_vprime_prime: usize
+}
+
+impl Default for CredentialKeyConfig {
+    fn default() -> CredentialKeyConfig {
+        CredentialKeyConfig {
+            large_prime: constants::LARGE_PRIME,
+            large_e_start: constants::LARGE_E_START,
+            large_e_end_range: constants::LARGE_E_END_RANGE,
+            large_vprime: constants::LARGE_VPRIME,
+            large_vprime_prime: constants::LARGE_VPRIME_PRIME
+        }
+    }
+}
+
+impl CredentialKeyConfig {
+    /// Creates configuration with recommended parameters for modulus of the given size.
+    ///
+    /// # Arguments
+    /// * `modulus_size` - Size of modulus `n` in bits: 2048, 3072 or 4096.
+    pub fn new(modulus_size: usize) -> Result<CredentialKeyConfig, IndyCryptoError> {
+        match modulus_size {
+            2048 | 3072 | 4096 => {}
+            _ => return Err(IndyCryptoError::InvalidParam1(format!("Unsupported modulus size: {}", modulus_size)))
+        }
+
+        let default = CredentialKeyConfig::default();
+        let modulus_delta = modulus_size - 2 * default.large_prime;
+
+        CredentialKeyConfig::from_params(modulus_size / 2,
+                                         default.large_e_start,
+                                         default.large_e_end_range,
+                                         default.large_vprime + modulus_delta,
+                                         default.large_vprime_prime + modulus_delta)
+    }
+
+    /// Creates configuration with custom parameters. Parameters are checked for consistency.
+    ///
+    /// # Arguments
+    /// * `large_prime` - Size of primes `p'` and `q'` (half of modulus size).
+    /// * `large_e_start` - `e` is a prime from `[2^large_e_start, 2^large_e_start + 2^large_e_end_range]`.
+    /// * `large_e_end_range` - See `large_e_start`.
+    /// * `large_vprime` - Size of Prover's blinding factor `v'`.
+    /// * `large_vprime_prime` - Size of Issuer's signature part `v''`.
+    pub fn from_params(large_prime: usize,
+                       large_e_start: usize,
+                       large_e_end_range: usize,
+                       large_vprime: usize,
+                       large_vprime_prime: usize) -> Result<CredentialKeyConfig, IndyCryptoError> {
+        let config = CredentialKeyConfig { large_prime, large_e_start, large_e_end_range, large_vprime, large_vprime_prime };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns size of modulus `n` in bits.
+    pub fn modulus_size(&self) -> usize {
+        2 * self.large_prime
+    }
+
+    /// Checks parameters against constraints of CL signature security analysis:
+    /// `e` is larger than responses for attributes and can't be forged from them,
+    /// `v'` statistically hides committed attributes, `v''` is larger than responses for `v'` and attributes.
+    pub fn validate(&self) -> Result<(), IndyCryptoError> {
+        // l_H - hash size, l_phi - statistical zero knowledge parameter, l_m - attribute size, l_r - security parameter
+        let (l_h, l_phi, l_m, l_r) = (256, 80, 256, 80);
+
+        match self.modulus_size() {
+            2048 | 3072 | 4096 => {}
+            modulus_size => return Err(IndyCryptoError::InvalidStructure(format!("Unsupported modulus size: {}", modulus_size)))
+        }
+
+        // `e` is `large_e_start + 1` bits long
+        if self.large_e_start < l_phi + l_h + ::std::cmp::max(l_m + 4, self.large_e_end_range + 2) {
+            return Err(IndyCryptoError::InvalidStructure("Size of e is too small".to_string()));
+        }
+
+        if self.large_vprime < self.modulus_size() + l_phi {
+            return Err(IndyCryptoError::InvalidStructure("Size of v' is too small for modulus size".to_string()));
+        }
+
+        if self.large_vprime_prime <= self.modulus_size() + l_phi + l_h + ::std::cmp::max(l_m + l_r + 3, l_phi + 2) {
+            return Err(IndyCryptoError::InvalidStructure("Size of v'' is too small for modulus size".to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn is_default(&self) -> bool {
+        *self == CredentialKeyConfig::default()
+    }
+
+    fn large_etilde(&self) -> usize {
+        self.large_e_end_range + constants::LARGE_ETILDE - constants::LARGE_E_END_RANGE
+    }
+
+    fn large_vtilde(&self) -> usize {
+        self.large_vprime_prime + constants::LARGE_VTILDE - constants::LARGE_VPRIME_PRIME
+    }
+
+    fn large_alphatilde(&self) -> usize {
+        self.large_vprime_prime + constants::LARGE_ALPHATILDE - constants::LARGE_VPRIME_PRIME
+    }
+
+    fn e_start_value(&self) -> Result<BigNumber, IndyCryptoError> {
+        BIGNUMBER_2.exp(&BigNumber::from_u32(self.large_e_start)?, None)
+    }
+
+    fn e_end_range_value(&self) -> Result<BigNumber, IndyCryptoError> {
+        BIGNUMBER_2.exp(&BigNumber::from_u32(self.large_e_end_range)?, None)?
+            .add(&self.e_start_value()?)
+    }
+}
+
+/// Progress of credential keys generation reported after every tested safe prime candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyGenProgress {
+    /// Number of safe primes found (two are required for primary key).
+    pub primes_found: usize,
+    /// Number of safe prime candidates tested.
+    pub candidates_tested: usize
+}
+
+/// Token that allows to cancel long running operation (e.g. credential keys generation) from other thread.
+/// Clones of the token share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Requests cancellation. Operation stops before testing the next candidate.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, AtomicOrdering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(AtomicOrdering::SeqCst)
+    }
+}
+
+/// Issuer's "Public Key" is used to verify the Issuer's signature over the Credential's attributes' values (primary credential).
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CredentialPrimaryPublicKey {
+    n: BigNumber,
+    s: BigNumber,
+    r: HashMap<String /* attr_name */, BigNumber>,
+    rctxt: BigNumber,
+    z: BigNumber,
+    #[serde(skip_serializing_if = "CredentialKeyConfig::is_default")]
+    config: CredentialKeyConfig
+}
+
+impl CredentialPrimaryPublicKey {
+    pub fn clone(&self) -> Result<CredentialPrimaryPublicKey, IndyCryptoError> {
+        Ok(CredentialPrimaryPublicKey {
+            n: self.n.clone()?,
+            s: self.s.clone()?,
+            r: clone_bignum_map(&self.r)?,
+            rctxt: self.rctxt.clone()?,
+            z: self.z.clone()?,
+            config: self.config
+        })
+    }
+
+    /// Returns sizes of key and signature parameters.
+    pub fn config(&self) -> &CredentialKeyConfig {
+        &self.config
+    }
+}
+
+impl <'a> ::serde::de::Deserialize<'a> for CredentialPrimaryPublicKey {
+    fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct CredentialPrimaryPublicKeyV1 {
+            n: BigNumber,
+            s: BigNumber,
+            r: HashMap<String /* attr_name */, BigNumber>,
+            rctxt: BigNumber,
+            #[serde(default)]
+            rms: BigNumber,
+            z: BigNumber,
+            #[serde(default)]
+            config: CredentialKeyConfig
+        }
+
+        let mut helper = CredentialPrimaryPublicKeyV1::deserialize(deserializer)?;
+        if helper.rms != BigNumber::default() {
+            helper.r.insert("master_secret".to_string(), helper.rms);
+        }
+        Ok(CredentialPrimaryPublicKey {
+            n: helper.n,
+            s: helper.s,
+            rctxt: helper.rctxt,
+            z: helper.z,
+            r: helper.r,
+            config: helper.config
+        })
+    }
+}
+
+/// Issuer's "Private Key" used for signing Credential's attributes' values (primary credential)
+#[derive(PartialEq, Deserialize, Serialize)]
+pub struct CredentialPrimaryPrivateKey {
+    p: BigNumber,
+    q: BigNumber
+}
+
+impl fmt::Debug for CredentialPrimaryPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialPrimaryPrivateKey {{ p: <redacted>, q: <redacted> }}")
+    }
+}
+
+impl CredentialPrimaryPrivateKey {
+    /// Exports primary part of private key separately from revocation part.
+    /// Blob has the same format as `IssuerKeyPair::export`.
+    ///
+    /// # Arguments
+    /// * `password` - Password to derive encryption key from.
+    pub fn export(&self, password: &str) -> Result<Vec<u8>, IndyCryptoError> {
+        _export_encrypted(self, password)
+    }
+
+    /// Imports primary part of private key exported by `export`
+    /// checking that it corresponds to the credential public key.
+    ///
+    /// # Arguments
+    /// * `blob` - Exported primary private key.
+    /// * `password` - Password key was exported with.
+    /// * `pub_key` - Credential public key.
+    pub fn import(blob: &[u8], password: &str, pub_key: &CredentialPublicKey) -> Result<CredentialPrimaryPrivateKey, IndyCryptoError> {
+        trace!("CredentialPrimaryPrivateKey::import: >>> blob: {:?} bytes, pub_key: {:?}", blob.len(), pub_key);
+
+        let p_key: CredentialPrimaryPrivateKey = _import_encrypted(blob, password)?;
+        p_key._check(pub_key)?;
+
+        trace!("CredentialPrimaryPrivateKey::import: <<<");
+
+        Ok(p_key)
+    }
+
+    fn _check(&self, pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        let p_safe = self.p.lshift1()?.increment()?;
+        let q_safe = self.q.lshift1()?.increment()?;
+
+        if p_safe.mul(&q_safe, None)? != pub_key.p_key.n {
+            return Err(IndyCryptoError::InvalidStructure("Primary private key doesn't correspond to public key".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// `Primary Public Key Metadata` required for building of Proof Correctness of `Issuer Public Key`
+#[derive(Debug)]
+pub struct CredentialPrimaryPublicKeyMetadata {
+    xz: BigNumber,
+    xr: HashMap<String, BigNumber>
+}
+
+/// Proof of `Issuer Public Key` correctness
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct CredentialKeyCorrectnessProof {
+    c: BigNumber,
+    #[serde(alias = "xzCap")]
+    xz_cap: BigNumber,
+    #[serde(alias = "xrCap")]
+    xr_cap: Vec<(String, BigNumber)>,
+}
+
+impl CredentialKeyCorrectnessProof {
+    pub fn clone(&self) -> Result<CredentialKeyCorrectnessProof, IndyCryptoError> {
+        let mut xr_cap = Vec::new();
+        for (attr, value) in self.xr_cap.iter() {
+            xr_cap.push((attr.clone(), value.clone()?));
+        }
+
+        Ok(CredentialKeyCorrectnessProof {
+            c: self.c.clone()?,
+            xz_cap: self.xz_cap.clone()?,
+            xr_cap
+        })
+    }
+}
+
+/// `Revocation Public Key` is used to verify that credential was'nt revoked by Issuer.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CredentialRevocationPublicKey {
+    g: PointG1,
+    #[serde(alias = "gDash")]
+    g_dash: PointG2,
+    h: PointG1,
+    h0: PointG1,
+    h1: PointG1,
+    h2: PointG1,
+    htilde: PointG1,
+    #[serde(alias = "hCap")]
+    h_cap: PointG2,
+    u: PointG2,
+    pk: PointG1,
+    y: PointG2,
+}
+
+/// `Revocation Private Key` is used for signing Credential.
+#[derive(Deserialize, Serialize)]
+pub struct CredentialRevocationPrivateKey {
+    x: GroupOrderElement,
+    sk: GroupOrderElement
+}
+
+impl fmt::Debug for CredentialRevocationPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialRevocationPrivateKey {{ x: <redacted>, sk: <redacted> }}")
+    }
+}
+
+impl CredentialRevocationPrivateKey {
+    /// Exports revocation part of private key separately from primary part.
+    /// Blob has the same format as `IssuerKeyPair::export`.
+    ///
+    /// # Arguments
+    /// * `password` - Password to derive encryption key from.
+    pub fn export(&self, password: &str) -> Result<Vec<u8>, IndyCryptoError> {
+        _export_encrypted(self, password)
+    }
+
+    /// Imports revocation part of private key exported by `export`
+    /// checking that it corresponds to the credential public key.
+    ///
+    /// # Arguments
+    /// * `blob` - Exported revocation private key.
+    /// * `password` - Password key was exported with.
+    /// * `pub_key` - Credential public key.
+    pub fn import(blob: &[u8], password: &str, pub_key: &CredentialPublicKey) -> Result<CredentialRevocationPrivateKey, IndyCryptoError> {
+        trace!("CredentialRevocationPrivateKey::import: >>> blob: {:?} bytes, pub_key: {:?}", blob.len(), pub_key);
+
+        let r_key: CredentialRevocationPrivateKey = _import_encrypted(blob, password)?;
+        r_key._check(pub_key)?;
+
+        trace!("CredentialRevocationPrivateKey::import: <<<");
+
+        Ok(r_key)
+    }
+
+    fn _check(&self, pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        let r_pub_key = pub_key.r_key.as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure("There are not revocation keys in the credential public key".to_string()))?;
+
+        if r_pub_key.g.mul(&self.sk)? != r_pub_key.pk || r_pub_key.h_cap.mul(&self.x)? != r_pub_key.y {
+            return Err(IndyCryptoError::InvalidStructure("Revocation private key doesn't correspond to public key".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+pub type Accumulator = PointG2;
+
+/// `Revocation Registry` contains accumulator.
+/// Must be published by Issuer on a tamper-evident and highly available storage
+/// Used by prover to prove that a credential hasn't revoked by the issuer
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RevocationRegistry {
+    accum: Accumulator
+}
+
+impl RevocationRegistry {
+    /// Returns current accumulator of the registry.
+    pub fn accum(&self) -> &Accumulator {
+        &self.accum
+    }
+
+    /// Applies delta published by Issuer to this registry.
+    ///
+    /// Delta must start from the current accumulator of the registry,
+    /// so deltas have to be applied in the order they were produced (or merged first).
+    pub fn apply_delta(&mut self, rev_reg_delta: &RevocationRegistryDelta) -> Result<(), IndyCryptoError> {
+        match rev_reg_delta.prev_accum {
+            Some(prev_accum) if prev_accum == self.accum => {
+                self.accum = rev_reg_delta.accum;
+                Ok(())
+            }
+            _ => Err(IndyCryptoError::InvalidStructure("Delta can not be applied to the revocation registry.".to_string()))
+        }
+    }
+
+    /// Moves this registry into the new accumulator epoch published by Issuer.
+    ///
+    /// Transition must start from the current accumulator of the registry.
+    pub fn apply_epoch_transition(&mut self, transition: &RevocationRegistryEpochTransition) -> Result<(), IndyCryptoError> {
+        if transition.prev_accum != self.accum {
+            return Err(IndyCryptoError::InvalidStructure("Epoch transition can not be applied to the revocation registry.".to_string()));
+        }
+        self.accum = transition.accum;
+        Ok(())
+    }
+
+    /// Serializes revocation registry as json directly to writer (e.g. socket or file) without building intermediate string.
+    pub fn write_to<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), IndyCryptoError> {
+        ::utils::json::to_writer(self, writer)
+    }
+
+    /// Deserializes revocation registry from json read directly from reader.
+    pub fn read_from<R: ::std::io::Read>(reader: &mut R) -> Result<RevocationRegistry, IndyCryptoError> {
+        ::utils::json::from_reader(reader)
+    }
+
+    /// Returns occupancy and revocation statistics of the registry replaying its history.
+    ///
+    /// # Arguments
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `issuance_by_default` - Type of issuance of revocation registry.
+    /// * `history` - History of revocation registry deltas in chronological order.
+    pub fn stats(max_cred_num: u32,
+                 issuance_by_default: bool,
+                 history: &[RevocationRegistryHistoryEntry]) -> Result<RevocationRegistryStats, IndyCryptoError> {
+        trace!("RevocationRegistry::stats: >>> max_cred_num: {:?}, issuance_by_default: {:?}, history: {:?}",
+               max_cred_num, issuance_by_default, history);
+
+        if max_cred_num == 0 {
+            return Err(IndyCryptoError::InvalidParam1("Max credential number must be positive".to_string()));
+        }
+
+        // Indices issued or revoked by deltas; with issuance by default all indices are used from the start
+        let mut used: HashSet<u32> = HashSet::new();
+        let mut revoked: HashSet<u32> = HashSet::new();
+        let mut revocations: u64 = 0;
+        let mut deltas = Vec::with_capacity(history.len());
+
+        for entry in history {
+            let rev_reg_delta = &entry.rev_reg_delta;
+
+            if let Some(rev_idx) = rev_reg_delta.issued.iter().chain(rev_reg_delta.revoked.iter())
+                .find(|rev_idx| **rev_idx == 0 || **rev_idx > max_cred_num) {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Revocation index {} of delta at {} is out of registry range", rev_idx, entry.timestamp)));
+            }
+
+            for rev_idx in rev_reg_delta.issued.iter() {
+                used.insert(*rev_idx);
+                revoked.remove(rev_idx);
+            }
+
+            for rev_idx in rev_reg_delta.revoked.iter() {
+                used.insert(*rev_idx);
+                revoked.insert(*rev_idx);
+            }
+
+            revocations += rev_reg_delta.revoked.len() as u64;
+
+            let used_count = if issuance_by_default { max_cred_num } else { used.len() as u32 };
+            deltas.push(RevocationRegistryDeltaStats {
+                timestamp: entry.timestamp,
+                issued: rev_reg_delta.issued.len() as u32,
+                revoked: rev_reg_delta.revoked.len() as u32,
+                active: used_count - revoked.len() as u32
+            });
+        }
+
+        let used_count = if issuance_by_default { max_cred_num } else { used.len() as u32 };
+
+        let stats = RevocationRegistryStats {
+            max_cred_num,
+            used: used_count,
+            active: used_count - revoked.len() as u32,
+            revoked: revoked.len() as u32,
+            revocations,
+            occupancy: used_count as f64 / max_cred_num as f64,
+            deltas
+        };
+
+        trace!("RevocationRegistry::stats: <<< stats: {:?}", stats);
+
+        Ok(stats)
+    }
+}
+
+/// Statistics of `Revocation Registry` for issuer dashboards.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationRegistryStats {
+    pub max_cred_num: u32,
+    /// Number of indices that have ever been issued.
+    pub used: u32,
+    /// Number of currently issued and not revoked indices.
+    pub active: u32,
+    /// Number of currently revoked indices.
+    pub revoked: u32,
+    /// Total number of revocations in the history, including later recovered ones.
+    pub revocations: u64,
+    /// Share of used indices in the registry (0 to 1).
+    pub occupancy: f64,
+    pub deltas: Vec<RevocationRegistryDeltaStats>
+}
+
+impl RevocationRegistryStats {
+    pub fn to_json(&self) -> Result<String, IndyCryptoError> {
+        Ok(::serde_json::to_string(self)?)
+    }
+}
+
+/// Size of `Revocation Registry Delta` of the history and number of active indices after it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationRegistryDeltaStats {
+    pub timestamp: Timestamp,
+    pub issued: u32,
+    pub revoked: u32,
+    pub active: u32
+}
+
+impl From<RevocationRegistryDelta> for RevocationRegistry {
+    fn from(rev_reg_delta: RevocationRegistryDelta) -> RevocationRegistry {
+        RevocationRegistry { accum: rev_reg_delta.accum }
+    }
+}
+
+/// `Revocation Registry Delta` contains Accumulator changes.
+/// Must be applied to `Revocation Registry`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationRegistryDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "prev_accum")]
+    prev_accum: Option<Accumulator>,
+    accum: Accumulator,
+    #[serde(skip_serializing_if = "HashSet::is_empty")]
+    #[serde(default)]
+    issued: HashSet<u32>,
+    #[serde(skip_serializing_if = "HashSet::is_empty")]
+    #[serde(default)]
+    revoked: HashSet<u32>
+}
+
+impl RevocationRegistryDelta {
+    pub fn from_parts(rev_reg_from: Option<&RevocationRegistry>,
+                      rev_reg_to: &RevocationRegistry,
+                      issued: &HashSet<u32>,
+                      revoked: &HashSet<u32>) -> RevocationRegistryDelta {
+        RevocationRegistryDelta {
+            prev_accum: rev_reg_from.map(|rev_reg| rev_reg.accum),
+            accum: rev_reg_to.accum.clone(),
+            issued: issued.clone(),
+            revoked: revoked.clone()
+        }
+    }
+
+    /// Indices issued by this delta.
+    pub fn issued(&self) -> &HashSet<u32> {
+        &self.issued
+    }
+
+    /// Indices revoked by this delta.
+    pub fn revoked(&self) -> &HashSet<u32> {
+        &self.revoked
+    }
+
+    /// Serializes revocation registry delta as json directly to writer (e.g. socket or file) without building intermediate string.
+    pub fn write_to<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), IndyCryptoError> {
+        ::utils::json::to_writer(self, writer)
+    }
+
+    /// Deserializes revocation registry delta from json read directly from reader.
+    pub fn read_from<R: ::std::io::Read>(reader: &mut R) -> Result<RevocationRegistryDelta, IndyCryptoError> {
+        ::utils::json::from_reader(reader)
+    }
+
+    pub fn merge(&mut self, other_delta: &RevocationRegistryDelta) -> Result<(), IndyCryptoError> {
+        if other_delta.prev_accum != Some(self.accum) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Deltas can not be merged.")));
+        }
+
+        self.accum = other_delta.accum;
+
+        self.issued.extend(
+            other_delta.issued.difference(&self.revoked));
+
+        self.revoked.extend(
+            other_delta.revoked.difference(&self.issued));
+
+        for index in other_delta.revoked.iter() {
+            self.issued.remove(index);
+        }
+
+        for index in other_delta.issued.iter() {
+            self.revoked.remove(index);
+        }
+
+        Ok(())
+    }
+}
+
+/// Transition of `Revocation Registry` into a new accumulator epoch produced by Issuer.
+///
+/// Accumulator of the new epoch is computed from scratch over the issued set carried over from the previous epoch,
+/// so delta history of previous epochs is no longer needed. Indices that are not carried over are revoked.
+/// Revocation keys and tails don't change, so credentials stay valid and provers only re-derive witnesses.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationRegistryEpochTransition {
+    epoch: u32,
+    prev_accum: Accumulator,
+    accum: Accumulator,
+    issued: HashSet<u32>
+}
+
+impl RevocationRegistryEpochTransition {
+    /// Number of the new epoch.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Indices carried over to the new epoch.
+    pub fn issued(&self) -> &HashSet<u32> {
+        &self.issued
+    }
+
+    /// Revocation registry at the start of the new epoch.
+    pub fn rev_reg(&self) -> RevocationRegistry {
+        RevocationRegistry { accum: self.accum }
+    }
+}
+
+/// `Revocation Registry Delta` published by Issuer at given time.
+/// Delta contains changes since the previous entry of the history.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RevocationRegistryHistoryEntry {
+    timestamp: Timestamp,
+    #[serde(alias = "revRegDelta")]
+    rev_reg_delta: RevocationRegistryDelta
+}
+
+impl RevocationRegistryHistoryEntry {
+    pub fn new(timestamp: Timestamp, rev_reg_delta: RevocationRegistryDelta) -> RevocationRegistryHistoryEntry {
+        RevocationRegistryHistoryEntry { timestamp, rev_reg_delta }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    pub fn rev_reg_delta(&self) -> &RevocationRegistryDelta {
+        &self.rev_reg_delta
+    }
+}
+
+/// Time window verifier requires credential to be non-revoked within.
+/// Absent bounds are open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NonRevocationInterval {
+    from: Option<Timestamp>,
+    to: Option<Timestamp>
+}
+
+impl NonRevocationInterval {
+    pub fn new(from: Option<Timestamp>, to: Option<Timestamp>) -> Result<NonRevocationInterval, IndyCryptoError> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(IndyCryptoError::InvalidStructure(format!("Invalid non-revocation interval: from {} is greater than to {}", from, to)));
+            }
+        }
+        Ok(NonRevocationInterval { from, to })
+    }
+
+    pub fn from(&self) -> Option<Timestamp> {
+        self.from
+    }
+
+    pub fn to(&self) -> Option<Timestamp> {
+        self.to
+    }
+
+    /// Returns true if `timestamp` is within the interval (bounds are inclusive).
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        self.from.map(|from| from <= timestamp).unwrap_or(true) && self.to.map(|to| timestamp <= to).unwrap_or(true)
+    }
+}
+
+/// State of `Revocation Registry` selected by prover for the given `NonRevocationInterval`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RevocationRegistryState {
+    timestamp: Timestamp,
+    #[serde(alias = "revReg")]
+    rev_reg: RevocationRegistry,
+    witness: Witness
+}
+
+impl RevocationRegistryState {
+    /// Returns time the state was published at; must be reported to verifier along with the proof.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    pub fn rev_reg(&self) -> &RevocationRegistry {
+        &self.rev_reg
+    }
+
+    pub fn witness(&self) -> &Witness {
+        &self.witness
+    }
+}
+
+/// Representation of revocation indices set in stored `RevocationRegistryDelta`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevocationIndexSetFormat {
+    /// Plain list of indices (format of `RevocationRegistryDelta`).
+    HashSet,
+    /// Sorted list of non-overlapping inclusive intervals.
+    Intervals,
+    /// Bit `i - 1` is set if index `i` belongs to the set.
+    Bitmap
+}
+
+/// Revocation indices set stored in one of `RevocationIndexSetFormat` representations.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct RevocationRegistryDelta {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    prev_accum: Option<Accumulator>,
-    accum: Accumulator,
-    #[serde(skip_serializing_if = "HashSet::is_empty")]
-    #[serde(default)]
-    issued: HashSet<u32>,
-    #[serde(skip_serializing_if = "HashSet::is_empty")]
-    #[serde(default)]
-    revoked: HashSet<u32>
+pub enum RevocationIndexSet {
+    HashSet(HashSet<u32>),
+    Intervals(Vec<(u32, u32)>),
+    Bitmap(Vec<u8>)
 }
 
-impl RevocationRegistryDelta {
-    pub fn from_parts(rev_reg_from: Option<&RevocationRegistry>,
-                      rev_reg_to: &RevocationRegistry,
-                      issued: &HashSet<u32>,
-                      revoked: &HashSet<u32>) -> RevocationRegistryDelta {
-        RevocationRegistryDelta {
-            prev_accum: rev_reg_from.map(|rev_reg| rev_reg.accum),
-            accum: rev_reg_to.accum.clone(),
-            issued: issued.clone(),
-            revoked: revoked.clone()
+impl RevocationIndexSet {
+    pub fn new(indices: &HashSet<u32>, format: RevocationIndexSetFormat) -> Result<RevocationIndexSet, IndyCryptoError> {
+        if indices.contains(&0) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Revocation index can't be 0")));
         }
+
+        let mut sorted = indices.iter().cloned().collect::<Vec<u32>>();
+        sorted.sort();
+
+        let index_set = match format {
+            RevocationIndexSetFormat::HashSet => RevocationIndexSet::HashSet(indices.clone()),
+            RevocationIndexSetFormat::Intervals => {
+                let mut intervals: Vec<(u32, u32)> = Vec::new();
+                for index in sorted {
+                    match intervals.last_mut() {
+                        // Indices are unique, sorted and non-zero, so `index - 1` doesn't overflow
+                        Some(ref mut interval) if index - 1 == interval.1 => interval.1 = index,
+                        _ => intervals.push((index, index))
+                    }
+                }
+                RevocationIndexSet::Intervals(intervals)
+            }
+            RevocationIndexSetFormat::Bitmap => {
+                let mut bitmap = vec![0u8; sorted.last().map(|max| ((*max as usize) + 7) / 8).unwrap_or(0)];
+                for index in sorted {
+                    let bit = (index - 1) as usize;
+                    bitmap[bit / 8] |= 1 << (bit % 8);
+                }
+                RevocationIndexSet::Bitmap(bitmap)
+            }
+        };
+
+        Ok(index_set)
     }
 
-    pub fn merge(&mut self, other_delta: &RevocationRegistryDelta) -> Result<(), IndyCryptoError> {
-        if other_delta.prev_accum.is_none() || self.accum != other_delta.prev_accum.unwrap() {
-            return Err(IndyCryptoError::InvalidStructure(format!("Deltas can not be merged.")));
+    pub fn format(&self) -> RevocationIndexSetFormat {
+        match *self {
+            RevocationIndexSet::HashSet(_) => RevocationIndexSetFormat::HashSet,
+            RevocationIndexSet::Intervals(_) => RevocationIndexSetFormat::Intervals,
+            RevocationIndexSet::Bitmap(_) => RevocationIndexSetFormat::Bitmap
         }
+    }
 
-        self.accum = other_delta.accum;
-
-        self.issued.extend(
-            other_delta.issued.difference(&self.revoked));
+    /// Returns indices of the set. Fails if representation is malformed or contains indices greater than `max_cred_num`.
+    ///
+    /// Intervals and bitmap are checked against `max_cred_num` before they are expanded,
+    /// so untrusted representations can't make it allocate more than `max_cred_num` indices.
+    pub fn to_hash_set(&self, max_cred_num: u32) -> Result<HashSet<u32>, IndyCryptoError> {
+        let out_of_range = |index: u32| IndyCryptoError::InvalidStructure(format!("Revocation index {} is out of registry range", index));
+
+        let indices = match *self {
+            RevocationIndexSet::HashSet(ref indices) => {
+                if indices.contains(&0) {
+                    return Err(IndyCryptoError::InvalidStructure(format!("Revocation index can't be 0")));
+                }
+                if let Some(index) = indices.iter().find(|index| **index > max_cred_num) {
+                    return Err(out_of_range(*index));
+                }
+                indices.clone()
+            }
+            RevocationIndexSet::Intervals(ref intervals) => {
+                let mut indices = HashSet::new();
+                let mut prev_end = 0;
+                for &(start, end) in intervals.iter() {
+                    if start == 0 || start > end || (prev_end != 0 && start <= prev_end) {
+                        return Err(IndyCryptoError::InvalidStructure(format!("Invalid revocation indices interval: ({}, {})", start, end)));
+                    }
+                    if end > max_cred_num {
+                        return Err(out_of_range(end));
+                    }
+                    // `end` may be u32::MAX, so it is added separately instead of the end of the range
+                    indices.extend(start..end);
+                    indices.insert(end);
+                    prev_end = end;
+                }
+                indices
+            }
+            RevocationIndexSet::Bitmap(ref bitmap) => {
+                if bitmap.len() as u64 > (max_cred_num as u64 + 7) / 8 {
+                    return Err(IndyCryptoError::InvalidStructure(format!("Revocation indices bitmap is longer than registry range")));
+                }
+
+                let mut indices = HashSet::new();
+                for (byte_idx, byte) in bitmap.iter().enumerate() {
+                    for bit in 0..8 {
+                        if byte & (1 << bit) != 0 {
+                            let index = (byte_idx * 8 + bit + 1) as u32;
+                            if index > max_cred_num {
+                                return Err(out_of_range(index));
+                            }
+                            indices.insert(index);
+                        }
+                    }
+                }
+                indices
+            }
+        };
 
-        self.revoked.extend(
-            other_delta.revoked.difference(&self.issued));
+        Ok(indices)
+    }
 
-        for index in other_delta.revoked.iter() {
-            self.issued.remove(index);
+    /// Converts set to another representation and checks that converted set contains the same indices.
+    ///
+    /// # Arguments
+    /// * `format` - Representation to convert to.
+    /// * `max_cred_num` - Max number of credentials in the registry the indices belong to.
+    pub fn convert(&self, format: RevocationIndexSetFormat, max_cred_num: u32) -> Result<RevocationIndexSet, IndyCryptoError> {
+        let indices = self.to_hash_set(max_cred_num)?;
+        let converted = RevocationIndexSet::new(&indices, format)?;
+
+        if converted.to_hash_set(max_cred_num)? != indices {
+            return Err(IndyCryptoError::InvalidState(format!("Revocation indices set was changed by conversion")));
         }
 
-        for index in other_delta.issued.iter() {
-            self.revoked.remove(index);
+        Ok(converted)
+    }
+}
+
+/// `RevocationRegistryDelta` with issued and revoked indices stored in the chosen `RevocationIndexSetFormat`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedRevocationRegistryDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "prev_accum")]
+    prev_accum: Option<Accumulator>,
+    accum: Accumulator,
+    issued: RevocationIndexSet,
+    revoked: RevocationIndexSet
+}
+
+impl RevocationRegistryDelta {
+    /// Converts delta to representation with issued and revoked indices stored in the given format.
+    pub fn pack(&self, format: RevocationIndexSetFormat) -> Result<PackedRevocationRegistryDelta, IndyCryptoError> {
+        Ok(PackedRevocationRegistryDelta {
+            prev_accum: self.prev_accum,
+            accum: self.accum,
+            issued: RevocationIndexSet::HashSet(self.issued.clone()).convert(format, ::std::u32::MAX)?,
+            revoked: RevocationIndexSet::HashSet(self.revoked.clone()).convert(format, ::std::u32::MAX)?
+        })
+    }
+}
+
+impl PackedRevocationRegistryDelta {
+    /// Converts packed delta back to `RevocationRegistryDelta`.
+    ///
+    /// Checks that all indices belong to registry with `max_cred_num` credentials
+    /// and no index is both issued and revoked.
+    pub fn unpack(&self, max_cred_num: u32) -> Result<RevocationRegistryDelta, IndyCryptoError> {
+        let issued = self.issued.to_hash_set(max_cred_num)?;
+        let revoked = self.revoked.to_hash_set(max_cred_num)?;
+
+        if let Some(index) = issued.intersection(&revoked).next() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Revocation index {} is both issued and revoked", index)));
         }
 
-        Ok(())
+        Ok(RevocationRegistryDelta {
+            prev_accum: self.prev_accum,
+            accum: self.accum,
+            issued,
+            revoked
+        })
+    }
+
+    /// Converts issued and revoked indices of packed delta of registry with `max_cred_num` credentials to another format.
+    pub fn convert(&self, format: RevocationIndexSetFormat, max_cred_num: u32) -> Result<PackedRevocationRegistryDelta, IndyCryptoError> {
+        Ok(PackedRevocationRegistryDelta {
+            prev_accum: self.prev_accum,
+            accum: self.accum,
+            issued: self.issued.convert(format, max_cred_num)?,
+            revoked: self.revoked.convert(format, max_cred_num)?
+        })
     }
 }
 
@@ -477,7 +2007,9 @@ impl Tail {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RevocationTailsGenerator {
     size: u32,
+    #[serde(alias = "currentIndex")]
     current_index: u32,
+    #[serde(alias = "gDash")]
     g_dash: PointG2,
     gamma: GroupOrderElement
 }
@@ -496,6 +2028,11 @@ impl RevocationTailsGenerator {
         self.size - self.current_index
     }
 
+    /// Returns iterator generating the rest of tails on demand.
+    pub fn tails<'a>(&'a mut self) -> RevocationTails<'a> {
+        RevocationTails { generator: self }
+    }
+
     pub fn next(&mut self) -> Result<Option<Tail>, IndyCryptoError> {
         if self.current_index >= self.size {
             return Ok(None);
@@ -509,10 +2046,41 @@ impl RevocationTailsGenerator {
     }
 }
 
+/// Iterator over the rest of tails of `RevocationTailsGenerator`.
+/// Tails are computed one by one on demand, so the whole tails file is never held in memory.
+pub struct RevocationTails<'a> {
+    generator: &'a mut RevocationTailsGenerator
+}
+
+impl<'a> Iterator for RevocationTails<'a> {
+    type Item = Result<Tail, IndyCryptoError>;
+
+    fn next(&mut self) -> Option<Result<Tail, IndyCryptoError>> {
+        match self.generator.next() {
+            Ok(Some(tail)) => Some(Ok(tail)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.generator.count() as usize;
+        (count, Some(count))
+    }
+}
+
 pub trait RevocationTailsAccessor {
     fn access_tail(&self, tail_id: u32, accessor: &mut FnMut(&Tail)) -> Result<(), IndyCryptoError>;
 }
 
+/// Accesses tail with fallible `accessor` (e.g. point addition) returning error of the accessor instead of panicking.
+fn access_tail_with<RTA, F>(rev_tails_accessor: &RTA, tail_id: u32, mut accessor: F) -> Result<(), IndyCryptoError>
+    where RTA: RevocationTailsAccessor + ?Sized, F: FnMut(&Tail) -> Result<(), IndyCryptoError> {
+    let mut res = Ok(());
+    rev_tails_accessor.access_tail(tail_id, &mut |tail| res = accessor(tail))?;
+    res
+}
+
 /// Simple implementation of `RevocationTailsAccessor` that stores all tails as BTreeMap.
 #[derive(Debug, Clone)]
 pub struct SimpleTailsAccessor {
@@ -521,16 +2089,15 @@ pub struct SimpleTailsAccessor {
 
 impl RevocationTailsAccessor for SimpleTailsAccessor {
     fn access_tail(&self, tail_id: u32, accessor: &mut FnMut(&Tail)) -> Result<(), IndyCryptoError> {
-        Ok(accessor(&self.tails[tail_id as usize]))
+        let tail = self.tails.get(tail_id as usize)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Tail {} is out of tails range", tail_id)))?;
+        Ok(accessor(tail))
     }
 }
 
 impl SimpleTailsAccessor {
     pub fn new(rev_tails_generator: &mut RevocationTailsGenerator) -> Result<SimpleTailsAccessor, IndyCryptoError> {
-        let mut tails: Vec<Tail> = Vec::new();
-        while let Some(tail) = rev_tails_generator.next()? {
-            tails.push(tail);
-        }
+        let tails = rev_tails_generator.tails().collect::<Result<Vec<Tail>, IndyCryptoError>>()?;
         Ok(SimpleTailsAccessor { tails })
     }
 }
@@ -539,8 +2106,14 @@ impl SimpleTailsAccessor {
 /// Issuer's signature over Credential attribute values.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CredentialSignature {
+    #[serde(alias = "pCredential")]
     p_credential: PrimaryCredentialSignature,
+    #[serde(alias = "rCredential")]
     r_credential: Option<NonRevocationCredentialSignature> /* will be used to proof is credential revoked preparation */,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(alias = "attrRCredentials")]
+    attr_r_credentials: BTreeMap<String /* attr_name */, NonRevocationCredentialSignature>
 }
 
 impl CredentialSignature {
@@ -549,39 +2122,129 @@ impl CredentialSignature {
             .as_ref()
             .map(|r_credential| r_credential.i)
     }
+
+    pub fn clone(&self) -> Result<CredentialSignature, IndyCryptoError> {
+        Ok(CredentialSignature {
+            p_credential: self.p_credential.clone()?,
+            r_credential: self.r_credential.clone(),
+            attr_r_credentials: self.attr_r_credentials.clone()
+        })
+    }
+
+    /// Returns non revocation part of the signature.
+    pub fn non_revocation_signature(&self) -> Option<&NonRevocationCredentialSignature> {
+        self.r_credential.as_ref()
+    }
+
+    /// Returns index in revocation registry of the attribute that can be revoked separately.
+    pub fn extract_attr_index(&self, attr: &str) -> Option<u32> {
+        self.attr_r_credentials
+            .get(attr)
+            .map(|r_credential| r_credential.i)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryCredentialSignature {
+    #[serde(alias = "m2")]
     m_2: BigNumber,
     a: BigNumber,
     e: BigNumber,
     v: BigNumber
 }
 
+impl PrimaryCredentialSignature {
+    pub fn clone(&self) -> Result<PrimaryCredentialSignature, IndyCryptoError> {
+        Ok(PrimaryCredentialSignature {
+            m_2: self.m_2.clone()?,
+            a: self.a.clone()?,
+            e: self.e.clone()?,
+            v: self.v.clone()?
+        })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NonRevocationCredentialSignature {
     sigma: PointG1,
     c: GroupOrderElement,
+    #[serde(alias = "vrPrimePrime")]
     vr_prime_prime: GroupOrderElement,
+    #[serde(alias = "witnessSignature")]
     witness_signature: WitnessSignature,
+    #[serde(alias = "gI")]
     g_i: PointG1,
     i: u32,
     m2: GroupOrderElement
 }
 
+impl NonRevocationCredentialSignature {
+    /// Checks that stored non revocation credential signature is still valid
+    /// (e.g. after wallet restore or revocation registry migration).
+    ///
+    /// Performs structural checks of signature and the same pairing checks as Prover does on credential processing.
+    ///
+    /// # Arguments
+    /// * `cred_pub_key` - Credential public key.
+    /// * `rev_key_pub` - Revocation registry public key.
+    /// * `rev_reg` - Revocation registry.
+    /// * `witness` - Witness of the credential for the given revocation registry state.
+    pub fn validate(&self,
+                    cred_pub_key: &CredentialPublicKey,
+                    rev_key_pub: &RevocationKeyPublic,
+                    rev_reg: &RevocationRegistry,
+                    witness: &Witness) -> Result<(), IndyCryptoError> {
+        trace!("NonRevocationCredentialSignature::validate: >>> self: {:?}, cred_pub_key: {:?}, rev_key_pub: {:?}, rev_reg: {:?}, witness: {:?}",
+               self, cred_pub_key, rev_key_pub, rev_reg, witness);
+
+        let cred_rev_pub_key = cred_pub_key.r_key.as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure("No revocation part present in credential revocation public key.".to_string()))?;
+
+        if self.i == 0 {
+            return Err(IndyCryptoError::InvalidStructure("Revocation index must be positive".to_string()));
+        }
+
+        if self.g_i != self.witness_signature.g_i {
+            return Err(IndyCryptoError::InvalidStructure("Witness signature doesn't correspond to credential revocation index".to_string()));
+        }
+
+        if !self.sigma.is_valid()? || !self.g_i.is_valid()? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid point in non revocation credential signature".to_string()));
+        }
+
+        let m2 = BigNumber::from_bytes(&self.m2.to_bytes()?)?;
+        prover::Prover::_test_witness_signature(self, cred_rev_pub_key, rev_key_pub, rev_reg, witness, &m2)?;
+
+        trace!("NonRevocationCredentialSignature::validate: <<<");
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct SignatureCorrectnessProof {
     se: BigNumber,
     c: BigNumber
 }
 
+/// Witness of credential membership in revocation accumulator.
+///
+/// Witness is kept by Prover independently of credential signature and proof creation:
+/// it can be persisted as json and updated with `Witness::update` after every revocation registry delta.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Witness {
     omega: PointG2
 }
 
 impl Witness {
+    /// Creates witness for the credential with index `rev_idx` in revocation registry.
+    ///
+    /// # Arguments
+    /// * `rev_idx` - Index of the credential in revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `issuance_by_default` - Type of issuance of revocation registry.
+    /// * `rev_reg_delta` - Revocation registry delta accumulated from registry creation.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
     pub fn new<RTA>(rev_idx: u32,
                     max_cred_num: u32,
                     issuance_by_default: bool,
@@ -590,23 +2253,16 @@ impl Witness {
         trace!("Witness::new: >>> rev_idx: {:?}, max_cred_num: {:?}, issuance_by_default: {:?}, rev_reg_delta: {:?}",
                rev_idx, max_cred_num, issuance_by_default, rev_reg_delta);
 
-        let mut omega = PointG2::new_inf()?;
-
-        let mut issued = if issuance_by_default {
-            (1..max_cred_num + 1).collect::<HashSet<u32>>()
-                .difference(&rev_reg_delta.revoked).cloned().collect::<HashSet<u32>>()
+        // Issued indices are iterated lazily, so registries with millions of indices don't need O(n) memory
+        let omega = if issuance_by_default {
+            Witness::_sum_tails(rev_idx,
+                                max_cred_num,
+                                (1..max_cred_num + 1).filter(|j| !rev_reg_delta.revoked.contains(j)),
+                                rev_tails_accessor)?
         } else {
-            rev_reg_delta.issued.clone()
+            Witness::_sum_tails(rev_idx, max_cred_num, rev_reg_delta.issued.iter().cloned(), rev_tails_accessor)?
         };
 
-        issued.remove(&rev_idx);
-        for j in issued.iter() {
-            let index = max_cred_num + 1 - j + rev_idx;
-            rev_tails_accessor.access_tail(index, &mut |tail| {
-                omega = omega.add(tail).unwrap();
-            })?;
-        }
-
         let witness = Witness { omega };
 
         trace!("Witness::new: <<< witness: {:?}", witness);
@@ -614,6 +2270,30 @@ impl Witness {
         Ok(witness)
     }
 
+    fn _sum_tails<I, RTA>(rev_idx: u32,
+                          max_cred_num: u32,
+                          issued: I,
+                          rev_tails_accessor: &RTA) -> Result<PointG2, IndyCryptoError> where I: Iterator<Item=u32>, RTA: RevocationTailsAccessor {
+        let mut omega = PointG2::new_inf()?;
+
+        for j in issued.filter(|j| *j != rev_idx) {
+            let index = max_cred_num + 1 - j + rev_idx;
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                omega = omega.add(tail)?;
+                Ok(())
+            })?;
+        }
+
+        Ok(omega)
+    }
+
+    /// Updates witness with revocation registry delta published after witness creation or last update.
+    ///
+    /// # Arguments
+    /// * `rev_idx` - Index of the credential in revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_reg_delta` - Revocation registry delta.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
     pub fn update<RTA>(&mut self,
                        rev_idx: u32,
                        max_cred_num: u32,
@@ -627,8 +2307,9 @@ impl Witness {
             if rev_idx.eq(j) { continue; }
 
             let index = max_cred_num + 1 - j + rev_idx;
-            rev_tails_accessor.access_tail(index, &mut |tail| {
-                omega_denom = omega_denom.add(tail).unwrap();
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                omega_denom = omega_denom.add(tail)?;
+                Ok(())
             })?;
         }
 
@@ -637,8 +2318,9 @@ impl Witness {
             if rev_idx.eq(j) { continue; }
 
             let index = max_cred_num + 1 - j + rev_idx;
-            rev_tails_accessor.access_tail(index, &mut |tail| {
-                omega_num = omega_num.add(tail).unwrap();
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                omega_num = omega_num.add(tail)?;
+                Ok(())
             })?;
         }
 
@@ -650,12 +2332,148 @@ impl Witness {
 
         Ok(())
     }
+
+    /// Re-derives witness for the accumulator of the new epoch from the carried over issued set.
+    ///
+    /// # Arguments
+    /// * `rev_idx` - Index of the credential in revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `transition` - Epoch transition published by Issuer.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
+    pub fn migrate_to_epoch<RTA>(&mut self,
+                                 rev_idx: u32,
+                                 max_cred_num: u32,
+                                 transition: &RevocationRegistryEpochTransition,
+                                 rev_tails_accessor: &RTA) -> Result<(), IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Witness::migrate_to_epoch: >>> rev_idx: {:?}, max_cred_num: {:?}, transition: {:?}", rev_idx, max_cred_num, transition);
+
+        if !transition.issued.contains(&rev_idx) {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential is not carried over to revocation registry epoch {}", transition.epoch)));
+        }
+
+        self.omega = Witness::_sum_tails(rev_idx, max_cred_num, transition.issued.iter().cloned(), rev_tails_accessor)?;
+
+        trace!("Witness::migrate_to_epoch: <<<");
+
+        Ok(())
+    }
+
+    /// Applies a stream of revocation registry deltas in chronological order.
+    ///
+    /// Every delta must continue the accumulator of the previous one (the first delta must continue `rev_reg`),
+    /// issuances and revocations are netted across the whole stream, so every tail is accessed at most once.
+    ///
+    /// # Arguments
+    /// * `rev_idx` - Index of the credential in revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_reg` - Revocation registry state the witness currently corresponds to.
+    /// * `rev_reg_deltas` - Revocation registry deltas in chronological order.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
+    ///
+    /// Returns revocation registry state the updated witness corresponds to.
+    pub fn apply_delta_stream<'a, I, RTA>(&mut self,
+                                          rev_idx: u32,
+                                          max_cred_num: u32,
+                                          rev_reg: &RevocationRegistry,
+                                          rev_reg_deltas: I,
+                                          rev_tails_accessor: &RTA) -> Result<RevocationRegistry, IndyCryptoError>
+        where I: IntoIterator<Item=&'a RevocationRegistryDelta>, RTA: RevocationTailsAccessor {
+        trace!("Witness::apply_delta_stream: >>> rev_idx: {:?}, max_cred_num: {:?}, rev_reg: {:?}", rev_idx, max_cred_num, rev_reg);
+
+        let mut accum = rev_reg.accum;
+        let mut changes: BTreeMap<u32, i64> = BTreeMap::new();
+
+        for (i, rev_reg_delta) in rev_reg_deltas.into_iter().enumerate() {
+            match rev_reg_delta.prev_accum {
+                Some(ref prev_accum) if *prev_accum == accum => {}
+                _ => return Err(IndyCryptoError::InvalidStructure(
+                    format!("Revocation registry delta {} doesn't continue accumulator of the previous state", i)))
+            }
+
+            for j in rev_reg_delta.issued.iter() {
+                *changes.entry(*j).or_insert(0) += 1;
+            }
+
+            for j in rev_reg_delta.revoked.iter() {
+                *changes.entry(*j).or_insert(0) -= 1;
+            }
+
+            accum = rev_reg_delta.accum;
+        }
+
+        let mut omega_num = PointG2::new_inf()?;
+        let mut omega_denom = PointG2::new_inf()?;
+
+        for (j, change) in changes.iter() {
+            if rev_idx.eq(j) || *change == 0 { continue; }
+
+            let index = max_cred_num + 1 - j + rev_idx;
+            access_tail_with(rev_tails_accessor, index, |tail| {
+                for _ in 0..change.abs() {
+                    if *change > 0 {
+                        omega_num = omega_num.add(tail)?;
+                    } else {
+                        omega_denom = omega_denom.add(tail)?;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        self.omega = self.omega.add(&omega_num.sub(&omega_denom)?)?;
+
+        let rev_reg = RevocationRegistry { accum };
+
+        trace!("Witness::apply_delta_stream: <<< rev_reg: {:?}", rev_reg);
+
+        Ok(rev_reg)
+    }
+}
+
+/// Storage of witnesses already updated to some accumulator state.
+///
+/// Witnesses are keyed by revocation registry id, index of the credential and accumulator,
+/// so `ProofBuilder` can reuse witness computed by previous proofs against the same accumulator.
+pub trait WitnessStore {
+    fn get(&self, rev_reg_id: &str, rev_idx: u32, accum: &Accumulator) -> Result<Option<Witness>, IndyCryptoError>;
+    fn put(&self, rev_reg_id: &str, rev_idx: u32, accum: &Accumulator, witness: &Witness) -> Result<(), IndyCryptoError>;
+}
+
+/// Simple implementation of `WitnessStore` that keeps witnesses in memory.
+#[derive(Debug, Default)]
+pub struct SimpleWitnessStore {
+    witnesses: RwLock<HashMap<(String, u32, Vec<u8>), Witness>>
+}
+
+impl SimpleWitnessStore {
+    pub fn new() -> SimpleWitnessStore {
+        SimpleWitnessStore::default()
+    }
+}
+
+impl WitnessStore for SimpleWitnessStore {
+    fn get(&self, rev_reg_id: &str, rev_idx: u32, accum: &Accumulator) -> Result<Option<Witness>, IndyCryptoError> {
+        let witnesses = self.witnesses.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness store lock is poisoned".to_string()))?;
+        Ok(witnesses.get(&(rev_reg_id.to_owned(), rev_idx, accum.to_bytes()?)).cloned())
+    }
+
+    fn put(&self, rev_reg_id: &str, rev_idx: u32, accum: &Accumulator, witness: &Witness) -> Result<(), IndyCryptoError> {
+        let mut witnesses = self.witnesses.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness store lock is poisoned".to_string()))?;
+        witnesses.insert((rev_reg_id.to_owned(), rev_idx, accum.to_bytes()?), witness.clone());
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WitnessSignature {
+    #[serde(alias = "sigmaI")]
     sigma_i: PointG2,
+    #[serde(alias = "uI")]
     u_i: PointG2,
+    #[serde(alias = "gI")]
     g_i: PointG1
 }
 
@@ -664,11 +2482,17 @@ pub struct WitnessSignature {
 /// Prover blinds master secret, generating `BlindedCredentialSecrets` and `CredentialSecretsBlindingFactors` (blinding factors)
 /// and sends the `BlindedCredentialSecrets` to Issuer who then encodes it credential creation.
 /// The blinding factors are used by Prover for post processing of issued credentials.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Deserialize, Serialize)]
 pub struct MasterSecret {
     ms: BigNumber,
 }
 
+impl fmt::Debug for MasterSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MasterSecret {{ ms: <redacted> }}")
+    }
+}
+
 impl MasterSecret {
     pub fn clone(&self) -> Result<MasterSecret, IndyCryptoError> {
         Ok(MasterSecret { ms: self.ms.clone()? })
@@ -679,22 +2503,196 @@ impl MasterSecret {
     }
 }
 
+/// Storage of master secrets (link secrets) referenced by id.
+///
+/// Holder keeps a separate master secret for every persona, so credentials issued to different personas
+/// can't be linked, and passes only the id of the persona to `Prover` and `ProofBuilder`.
+pub trait MasterSecretStore {
+    fn get(&self, master_secret_id: &str) -> Result<Option<MasterSecret>, IndyCryptoError>;
+    fn put(&self, master_secret_id: &str, master_secret: &MasterSecret) -> Result<(), IndyCryptoError>;
+}
+
+/// Simple implementation of `MasterSecretStore` that keeps master secrets in memory.
+#[derive(Default)]
+pub struct SimpleMasterSecretStore {
+    master_secrets: RwLock<HashMap<String, MasterSecret>>
+}
+
+impl SimpleMasterSecretStore {
+    pub fn new() -> SimpleMasterSecretStore {
+        SimpleMasterSecretStore::default()
+    }
+}
+
+impl MasterSecretStore for SimpleMasterSecretStore {
+    fn get(&self, master_secret_id: &str) -> Result<Option<MasterSecret>, IndyCryptoError> {
+        let master_secrets = self.master_secrets.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Master secret store lock is poisoned".to_string()))?;
+        match master_secrets.get(master_secret_id) {
+            Some(master_secret) => Ok(Some(master_secret.clone()?)),
+            None => Ok(None)
+        }
+    }
+
+    fn put(&self, master_secret_id: &str, master_secret: &MasterSecret) -> Result<(), IndyCryptoError> {
+        let mut master_secrets = self.master_secrets.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Master secret store lock is poisoned".to_string()))?;
+        master_secrets.insert(master_secret_id.to_owned(), master_secret.clone()?);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SimpleMasterSecretStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.master_secrets.read() {
+            Ok(master_secrets) => write!(f, "SimpleMasterSecretStore {{ ids: {:?} }}", master_secrets.keys().collect::<BTreeSet<_>>()),
+            Err(_) => write!(f, "SimpleMasterSecretStore {{ <poisoned> }}")
+        }
+    }
+}
+
+/// Nonces of a single credential issuance session.
+///
+/// Issuer sends `offer_nonce` with credential offer, Prover sends `request_nonce` with credential request.
+/// Nonces derived from both are used instead of them in correctness proofs,
+/// so blinded credential secrets and credential signature can't be replayed in other issuance session.
+///
+/// # Example
+/// ```
+/// use indy_crypto::cl::{new_nonce, CredentialIssuanceNonces};
+///
+/// let offer_nonce = new_nonce().unwrap();
+/// let request_nonce = new_nonce().unwrap();
+/// let nonces = CredentialIssuanceNonces::new(&offer_nonce, &request_nonce).unwrap();
+/// // Pass to `Prover::blind_credential_secrets_with_nonces`, `Issuer::sign_credential_with_nonces`
+/// // and `Prover::process_credential_signature_with_nonces`
+/// let _credential_nonce = nonces.blinding_nonce().unwrap();
+/// let _credential_issuance_nonce = nonces.signature_nonce().unwrap();
+/// ```
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CredentialIssuanceNonces {
+    #[serde(alias = "offerNonce")]
+    offer_nonce: Nonce,
+    #[serde(alias = "requestNonce")]
+    request_nonce: Nonce
+}
+
+impl CredentialIssuanceNonces {
+    pub fn new(offer_nonce: &Nonce, request_nonce: &Nonce) -> Result<CredentialIssuanceNonces, IndyCryptoError> {
+        if offer_nonce == request_nonce {
+            return Err(IndyCryptoError::InvalidStructure("Offer and request nonces must be different".to_string()));
+        }
+        Ok(CredentialIssuanceNonces { offer_nonce: offer_nonce.clone()?, request_nonce: request_nonce.clone()? })
+    }
+
+    pub fn offer_nonce(&self) -> &Nonce {
+        &self.offer_nonce
+    }
+
+    pub fn request_nonce(&self) -> &Nonce {
+        &self.request_nonce
+    }
+
+    /// Returns nonce for blinded credential secrets correctness proof.
+    pub fn blinding_nonce(&self) -> Result<Nonce, IndyCryptoError> {
+        self._derive_nonce(constants::CREDENTIAL_BLINDING_DOMAIN)
+    }
+
+    /// Returns nonce for signature correctness proof.
+    pub fn signature_nonce(&self) -> Result<Nonce, IndyCryptoError> {
+        self._derive_nonce(constants::CREDENTIAL_SIGNATURE_DOMAIN)
+    }
+
+    fn _derive_nonce(&self, domain: &[u8]) -> Result<Nonce, IndyCryptoError> {
+        let values: Vec<Vec<u8>> = vec![domain.to_vec(), self.offer_nonce.to_bytes()?, self.request_nonce.to_bytes()?];
+
+        get_hash_as_int(&values)
+    }
+}
+
 /// Blinded Master Secret uses by Issuer in credential creation.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct BlindedCredentialSecrets {
     u: BigNumber,
     ur: Option<PointG1>,
+    #[serde(alias = "hiddenAttributes")]
     hidden_attributes: BTreeSet<String>,
+    #[serde(alias = "committedAttributes")]
     committed_attributes: BTreeMap<String, BigNumber>
 }
 
+impl BlindedCredentialSecrets {
+    /// Derives nonce that binds master secret rotation proof to these blinded secrets.
+    fn rotation_nonce(&self, nonce: &Nonce) -> Result<Nonce, IndyCryptoError> {
+        let mut values: Vec<Vec<u8>> = Vec::new();
+        values.push(constants::MASTER_SECRET_ROTATION_DOMAIN.to_vec());
+        values.push(self.u.to_bytes()?);
+        if let Some(ref ur) = self.ur {
+            values.push(ur.to_bytes()?);
+        }
+        values.push(nonce.to_bytes()?);
+
+        get_hash_as_int(&values)
+    }
+}
+
 /// `CredentialSecretsBlindingFactors` used by Prover for post processing of credentials received from Issuer.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CredentialSecretsBlindingFactors {
+    #[serde(alias = "vPrime")]
     v_prime: BigNumber,
+    #[serde(alias = "vrPrime")]
     vr_prime: Option<GroupOrderElement>
 }
 
+impl fmt::Debug for CredentialSecretsBlindingFactors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialSecretsBlindingFactors {{ v_prime: <redacted>, vr_prime: <redacted> }}")
+    }
+}
+
+impl CredentialSecretsBlindingFactors {
+    /// Exports blinding factors encrypted with AES-256-GCM, so they can be kept in wallet storage
+    /// between credential request and `Prover::process_credential_signature`.
+    /// Blob has the same format as `IssuerKeyPair::export`.
+    ///
+    /// # Arguments
+    /// * `password` - Wallet key or password to derive encryption key from.
+    pub fn export(&self, password: &str) -> Result<Vec<u8>, IndyCryptoError> {
+        trace!("CredentialSecretsBlindingFactors::export: >>>");
+
+        let blob = _export_encrypted(self, password)?;
+
+        trace!("CredentialSecretsBlindingFactors::export: <<< blob: {:?} bytes", blob.len());
+
+        Ok(blob)
+    }
+
+    /// Imports blinding factors exported by `export`. Fails if password is wrong or blob was modified.
+    ///
+    /// # Arguments
+    /// * `blob` - Exported blinding factors.
+    /// * `password` - Wallet key or password blinding factors were exported with.
+    pub fn import(blob: &[u8], password: &str) -> Result<CredentialSecretsBlindingFactors, IndyCryptoError> {
+        trace!("CredentialSecretsBlindingFactors::import: >>> blob: {:?} bytes", blob.len());
+
+        let blinding_factors: CredentialSecretsBlindingFactors = _import_encrypted(blob, password)?;
+
+        trace!("CredentialSecretsBlindingFactors::import: <<<");
+
+        Ok(blinding_factors)
+    }
+}
+
+impl Drop for CredentialSecretsBlindingFactors {
+    fn drop(&mut self) {
+        // `v_prime` is wiped on drop as every `BigNumber`, `GroupOrderElement` is `Copy` and has to be wiped here
+        if let Some(ref mut vr_prime) = self.vr_prime {
+            vr_prime.clear();
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub struct PrimaryBlindedCredentialSecretsFactors {
     u: BigNumber,
@@ -709,20 +2707,119 @@ pub struct RevocationBlindedCredentialSecretsFactors {
     vr_prime: GroupOrderElement,
 }
 
+impl Drop for RevocationBlindedCredentialSecretsFactors {
+    fn drop(&mut self) {
+        self.vr_prime.clear();
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Deserialize, Serialize)]
 pub struct BlindedCredentialSecretsCorrectnessProof {
     c: BigNumber, // Fiat-Shamir challenge hash
+    #[serde(alias = "vDashCap")]
     v_dash_cap: BigNumber, // Value to prove knowledge of `u` construction in `BlindedCredentialSecrets`
+    #[serde(alias = "mCaps")]
     m_caps: BTreeMap<String, BigNumber>, // Values for proving knowledge of committed values
+    #[serde(alias = "rCaps")]
     r_caps: BTreeMap<String, BigNumber>, // Blinding values for m_caps
 }
 
 /// “Sub Proof Request” - input to create a Proof for a credential;
 /// Contains attributes to be revealed and predicates.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SubProofRequest {
     revealed_attrs: BTreeSet<String>,
     predicates: BTreeSet<Predicate>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    unrevoked_attrs: BTreeSet<String>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    committed_attrs: BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_revoc_interval: Option<NonRevocationInterval>,
+}
+
+impl SubProofRequest {
+    /// Returns SHA-256 fingerprint of the canonical serialization of sub proof request.
+    ///
+    /// Attributes and predicates are serialized sorted, so requests built in different order
+    /// have the same fingerprint. Fingerprint can be used to reference the request in receipts and caches.
+    pub fn fingerprint(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        let values: Vec<Vec<u8>> = vec![constants::SUB_PROOF_REQUEST_FINGERPRINT_DOMAIN.to_vec(), self.to_canonical_bytes()?];
+        BigNumber::hash_array(&values)
+    }
+
+    /// Returns canonical serialization of sub proof request: canonical json with sorted attributes and predicates.
+    /// Empty sets of unrevoked and committed attributes are omitted.
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        Ok(::utils::json::to_canonical_json(self)?.into_bytes())
+    }
+
+    /// Returns interval the credential must be non-revoked within, if requested.
+    pub fn non_revoc_interval(&self) -> Option<&NonRevocationInterval> {
+        self.non_revoc_interval.as_ref()
+    }
+
+    /// Reports which revealed attributes and predicates of the request can be satisfied by credential,
+    /// without any crypto. Wallets can filter candidate credentials before `ProofBuilder::add_sub_proof_request`.
+    ///
+    /// # Arguments
+    /// * `credential_values` - Credential values.
+    /// * `credential_schema` - Credential schema.
+    pub fn check_satisfiable(&self,
+                             credential_values: &CredentialValues,
+                             credential_schema: &CredentialSchema) -> Result<SatisfiabilityReport, IndyCryptoError> {
+        trace!("SubProofRequest::check_satisfiable: >>> sub_proof_request: {:?}, credential_schema: {:?}", self, credential_schema);
+
+        let unsatisfied_revealed_attrs = self.revealed_attrs.iter()
+            .filter(|attr| !credential_schema.attrs.contains(*attr) || !credential_values.attrs_values.contains_key(*attr))
+            .cloned()
+            .collect::<BTreeSet<String>>();
+
+        let mut unsatisfied_predicates = BTreeSet::new();
+
+        for predicate in self.predicates.iter() {
+            let satisfied = credential_schema.attrs.contains(&predicate.attr_name) &&
+                credential_schema.check_predicate_attr(&predicate.attr_name).is_ok() &&
+                match credential_values.attrs_values.get(&predicate.attr_name) {
+                    Some(value) => predicate.is_satisfied_by(value.value())?,
+                    None => false
+                };
+
+            if !satisfied {
+                unsatisfied_predicates.insert(predicate.clone());
+            }
+        }
+
+        let report = SatisfiabilityReport { unsatisfied_revealed_attrs, unsatisfied_predicates };
+
+        trace!("SubProofRequest::check_satisfiable: <<< report: {:?}", report);
+
+        Ok(report)
+    }
+}
+
+/// Result of `SubProofRequest::check_satisfiable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatisfiabilityReport {
+    unsatisfied_revealed_attrs: BTreeSet<String>,
+    unsatisfied_predicates: BTreeSet<Predicate>
+}
+
+impl SatisfiabilityReport {
+    /// Returns true if proof for the sub proof request can be built from the credential.
+    pub fn is_satisfiable(&self) -> bool {
+        self.unsatisfied_revealed_attrs.is_empty() && self.unsatisfied_predicates.is_empty()
+    }
+
+    /// Returns requested revealed attributes the credential doesn't contain.
+    pub fn unsatisfied_revealed_attrs(&self) -> &BTreeSet<String> {
+        &self.unsatisfied_revealed_attrs
+    }
+
+    /// Returns predicates the credential doesn't satisfy: attribute is absent, non-numeric or out of the bound.
+    pub fn unsatisfied_predicates(&self) -> &BTreeSet<Predicate> {
+        &self.unsatisfied_predicates
+    }
 }
 
 /// Builder of “Sub Proof Request”.
@@ -736,7 +2833,10 @@ impl SubProofRequestBuilder {
         Ok(SubProofRequestBuilder {
             value: SubProofRequest {
                 revealed_attrs: BTreeSet::new(),
-                predicates: BTreeSet::new()
+                predicates: BTreeSet::new(),
+                unrevoked_attrs: BTreeSet::new(),
+                committed_attrs: BTreeSet::new(),
+                non_revoc_interval: None
             }
         })
     }
@@ -746,9 +2846,16 @@ impl SubProofRequestBuilder {
         Ok(())
     }
 
-    pub fn add_predicate(&mut self, attr_name: &str, p_type: &str, value: i32) -> Result<(), IndyCryptoError> {
+    /// Requires prover to show that attribute compares with `value` as `p_type` (`GE`, `LE`, `GT` or `LT`).
+    ///
+    /// Value is 64-bit, so predicates can be applied to timestamps and other large numeric attributes
+    /// encoded as non-negative 64-bit integers (e.g. added with `CredentialValuesBuilder::add_dec_known`).
+    pub fn add_predicate(&mut self, attr_name: &str, p_type: &str, value: i64) -> Result<(), IndyCryptoError> {
         let p_type = match p_type {
             "GE" => PredicateType::GE,
+            "LE" => PredicateType::LE,
+            "GT" => PredicateType::GT,
+            "LT" => PredicateType::LT,
             p_type => return Err(IndyCryptoError::InvalidStructure(format!("Invalid predicate type: {:?}", p_type)))
         };
 
@@ -762,7 +2869,54 @@ impl SubProofRequestBuilder {
         Ok(())
     }
 
+    /// Requests to reveal all attributes of logical schema `namespace` of the credential schema.
+    pub fn add_revealed_namespace(&mut self, credential_schema: &CredentialSchema, namespace: &str) -> Result<(), IndyCryptoError> {
+        let attrs = credential_schema.namespace_attrs(namespace);
+        if attrs.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Credential schema doesn't contain attributes of namespace '{}'", namespace)));
+        }
+        self.value.revealed_attrs.extend(attrs);
+        Ok(())
+    }
+
+    /// Requires prover to show that `from <= attr_name <= to`.
+    ///
+    /// Range is composed of GE and LE predicates on the same attribute,
+    /// both bound to the same attribute value of the equality proof.
+    pub fn add_range_predicate(&mut self, attr_name: &str, from: i64, to: i64) -> Result<(), IndyCryptoError> {
+        if from > to {
+            return Err(IndyCryptoError::InvalidStructure(format!("Invalid range for attribute '{}': {} > {}", attr_name, from, to)));
+        }
+
+        self.add_predicate(attr_name, "GE", from)?;
+        self.add_predicate(attr_name, "LE", to)?;
+        Ok(())
+    }
+
+    /// Requires prover to show that attribute with own revocation index is not revoked.
+    pub fn add_unrevoked_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
+        self.value.unrevoked_attrs.insert(attr.to_owned());
+        Ok(())
+    }
+
+    /// Requires prover to commit to the value of hidden attribute (experimental).
+    /// Commitment can be used as public input of external circuit, see `cl::snark`.
+    pub fn add_committed_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
+        self.value.committed_attrs.insert(attr.to_owned());
+        Ok(())
+    }
+
+    /// Requires prover to show that credential is non-revoked in the state of revocation registry
+    /// published within `interval`. Timestamp of the state is hashed into the proof challenge.
+    pub fn set_non_revoc_interval(&mut self, interval: &NonRevocationInterval) -> Result<(), IndyCryptoError> {
+        self.value.non_revoc_interval = Some(*interval);
+        Ok(())
+    }
+
     pub fn finalize(self) -> Result<SubProofRequest, IndyCryptoError> {
+        if let Some(attr) = self.value.committed_attrs.intersection(&self.value.revealed_attrs).next() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Attribute '{}' can't be both revealed and committed", attr)));
+        }
         Ok(self.value)
     }
 }
@@ -770,20 +2924,80 @@ impl SubProofRequestBuilder {
 /// Some condition that must be satisfied.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub struct Predicate {
+    #[serde(alias = "attrName")]
     attr_name: String,
+    #[serde(alias = "pType")]
     p_type: PredicateType,
-    value: i32,
+    value: i64,
+}
+
+impl Predicate {
+    /// Returns true if encoded attribute value is a 64-bit non-negative integer satisfying the predicate,
+    /// so prover can show the difference with four squares.
+    fn is_satisfied_by(&self, attr_value: &BigNumber) -> Result<bool, IndyCryptoError> {
+        if attr_value.is_negative() || attr_value.num_bits()? > 63 {
+            return Ok(false);
+        }
+
+        let attr_value = match attr_value.to_dec()?.parse::<i64>() {
+            Ok(attr_value) => attr_value,
+            Err(_) => return Ok(false)
+        };
+
+        Ok(!self.get_delta(attr_value)?.is_negative())
+    }
+
+    /// Returns the non-negative difference that prover shows with four squares
+    /// if `attr_value` satisfies the predicate (negative otherwise).
+    ///
+    /// Computed in BigNumber as difference of two i64 values can overflow i64;
+    /// non-negative difference of non-negative attribute value always fits u64.
+    fn get_delta(&self, attr_value: i64) -> Result<BigNumber, IndyCryptoError> {
+        let attr_value = BigNumber::from_dec(&attr_value.to_string())?;
+        let value = BigNumber::from_dec(&self.value.to_string())?;
+
+        match self.p_type {
+            PredicateType::GE => attr_value.sub(&value),
+            PredicateType::GT => attr_value.sub(&value)?.sub(&BigNumber::from_u32(1)?),
+            PredicateType::LE => value.sub(&attr_value),
+            PredicateType::LT => value.sub(&attr_value)?.sub(&BigNumber::from_u32(1)?)
+        }
+    }
+
+    /// Returns the inclusive bound the attribute is compared with.
+    fn get_bound(&self) -> Result<BigNumber, IndyCryptoError> {
+        let value = BigNumber::from_dec(&self.value.to_string())?;
+
+        match self.p_type {
+            PredicateType::GE | PredicateType::LE => Ok(value),
+            PredicateType::GT => value.add(&BigNumber::from_u32(1)?),
+            PredicateType::LT => value.sub(&BigNumber::from_u32(1)?)
+        }
+    }
+
+    /// Returns true if attribute must be less than (or equal to) the bound.
+    fn is_less(&self) -> bool {
+        match self.p_type {
+            PredicateType::GE | PredicateType::GT => false,
+            PredicateType::LE | PredicateType::LT => true
+        }
+    }
 }
 
-/// Condition type (Currently GE only).
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+/// Condition type.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
 pub enum PredicateType {
-    GE
+    GE,
+    LE,
+    GT,
+    LT
 }
 
 impl Ord for Predicate {
     fn cmp(&self, other: &Self) -> Ordering {
         self.attr_name.cmp(&other.attr_name)
+            .then_with(|| self.p_type.cmp(&other.p_type))
+            .then_with(|| self.value.cmp(&other.value))
     }
 }
 
@@ -793,6 +3007,132 @@ impl PartialOrd for Predicate {
     }
 }
 
+/// Credential data delegated by holder to a guardian that allows the guardian to build proofs on holder's behalf.
+/// Delegation is restricted to the attributes from `scope` and is valid until `expires_at` (seconds since the epoch).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DelegationBundle {
+    #[serde(alias = "credSignature")]
+    cred_signature: CredentialSignature,
+    #[serde(alias = "credValues")]
+    cred_values: CredentialValues,
+    witness: Option<Witness>,
+    scope: BTreeSet<String>,
+    #[serde(alias = "expiresAt")]
+    expires_at: u64
+}
+
+impl DelegationBundle {
+    pub fn cred_signature(&self) -> &CredentialSignature {
+        &self.cred_signature
+    }
+
+    pub fn cred_values(&self) -> &CredentialValues {
+        &self.cred_values
+    }
+
+    pub fn witness(&self) -> Option<&Witness> {
+        self.witness.as_ref()
+    }
+
+    pub fn guardianship_marker(&self) -> GuardianshipMarker {
+        GuardianshipMarker { scope: self.scope.clone(), expires_at: self.expires_at }
+    }
+}
+
+/// `DelegationBundle` encrypted for the guardian's BLS verification key.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SealedDelegationBundle {
+    #[serde(alias = "ephemeralKey")]
+    ephemeral_key: PointG2,
+    iv: Vec<u8>,
+    ciphertext: Vec<u8>,
+    tag: Vec<u8>,
+    #[serde(alias = "expiresAt")]
+    expires_at: u64
+}
+
+impl SealedDelegationBundle {
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+}
+
+/// Verifier visible marker of the proof built by a guardian on behalf of credential holder.
+/// Marker is bound to the proof challenge so it can't be stripped or altered.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GuardianshipMarker {
+    scope: BTreeSet<String>,
+    #[serde(alias = "expiresAt")]
+    expires_at: u64
+}
+
+impl GuardianshipMarker {
+    pub fn scope(&self) -> &BTreeSet<String> {
+        &self.scope
+    }
+
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    pub fn is_expired<C>(&self, clock: &C) -> Result<bool, IndyCryptoError> where C: Clock {
+        Ok(clock.now()? >= self.expires_at)
+    }
+
+    fn allows(&self, sub_proof_request: &SubProofRequest) -> bool {
+        sub_proof_request.revealed_attrs.is_subset(&self.scope) &&
+            sub_proof_request.committed_attrs.is_subset(&self.scope) &&
+            sub_proof_request.predicates.iter().all(|predicate| self.scope.contains(&predicate.attr_name))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        Ok(::serde_json::to_vec(self)?)
+    }
+}
+
+/// Policy of reducing the proof challenge hash to the challenge (`c_hash`) used by all sub proofs.
+///
+/// Policy is recorded in the proof, so verifier reproduces the challenge the same way as prover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChallengeReduction {
+    /// Version 1: full 256-bit SHA-256 value. Primary proofs use it as is,
+    /// non-revocation proofs use its bytes as group order element that is reduced implicitly by group arithmetic.
+    V1Full,
+    /// Version 2: SHA-256 value reduced modulo group order once, so primary and non-revocation proofs use the same value.
+    V2ReducedModOrder
+}
+
+impl Default for ChallengeReduction {
+    fn default() -> ChallengeReduction {
+        ChallengeReduction::V1Full
+    }
+}
+
+impl ChallengeReduction {
+    /// Returns version number of the policy.
+    pub fn version(&self) -> u32 {
+        match *self {
+            ChallengeReduction::V1Full => 1,
+            ChallengeReduction::V2ReducedModOrder => 2
+        }
+    }
+
+    /// Reduces hash of the proof values to the challenge.
+    pub fn reduce(&self, hash: BigNumber) -> Result<BigNumber, IndyCryptoError> {
+        match *self {
+            ChallengeReduction::V1Full => Ok(hash),
+            ChallengeReduction::V2ReducedModOrder => {
+                let order = BigNumber::from_bytes(&GroupOrderElement::order_bytes()?)?;
+                hash.modulus(&order, None)
+            }
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == ChallengeReduction::default()
+    }
+}
+
 /// Proof is complex crypto structure created by prover over multiple credentials that allows to prove that prover:
 /// 1) Knows signature over credentials issued with specific issuer keys (identified by key id)
 /// 2) Credential contains attributes with specific values that prover wants to disclose
@@ -800,33 +3140,457 @@ impl PartialOrd for Predicate {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Proof {
     proofs: Vec<SubProof>,
+    #[serde(alias = "aggregatedProof")]
     aggregated_proof: AggregatedProof,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guardianship: Option<GuardianshipMarker>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ChallengeReduction::is_default")]
+    #[serde(alias = "challengeReduction")]
+    challenge_reduction: ChallengeReduction,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "snarkProofs")]
+    snark_proofs: Vec<SnarkProof>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(alias = "selfAttestedAttrs")]
+    self_attested_attrs: BTreeMap<String /* attr_name */, String /* value */>,
+}
+
+impl Proof {
+    /// Converts proof to the compact form.
+    ///
+    /// Compact proof omits aggregated `c_list` as all its values are contained in sub proofs
+    /// and can be recomputed by verifier.
+    pub fn compact(self) -> Proof {
+        Proof {
+            proofs: self.proofs,
+            aggregated_proof: AggregatedProof { c_hash: self.aggregated_proof.c_hash, c_list: Vec::new() },
+            guardianship: self.guardianship,
+            challenge_reduction: self.challenge_reduction,
+            snark_proofs: self.snark_proofs,
+            self_attested_attrs: self.self_attested_attrs
+        }
+    }
+
+    /// Converts proof to the canonical form.
+    ///
+    /// Group elements of non-revocation proofs are stored in projective coordinates, so the same proof
+    /// can be serialized in different ways. Canonical proof keeps them normalized and its serialization
+    /// is stable: serialize -> deserialize -> serialize produces the same bytes.
+    /// Canonicalization doesn't change the proof for verifier.
+    pub fn canonicalize(self) -> Result<Proof, IndyCryptoError> {
+        Ok(Proof {
+            proofs: self.proofs.into_iter().map(SubProof::canonicalize).collect::<Result<Vec<SubProof>, IndyCryptoError>>()?,
+            aggregated_proof: self.aggregated_proof,
+            guardianship: self.guardianship,
+            challenge_reduction: self.challenge_reduction,
+            snark_proofs: self.snark_proofs,
+            self_attested_attrs: self.self_attested_attrs
+        })
+    }
+
+    /// Returns policy used to reduce challenge hash of the proof.
+    pub fn challenge_reduction(&self) -> ChallengeReduction {
+        self.challenge_reduction
+    }
+
+    /// Returns guardianship marker if proof was built by a guardian on behalf of credential holder.
+    pub fn guardianship(&self) -> Option<&GuardianshipMarker> {
+        self.guardianship.as_ref()
+    }
+
+    /// Returns commitments to attributes requested as committed in all sub proofs.
+    pub fn attribute_commitments(&self) -> Result<Vec<AttributeCommitment>, IndyCryptoError> {
+        let mut commitments = Vec::new();
+        for (idx, proof) in self.proofs.iter().enumerate() {
+            for (attr, committed_attr_proof) in proof.committed_attr_proofs.iter() {
+                commitments.push(AttributeCommitment::new(idx, attr, committed_attr_proof.commitment.clone()?));
+            }
+        }
+        Ok(commitments)
+    }
+
+    /// Returns timestamp of revocation registry state the sub proof was built against.
+    /// Verifier uses it to take the registry state from the ledger and pass it to `ProofVerifier`.
+    pub fn non_revoc_timestamp(&self, sub_proof_idx: usize) -> Option<Timestamp> {
+        self.proofs.get(sub_proof_idx).and_then(|sub_proof| sub_proof.non_revoc_timestamp)
+    }
+
+    /// Returns revealed attribute values of the sub proof.
+    pub fn revealed_attrs(&self, sub_proof_idx: usize) -> Option<&BTreeMap<String, BigNumber>> {
+        self.proofs.get(sub_proof_idx).map(|sub_proof| &sub_proof.primary_proof.eq_proof.revealed_attrs)
+    }
+
+    /// Returns raw values of revealed attributes of the sub proof that were added with
+    /// `CredentialValuesBuilder::add_raw_value`. Verified proof carries only raw values matching revealed encoded values.
+    pub fn revealed_raw_attrs(&self, sub_proof_idx: usize) -> Option<&BTreeMap<String, String>> {
+        self.proofs.get(sub_proof_idx).map(|sub_proof| &sub_proof.primary_proof.eq_proof.revealed_raw_attrs)
+    }
+
+    /// Exports statements each sub proof makes about attributes of the holder,
+    /// e.g. for wallets showing the holder what was disclosed.
+    /// Self-attested attributes aren't bound to sub proofs, see `self_attested_attrs`.
+    pub fn statements(&self) -> Result<Vec<BTreeSet<ProofStatement>>, IndyCryptoError> {
+        let mut statements = Vec::with_capacity(self.proofs.len());
+
+        for sub_proof in self.proofs.iter() {
+            let eq_proof = &sub_proof.primary_proof.eq_proof;
+            let mut sub_proof_statements = BTreeSet::new();
+
+            for (attr, value) in eq_proof.revealed_attrs.iter() {
+                sub_proof_statements.insert(ProofStatement::Revealed {
+                    attr: attr.clone(),
+                    value: value.to_dec()?,
+                    raw_value: eq_proof.revealed_raw_attrs.get(attr).cloned()
+                });
+            }
+            for ge_proof in sub_proof.primary_proof.ge_proofs.iter() {
+                sub_proof_statements.insert(ProofStatement::Predicate(ge_proof.predicate.clone()));
+            }
+            for attr in sub_proof.committed_attr_proofs.keys() {
+                sub_proof_statements.insert(ProofStatement::Committed { attr: attr.clone() });
+            }
+            if sub_proof.non_revoc_proof.is_some() {
+                sub_proof_statements.insert(ProofStatement::CredentialUnrevoked);
+            }
+            for attr in sub_proof.attr_non_revoc_proofs.keys() {
+                sub_proof_statements.insert(ProofStatement::AttrUnrevoked { attr: attr.clone() });
+            }
+
+            statements.push(sub_proof_statements);
+        }
+
+        Ok(statements)
+    }
+
+    /// Returns revealed attribute values of the sub proof grouped by namespace:
+    /// namespace -> attribute name within namespace -> value.
+    /// Attributes without namespace are grouped under empty namespace with their full names.
+    pub fn revealed_attrs_by_namespace(&self, sub_proof_idx: usize) -> Option<BTreeMap<String, BTreeMap<String, &BigNumber>>> {
+        self.revealed_attrs(sub_proof_idx).map(|revealed_attrs| {
+            let mut groups: BTreeMap<String, BTreeMap<String, &BigNumber>> = BTreeMap::new();
+            for (attr, value) in revealed_attrs.iter() {
+                let (namespace, name) = split_namespaced_attr(attr).unwrap_or(("", attr));
+                groups.entry(namespace.to_owned()).or_insert_with(BTreeMap::new).insert(name.to_owned(), value);
+            }
+            groups
+        })
+    }
+
+    /// Returns values of self-attested attributes; they are hashed into the challenge.
+    pub fn self_attested_attrs(&self) -> &BTreeMap<String, String> {
+        &self.self_attested_attrs
+    }
+
+    /// Returns proofs of external circuits over attribute commitments.
+    pub fn snark_proofs(&self) -> &[SnarkProof] {
+        &self.snark_proofs
+    }
+
+    /// Returns true if proof was converted to the compact form.
+    pub fn is_compact(&self) -> bool {
+        self.aggregated_proof.c_list.is_empty()
+    }
+
+    /// Serializes proof as json directly to writer (e.g. socket or file) without building intermediate string.
+    pub fn write_to<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), IndyCryptoError> {
+        ::utils::json::to_writer(self, writer)
+    }
+
+    /// Deserializes proof from json read directly from reader.
+    pub fn read_from<R: ::std::io::Read>(reader: &mut R) -> Result<Proof, IndyCryptoError> {
+        ::utils::json::from_reader(reader)
+    }
+
+    fn restore_c_list(&self) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+        let mut c_list: Vec<Vec<u8>> = Vec::new();
+        for proof in self.proofs.iter() {
+            c_list.extend_from_slice(&proof.as_c_list()?);
+        }
+        Ok(c_list)
+    }
+}
+
+/// Proof that links credential bound to the old master secret with blinded credential secrets
+/// containing the new one.
+///
+/// It is a regular proof over the old credential finalized with a nonce derived from
+/// the new blinded secrets, so it can't be replayed for other blinded secrets.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MasterSecretRotationProof {
+    proof: Proof
+}
+
+impl MasterSecretRotationProof {
+    /// Returns underlying proof over the credential bound to the old master secret.
+    pub fn proof(&self) -> &Proof {
+        &self.proof
+    }
+}
+
+/// Commitment to a stable personal attribute (e.g. biometric template hash) that holder presents to many issuers.
+///
+/// It doesn't depend on issuer keys, so credentials independently issued with the committed attribute
+/// are provably about the same value. The value is bound modulo the order of the pairing group.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HolderBindingCommitment {
+    commitment: PointG1
+}
+
+impl HolderBindingCommitment {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        self.commitment.to_bytes()
+    }
+}
+
+/// Opening of holder binding commitment known to holder only.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HolderBindingOpening {
+    value: BigNumber,
+    #[serde(alias = "blindingFactor")]
+    blinding_factor: GroupOrderElement
+}
+
+impl HolderBindingOpening {
+    pub fn clone(&self) -> Result<HolderBindingOpening, IndyCryptoError> {
+        Ok(HolderBindingOpening { value: self.value.clone()?, blinding_factor: self.blinding_factor })
+    }
+
+    pub fn value(&self) -> Result<BigNumber, IndyCryptoError> {
+        self.value.clone()
+    }
+}
+
+/// Proof that committed attribute of blinded credential secrets has the value of holder binding commitment.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HolderBindingProof {
+    c: BigNumber,
+    #[serde(alias = "mCap")]
+    m_cap: BigNumber,
+    #[serde(alias = "rCap")]
+    r_cap: BigNumber,
+    #[serde(alias = "rhoCap")]
+    rho_cap: GroupOrderElement
+}
+
+/// Part of sub proof tau list values belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TauListPart {
+    NonRevocation,
+    AttrNonRevocation(String),
+    Primary,
+    CommittedAttr(String)
+}
+
+/// Tau list values of one part of sub proof.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TauListEntry {
+    sub_proof_idx: usize,
+    part: TauListPart,
+    values: Vec<Vec<u8>>
+}
+
+impl TauListEntry {
+    pub fn sub_proof_idx(&self) -> usize {
+        self.sub_proof_idx
+    }
+
+    pub fn part(&self) -> &TauListPart {
+        &self.part
+    }
+
+    pub fn values(&self) -> &[Vec<u8>] {
+        &self.values
+    }
+}
+
+/// Tau list (t-values hashed into the proof challenge) labeled by sub proof and its part.
+///
+/// Debugging aid: Prover dumps it with `ProofBuilder::tau_list_trace`, Verifier compares it
+/// with recomputed values by `ProofVerifier::diagnose`. It must never be sent along with real proofs
+/// as tau list values together with the proof reveal hidden attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TauListTrace {
+    entries: Vec<TauListEntry>
+}
+
+impl TauListTrace {
+    pub fn entries(&self) -> &[TauListEntry] {
+        &self.entries
+    }
+
+    fn push(&mut self, sub_proof_idx: usize, part: TauListPart, values: Vec<Vec<u8>>) {
+        self.entries.push(TauListEntry { sub_proof_idx, part, values });
+    }
+
+    fn into_values(self) -> Vec<Vec<u8>> {
+        self.entries.into_iter().flat_map(|entry| entry.values).collect()
+    }
+
+    /// Returns first tau list value that differs from the other trace.
+    pub fn find_mismatch(&self, other: &TauListTrace) -> Option<TauListMismatch> {
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let other_entry = match other.entries.get(idx) {
+                Some(other_entry) if other_entry.sub_proof_idx == entry.sub_proof_idx && other_entry.part == entry.part => other_entry,
+                _ => return Some(TauListMismatch { sub_proof_idx: entry.sub_proof_idx, part: entry.part.clone(), index: None })
+            };
+
+            if let Some(index) = (0..entry.values.len().max(other_entry.values.len()))
+                .find(|i| entry.values.get(*i) != other_entry.values.get(*i)) {
+                return Some(TauListMismatch { sub_proof_idx: entry.sub_proof_idx, part: entry.part.clone(), index: Some(index) });
+            }
+        }
+
+        other.entries.get(self.entries.len())
+            .map(|entry| TauListMismatch { sub_proof_idx: entry.sub_proof_idx, part: entry.part.clone(), index: None })
+    }
+}
+
+/// Location of the first diverging tau list value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TauListMismatch {
+    sub_proof_idx: usize,
+    part: TauListPart,
+    index: Option<usize>
+}
+
+impl TauListMismatch {
+    /// Index of the sub proof (in order sub proof requests were added).
+    pub fn sub_proof_idx(&self) -> usize {
+        self.sub_proof_idx
+    }
+
+    pub fn part(&self) -> &TauListPart {
+        &self.part
+    }
+
+    /// Index of the value inside the part, `None` if the part itself is missed in one of the traces.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+}
+
+/// Statement of a sub proof about attributes of the holder exported by `Proof::statements`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum ProofStatement {
+    /// Attribute value is revealed: decimal encoded value and raw value if proof carries it.
+    Revealed { attr: String, value: String, raw_value: Option<String> },
+    /// Attribute satisfies the predicate, its value isn't revealed.
+    Predicate(Predicate),
+    /// Commitment to the attribute value is revealed.
+    Committed { attr: String },
+    /// Credential isn't revoked.
+    CredentialUnrevoked,
+    /// Attribute isn't revoked.
+    AttrUnrevoked { attr: String }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SubProof {
+    #[serde(alias = "primaryProof")]
     primary_proof: PrimaryProof,
-    non_revoc_proof: Option<NonRevocProof>
+    #[serde(alias = "nonRevocProof")]
+    non_revoc_proof: Option<NonRevocProof>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(alias = "attrNonRevocProofs")]
+    attr_non_revoc_proofs: BTreeMap<String /* attr_name */, NonRevocProof>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(alias = "committedAttrProofs")]
+    committed_attr_proofs: BTreeMap<String /* attr_name */, CommittedAttributeProof>,
+    /// Fingerprint of the sub proof request answered by the sub proof; it's hashed into the challenge,
+    /// so Verifier detects proofs answering other requests than the ones it issued.
+    /// Empty for proofs created before fingerprints were introduced.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "subProofRequestFingerprint")]
+    sub_proof_request_fingerprint: Vec<u8>,
+    /// Timestamp of revocation registry state non-revocation proof was built against; it's hashed into the challenge.
+    /// Present only if sub proof request has non-revocation interval.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "nonRevocTimestamp")]
+    non_revoc_timestamp: Option<Timestamp>
+}
+
+impl SubProof {
+    fn canonicalize(self) -> Result<SubProof, IndyCryptoError> {
+        let mut attr_non_revoc_proofs = BTreeMap::new();
+        for (attr, non_revoc_proof) in self.attr_non_revoc_proofs {
+            attr_non_revoc_proofs.insert(attr, non_revoc_proof.canonicalize()?);
+        }
+        Ok(SubProof {
+            primary_proof: self.primary_proof,
+            non_revoc_proof: match self.non_revoc_proof {
+                Some(non_revoc_proof) => Some(non_revoc_proof.canonicalize()?),
+                None => None
+            },
+            attr_non_revoc_proofs,
+            committed_attr_proofs: self.committed_attr_proofs,
+            sub_proof_request_fingerprint: self.sub_proof_request_fingerprint,
+            non_revoc_timestamp: self.non_revoc_timestamp
+        })
+    }
+
+    fn as_c_list(&self) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+        let mut c_list: Vec<Vec<u8>> = Vec::new();
+        if let Some(ref non_revoc_proof) = self.non_revoc_proof {
+            c_list.extend_from_slice(&non_revoc_proof.c_list.as_list()?);
+        }
+        for non_revoc_proof in self.attr_non_revoc_proofs.values() {
+            c_list.extend_from_slice(&non_revoc_proof.c_list.as_list()?);
+        }
+        c_list.push(self.primary_proof.eq_proof.a_prime.to_bytes()?);
+        for ge_proof in self.primary_proof.ge_proofs.iter() {
+            c_list.append_vec(&ge_proof.as_c_list()?)?;
+        }
+        for committed_attr_proof in self.committed_attr_proofs.values() {
+            c_list.push(committed_attr_proof.commitment.to_bytes()?);
+        }
+        Ok(c_list)
+    }
+}
+
+/// Proof that commitment `Z^m * S^r` commits to the attribute value `m` of the credential.
+/// Response for `m` is shared with the equality proof.
+#[derive(Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CommittedAttributeProof {
+    commitment: BigNumber,
+    #[serde(alias = "rCap")]
+    r_cap: BigNumber
 }
 
 #[derive(Debug, Eq, PartialEq, Deserialize, Serialize)]
 pub struct AggregatedProof {
+    #[serde(alias = "cHash")]
     c_hash: BigNumber,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "cList")]
     c_list: Vec<Vec<u8>>
 }
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryProof {
+    #[serde(alias = "eqProof")]
     eq_proof: PrimaryEqualProof,
+    #[serde(alias = "geProofs")]
     ge_proofs: Vec<PrimaryPredicateGEProof>
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize)]
 pub struct PrimaryEqualProof {
     revealed_attrs: BTreeMap<String /* attr_name of revealed */, BigNumber>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    revealed_raw_attrs: BTreeMap<String /* attr_name of revealed */, String>,
     a_prime: BigNumber,
     e: BigNumber,
     v: BigNumber,
+    #[serde(serialize_with = "::utils::json::serialize_sorted_map")]
     m: HashMap<String /* attr_name of all except revealed */, BigNumber>,
     m2: BigNumber
 }
@@ -835,7 +3599,11 @@ impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
     fn deserialize<D: ::serde::de::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
         #[derive(Deserialize)]
         struct PrimaryEqualProofV1 {
+            #[serde(alias = "revealedAttrs")]
             revealed_attrs: BTreeMap<String /* attr_name of revealed */, BigNumber>,
+            #[serde(default)]
+            revealed_raw_attrs: BTreeMap<String /* attr_name of revealed */, String>,
+            #[serde(alias = "aPrime")]
             a_prime: BigNumber,
             e: BigNumber,
             v: BigNumber,
@@ -851,6 +3619,7 @@ impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
         }
         Ok(PrimaryEqualProof {
             revealed_attrs: helper.revealed_attrs,
+            revealed_raw_attrs: helper.revealed_raw_attrs,
             a_prime: helper.a_prime,
             e: helper.e,
             v: helper.v,
@@ -862,28 +3631,68 @@ impl <'a> ::serde::de::Deserialize<'a> for PrimaryEqualProof {
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PrimaryPredicateGEProof {
+    #[serde(serialize_with = "::utils::json::serialize_sorted_map")]
     u: HashMap<String, BigNumber>,
+    #[serde(serialize_with = "::utils::json::serialize_sorted_map")]
     r: HashMap<String, BigNumber>,
     mj: BigNumber,
     alpha: BigNumber,
+    #[serde(serialize_with = "::utils::json::serialize_sorted_map")]
     t: HashMap<String, BigNumber>,
     predicate: Predicate
 }
 
+impl PrimaryPredicateGEProof {
+    fn as_c_list(&self) -> Result<Vec<BigNumber>, IndyCryptoError> {
+        let mut c_list: Vec<BigNumber> = Vec::new();
+        for i in 0..constants::ITERATION {
+            let cur_t = self.t.get(&i.to_string())
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in t", i)))?;
+            c_list.push(cur_t.clone()?);
+        }
+        let t_delta = self.t.get("DELTA")
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in t", "DELTA")))?;
+        c_list.push(t_delta.clone()?);
+        Ok(c_list)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NonRevocProof {
+    #[serde(alias = "xList")]
     x_list: NonRevocProofXList,
+    #[serde(alias = "cList")]
     c_list: NonRevocProofCList
 }
 
+impl NonRevocProof {
+    fn canonicalize(self) -> Result<NonRevocProof, IndyCryptoError> {
+        Ok(NonRevocProof {
+            x_list: self.x_list.canonicalize()?,
+            c_list: self.c_list.canonicalize()?
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct InitProof {
     primary_init_proof: PrimaryInitProof,
     non_revoc_init_proof: Option<NonRevocInitProof>,
+    attr_non_revoc_init_proofs: BTreeMap<String /* attr_name */, NonRevocInitProof>,
+    committed_attr_init_proofs: BTreeMap<String /* attr_name */, CommittedAttributeInitProof>,
     credential_values: CredentialValues,
     sub_proof_request: SubProofRequest,
     credential_schema: CredentialSchema,
     non_credential_schema: NonCredentialSchema,
+    non_revoc_timestamp: Option<Timestamp>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CommittedAttributeInitProof {
+    commitment: BigNumber,
+    r: BigNumber,
+    r_tilde: BigNumber,
+    t: BigNumber
 }
 
 
@@ -981,14 +3790,20 @@ impl PrimaryPredicateGEInitProof {
 pub struct NonRevocProofXList {
     rho: GroupOrderElement,
     r: GroupOrderElement,
+    #[serde(alias = "rPrime")]
     r_prime: GroupOrderElement,
+    #[serde(alias = "rPrimePrime")]
     r_prime_prime: GroupOrderElement,
+    #[serde(alias = "rPrimePrimePrime")]
     r_prime_prime_prime: GroupOrderElement,
     o: GroupOrderElement,
+    #[serde(alias = "oPrime")]
     o_prime: GroupOrderElement,
     m: GroupOrderElement,
+    #[serde(alias = "mPrime")]
     m_prime: GroupOrderElement,
     t: GroupOrderElement,
+    #[serde(alias = "tPrime")]
     t_prime: GroupOrderElement,
     m2: GroupOrderElement,
     s: GroupOrderElement,
@@ -1015,6 +3830,14 @@ impl NonRevocProofXList {
         ])
     }
 
+    fn canonicalize(&self) -> Result<NonRevocProofXList, IndyCryptoError> {
+        let list = self.as_list()?
+            .iter()
+            .map(|el| GroupOrderElement::from_bytes(&el.to_bytes()?))
+            .collect::<Result<Vec<GroupOrderElement>, IndyCryptoError>>()?;
+        Ok(NonRevocProofXList::from_list(list))
+    }
+
     pub fn from_list(seq: Vec<GroupOrderElement>) -> NonRevocProofXList {
         NonRevocProofXList {
             rho: seq[0],
@@ -1047,6 +3870,18 @@ pub struct NonRevocProofCList {
 }
 
 impl NonRevocProofCList {
+    fn canonicalize(&self) -> Result<NonRevocProofCList, IndyCryptoError> {
+        Ok(NonRevocProofCList {
+            e: PointG1::from_bytes(&self.e.to_bytes()?)?,
+            d: PointG1::from_bytes(&self.d.to_bytes()?)?,
+            a: PointG1::from_bytes(&self.a.to_bytes()?)?,
+            g: PointG1::from_bytes(&self.g.to_bytes()?)?,
+            w: PointG2::from_bytes(&self.w.to_bytes()?)?,
+            s: PointG2::from_bytes(&self.s.to_bytes()?)?,
+            u: PointG2::from_bytes(&self.u.to_bytes()?)?
+        })
+    }
+
     pub fn as_list(&self) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
         Ok(vec![
             self.e.to_bytes()?,
@@ -1087,6 +3922,65 @@ impl NonRevocProofTauList {
     }
 }
 
+/// Pairings of non-revocation proof tau list that don't depend on the proof (short proof mode).
+///
+/// They depend on revocation public key and accumulator only, so they can be computed once
+/// (e.g. by witness service next to the registry) and reused for all presentations against the same registry state.
+/// Prover passes them to `ProofBuilder::add_non_revoc_proof_pairings`; the resulting proof is the same as without them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NonRevocProofPairings {
+    r_pub_key: CredentialRevocationPublicKey,
+    accum: Accumulator,
+    htilde_h_cap: Pair,
+    htilde_y: Pair,
+    h1_h_cap: Pair,
+    h2_h_cap: Pair,
+    neg_g_h_cap: Pair,
+    htilde_u: Pair,
+    htilde_accum: Pair
+}
+
+impl NonRevocProofPairings {
+    /// Computes pairings for revocation public key and registry state.
+    ///
+    /// # Arguments
+    /// * `r_pub_key` - Credential revocation public key.
+    /// * `rev_reg` - Revocation registry.
+    pub fn new(r_pub_key: &CredentialRevocationPublicKey,
+               rev_reg: &RevocationRegistry) -> Result<NonRevocProofPairings, IndyCryptoError> {
+        Ok(NonRevocProofPairings {
+            r_pub_key: r_pub_key.clone(),
+            accum: rev_reg.accum,
+            htilde_h_cap: Pair::pair(&r_pub_key.htilde, &r_pub_key.h_cap)?,
+            htilde_y: Pair::pair(&r_pub_key.htilde, &r_pub_key.y)?,
+            h1_h_cap: Pair::pair(&r_pub_key.h1, &r_pub_key.h_cap)?,
+            h2_h_cap: Pair::pair(&r_pub_key.h2, &r_pub_key.h_cap)?,
+            neg_g_h_cap: Pair::pair(&r_pub_key.g.neg()?, &r_pub_key.h_cap)?,
+            htilde_u: Pair::pair(&r_pub_key.htilde, &r_pub_key.u)?,
+            htilde_accum: Pair::pair(&r_pub_key.htilde, &rev_reg.accum)?
+        })
+    }
+
+    /// Returns true if pairings were computed for this revocation public key.
+    pub fn is_for_key(&self, r_pub_key: &CredentialRevocationPublicKey) -> bool {
+        self.r_pub_key == *r_pub_key
+    }
+
+    /// Returns pairings for the new state of revocation registry.
+    /// Only accumulator pairing is recomputed and only if accumulator has changed.
+    ///
+    /// # Arguments
+    /// * `rev_reg` - Revocation registry.
+    pub fn for_rev_reg(&self, rev_reg: &RevocationRegistry) -> Result<NonRevocProofPairings, IndyCryptoError> {
+        let mut pairings = self.clone();
+        if pairings.accum != rev_reg.accum {
+            pairings.accum = rev_reg.accum;
+            pairings.htilde_accum = Pair::pair(&pairings.r_pub_key.htilde, &rev_reg.accum)?;
+        }
+        Ok(pairings)
+    }
+}
+
 /// Random BigNumber that uses `Prover` for proof generation and `Verifier` for proof verification.
 pub type Nonce = BigNumber;
 
@@ -1097,7 +3991,133 @@ pub struct VerifiableCredential {
     credential_schema: CredentialSchema,
     non_credential_schema: NonCredentialSchema,
     rev_key_pub: Option<RevocationKeyPublic>,
-    rev_reg: Option<RevocationRegistry>
+    rev_reg: Option<RevocationRegistry>,
+    attr_rev_regs: BTreeMap<String /* attr_name */, (RevocationKeyPublic, RevocationRegistry)>,
+    /// Time `rev_reg` state was published at, if known to verifier
+    rev_reg_timestamp: Option<Timestamp>,
+    /// Credential is extension of the credential of the previous sub proof request
+    extends_previous: bool
+}
+
+/// Returns values binding proof challenge to the presentation session: channel binding (if any) and nonce.
+fn session_values(nonce: &Nonce, channel_binding: Option<&[u8]>) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+    let mut values = Vec::new();
+    if let Some(channel_binding) = channel_binding {
+        values.push(channel_binding.to_vec());
+    }
+    values.push(nonce.to_bytes()?);
+    Ok(values)
+}
+
+/// Returns values binding proof challenge to self-attested attributes: their canonical json (if any).
+fn self_attested_values(self_attested_attrs: &BTreeMap<String, String>) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+    if self_attested_attrs.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![::utils::json::to_canonical_json(self_attested_attrs)?.into_bytes()])
+}
+
+/// Returns short fingerprint of the data that can be printed instead of the data itself.
+fn fingerprint(bytes: &[u8]) -> Result<String, IndyCryptoError> {
+    let hash = BigNumber::hash(bytes)?;
+    Ok(hash.iter().take(4).map(|b| format!("{:02x}", b)).collect())
+}
+
+fn bn_summary(bn: &BigNumber) -> Result<String, IndyCryptoError> {
+    Ok(format!("{} bits, fp {}", bn.num_bits()?, fingerprint(&bn.to_bytes()?)?))
+}
+
+fn join<'a, I: Iterator<Item=&'a String>>(items: I) -> String {
+    items.map(|item| item.as_str()).collect::<Vec<&str>>().join(", ")
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+impl fmt::Display for CredentialPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut attrs = self.p_key.r.keys().collect::<Vec<&String>>();
+        attrs.sort();
+        write!(f, "CredentialPublicKey {{ n: {}, attrs: [{}], revocation: {} }}",
+               bn_summary(&self.p_key.n).map_err(|_| fmt::Error)?,
+               join(attrs.into_iter()),
+               yes_no(self.r_key.is_some()))
+    }
+}
+
+impl fmt::Display for CredentialPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialPrivateKey {{ <redacted>, revocation: {} }}", yes_no(self.r_key.is_some()))
+    }
+}
+
+impl fmt::Display for MasterSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MasterSecret {{ <redacted> }}")
+    }
+}
+
+impl fmt::Display for CredentialValues {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let attrs = self.attrs_values.iter()
+            .map(|(attr, value)| {
+                let kind = match *value {
+                    CredentialValue::Known { .. } => "known",
+                    CredentialValue::Hidden { .. } => "hidden",
+                    CredentialValue::Commitment { .. } => "commitment"
+                };
+                format!("{}: {}", attr, kind)
+            })
+            .collect::<Vec<String>>();
+        write!(f, "CredentialValues {{ {} }}", join(attrs.iter()))
+    }
+}
+
+impl fmt::Display for CredentialSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CredentialSignature {{ a: {}, revocation: {}, attr_revocation: [{}] }}",
+               bn_summary(&self.p_credential.a).map_err(|_| fmt::Error)?,
+               yes_no(self.r_credential.is_some()),
+               join(self.attr_r_credentials.keys()))
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:?} {}", self.attr_name, self.p_type, self.value)
+    }
+}
+
+impl fmt::Display for SubProofRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let predicates = self.predicates.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+        write!(f, "SubProofRequest {{ revealed: [{}], predicates: [{}], unrevoked: [{}] }}",
+               join(self.revealed_attrs.iter()),
+               join(predicates.iter()),
+               join(self.unrevoked_attrs.iter()))
+    }
+}
+
+impl fmt::Display for SubProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let predicates = self.primary_proof.ge_proofs.iter().map(|p| p.predicate.to_string()).collect::<Vec<String>>();
+        write!(f, "SubProof {{ revealed: [{}], predicates: [{}], non_revocation: {}, unrevoked: [{}] }}",
+               join(self.primary_proof.eq_proof.revealed_attrs.keys()),
+               join(predicates.iter()),
+               yes_no(self.non_revoc_proof.is_some()),
+               join(self.attr_non_revoc_proofs.keys()))
+    }
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let proofs = self.proofs.iter().map(|p| p.to_string()).collect::<Vec<String>>();
+        write!(f, "Proof {{ c_hash: {}, compact: {}, proofs: [{}] }}",
+               bn_summary(&self.aggregated_proof.c_hash).map_err(|_| fmt::Error)?,
+               yes_no(self.is_compact()),
+               join(proofs.iter()))
+    }
 }
 
 trait BytesView {
@@ -1166,6 +4186,661 @@ mod test {
     use self::prover::Prover;
     use self::verifier::Verifier;
 
+    #[test]
+    fn encode_raw_value_works() {
+        assert_eq!("-2147483648", encode_raw_value("-2147483648").unwrap().to_dec().unwrap());
+        assert_eq!(BigNumber::from_bytes(&BigNumber::hash(b"2147483648").unwrap()).unwrap(), encode_raw_value("2147483648").unwrap());
+        assert_eq!(BigNumber::from_bytes(&BigNumber::hash(b"").unwrap()).unwrap(), encode_raw_value("").unwrap());
+    }
+
+    #[test]
+    fn new_nonce_with_params_works() {
+        for &bit_length in [80, 81, 256, 1000].iter() {
+            assert!(new_nonce_with_params(bit_length, None).unwrap().num_bits().unwrap() <= bit_length as i32);
+            assert!(new_nonce_with_params(bit_length, Some("proof_request")).unwrap().num_bits().unwrap() <= bit_length as i32);
+        }
+        assert_ne!(new_nonce_with_params(256, Some("ctx")).unwrap(), new_nonce_with_params(256, Some("ctx")).unwrap());
+    }
+
+    #[test]
+    fn new_nonce_with_params_works_for_invalid_bit_length() {
+        assert!(new_nonce_with_params(79, None).is_err());
+        assert!(new_nonce_with_params(4097, Some("ctx")).is_err());
+    }
+
+    #[test]
+    fn revocation_registry_stats_works() {
+        let delta = |issued: &[u32], revoked: &[u32]| RevocationRegistryDelta {
+            prev_accum: None,
+            accum: PointG2::new_inf().unwrap(),
+            issued: issued.iter().cloned().collect(),
+            revoked: revoked.iter().cloned().collect()
+        };
+        let history = vec![
+            RevocationRegistryHistoryEntry::new(Timestamp::from_secs(10).unwrap(), delta(&[1, 2, 3], &[])),
+            RevocationRegistryHistoryEntry::new(Timestamp::from_secs(20).unwrap(), delta(&[], &[1, 2])),
+            RevocationRegistryHistoryEntry::new(Timestamp::from_secs(30).unwrap(), delta(&[1], &[])),
+        ];
+
+        let stats = RevocationRegistry::stats(10, false, &history).unwrap();
+        assert_eq!((3, 2, 1, 2), (stats.used, stats.active, stats.revoked, stats.revocations));
+        assert_eq!(0.3, stats.occupancy);
+        assert_eq!(vec![3, 1, 2], stats.deltas.iter().map(|delta| delta.active).collect::<Vec<u32>>());
+        assert!(stats.to_json().unwrap().contains(r#""maxCredNum":10"#));
+
+        let stats = RevocationRegistry::stats(10, true, &history[1..]).unwrap();
+        assert_eq!((10, 9, 1, 2), (stats.used, stats.active, stats.revoked, stats.revocations));
+
+        assert!(RevocationRegistry::stats(2, false, &history).is_err());
+    }
+
+    #[test]
+    fn credential_values_builder_works_for_raw_values() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_raw_known("name", "Alex").unwrap();
+        credential_values_builder.add_raw_hidden("age", "28").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        assert_eq!(CredentialValue::Known { value: encode_raw_value("Alex").unwrap() }, credential_values.attrs_values["name"]);
+        assert_eq!(CredentialValue::Hidden { value: BigNumber::from_dec("28").unwrap() }, credential_values.attrs_values["age"]);
+    }
+
+    #[test]
+    fn credential_values_builder_works_for_add_raw_value() {
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_raw_value("name", "Alex").unwrap();
+        credential_values_builder.add_raw_value("age", "28").unwrap();
+        credential_values_builder.add_dec_known("age", "29").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        assert_eq!(CredentialValue::Known { value: encode_raw_value("Alex").unwrap() }, credential_values.attrs_values["name"]);
+        assert_eq!(Some("Alex"), credential_values.raw_value("name"));
+        assert_eq!(None, credential_values.raw_value("age"));
+
+        let credential_values: CredentialValues = ::serde_json::from_str(&::serde_json::to_string(&credential_values).unwrap()).unwrap();
+        assert_eq!(Some("Alex"), credential_values.raw_value("name"));
+    }
+
+    #[test]
+    fn debug_works_for_secrets() {
+        let master_secret = Prover::new_master_secret().unwrap();
+        let master_secret_dec = master_secret.value().unwrap().to_dec().unwrap();
+        assert!(!format!("{:?}", master_secret).contains(&master_secret_dec));
+
+        let mut credential_values_builder = CredentialValuesBuilder::new().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_raw_hidden("age", "28").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let debug = format!("{:?}", credential_values);
+        assert!(!debug.contains(&master_secret_dec));
+        assert!(!debug.contains("28"));
+        assert!(debug.contains("175"));
+    }
+
+    #[test]
+    fn attribute_type_encode_works() {
+        assert_eq!(encode_raw_value("Alex").unwrap(), AttributeType::String.encode("Alex").unwrap());
+        assert_eq!("-28", AttributeType::Integer.encode("-28").unwrap().to_dec().unwrap());
+        assert_eq!("20180101", AttributeType::Date.encode("2018-01-01").unwrap().to_dec().unwrap());
+        assert_eq!("1", AttributeType::Boolean.encode("true").unwrap().to_dec().unwrap());
+        assert_eq!("0", AttributeType::Boolean.encode("false").unwrap().to_dec().unwrap());
+
+        assert!(AttributeType::Integer.encode("Alex").is_err());
+        assert!(AttributeType::Date.encode("2018-13-01").is_err());
+        assert!(AttributeType::Date.encode("2023-02-31").is_err());
+        assert!(AttributeType::Date.encode("2023-02-29").is_err());
+        assert!(AttributeType::Date.encode("2024-02-29").is_ok());
+        assert!(AttributeType::Date.encode("2018-04-31").is_err());
+        assert!(AttributeType::Date.encode("2018-01-00").is_err());
+        assert!(AttributeType::Date.encode("18-01-01").is_err());
+        assert!(AttributeType::Boolean.encode("yes").is_err());
+    }
+
+    #[test]
+    fn credential_values_builder_works_for_typed_schema() {
+        let mut credential_schema_builder = CredentialSchemaBuilder::new().unwrap();
+        credential_schema_builder.add_typed_attr("name", AttributeType::String).unwrap();
+        credential_schema_builder.add_typed_attr("birthdate", AttributeType::Date).unwrap();
+        credential_schema_builder.add_attr("sex").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        assert_eq!(Some(AttributeType::Date), credential_schema.attr_type("birthdate"));
+        assert_eq!(None, credential_schema.attr_type("sex"));
+        assert!(credential_schema.check_predicate_attr("birthdate").is_ok());
+        assert!(credential_schema.check_predicate_attr("sex").is_ok());
+        assert!(credential_schema.check_predicate_attr("name").is_err());
+
+        let mut credential_values_builder = CredentialValuesBuilder::new_for_schema(&credential_schema).unwrap();
+        credential_values_builder.add_raw_known("name", "Alex").unwrap();
+        credential_values_builder.add_raw_known("birthdate", "1990-05-17").unwrap();
+        credential_values_builder.add_raw_known("sex", "male").unwrap();
+        assert!(credential_values_builder.add_raw_known("birthdate", "Alex").is_err());
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        assert_eq!(CredentialValue::Known { value: BigNumber::from_dec("19900517").unwrap() }, credential_values.attrs_values["birthdate"]);
+        assert_eq!(CredentialValue::Known { value: encode_raw_value("male").unwrap() }, credential_values.attrs_values["sex"]);
+    }
+
+    #[test]
+    fn deserialization_works_for_camel_case_fields() {
+        let predicate: Predicate = serde_json::from_str(r#"{"attrName":"age","pType":"GE","value":18}"#).unwrap();
+        assert_eq!(prover::mocks::predicate(), predicate);
+
+        let canonical_predicate_json = ::utils::json::to_canonical_json(&predicate).unwrap();
+        assert_eq!(r#"{"attr_name":"age","p_type":"GE","value":18}"#, canonical_predicate_json);
+    }
+
+    #[test]
+    fn display_works_without_secrets() {
+        let master_secret = prover::mocks::master_secret();
+        let master_secret_str = master_secret.to_string();
+        assert_eq!("MasterSecret { <redacted> }", master_secret_str);
+
+        let credential_values = issuer::mocks::credential_values();
+        let credential_values_str = credential_values.to_string();
+        assert_eq!("CredentialValues { age: known, height: known, master_secret: hidden, name: known, sex: known }", credential_values_str);
+
+        let pub_key = issuer::mocks::credential_public_key();
+        let pub_key_str = pub_key.to_string();
+        assert!(pub_key_str.contains("attrs: [age, height, master_secret, name, sex]"));
+        assert!(!pub_key_str.contains(&pub_key.p_key.n.to_dec().unwrap()));
+
+        let proof = Proof {
+            proofs: vec![SubProof {
+                primary_proof: prover::mocks::primary_proof(),
+                non_revoc_proof: None,
+                attr_non_revoc_proofs: BTreeMap::new(),
+                committed_attr_proofs: BTreeMap::new(),
+                sub_proof_request_fingerprint: Vec::new(),
+                non_revoc_timestamp: None
+            }],
+            aggregated_proof: prover::mocks::aggregated_proof(),
+            guardianship: None,
+            challenge_reduction: ChallengeReduction::V1Full,
+            snark_proofs: Vec::new(),
+            self_attested_attrs: BTreeMap::new()
+        };
+        let proof_str = proof.to_string();
+        assert!(proof_str.contains("revealed: [name], predicates: [age GE 18], non_revocation: no"));
+        assert!(!proof_str.contains(&proof.aggregated_proof.c_hash.to_dec().unwrap()));
+    }
+
+    #[test]
+    fn credential_key_config_works() {
+        let config = CredentialKeyConfig::new(2048).unwrap();
+        assert_eq!(CredentialKeyConfig::default(), config);
+        assert_eq!(constants::LARGE_VTILDE, config.large_vtilde());
+        assert_eq!(constants::LARGE_ETILDE, config.large_etilde());
+        assert_eq!(constants::LARGE_ALPHATILDE, config.large_alphatilde());
+        assert_eq!(*constants::LARGE_E_START_VALUE, config.e_start_value().unwrap());
+        assert_eq!(*constants::LARGE_E_END_RANGE_VALUE, config.e_end_range_value().unwrap());
+
+        let config = CredentialKeyConfig::new(4096).unwrap();
+        assert_eq!(4096, config.modulus_size());
+        assert_eq!(constants::LARGE_VPRIME + 2048, config.large_vprime);
+        assert_eq!(constants::LARGE_VPRIME_PRIME + 2048, config.large_vprime_prime);
+
+        assert!(CredentialKeyConfig::new(1024).is_err());
+        assert!(CredentialKeyConfig::from_params(1536, 596, 119, 2128, 2724).is_err());
+        assert!(CredentialKeyConfig::from_params(1024, 500, 119, 2128, 2724).is_err());
+    }
+
+    #[test]
+    fn cancellation_token_works() {
+        let token = CancellationToken::new();
+        let shared = token.clone();
+        assert!(!shared.is_cancelled());
+
+        token.cancel();
+        assert!(shared.is_cancelled());
+    }
+
+    #[test]
+    fn credential_public_key_fingerprint_works() {
+        let pub_key = CredentialPublicKey::build_from_parts(&issuer::mocks::credential_primary_public_key(), None).unwrap();
+
+        let fingerprint = pub_key.fingerprint().unwrap();
+        assert_eq!(32, fingerprint.len());
+        assert_eq!(64, pub_key.key_id().unwrap().len());
+        assert!(pub_key.has_fingerprint(&fingerprint).unwrap());
+
+        // Fingerprint doesn't depend on order of attributes in json
+        let pub_key_json = serde_json::to_string(&pub_key).unwrap();
+        let restored: CredentialPublicKey = serde_json::from_str(&pub_key_json).unwrap();
+        assert!(pub_key.is_same_key(&restored).unwrap());
+
+        let mut other_p_key = issuer::mocks::credential_primary_public_key();
+        other_p_key.rctxt = other_p_key.rctxt.increment().unwrap();
+        let other_pub_key = CredentialPublicKey::build_from_parts(&other_p_key, None).unwrap();
+        assert!(!pub_key.is_same_key(&other_pub_key).unwrap());
+        assert!(!other_pub_key.has_fingerprint(&fingerprint).unwrap());
+    }
+
+    fn _primary_key_pair() -> (CredentialPublicKey, CredentialPrivateKey) {
+        (CredentialPublicKey::build_from_parts(&issuer::mocks::credential_primary_public_key(), None).unwrap(),
+         CredentialPrivateKey { p_key: issuer::mocks::credential_primary_private_key(), r_key: None })
+    }
+
+    #[test]
+    fn issuer_key_pair_export_import_works() {
+        let (pub_key, priv_key) = _primary_key_pair();
+        let key_pair = IssuerKeyPair::new(pub_key, priv_key).unwrap();
+
+        let blob = key_pair.export("password").unwrap();
+        let imported = IssuerKeyPair::import(&blob, "password").unwrap();
+        assert_eq!(key_pair.pub_key(), imported.pub_key());
+        assert_eq!(key_pair.priv_key().p_key, imported.priv_key().p_key);
+
+        assert!(IssuerKeyPair::import(&blob, "other").is_err());
+
+        let mut modified = blob.clone();
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        assert!(IssuerKeyPair::import(&modified, "password").is_err());
+
+        assert!(IssuerKeyPair::import(&blob[..20], "password").is_err());
+    }
+
+    #[test]
+    fn witness_new_works_for_issuance_by_default() {
+        let max_cred_num = 5;
+        let mut generator = RevocationTailsGenerator::new(max_cred_num, GroupOrderElement::new().unwrap(), PointG2::new().unwrap());
+        let simple_tails_accessor = SimpleTailsAccessor::new(&mut generator).unwrap();
+
+        let accum = PointG2::new().unwrap();
+        let by_default_delta = RevocationRegistryDelta { prev_accum: None, accum, issued: HashSet::new(), revoked: hashset![2] };
+        let on_demand_delta = RevocationRegistryDelta { prev_accum: None, accum, issued: hashset![1, 3, 4, 5], revoked: HashSet::new() };
+
+        let by_default_witness = Witness::new(1, max_cred_num, true, &by_default_delta, &simple_tails_accessor).unwrap();
+        let on_demand_witness = Witness::new(1, max_cred_num, false, &on_demand_delta, &simple_tails_accessor).unwrap();
+        assert_eq!(on_demand_witness.omega, by_default_witness.omega);
+    }
+
+    #[test]
+    fn simple_witness_store_works() {
+        let witness_store = SimpleWitnessStore::new();
+        let accum = PointG2::new().unwrap();
+        let witness = Witness { omega: PointG2::new().unwrap() };
+
+        assert!(witness_store.get("rev_reg", 1, &accum).unwrap().is_none());
+
+        witness_store.put("rev_reg", 1, &accum, &witness).unwrap();
+        assert_eq!(witness.omega, witness_store.get("rev_reg", 1, &accum).unwrap().unwrap().omega);
+
+        assert!(witness_store.get("rev_reg", 2, &accum).unwrap().is_none());
+        assert!(witness_store.get("other_rev_reg", 1, &accum).unwrap().is_none());
+        assert!(witness_store.get("rev_reg", 1, &PointG2::new().unwrap()).unwrap().is_none());
+    }
+
+    #[test]
+    fn credential_values_with_master_secret_works() {
+        let master_secret_store = SimpleMasterSecretStore::new();
+        master_secret_store.put("persona", &prover::mocks::master_secret()).unwrap();
+        master_secret_store.put("other_persona", &Prover::new_master_secret().unwrap()).unwrap();
+
+        let mut credential_values = issuer::mocks::credential_values();
+        let expected_values = credential_values.clone().unwrap();
+        credential_values.attrs_values.remove("master_secret");
+
+        let credential_values_with_ms = credential_values.with_master_secret(&master_secret_store, "persona").unwrap();
+        assert_eq!(expected_values.attrs_values, credential_values_with_ms.attrs_values);
+
+        assert!(expected_values.with_master_secret(&master_secret_store, "persona").is_ok());
+        assert!(expected_values.with_master_secret(&master_secret_store, "other_persona").is_err());
+        assert!(credential_values.with_master_secret(&master_secret_store, "unknown_persona").is_err());
+    }
+
+    #[test]
+    fn witness_serialization_works() {
+        let witness = Witness { omega: PointG2::new().unwrap() };
+
+        let witness_json = serde_json::to_string(&witness).unwrap();
+        let deserialized_witness: Witness = serde_json::from_str(&witness_json).unwrap();
+        assert_eq!(witness.omega, deserialized_witness.omega);
+    }
+
+    #[test]
+    fn revocation_tails_generator_iterator_works() {
+        let g_dash = PointG2::new().unwrap();
+        let gamma = GroupOrderElement::new().unwrap();
+        let mut generator = RevocationTailsGenerator::new(2, gamma, g_dash);
+
+        let first = generator.next().unwrap().unwrap();
+        assert_eq!(first, Tail::new_tail(0, &g_dash, &gamma).unwrap());
+        assert_eq!((4, Some(4)), generator.tails().size_hint());
+
+        let tails = generator.tails().collect::<Result<Vec<Tail>, IndyCryptoError>>().unwrap();
+        assert_eq!(4, tails.len());
+        assert_eq!(tails[3], Tail::new_tail(4, &g_dash, &gamma).unwrap());
+        assert!(generator.tails().next().is_none());
+        assert_eq!(0, generator.count());
+    }
+
+    #[test]
+    fn credential_private_key_parts_export_import_works() {
+        let (r_pub_key, r_priv_key) = issuer::Issuer::_new_credential_revocation_keys().unwrap();
+        let pub_key = CredentialPublicKey::build_from_parts(&issuer::mocks::credential_primary_public_key(), Some(&r_pub_key)).unwrap();
+        let priv_key = CredentialPrivateKey::from_parts(issuer::mocks::credential_primary_private_key(), Some(r_priv_key));
+
+        let (p_key, r_key) = priv_key.into_parts();
+        let r_key = r_key.unwrap();
+        let p_blob = p_key.export("primary").unwrap();
+        let r_blob = r_key.export("revocation").unwrap();
+
+        assert_eq!(p_key, CredentialPrimaryPrivateKey::import(&p_blob, "primary", &pub_key).unwrap());
+        assert!(CredentialPrimaryPrivateKey::import(&p_blob, "revocation", &pub_key).is_err());
+        assert!(CredentialPrimaryPrivateKey::import(&r_blob, "revocation", &pub_key).is_err());
+
+        let imported_r_key = CredentialRevocationPrivateKey::import(&r_blob, "revocation", &pub_key).unwrap();
+        assert_eq!(r_key.x, imported_r_key.x);
+        assert_eq!(r_key.sk, imported_r_key.sk);
+        assert!(CredentialRevocationPrivateKey::import(&r_blob, "primary", &pub_key).is_err());
+
+        let (primary_pub_key, _) = _primary_key_pair();
+        assert!(CredentialRevocationPrivateKey::import(&r_blob, "revocation", &primary_pub_key).is_err());
+
+        let priv_key = CredentialPrivateKey::from_parts(p_key, Some(imported_r_key));
+        IssuerKeyPair::new(pub_key, priv_key).unwrap();
+    }
+
+    #[test]
+    fn credential_secrets_blinding_factors_export_import_works() {
+        let blinding_factors = prover::mocks::credential_secrets_blinding_factors();
+        let blob = blinding_factors.export("wallet key").unwrap();
+
+        let imported = CredentialSecretsBlindingFactors::import(&blob, "wallet key").unwrap();
+        assert_eq!(blinding_factors.v_prime, imported.v_prime);
+        assert_eq!(::serde_json::to_string(&blinding_factors.vr_prime).unwrap(), ::serde_json::to_string(&imported.vr_prime).unwrap());
+
+        assert!(CredentialSecretsBlindingFactors::import(&blob, "other key").is_err());
+
+        let mut modified_blob = blob;
+        let last = modified_blob.len() - 1;
+        modified_blob[last] ^= 1;
+        assert!(CredentialSecretsBlindingFactors::import(&modified_blob, "wallet key").is_err());
+
+        let (_, priv_key) = _primary_key_pair();
+        assert!(CredentialSecretsBlindingFactors::import(&priv_key.p_key.export("wallet key").unwrap(), "wallet key").is_err());
+    }
+
+    #[test]
+    fn issuer_key_pair_new_works_for_mismatched_keys() {
+        let (pub_key, mut priv_key) = _primary_key_pair();
+        priv_key.p_key.p = priv_key.p_key.p.increment().unwrap();
+        assert!(IssuerKeyPair::new(pub_key, priv_key).is_err());
+    }
+
+    #[test]
+    fn credential_key_config_is_omitted_from_public_key_json_by_default() {
+        let pub_key = issuer::mocks::credential_primary_public_key();
+        let pub_key_json = serde_json::to_string(&pub_key).unwrap();
+        assert!(!pub_key_json.contains("config"));
+
+        let pub_key: CredentialPrimaryPublicKey = serde_json::from_str(&pub_key_json).unwrap();
+        assert_eq!(CredentialKeyConfig::default(), pub_key.config);
+    }
+
+    #[test]
+    fn credential_values_with_updates_works() {
+        let credential_values = issuer::mocks::credential_values();
+
+        let mut updates = BTreeMap::new();
+        updates.insert("age".to_string(), BigNumber::from_dec("29").unwrap());
+        updates.insert("master_secret".to_string(), BigNumber::from_dec("42").unwrap());
+        let updated_credential_values = credential_values.with_updates(&updates).unwrap();
+
+        assert_eq!(CredentialValue::Known { value: BigNumber::from_dec("29").unwrap() }, updated_credential_values.attrs_values["age"]);
+        assert_eq!(CredentialValue::Hidden { value: BigNumber::from_dec("42").unwrap() }, updated_credential_values.attrs_values["master_secret"]);
+        assert_eq!(credential_values.attrs_values["name"], updated_credential_values.attrs_values["name"]);
+        assert_eq!(BigNumber::from_dec("25").unwrap(), *credential_values.attrs_values["age"].value());
+
+        let mut updates = BTreeMap::new();
+        updates.insert("unknown".to_string(), BigNumber::from_dec("1").unwrap());
+        assert!(credential_values.with_updates(&updates).is_err());
+    }
+
+    #[test]
+    fn credential_values_check_encoding_works() {
+        let credential_values = issuer::mocks::credential_values();
+        let encode = |raw_value: &str| BigNumber::from_dec(raw_value);
+
+        let mut raw_values = BTreeMap::new();
+        raw_values.insert("age".to_string(), "25".to_string());
+        raw_values.insert("height".to_string(), "175".to_string());
+        credential_values.check_encoding(&raw_values, encode).unwrap();
+
+        raw_values.insert("age".to_string(), "26".to_string());
+        assert!(credential_values.check_encoding(&raw_values, encode).is_err());
+    }
+
+    #[test]
+    fn sub_proof_request_fingerprint_works() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_predicate("height", "LT", 200).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_predicate("height", "LT", 200).unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        let same_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_eq!(r#"{"predicates":[{"attr_name":"age","p_type":"GE","value":18},{"attr_name":"height","p_type":"LT","value":200}],"revealed_attrs":["name","sex"]}"#,
+                   String::from_utf8(sub_proof_request.to_canonical_bytes().unwrap()).unwrap());
+        assert_eq!(32, sub_proof_request.fingerprint().unwrap().len());
+        assert_eq!(sub_proof_request.fingerprint().unwrap(), same_sub_proof_request.fingerprint().unwrap());
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_predicate("height", "LT", 200).unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        sub_proof_request_builder.add_unrevoked_attr("name").unwrap();
+        let other_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_ne!(sub_proof_request.fingerprint().unwrap(), other_sub_proof_request.fingerprint().unwrap());
+    }
+
+    #[test]
+    fn sub_proof_request_works_for_non_revoc_interval() {
+        let interval = NonRevocationInterval::new(Some(Timestamp::from_secs(100).unwrap()), None).unwrap();
+        assert!(!interval.contains(Timestamp::from_secs(99).unwrap()));
+        assert!(interval.contains(Timestamp::from_secs(100).unwrap()));
+        assert!(interval.contains(Timestamp::from_secs(1000).unwrap()));
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.set_non_revoc_interval(&interval).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_eq!(r#"{"non_revoc_interval":{"from":100,"to":null},"predicates":[],"revealed_attrs":["name"]}"#,
+                   String::from_utf8(sub_proof_request.to_canonical_bytes().unwrap()).unwrap());
+        assert_eq!(Some(&interval), sub_proof_request.non_revoc_interval());
+    }
+
+    #[test]
+    fn namespaced_attr_works() {
+        assert_eq!("core.name", namespaced_attr("core", "name").unwrap());
+        assert_eq!(Some(("core", "name")), split_namespaced_attr("core.name"));
+        assert_eq!(Some(("address", "street.line1")), split_namespaced_attr("address.street.line1"));
+        assert_eq!(None, split_namespaced_attr("name"));
+        assert_eq!(None, split_namespaced_attr(".name"));
+
+        assert!(namespaced_attr("", "name").is_err());
+        assert!(namespaced_attr("co.re", "name").is_err());
+        assert!(namespaced_attr("core", "").is_err());
+    }
+
+    #[test]
+    fn sub_proof_request_works_for_revealed_namespace() {
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_namespaced_attr("core", "name").unwrap();
+        credential_schema_builder.add_namespaced_attr("address", "city").unwrap();
+        credential_schema_builder.add_namespaced_attr("address", "street").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        assert_eq!(btreeset!["address".to_owned(), "core".to_owned()], credential_schema.namespaces());
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_namespace(&credential_schema, "address").unwrap();
+        assert!(sub_proof_request_builder.add_revealed_namespace(&credential_schema, "unknown").is_err());
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_eq!(btreeset!["address.city".to_owned(), "address.street".to_owned()], sub_proof_request.revealed_attrs);
+    }
+
+    #[test]
+    fn sub_proof_request_check_satisfiable_works() {
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_raw_known("name", "Alex").unwrap();
+        credential_values_builder.add_raw_known("age", "28").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let report = sub_proof_request.check_satisfiable(&credential_values, &credential_schema).unwrap();
+        assert!(report.is_satisfiable());
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 30).unwrap();
+        sub_proof_request_builder.add_predicate("age", "LE", 30).unwrap();
+        sub_proof_request_builder.add_predicate("name", "GE", 1).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let report = sub_proof_request.check_satisfiable(&credential_values, &credential_schema).unwrap();
+        assert!(!report.is_satisfiable());
+        assert_eq!(&btreeset!["sex".to_owned()], report.unsatisfied_revealed_attrs());
+        assert_eq!(2, report.unsatisfied_predicates().len());
+        assert!(report.unsatisfied_predicates().iter().all(|predicate| predicate.p_type == PredicateType::GE));
+    }
+
+    #[test]
+    fn challenge_reduction_works_for_test_vectors() {
+        use utils::get_hash_as_int;
+
+        let values = vec![b"tau_list".to_vec(), b"c_list".to_vec(), b"nonce".to_vec()];
+        let hash = get_hash_as_int(&values).unwrap();
+        assert_eq!("38097732497754862246987434859259526084380372505639065950649729941870063204300", hash.to_dec().unwrap());
+
+        let challenge = ChallengeReduction::V1Full.reduce(hash.clone().unwrap()).unwrap();
+        assert_eq!("38097732497754862246987434859259526084380372505639065950649729941870063204300", challenge.to_dec().unwrap());
+
+        let challenge = ChallengeReduction::V2ReducedModOrder.reduce(hash).unwrap();
+        assert_eq!("4501515035723197677105826574796058264861213298829560452592972213538922772402", challenge.to_dec().unwrap());
+
+        let hash = get_hash_as_int(&vec![Vec::new()]).unwrap();
+        let challenge = ChallengeReduction::V2ReducedModOrder.reduce(hash).unwrap();
+        assert_eq!("2198683863459103319890387469190919331242423027769518499209123816122243790855", challenge.to_dec().unwrap());
+    }
+
+    #[test]
+    fn challenge_reduction_v1_is_omitted_from_proof_json() {
+        let proof = Proof {
+            proofs: vec![SubProof {
+                primary_proof: prover::mocks::primary_proof(),
+                non_revoc_proof: None,
+                attr_non_revoc_proofs: BTreeMap::new(),
+                committed_attr_proofs: BTreeMap::new(),
+                sub_proof_request_fingerprint: Vec::new(),
+                non_revoc_timestamp: None
+            }],
+            aggregated_proof: prover::mocks::aggregated_proof(),
+            guardianship: None,
+            challenge_reduction: ChallengeReduction::V1Full,
+            snark_proofs: Vec::new(),
+            self_attested_attrs: BTreeMap::new()
+        };
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        assert!(!proof_json.contains("challenge_reduction"));
+
+        let proof: Proof = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(ChallengeReduction::V1Full, proof.challenge_reduction());
+    }
+
+    #[test]
+    fn revocation_index_set_conversion_works() {
+        let indices = hashset![1, 2, 3, 7, 9, 10, 16, 17];
+
+        let intervals = RevocationIndexSet::new(&indices, RevocationIndexSetFormat::Intervals).unwrap();
+        assert_eq!(RevocationIndexSet::Intervals(vec![(1, 3), (7, 7), (9, 10), (16, 17)]), intervals);
+
+        let bitmap = intervals.convert(RevocationIndexSetFormat::Bitmap, 17).unwrap();
+        assert_eq!(RevocationIndexSet::Bitmap(vec![0b0100_0111, 0b1000_0011, 0b0000_0001]), bitmap);
+
+        let hash_set = bitmap.convert(RevocationIndexSetFormat::HashSet, 17).unwrap();
+        assert_eq!(RevocationIndexSet::HashSet(indices), hash_set);
+    }
+
+    #[test]
+    fn revocation_index_set_conversion_works_for_malformed_intervals() {
+        assert!(RevocationIndexSet::Intervals(vec![(3, 1)]).convert(RevocationIndexSetFormat::Bitmap, 10).is_err());
+        assert!(RevocationIndexSet::Intervals(vec![(1, 3), (2, 4)]).convert(RevocationIndexSetFormat::Bitmap, 10).is_err());
+        assert!(RevocationIndexSet::Intervals(vec![(0, 3)]).convert(RevocationIndexSetFormat::Bitmap, 10).is_err());
+        assert!(RevocationIndexSet::HashSet(hashset![0, 1]).convert(RevocationIndexSetFormat::Bitmap, 10).is_err());
+    }
+
+    #[test]
+    fn revocation_index_set_to_hash_set_works_for_out_of_range_indices() {
+        assert!(RevocationIndexSet::Intervals(vec![(1, 4294967294)]).to_hash_set(10).is_err());
+        assert!(RevocationIndexSet::Intervals(vec![(1, ::std::u32::MAX)]).to_hash_set(10).is_err());
+        assert!(RevocationIndexSet::HashSet(hashset![11]).to_hash_set(10).is_err());
+        assert!(RevocationIndexSet::Bitmap(vec![0; 3]).to_hash_set(10).is_err());
+        assert!(RevocationIndexSet::Bitmap(vec![0, 0b0000_0100]).to_hash_set(10).is_err());
+
+        assert_eq!(hashset![10], RevocationIndexSet::Bitmap(vec![0, 0b0000_0010]).to_hash_set(10).unwrap());
+        assert_eq!(hashset![::std::u32::MAX], RevocationIndexSet::Intervals(vec![(::std::u32::MAX, ::std::u32::MAX)]).to_hash_set(::std::u32::MAX).unwrap());
+    }
+
+    #[test]
+    fn revocation_registry_delta_pack_unpack_works() {
+        let accum = PointG2::new().unwrap();
+        let delta = RevocationRegistryDelta {
+            prev_accum: None,
+            accum,
+            issued: hashset![1, 2, 3, 5],
+            revoked: hashset![4]
+        };
+
+        let packed = delta.pack(RevocationIndexSetFormat::Intervals).unwrap();
+        let packed_json = serde_json::to_string(&packed).unwrap();
+        let packed: PackedRevocationRegistryDelta = serde_json::from_str(&packed_json).unwrap();
+
+        let unpacked = packed.convert(RevocationIndexSetFormat::Bitmap, 5).unwrap().unpack(5).unwrap();
+        assert_eq!(delta.issued, unpacked.issued);
+        assert_eq!(delta.revoked, unpacked.revoked);
+        assert_eq!(delta.accum, unpacked.accum);
+
+        assert!(packed.unpack(4).is_err());
+    }
+
+    #[test]
+    fn revocation_registry_delta_unpack_works_for_issued_and_revoked_index() {
+        let packed = PackedRevocationRegistryDelta {
+            prev_accum: None,
+            accum: PointG2::new().unwrap(),
+            issued: RevocationIndexSet::Intervals(vec![(1, 3)]),
+            revoked: RevocationIndexSet::Bitmap(vec![0b0000_0100])
+        };
+
+        assert!(packed.unpack(5).is_err());
+    }
+
     #[test]
     fn credential_primary_public_key_conversion_works() {
         let string1 = r#"{
@@ -0,0 +1,212 @@
+//! Holder-side log of presentations for anti-correlation audit.
+//!
+//! Every proof reveals something: attribute values, predicates, the fact that a credential isn't revoked.
+//! Separately harmless presentations add up, and revealed values seen in several presentations let
+//! a verifier link them to the same holder. `PresentationLog` records statements of presented proofs
+//! (see `Proof::statements`) by verifier, so a wallet can answer "what does verifier X cumulatively
+//! know about me" and warn the holder before the next presentation.
+//!
+//! The log holds disclosed data itself: it's local to the holder and must be stored as securely as credentials.
+use cl::*;
+use errors::IndyCryptoError;
+use utils::clock::Timestamp;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Presentation of a proof to a verifier.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PresentationLogEntry {
+    verifier: String,
+    timestamp: Timestamp,
+    statements: BTreeMap<String /* credential id */, BTreeSet<ProofStatement>>,
+    self_attested_attrs: BTreeMap<String, String>
+}
+
+impl PresentationLogEntry {
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns statements of the presented proof by id of credential they are about.
+    pub fn statements(&self) -> &BTreeMap<String, BTreeSet<ProofStatement>> {
+        &self.statements
+    }
+
+    pub fn self_attested_attrs(&self) -> &BTreeMap<String, String> {
+        &self.self_attested_attrs
+    }
+}
+
+/// What a verifier cumulatively learned from all presentations recorded in `PresentationLog`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VerifierKnowledge {
+    presentations: usize,
+    statements: BTreeMap<String /* credential id */, BTreeSet<ProofStatement>>,
+    self_attested_attrs: BTreeMap<String, BTreeSet<String>>
+}
+
+impl VerifierKnowledge {
+    /// Returns number of presentations to the verifier.
+    pub fn presentations(&self) -> usize {
+        self.presentations
+    }
+
+    /// Returns union of statements of all presentations by id of credential they are about.
+    pub fn statements(&self) -> &BTreeMap<String, BTreeSet<ProofStatement>> {
+        &self.statements
+    }
+
+    /// Returns all values of self-attested attributes by attribute name.
+    pub fn self_attested_attrs(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.self_attested_attrs
+    }
+
+    /// Returns names of revealed attributes by credential id.
+    pub fn revealed_attrs(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+        self.statements.iter()
+            .map(|(credential_id, statements)| {
+                let attrs = statements.iter()
+                    .filter_map(|statement| match *statement {
+                        ProofStatement::Revealed { ref attr, .. } => Some(attr.as_str()),
+                        _ => None
+                    })
+                    .collect::<BTreeSet<&str>>();
+                (credential_id.as_str(), attrs)
+            })
+            .filter(|&(_, ref attrs)| !attrs.is_empty())
+            .collect()
+    }
+}
+
+/// Local log of presentations made by the holder.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PresentationLog {
+    entries: Vec<PresentationLogEntry>
+}
+
+impl PresentationLog {
+    pub fn new() -> PresentationLog {
+        PresentationLog::default()
+    }
+
+    /// Records presentation of the proof.
+    ///
+    /// # Arguments
+    /// * `verifier` - Fingerprint of the verifier (e.g. its DID or public key hash).
+    /// * `timestamp` - Time of the presentation.
+    /// * `proof` - Presented proof.
+    /// * `credential_ids` - Holder ids of credentials used for sub proofs of the proof, in order of sub proofs.
+    pub fn record(&mut self,
+                  verifier: &str,
+                  timestamp: Timestamp,
+                  proof: &Proof,
+                  credential_ids: &[&str]) -> Result<(), IndyCryptoError> {
+        trace!("PresentationLog::record: >>> verifier: {:?}, timestamp: {:?}, credential_ids: {:?}", verifier, timestamp, credential_ids);
+
+        let sub_proofs_statements = proof.statements()?;
+
+        if sub_proofs_statements.len() != credential_ids.len() {
+            return Err(IndyCryptoError::InvalidParam4(
+                format!("Proof has {} sub proofs, {} credential ids given", sub_proofs_statements.len(), credential_ids.len())));
+        }
+
+        let mut statements: BTreeMap<String, BTreeSet<ProofStatement>> = BTreeMap::new();
+
+        for (credential_id, sub_proof_statements) in credential_ids.iter().zip(sub_proofs_statements) {
+            statements.entry(credential_id.to_string()).or_insert_with(BTreeSet::new).extend(sub_proof_statements);
+        }
+
+        self.entries.push(PresentationLogEntry {
+            verifier: verifier.to_string(),
+            timestamp,
+            statements,
+            self_attested_attrs: proof.self_attested_attrs().clone()
+        });
+
+        trace!("PresentationLog::record: <<<");
+
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[PresentationLogEntry] {
+        &self.entries
+    }
+
+    /// Returns fingerprints of all verifiers the holder presented proofs to.
+    pub fn verifiers(&self) -> BTreeSet<&str> {
+        self.entries.iter().map(|entry| entry.verifier.as_str()).collect()
+    }
+
+    /// Returns what the verifier cumulatively learned from all presentations.
+    ///
+    /// # Arguments
+    /// * `verifier` - Fingerprint of the verifier.
+    pub fn knowledge(&self, verifier: &str) -> VerifierKnowledge {
+        let mut knowledge = VerifierKnowledge::default();
+
+        for entry in self.entries.iter().filter(|entry| entry.verifier == verifier) {
+            knowledge.presentations += 1;
+
+            for (credential_id, statements) in entry.statements.iter() {
+                knowledge.statements.entry(credential_id.clone()).or_insert_with(BTreeSet::new)
+                    .extend(statements.iter().cloned());
+            }
+            for (attr, value) in entry.self_attested_attrs.iter() {
+                knowledge.self_attested_attrs.entry(attr.clone()).or_insert_with(BTreeSet::new)
+                    .insert(value.clone());
+            }
+        }
+
+        knowledge
+    }
+
+    /// Returns fingerprints of verifiers the attribute of the credential was revealed to:
+    /// colluding verifiers can link presentations by the revealed value.
+    ///
+    /// # Arguments
+    /// * `credential_id` - Holder id of the credential.
+    /// * `attr` - Attribute name.
+    pub fn verifiers_with_revealed(&self, credential_id: &str, attr: &str) -> BTreeSet<&str> {
+        self.entries.iter()
+            .filter(|entry| entry.statements.get(credential_id).map_or(false, |statements| statements.iter().any(|statement| match *statement {
+                ProofStatement::Revealed { attr: ref revealed_attr, .. } => revealed_attr == attr,
+                _ => false
+            })))
+            .map(|entry| entry.verifier.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cl::prover::mocks;
+
+    #[test]
+    fn presentation_log_works() {
+        let mut presentation_log = PresentationLog::new();
+        presentation_log.record("verifier1", Timestamp::from_secs(100).unwrap(), &mocks::proof(), &["gvt"]).unwrap();
+        presentation_log.record("verifier1", Timestamp::from_secs(200).unwrap(), &mocks::proof(), &["gvt"]).unwrap();
+        presentation_log.record("verifier2", Timestamp::from_secs(300).unwrap(), &mocks::proof(), &["gvt"]).unwrap();
+
+        assert!(presentation_log.record("verifier2", Timestamp::from_secs(400).unwrap(), &mocks::proof(), &[]).is_err());
+
+        assert_eq!(btreeset!["verifier1", "verifier2"], presentation_log.verifiers());
+        assert_eq!(btreeset!["verifier1", "verifier2"], presentation_log.verifiers_with_revealed("gvt", "name"));
+        assert!(presentation_log.verifiers_with_revealed("gvt", "age").is_empty());
+
+        let knowledge = presentation_log.knowledge("verifier1");
+        assert_eq!(2, knowledge.presentations());
+        assert_eq!(btreemap!["gvt" => btreeset!["name"]], knowledge.revealed_attrs());
+
+        let statements = &knowledge.statements()["gvt"];
+        assert!(statements.contains(&ProofStatement::Predicate(mocks::predicate())));
+        assert!(statements.contains(&ProofStatement::CredentialUnrevoked));
+
+        assert_eq!(VerifierKnowledge::default(), presentation_log.knowledge("verifier3"));
+    }
+}
@@ -1,11 +1,16 @@
-use bn::BigNumber;
+use bls::{Generator, SignKey, VerKey};
+use bn::{BigNumber, BigNumberArena, BigNumberContext};
 use cl::*;
 use cl::constants::*;
+use cl::snark::{AttributeCommitment, AttributeCommitmentOpening, SnarkProof, SnarkProver};
 use errors::IndyCryptoError;
 use pair::*;
 use super::helpers::*;
+use utils::aead;
+use utils::clock::Clock;
 use utils::commitment::get_pedersen_commitment;
-use utils::get_hash_as_int;
+use utils::entropy::{self, IndyRng};
+use utils::{get_hash_as_int, u64_to_be_bytes};
 
 use std::collections::{HashSet, BTreeMap, BTreeSet};
 
@@ -27,8 +32,57 @@ impl Prover {
         Ok(MasterSecret { ms: bn_rand(LARGE_MASTER_SECRET)? })
     }
 
+    /// Creates a master secret taking randomness from `rng` instead of the global entropy source.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::prover::Prover;
+    /// use indy_crypto::utils::entropy::SeededRng;
+    ///
+    /// let master_secret = Prover::new_master_secret_with_rng(&mut SeededRng::new(b"test vector").unwrap()).unwrap();
+    /// let same_master_secret = Prover::new_master_secret_with_rng(&mut SeededRng::new(b"test vector").unwrap()).unwrap();
+    /// assert_eq!(master_secret.value().unwrap(), same_master_secret.value().unwrap());
+    /// ```
+    pub fn new_master_secret_with_rng<R: IndyRng>(rng: &mut R) -> Result<MasterSecret, IndyCryptoError> {
+        entropy::with_rng(rng, || Prover::new_master_secret())
+    }
+
+    /// Creates a master secret and puts it to master secret store under the given id.
+    ///
+    /// # Arguments
+    /// * `master_secret_store` - Master secret store.
+    /// * `master_secret_id` - Id of the new master secret (e.g. persona name).
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::SimpleMasterSecretStore;
+    /// use indy_crypto::cl::prover::Prover;
+    ///
+    /// let master_secret_store = SimpleMasterSecretStore::new();
+    /// Prover::new_master_secret_with_id(&master_secret_store, "work").unwrap();
+    /// Prover::new_master_secret_with_id(&master_secret_store, "personal").unwrap();
+    /// ```
+    pub fn new_master_secret_with_id<MSS>(master_secret_store: &MSS, master_secret_id: &str) -> Result<(), IndyCryptoError>
+        where MSS: MasterSecretStore {
+        trace!("Prover::new_master_secret_with_id: >>> master_secret_id: {:?}", master_secret_id);
+
+        if master_secret_store.get(master_secret_id)?.is_some() {
+            return Err(IndyCryptoError::InvalidParam2(format!("Master secret '{}' already exists", master_secret_id)));
+        }
+
+        master_secret_store.put(master_secret_id, &Prover::new_master_secret()?)?;
+
+        trace!("Prover::new_master_secret_with_id: <<<");
+
+        Ok(())
+    }
+
     /// Creates blinded master secret for given issuer key and master secret.
     ///
+    /// All hidden values of `credential_values` (not only master secret) are blinded,
+    /// so the holder can commit to attribute values that issuer signs without learning them.
+    /// Issuer signs such credential with values that contain only known attributes.
+    ///
     /// # Arguments
     /// * `credential_pub_key` - Credential public keys.
     /// * `credential_key_correctness_proof` - Credential key correctness proof.
@@ -122,8 +176,176 @@ impl Prover {
         ))
     }
 
+    /// Creates blinded credential secrets taking randomness from `rng` instead of the global entropy source.
+    ///
+    /// Same as `blind_credential_secrets`: the same `rng` state produces the same blinded credential secrets,
+    /// blinding factors and correctness proof.
+    pub fn blind_credential_secrets_with_rng<R: IndyRng>(credential_pub_key: &CredentialPublicKey,
+                                                         credential_key_correctness_proof: &CredentialKeyCorrectnessProof,
+                                                         credential_values: &CredentialValues,
+                                                         credential_nonce: &Nonce,
+                                                         rng: &mut R) -> Result<(BlindedCredentialSecrets,
+                                                                                 CredentialSecretsBlindingFactors,
+                                                                                 BlindedCredentialSecretsCorrectnessProof), IndyCryptoError> {
+        entropy::with_rng(rng, || Prover::blind_credential_secrets(credential_pub_key,
+                                                                   credential_key_correctness_proof,
+                                                                   credential_values,
+                                                                   credential_nonce))
+    }
+
+    /// Creates blinded credential secrets bound to the nonces of credential issuance session.
+    ///
+    /// Same as `blind_credential_secrets` with `credential_nonce` derived from both offer and request nonces,
+    /// so blinded credential secrets can't be replayed in other issuance session.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public keys.
+    /// * `credential_key_correctness_proof` - Credential key correctness proof.
+    /// * `credential_values` - Credential values.
+    /// * `nonces` - Nonces of credential issuance session.
+    pub fn blind_credential_secrets_with_nonces(credential_pub_key: &CredentialPublicKey,
+                                                credential_key_correctness_proof: &CredentialKeyCorrectnessProof,
+                                                credential_values: &CredentialValues,
+                                                nonces: &CredentialIssuanceNonces) -> Result<(BlindedCredentialSecrets,
+                                                                                              CredentialSecretsBlindingFactors,
+                                                                                              BlindedCredentialSecretsCorrectnessProof), IndyCryptoError> {
+        trace!("Prover::blind_credential_secrets_with_nonces: >>> nonces: {:?}", nonces);
+
+        let res = Prover::blind_credential_secrets(credential_pub_key,
+                                                   credential_key_correctness_proof,
+                                                   credential_values,
+                                                   &nonces.blinding_nonce()?)?;
+
+        trace!("Prover::blind_credential_secrets_with_nonces: <<<");
+
+        Ok(res)
+    }
+
+    /// Creates blinded credential secrets taking master secret from master secret store.
+    ///
+    /// Same as `blind_credential_secrets`, but `credential_values` don't contain `master_secret` attribute:
+    /// it is added as hidden value of master secret with id `master_secret_id`.
+    /// Use `CredentialValues::with_master_secret` to get values for `process_credential_signature`.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public keys.
+    /// * `credential_key_correctness_proof` - Credential key correctness proof.
+    /// * `credential_values` - Credential values without master secret.
+    /// * `master_secret_store` - Master secret store.
+    /// * `master_secret_id` - Id of the master secret in the store.
+    /// * `credential_nonce` - Nonce used for creation of blinded_credential_secrets_correctness_proof.
+    pub fn blind_credential_secrets_with_master_secret_id<MSS>(credential_pub_key: &CredentialPublicKey,
+                                                               credential_key_correctness_proof: &CredentialKeyCorrectnessProof,
+                                                               credential_values: &CredentialValues,
+                                                               master_secret_store: &MSS,
+                                                               master_secret_id: &str,
+                                                               credential_nonce: &Nonce) -> Result<(BlindedCredentialSecrets,
+                                                                                                    CredentialSecretsBlindingFactors,
+                                                                                                    BlindedCredentialSecretsCorrectnessProof), IndyCryptoError>
+        where MSS: MasterSecretStore {
+        trace!("Prover::blind_credential_secrets_with_master_secret_id: >>> master_secret_id: {:?}", master_secret_id);
+
+        let credential_values = credential_values.with_master_secret(master_secret_store, master_secret_id)?;
+        let res = Prover::blind_credential_secrets(credential_pub_key,
+                                                   credential_key_correctness_proof,
+                                                   &credential_values,
+                                                   credential_nonce)?;
+
+        trace!("Prover::blind_credential_secrets_with_master_secret_id: <<<");
+
+        Ok(res)
+    }
+
+    /// Creates holder binding commitment to a stable personal attribute and its opening.
+    ///
+    /// Holder keeps the opening, adds the value as committed attribute (`add_value_commitment`)
+    /// to credential values for every issuer and proves that it is the value of the binding
+    /// commitment with `new_holder_binding_proof`.
+    ///
+    /// # Arguments
+    /// * `value` - Encoded attribute value.
+    pub fn new_holder_binding(value: &BigNumber) -> Result<(HolderBindingCommitment, HolderBindingOpening), IndyCryptoError> {
+        trace!("Prover::new_holder_binding: >>>");
+
+        let blinding_factor = GroupOrderElement::new()?;
+        let commitment = get_holder_binding_commitment(&bignum_to_group_element_mod_order(value)?, &blinding_factor)?;
+
+        let holder_binding_commitment = HolderBindingCommitment { commitment };
+        let holder_binding_opening = HolderBindingOpening { value: value.clone()?, blinding_factor };
+
+        trace!("Prover::new_holder_binding: <<< holder_binding_commitment: {:?}", holder_binding_commitment);
+
+        Ok((holder_binding_commitment, holder_binding_opening))
+    }
+
+    /// Creates proof that committed attribute of blinded credential secrets has the value of holder binding commitment.
+    ///
+    /// Issuer checks it with `Issuer::verify_holder_binding_proof` before signing, so independently
+    /// issued credentials are provably about the same subject while the value stays hidden.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key blinded credential secrets were created for.
+    /// * `credential_values` - Credential values blinded credential secrets were created from.
+    /// * `attr_name` - Name of committed attribute.
+    /// * `holder_binding_commitment` - Holder binding commitment.
+    /// * `holder_binding_opening` - Opening of holder binding commitment.
+    /// * `nonce` - Nonce sent by Issuer.
+    pub fn new_holder_binding_proof(credential_pub_key: &CredentialPublicKey,
+                                    credential_values: &CredentialValues,
+                                    attr_name: &str,
+                                    holder_binding_commitment: &HolderBindingCommitment,
+                                    holder_binding_opening: &HolderBindingOpening,
+                                    nonce: &Nonce) -> Result<HolderBindingProof, IndyCryptoError> {
+        trace!("Prover::new_holder_binding_proof: >>> credential_pub_key: {:?}, attr_name: {:?}, holder_binding_commitment: {:?}, nonce: {:?}",
+               credential_pub_key, attr_name, holder_binding_commitment, nonce);
+
+        let p_pub_key = &credential_pub_key.p_key;
+
+        let (value, blinding_factor) = match credential_values.attrs_values.get(attr_name) {
+            Some(&CredentialValue::Commitment { ref value, ref blinding_factor }) => (value, blinding_factor),
+            _ => return Err(IndyCryptoError::InvalidParam3(format!("Attribute '{}' isn't committed in credential values", attr_name)))
+        };
+
+        if *value != holder_binding_opening.value {
+            return Err(IndyCryptoError::InvalidParam5(format!("Value of attribute '{}' differs from holder binding value", attr_name)));
+        }
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let committed_attr = get_pedersen_commitment(&p_pub_key.z, value, &p_pub_key.s, blinding_factor, &p_pub_key.n, &mut ctx)?;
+
+        let m_tilde = bn_rand(LARGE_MTILDE)?;
+        let r_tilde = bn_rand(LARGE_MTILDE)?;
+        let rho_tilde = GroupOrderElement::new()?;
+
+        let committed_attr_tau = get_pedersen_commitment(&p_pub_key.z, &m_tilde, &p_pub_key.s, &r_tilde, &p_pub_key.n, &mut ctx)?;
+        let holder_binding_tau = get_holder_binding_commitment(&bignum_to_group_element_mod_order(&m_tilde)?, &rho_tilde)?;
+
+        let c = get_holder_binding_challenge(attr_name,
+                                             &committed_attr,
+                                             &holder_binding_commitment.commitment,
+                                             &committed_attr_tau,
+                                             &holder_binding_tau,
+                                             nonce)?;
+
+        let m_cap = m_tilde.add(&c.mul(value, Some(&mut ctx))?)?;
+        let r_cap = r_tilde.add(&c.mul(blinding_factor, Some(&mut ctx))?)?;
+        let rho_cap = rho_tilde.add_mod(&bignum_to_group_element_mod_order(&c)?.mul_mod(&holder_binding_opening.blinding_factor)?)?;
+
+        let holder_binding_proof = HolderBindingProof { c, m_cap, r_cap, rho_cap };
+
+        trace!("Prover::new_holder_binding_proof: <<< holder_binding_proof: {:?}", holder_binding_proof);
+
+        Ok(holder_binding_proof)
+    }
+
     /// Updates the credential signature by a master secret blinding data.
     ///
+    /// Primary signature `(A, e, v)` is checked against `signature_correctness_proof` and `nonce`
+    /// (`Z = A^e S^v R^m mod n` with prime `e` in the range of credential key configuration),
+    /// non-revocation signature (if any) is checked against revocation registry and witness,
+    /// so misbehaving Issuer is detected before the credential is stored.
+    ///
     /// # Arguments
     /// * `credential_signature` - Credential signature generated by Issuer.
     /// * `credential_values` - Credential values.
@@ -239,6 +461,88 @@ impl Prover {
         Ok(())
     }
 
+    /// Updates the credential signature bound to the nonces of credential issuance session.
+    ///
+    /// Same as `process_credential_signature` with `nonce` derived from both offer and request nonces,
+    /// so signature correctness proof created in other issuance session is rejected.
+    ///
+    /// # Arguments
+    /// * `credential_signature` - Credential signature generated by Issuer.
+    /// * `credential_values` - Credential values.
+    /// * `signature_correctness_proof` - Credential signature correctness proof.
+    /// * `credential_secrets_blinding_factors` - Credential secrets blinding factors.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `nonces` - Nonces of credential issuance session.
+    /// * `rev_key_pub` - (Optional) Revocation registry public key.
+    /// * `rev_reg` - (Optional) Revocation registry.
+    /// * `witness` - (Optional) Witness.
+    pub fn process_credential_signature_with_nonces(credential_signature: &mut CredentialSignature,
+                                                    credential_values: &CredentialValues,
+                                                    signature_correctness_proof: &SignatureCorrectnessProof,
+                                                    credential_secrets_blinding_factors: &CredentialSecretsBlindingFactors,
+                                                    credential_pub_key: &CredentialPublicKey,
+                                                    nonces: &CredentialIssuanceNonces,
+                                                    rev_key_pub: Option<&RevocationKeyPublic>,
+                                                    rev_reg: Option<&RevocationRegistry>,
+                                                    witness: Option<&Witness>) -> Result<(), IndyCryptoError> {
+        trace!("Prover::process_credential_signature_with_nonces: >>> nonces: {:?}", nonces);
+
+        Prover::process_credential_signature(credential_signature,
+                                             credential_values,
+                                             signature_correctness_proof,
+                                             credential_secrets_blinding_factors,
+                                             credential_pub_key,
+                                             &nonces.signature_nonce()?,
+                                             rev_key_pub,
+                                             rev_reg,
+                                             witness)?;
+
+        trace!("Prover::process_credential_signature_with_nonces: <<<");
+
+        Ok(())
+    }
+
+    /// Updates attribute signature created by `Issuer::sign_attribute_with_revoc` by credential secrets blinding factors
+    /// and checks it against revocation registry state.
+    /// Must be called after `process_credential_signature`.
+    ///
+    /// # Arguments
+    /// * `credential_signature` - Credential signature containing attribute signature.
+    /// * `attr` - Name of the attribute with own revocation index.
+    /// * `credential_secrets_blinding_factors` - Credential secrets blinding factors.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_key_pub` - Revocation registry public key the attribute is registered in.
+    /// * `rev_reg` - Revocation registry the attribute is registered in.
+    /// * `witness` - Witness of the attribute.
+    pub fn process_attribute_signature(credential_signature: &mut CredentialSignature,
+                                       attr: &str,
+                                       credential_secrets_blinding_factors: &CredentialSecretsBlindingFactors,
+                                       credential_pub_key: &CredentialPublicKey,
+                                       rev_key_pub: &RevocationKeyPublic,
+                                       rev_reg: &RevocationRegistry,
+                                       witness: &Witness) -> Result<(), IndyCryptoError> {
+        trace!("Prover::process_attribute_signature: >>> credential_signature: {:?}, attr: {:?}, credential_secrets_blinding_factors: {:?}, \
+                credential_pub_key: {:?}, rev_key_pub: {:?}, rev_reg: {:?}, witness: {:?}",
+               credential_signature, attr, credential_secrets_blinding_factors, credential_pub_key, rev_key_pub, rev_reg, witness);
+
+        let vr_prime = credential_secrets_blinding_factors.vr_prime
+            .ok_or(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential secrets blinding factors.")))?;
+
+        let r_key = credential_pub_key.r_key
+            .as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential revocation public key.")))?;
+
+        let r_cred = credential_signature.attr_r_credentials
+            .get_mut(attr)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Attribute '{}' is not signed with revocation keys", attr)))?;
+
+        Prover::_process_non_revocation_credential(r_cred, &vr_prime, r_key, rev_key_pub, rev_reg, witness)?;
+
+        trace!("Prover::process_attribute_signature: <<<");
+
+        Ok(())
+    }
+
     /// Creates and returns proof builder.
     ///
     /// The purpose of proof builder is building of proof entity according to the given request .
@@ -252,10 +556,228 @@ impl Prover {
             common_attributes: HashMap::new(),
             init_proofs: Vec::new(),
             c_list: Vec::new(),
-            tau_list: Vec::new()
+            tau_list: Vec::new(),
+            guardianship: None,
+            challenge_reduction: ChallengeReduction::default(),
+            snark_proofs: Vec::new(),
+            channel_binding: None,
+            mod_exp_threads: 1,
+            non_revoc_pairings: Vec::new(),
+            master_secret: None,
+            sub_proof_slices: Vec::new(),
+            finalized_challenge: None
         })
     }
 
+    /// Creates proof of master secret rotation.
+    ///
+    /// Proof builder must contain sub proof over a credential bound to the old master secret.
+    /// Proof is bound to the blinded credential secrets containing the new master secret,
+    /// so Issuer can verify that they were created by the holder of the credential and re-issue it.
+    ///
+    /// # Arguments
+    /// * `proof_builder` - Proof builder with sub proof over the credential bound to the old master secret.
+    /// * `blinded_credential_secrets` - Blinded credential secrets containing the new master secret.
+    /// * `nonce` - Nonce provided by Issuer.
+    pub fn new_master_secret_rotation_proof(proof_builder: ProofBuilder,
+                                            blinded_credential_secrets: &BlindedCredentialSecrets,
+                                            nonce: &Nonce) -> Result<MasterSecretRotationProof, IndyCryptoError> {
+        trace!("Prover::new_master_secret_rotation_proof: >>> blinded_credential_secrets: {:?}, nonce: {:?}", blinded_credential_secrets, nonce);
+
+        let rotation_nonce = blinded_credential_secrets.rotation_nonce(nonce)?;
+        let rotation_proof = MasterSecretRotationProof { proof: proof_builder.finalize(&rotation_nonce)? };
+
+        trace!("Prover::new_master_secret_rotation_proof: <<< rotation_proof: {:?}", rotation_proof);
+
+        Ok(rotation_proof)
+    }
+
+    /// Selects state of revocation registry satisfying verifier's non-revocation interval
+    /// and creates witness of the credential for this state.
+    ///
+    /// Selected state is the latest state published not later than the end of the interval
+    /// (the latest state at all if the interval is open-ended), i.e. the state that was current at the end of the interval.
+    ///
+    /// # Arguments
+    /// * `history` - Revocation registry history sorted by timestamp; the first entry must contain registry creation delta.
+    /// * `interval` - Non-revocation interval requested by verifier.
+    /// * `rev_idx` - Index of the credential in revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `issuance_by_default` - Type of issuance.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    pub fn select_registry_state<RTA>(history: &[RevocationRegistryHistoryEntry],
+                                      interval: &NonRevocationInterval,
+                                      rev_idx: u32,
+                                      max_cred_num: u32,
+                                      issuance_by_default: bool,
+                                      rev_tails_accessor: &RTA) -> Result<RevocationRegistryState, IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Prover::select_registry_state: >>> history: {:?}, interval: {:?}, rev_idx: {:?}, max_cred_num: {:?}, issuance_by_default: {:?}",
+               history, interval, secret!(rev_idx), max_cred_num, issuance_by_default);
+
+        if history.windows(2).any(|entries| entries[0].timestamp >= entries[1].timestamp) {
+            return Err(IndyCryptoError::InvalidParam1("Revocation registry history is not sorted by timestamp".to_string()));
+        }
+
+        let selected = history.iter()
+            .take_while(|entry| interval.to.map(|to| entry.timestamp <= to).unwrap_or(true))
+            .count();
+
+        if selected == 0 {
+            return Err(IndyCryptoError::InvalidState("No revocation registry state published before the end of the interval".to_string()));
+        }
+
+        let mut rev_reg_delta = history[0].rev_reg_delta.clone();
+        for entry in history[1..selected].iter() {
+            rev_reg_delta.merge(&entry.rev_reg_delta)?;
+        }
+
+        let revoked = if issuance_by_default {
+            rev_reg_delta.revoked.contains(&rev_idx)
+        } else {
+            !rev_reg_delta.issued.contains(&rev_idx)
+        };
+
+        if revoked {
+            return Err(IndyCryptoError::AnoncredsCredentialRevoked("Credential is revoked in the selected revocation registry state".to_string()));
+        }
+
+        let witness = Witness::new(rev_idx, max_cred_num, issuance_by_default, &rev_reg_delta, rev_tails_accessor)?;
+
+        let rev_reg_state = RevocationRegistryState {
+            timestamp: history[selected - 1].timestamp,
+            rev_reg: RevocationRegistry::from(rev_reg_delta),
+            witness
+        };
+
+        trace!("Prover::select_registry_state: <<< rev_reg_state: {:?}", rev_reg_state);
+
+        Ok(rev_reg_state)
+    }
+
+    /// Creates delegation bundle that allows guardian to build proofs over the credential on holder's behalf.
+    ///
+    /// Building proofs requires all hidden values of the credential, so guardian learns them and `scope` restricts
+    /// only proofs it builds. Delegated credential must therefore be issued over a guardian-specific master secret
+    /// (new one created with `Prover::new_master_secret` for this delegation only), never over holder's link secret:
+    /// credential values containing `link_secret` as hidden value are rejected.
+    ///
+    /// # Arguments
+    /// * `cred_signature` - Processed credential signature.
+    /// * `cred_values` - Credential values.
+    /// * `witness` - Witness of the credential in revocation registry (if credential is revocable).
+    /// * `scope` - Attributes guardian is allowed to reveal or use in predicates.
+    /// * `expires_at` - Time (seconds since the epoch) the delegation expires at.
+    /// * `link_secret` - Holder's link secret that must not be delegated.
+    pub fn new_delegation_bundle(cred_signature: &CredentialSignature,
+                                 cred_values: &CredentialValues,
+                                 witness: Option<&Witness>,
+                                 scope: &BTreeSet<String>,
+                                 expires_at: u64,
+                                 link_secret: &MasterSecret) -> Result<DelegationBundle, IndyCryptoError> {
+        trace!("Prover::new_delegation_bundle: >>> cred_signature: {:?}, cred_values: {:?}, witness: {:?}, scope: {:?}, expires_at: {:?}",
+               secret!(cred_signature), secret!(cred_values), secret!(witness), scope, expires_at);
+
+        for attr in scope.iter() {
+            if !cred_values.attrs_values.contains_key(attr) {
+                return Err(IndyCryptoError::InvalidParam4(format!("Attribute '{}' from delegation scope is not found in credential values", attr)));
+            }
+        }
+
+        let bound_to_link_secret = cred_values.attrs_values.values().any(|value| match *value {
+            CredentialValue::Hidden { ref value } => *value == link_secret.ms,
+            _ => false
+        });
+
+        if bound_to_link_secret {
+            return Err(IndyCryptoError::InvalidParam2(
+                "Credential is bound to holder's link secret, delegate credential issued over guardian-specific master secret".to_string()));
+        }
+
+        let delegation_bundle = DelegationBundle {
+            cred_signature: cred_signature.clone()?,
+            cred_values: cred_values.clone()?,
+            witness: witness.cloned(),
+            scope: scope.clone(),
+            expires_at
+        };
+
+        trace!("Prover::new_delegation_bundle: <<< delegation_bundle: {:?}", secret!(&delegation_bundle));
+
+        Ok(delegation_bundle)
+    }
+
+    /// Seals delegation bundle for the guardian, so only owner of guardian's BLS sign key can open it.
+    ///
+    /// # Arguments
+    /// * `delegation_bundle` - Delegation bundle.
+    /// * `guardian_ver_key` - Guardian's BLS verification key.
+    /// * `gen` - BLS generator guardian's verification key was created with.
+    pub fn seal_delegation_bundle(delegation_bundle: &DelegationBundle,
+                                  guardian_ver_key: &VerKey,
+                                  gen: &Generator) -> Result<SealedDelegationBundle, IndyCryptoError> {
+        trace!("Prover::seal_delegation_bundle: >>> delegation_bundle: {:?}, guardian_ver_key: {:?}, gen: {:?}",
+               secret!(delegation_bundle), guardian_ver_key, gen);
+
+        let ephemeral_secret = GroupOrderElement::new()?;
+        let ephemeral_key = PointG2::from_bytes(gen.as_bytes())?.mul(&ephemeral_secret)?;
+        let shared_secret = PointG2::from_bytes(guardian_ver_key.as_bytes())?.mul(&ephemeral_secret)?;
+
+        let iv = aead::gen_iv()?;
+        let (ciphertext, tag) = aead::encrypt(&Prover::_delegation_key(&shared_secret)?,
+                                              &iv,
+                                              &::serde_json::to_vec(delegation_bundle)?)?;
+
+        let sealed_delegation_bundle = SealedDelegationBundle {
+            ephemeral_key,
+            iv,
+            ciphertext,
+            tag,
+            expires_at: delegation_bundle.expires_at
+        };
+
+        trace!("Prover::seal_delegation_bundle: <<< sealed_delegation_bundle: {:?}", sealed_delegation_bundle);
+
+        Ok(sealed_delegation_bundle)
+    }
+
+    /// Opens delegation bundle sealed for the guardian and checks it is not expired.
+    ///
+    /// # Arguments
+    /// * `sealed_delegation_bundle` - Sealed delegation bundle.
+    /// * `guardian_sign_key` - Guardian's BLS sign key.
+    /// * `clock` - Source of the current time.
+    pub fn open_delegation_bundle<C>(sealed_delegation_bundle: &SealedDelegationBundle,
+                                  guardian_sign_key: &SignKey,
+                                  clock: &C) -> Result<DelegationBundle, IndyCryptoError> where C: Clock {
+        trace!("Prover::open_delegation_bundle: >>> sealed_delegation_bundle: {:?}", sealed_delegation_bundle);
+
+        if clock.now()? >= sealed_delegation_bundle.expires_at {
+            return Err(IndyCryptoError::InvalidState("Delegation bundle expired".to_string()));
+        }
+
+        let shared_secret = sealed_delegation_bundle.ephemeral_key
+            .mul(&GroupOrderElement::from_bytes(guardian_sign_key.as_bytes())?)?;
+
+        let bytes = aead::decrypt(&Prover::_delegation_key(&shared_secret)?,
+                                  &sealed_delegation_bundle.iv,
+                                  &sealed_delegation_bundle.ciphertext,
+                                  &sealed_delegation_bundle.tag)?;
+
+        let delegation_bundle: DelegationBundle = ::serde_json::from_slice(&bytes)?;
+
+        if delegation_bundle.expires_at != sealed_delegation_bundle.expires_at {
+            return Err(IndyCryptoError::InvalidStructure("Delegation bundle expiration mismatch".to_string()));
+        }
+
+        trace!("Prover::open_delegation_bundle: <<< delegation_bundle: {:?}", secret!(&delegation_bundle));
+
+        Ok(delegation_bundle)
+    }
+
+    fn _delegation_key(shared_secret: &PointG2) -> Result<Vec<u8>, IndyCryptoError> {
+        BigNumber::hash(&shared_secret.to_bytes()?)
+    }
+
     #[cfg(test)]
     pub fn check_credential_key_correctness_proof(pr_pub_key: &CredentialPrimaryPublicKey,
                                                   key_correctness_proof: &CredentialKeyCorrectnessProof) -> Result<(), IndyCryptoError> {
@@ -342,7 +864,7 @@ impl Prover {
         );
 
         let mut ctx = BigNumber::new_context()?;
-        let v_prime = bn_rand(LARGE_VPRIME)?;
+        let v_prime = bn_rand(p_pub_key.config.large_vprime)?;
 
         //Hidden attributes are combined in this value
         let hidden_attributes = credential_values
@@ -600,6 +1122,10 @@ impl Prover {
             return Err(IndyCryptoError::InvalidStructure(format!("Invalid Signature correctness proof")));
         }
 
+        if p_cred_sig.e < p_pub_key.config.e_start_value()? || p_cred_sig.e > p_pub_key.config.e_end_range_value()? {
+            return Err(IndyCryptoError::InvalidStructure(format!("Invalid Signature correctness proof: e is out of range")));
+        }
+
         if let Some((ref attr, _)) = cred_values.attrs_values
             .iter()
             .find(|&(ref attr, ref value)|
@@ -668,12 +1194,12 @@ impl Prover {
         Ok(())
     }
 
-    fn _test_witness_signature(r_cred: &NonRevocationCredentialSignature,
-                               cred_rev_pub_key: &CredentialRevocationPublicKey,
-                               rev_key_pub: &RevocationKeyPublic,
-                               rev_reg: &RevocationRegistry,
-                               witness: &Witness,
-                               r_cnxt_m2: &BigNumber) -> Result<(), IndyCryptoError> {
+    pub(crate) fn _test_witness_signature(r_cred: &NonRevocationCredentialSignature,
+                                          cred_rev_pub_key: &CredentialRevocationPublicKey,
+                                          rev_key_pub: &RevocationKeyPublic,
+                                          rev_reg: &RevocationRegistry,
+                                          witness: &Witness,
+                                          r_cnxt_m2: &BigNumber) -> Result<(), IndyCryptoError> {
         trace!("Prover::_test_witness_signature: >>> r_cred: {:?}, cred_rev_pub_key: {:?}, rev_key_pub: {:?}, rev_reg: {:?}, r_cnxt_m2: {:?}",
                r_cred, cred_rev_pub_key, rev_key_pub, rev_reg, r_cnxt_m2);
 
@@ -717,14 +1243,206 @@ pub struct ProofBuilder {
     init_proofs: Vec<InitProof>,
     c_list: Vec<Vec<u8>>,
     tau_list: Vec<Vec<u8>>,
+    guardianship: Option<GuardianshipMarker>,
+    challenge_reduction: ChallengeReduction,
+    snark_proofs: Vec<SnarkProof>,
+    channel_binding: Option<Vec<u8>>,
+    mod_exp_threads: usize,
+    non_revoc_pairings: Vec<NonRevocProofPairings>,
+    master_secret: Option<MasterSecret>,
+    sub_proof_slices: Vec<SubProofSlice>,
+    finalized_challenge: Option<BigNumber>,
+}
+
+/// Credential key and lengths of c_list and tau_list parts of a sub proof added to `ProofBuilder`.
+#[derive(Debug)]
+struct SubProofSlice {
+    key_id: String,
+    c_list_len: usize,
+    tau_list_len: usize,
+    is_extension: bool
+}
+
+/// Random values of predicate proof gathered before batched exponentiation.
+struct GEInitProofParams<'a> {
+    predicate: Predicate,
+    delta: BigNumber,
+    u: HashMap<String, BigNumber>,
+    r: HashMap<String, BigNumber>,
+    u_tilde: HashMap<String, BigNumber>,
+    r_tilde: HashMap<String, BigNumber>,
+    alpha_tilde: BigNumber,
+    mj: &'a BigNumber
+}
+
+fn _get_ge_value<'a>(name: &str, values: &'a HashMap<String, BigNumber>, key: &str) -> Result<&'a BigNumber, IndyCryptoError> {
+    values.get(key)
+        .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in {}", key, name)))
 }
 
 impl ProofBuilder {
-    /// Creates m_tildes for attributes that will be the same across all subproofs
+    /// Marks the proof as built by a guardian on behalf of credential holder.
+    /// Sub proof requests must only reveal attributes and use predicates from the delegation scope.
+    ///
+    /// # Arguments
+    /// * `delegation_bundle` - Delegation bundle opened by guardian.
+    pub fn add_guardianship(&mut self, delegation_bundle: &DelegationBundle) -> Result<(), IndyCryptoError> {
+        self.guardianship = Some(delegation_bundle.guardianship_marker());
+        Ok(())
+    }
+
+    /// Sets policy of reducing challenge hash (`ChallengeReduction::V1Full` by default).
+    pub fn set_challenge_reduction(&mut self, challenge_reduction: ChallengeReduction) -> Result<(), IndyCryptoError> {
+        self.challenge_reduction = challenge_reduction;
+        Ok(())
+    }
+
+    /// Binds the proof to the transport session by mixing verifier supplied channel binding value
+    /// (e.g. TLS exporter or DIDComm session id) into the challenge hash.
+    ///
+    /// Proof is valid only for verifier that requires the same value with `ProofVerifier::set_channel_binding`,
+    /// so it can't be relayed to other sessions even if the nonce is leaked.
+    ///
+    /// # Arguments
+    /// * `channel_binding` - Channel binding value.
+    pub fn set_channel_binding(&mut self, channel_binding: &[u8]) -> Result<(), IndyCryptoError> {
+        if channel_binding.is_empty() {
+            return Err(IndyCryptoError::InvalidParam1("Channel binding is empty".to_string()));
+        }
+        self.channel_binding = Some(channel_binding.to_vec());
+        Ok(())
+    }
+
+    /// Sets number of threads used for modular exponentiations of predicate proofs (1 by default).
+    ///
+    /// Exponentiations of all predicates of a sub proof are executed in one batch,
+    /// so multiple threads reduce latency of sub proof requests with several predicates.
+    pub fn set_mod_exp_threads(&mut self, threads: usize) -> Result<(), IndyCryptoError> {
+        if threads == 0 {
+            return Err(IndyCryptoError::InvalidParam1("Number of threads must be positive".to_string()));
+        }
+        self.mod_exp_threads = threads;
+        Ok(())
+    }
+
+    /// Selects master secret of the proof by its id in master secret store.
+    ///
+    /// Master secret is added as common attribute and as hidden `master_secret` value of all sub proofs,
+    /// so credential values passed with sub proof requests don't have to contain it.
+    /// Must be called before sub proof requests are added.
+    ///
+    /// # Arguments
+    /// * `master_secret_store` - Master secret store.
+    /// * `master_secret_id` - Id of the master secret in the store.
+    pub fn set_master_secret_id<MSS>(&mut self, master_secret_store: &MSS, master_secret_id: &str) -> Result<(), IndyCryptoError>
+        where MSS: MasterSecretStore {
+        trace!("ProofBuilder::set_master_secret_id: >>> master_secret_id: {:?}", master_secret_id);
+
+        let master_secret = master_secret_store.get(master_secret_id)?
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Master secret '{}' not found in master secret store", master_secret_id)))?;
+
+        self.add_common_attribute("master_secret")?;
+        self.master_secret = Some(master_secret);
+
+        trace!("ProofBuilder::set_master_secret_id: <<<");
+
+        Ok(())
+    }
+
+    /// Enables short non-revocation proof mode for credentials of the revocation public key of `pairings`.
+    ///
+    /// Pairings that depend on revocation public key and accumulator only are taken from `pairings`
+    /// instead of being computed for every non-revocation proof. If registry of a sub proof has another
+    /// accumulator only the accumulator pairing is recomputed. Must be called before sub proof requests are added.
+    ///
+    /// # Arguments
+    /// * `pairings` - Pairings precomputed with `NonRevocProofPairings::new`.
+    pub fn add_non_revoc_proof_pairings(&mut self, pairings: &NonRevocProofPairings) -> Result<(), IndyCryptoError> {
+        self.non_revoc_pairings.retain(|cached| !cached.is_for_key(&pairings.r_pub_key));
+        self.non_revoc_pairings.push(pairings.clone());
+        Ok(())
+    }
+
+    /// Returns openings of commitments to attributes requested as committed in all added sub proofs (experimental).
+    pub fn attribute_commitment_openings(&self) -> Result<Vec<AttributeCommitmentOpening>, IndyCryptoError> {
+        let mut openings = Vec::new();
+        for (idx, init_proof) in self.init_proofs.iter().enumerate() {
+            for (attr, committed_attr_init_proof) in init_proof.committed_attr_init_proofs.iter() {
+                let value = init_proof.credential_values.attrs_values.get(attr)
+                    .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in credential values", attr)))?
+                    .value()
+                    .clone()?;
+
+                openings.push(AttributeCommitmentOpening::new(
+                    AttributeCommitment::new(idx, attr, committed_attr_init_proof.commitment.clone()?),
+                    value,
+                    committed_attr_init_proof.r.clone()?));
+            }
+        }
+        Ok(openings)
+    }
+
+    /// Adds proof of external circuit over committed attributes (experimental).
+    /// Must be called after all sub proof requests are added. Proof becomes part of the challenge.
+    ///
+    /// # Arguments
+    /// * `circuit_id` - Identifier of the circuit.
+    /// * `snark_prover` - Prover of the circuit.
+    pub fn add_snark_proof<P>(&mut self, circuit_id: &str, snark_prover: &P) -> Result<(), IndyCryptoError> where P: SnarkProver {
+        trace!("ProofBuilder::add_snark_proof: >>> circuit_id: {:?}", circuit_id);
+
+        let openings = self.attribute_commitment_openings()?;
+        if openings.is_empty() {
+            return Err(IndyCryptoError::InvalidState("No committed attributes to prove circuit over".to_string()));
+        }
+
+        let proof = snark_prover.prove(circuit_id, &openings)?;
+        self.snark_proofs.push(SnarkProof::new(circuit_id, proof));
+
+        trace!("ProofBuilder::add_snark_proof: <<<");
+
+        Ok(())
+    }
+
+    /// Marks attribute as common for all sub proofs: the same m_tilde is used for it in every sub proof,
+    /// so Verifier can check that the hidden value of the attribute (e.g. master secret or "did")
+    /// is identical in all presented credentials (see `ProofVerifier::add_common_attribute`).
+    ///
+    /// Common attributes must be added before sub proof requests and can't be revealed.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of the attribute.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::prover::Prover;
+    ///
+    /// let mut proof_builder = Prover::new_proof_builder().unwrap();
+    /// proof_builder.add_common_attribute("master_secret").unwrap();
+    /// proof_builder.add_common_attribute("did").unwrap();
+    /// ```
     pub fn add_common_attribute(&mut self, attr_name: &str) -> Result<(), IndyCryptoError> {
-        self.common_attributes.insert(attr_name.to_owned(), bn_rand(LARGE_MVECT)?);
+        trace!("ProofBuilder::add_common_attribute: >>> attr_name: {:?}", attr_name);
+
+        if !self.init_proofs.is_empty() {
+            return Err(IndyCryptoError::InvalidState(
+                format!("Common attribute '{}' must be added before sub proof requests", attr_name)));
+        }
+
+        if !self.common_attributes.contains_key(attr_name) {
+            self.common_attributes.insert(attr_name.to_owned(), bn_rand(LARGE_MVECT)?);
+        }
+
+        trace!("ProofBuilder::add_common_attribute: <<<");
+
         Ok(())
     }
+
+    /// Adds common attribute taking its blinding from `rng` instead of the global entropy source.
+    pub fn add_common_attribute_with_rng<R: IndyRng>(&mut self, attr_name: &str, rng: &mut R) -> Result<(), IndyCryptoError> {
+        entropy::with_rng(rng, || self.add_common_attribute(attr_name))
+    }
+
     /// Adds sub proof request to proof builder which will be used fo building of proof.
     /// Part of proof request related to a particular schema-key.
     /// The order of sub-proofs is important: both Prover and Verifier should use the same order.
@@ -736,7 +1454,12 @@ impl ProofBuilder {
     /// * `credential_signature` - Credential signature.
     /// * `credential_values` - Credential values.
     /// * `credential_pub_key` - Credential public key.
-    /// * `rev_reg_pub` - (Optional) Revocation registry public.
+    /// * `rev_reg` - (Optional) Revocation registry.
+    /// * `witness` - (Optional) Witness of the credential for `rev_reg`.
+    ///
+    /// Credential signature and witness are only read: proof builder never updates witness,
+    /// so the caller keeps it up to date with `Witness::update` once per revocation registry delta
+    /// and reuses it for all proofs.
     ///
     /// #Example
     /// ```
@@ -810,6 +1533,436 @@ impl ProofBuilder {
                                  credential_pub_key: &CredentialPublicKey,
                                  rev_reg: Option<&RevocationRegistry>,
                                  witness: Option<&Witness>) -> Result<(), IndyCryptoError> {
+        self.add_sub_proof_request_with_attr_revoc(sub_proof_request,
+                                                   credential_schema,
+                                                   non_credential_schema,
+                                                   credential_signature,
+                                                   credential_values,
+                                                   credential_pub_key,
+                                                   rev_reg,
+                                                   witness,
+                                                   &HashMap::new())
+    }
+
+    /// Adds sub proof request to proof builder taking randomness of the sub proof from `rng`
+    /// instead of the global entropy source.
+    ///
+    /// Proof built from the same inputs with common attributes and sub proofs added with the same `rng` state
+    /// is the same, so reproducible transcripts can be published as test vectors.
+    pub fn add_sub_proof_request_with_rng<R: IndyRng>(&mut self,
+                                                      sub_proof_request: &SubProofRequest,
+                                                      credential_schema: &CredentialSchema,
+                                                      non_credential_schema: &NonCredentialSchema,
+                                                      credential_signature: &CredentialSignature,
+                                                      credential_values: &CredentialValues,
+                                                      credential_pub_key: &CredentialPublicKey,
+                                                      rev_reg: Option<&RevocationRegistry>,
+                                                      witness: Option<&Witness>,
+                                                      rng: &mut R) -> Result<(), IndyCryptoError> {
+        entropy::with_rng(rng, || self.add_sub_proof_request(sub_proof_request,
+                                                             credential_schema,
+                                                             non_credential_schema,
+                                                             credential_signature,
+                                                             credential_values,
+                                                             credential_pub_key,
+                                                             rev_reg,
+                                                             witness))
+    }
+
+    /// Adds sub proof request to proof builder proving non-revocation against revocation registry state
+    /// selected with `Prover::select_registry_state`.
+    ///
+    /// Timestamp of the state must be within non-revocation interval of sub proof request (if any);
+    /// it is reported to verifier in the sub proof and hashed into the challenge.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes, predicates and non-revocation interval.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_signature` - Credential signature.
+    /// * `credential_values` - Credential values.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_reg_state` - Revocation registry state and witness of the credential.
+    pub fn add_sub_proof_request_with_rev_reg_state(&mut self,
+                                                    sub_proof_request: &SubProofRequest,
+                                                    credential_schema: &CredentialSchema,
+                                                    non_credential_schema: &NonCredentialSchema,
+                                                    credential_signature: &CredentialSignature,
+                                                    credential_values: &CredentialValues,
+                                                    credential_pub_key: &CredentialPublicKey,
+                                                    rev_reg_state: &RevocationRegistryState) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::add_sub_proof_request_with_rev_reg_state: >>> sub_proof_request: {:?}, rev_reg_state timestamp: {:?}",
+               sub_proof_request, rev_reg_state.timestamp);
+
+        if credential_signature.r_credential.is_none() || credential_pub_key.r_key.is_none() {
+            return Err(IndyCryptoError::InvalidStructure("Credential is not revocable".to_string()));
+        }
+
+        if let Some(ref interval) = sub_proof_request.non_revoc_interval {
+            if !interval.contains(rev_reg_state.timestamp) {
+                return Err(IndyCryptoError::InvalidParam7(
+                    format!("Revocation registry state published at {} is out of the requested non-revocation interval", rev_reg_state.timestamp)));
+            }
+        }
+
+        self.add_sub_proof_request(sub_proof_request,
+                                   credential_schema,
+                                   non_credential_schema,
+                                   credential_signature,
+                                   credential_values,
+                                   credential_pub_key,
+                                   Some(&rev_reg_state.rev_reg),
+                                   Some(&rev_reg_state.witness))?;
+
+        if let Some(init_proof) = self.init_proofs.last_mut() {
+            init_proof.non_revoc_timestamp = Some(rev_reg_state.timestamp);
+        }
+
+        trace!("ProofBuilder::add_sub_proof_request_with_rev_reg_state: <<<");
+
+        Ok(())
+    }
+
+    /// Adds sub proof request to proof builder proving non-revocation with pre-fetched revocation data only.
+    ///
+    /// Neither tails nor revocation registry are needed: prover supplies accumulator value and witness
+    /// already updated for it (e.g. with `Witness::update` on an online device), so proofs can be created
+    /// on air-gapped or constrained devices after a one-time sync.
+    /// Witness isn't checked against the accumulator, stale witness makes verifier reject the proof.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_signature` - Credential signature.
+    /// * `credential_values` - Credential values.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `accum` - Accumulator of revocation registry the proof is created against.
+    /// * `witness` - Witness of the credential for `accum`.
+    pub fn add_sub_proof_request_with_accumulator(&mut self,
+                                                  sub_proof_request: &SubProofRequest,
+                                                  credential_schema: &CredentialSchema,
+                                                  non_credential_schema: &NonCredentialSchema,
+                                                  credential_signature: &CredentialSignature,
+                                                  credential_values: &CredentialValues,
+                                                  credential_pub_key: &CredentialPublicKey,
+                                                  accum: &Accumulator,
+                                                  witness: &Witness) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::add_sub_proof_request_with_accumulator: >>> sub_proof_request: {:?}, accum: {:?}", sub_proof_request, accum);
+
+        if credential_signature.r_credential.is_none() || credential_pub_key.r_key.is_none() {
+            return Err(IndyCryptoError::InvalidStructure("Credential is not revocable".to_string()));
+        }
+
+        let rev_reg = RevocationRegistry { accum: *accum };
+
+        self.add_sub_proof_request(sub_proof_request,
+                                   credential_schema,
+                                   non_credential_schema,
+                                   credential_signature,
+                                   credential_values,
+                                   credential_pub_key,
+                                   Some(&rev_reg),
+                                   Some(witness))?;
+
+        trace!("ProofBuilder::add_sub_proof_request_with_accumulator: <<<");
+
+        Ok(())
+    }
+
+    /// Adds sub proof request to proof builder and proves that attributes
+    /// with own revocation indexes (`unrevoked_attrs` of sub proof request) are not revoked.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` -Requested attributes and predicates.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_signature` - Credential signature.
+    /// * `credential_values` - Credential values.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_reg` - (Optional) Revocation registry of the credential.
+    /// * `witness` - (Optional) Witness of the credential.
+    /// * `attr_rev_states` - Revocation registry and witness for every attribute requested as unrevoked.
+    pub fn add_sub_proof_request_with_attr_revoc(&mut self,
+                                                 sub_proof_request: &SubProofRequest,
+                                                 credential_schema: &CredentialSchema,
+                                                 non_credential_schema: &NonCredentialSchema,
+                                                 credential_signature: &CredentialSignature,
+                                                 credential_values: &CredentialValues,
+                                                 credential_pub_key: &CredentialPublicKey,
+                                                 rev_reg: Option<&RevocationRegistry>,
+                                                 witness: Option<&Witness>,
+                                                 attr_rev_states: &HashMap<String, (&RevocationRegistry, &Witness)>) -> Result<(), IndyCryptoError> {
+        self._add_sub_proof_request(sub_proof_request,
+                                    credential_schema,
+                                    non_credential_schema,
+                                    credential_signature,
+                                    credential_values,
+                                    credential_pub_key,
+                                    rev_reg,
+                                    witness,
+                                    attr_rev_states,
+                                    None)
+    }
+
+    /// Adds sub proof request to proof builder taking witness for the current accumulator from witness store.
+    ///
+    /// If the store doesn't contain witness for the accumulator reached by `rev_reg_deltas`,
+    /// a copy of `witness` is updated with the deltas and put to the store, so repeated proofs
+    /// against the same accumulator don't recompute it. Passed `witness` is never modified.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_signature` - Credential signature.
+    /// * `credential_values` - Credential values.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_reg_id` - Id of revocation registry used as a key in witness store.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_reg` - Revocation registry state `witness` corresponds to.
+    /// * `witness` - Witness of the credential.
+    /// * `rev_reg_deltas` - Revocation registry deltas published after `rev_reg` in chronological order.
+    /// * `witness_store` - Witness store.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
+    pub fn add_sub_proof_request_with_witness_store<'a, I, WS, RTA>(&mut self,
+                                                                    sub_proof_request: &SubProofRequest,
+                                                                    credential_schema: &CredentialSchema,
+                                                                    non_credential_schema: &NonCredentialSchema,
+                                                                    credential_signature: &CredentialSignature,
+                                                                    credential_values: &CredentialValues,
+                                                                    credential_pub_key: &CredentialPublicKey,
+                                                                    rev_reg_id: &str,
+                                                                    max_cred_num: u32,
+                                                                    rev_reg: &RevocationRegistry,
+                                                                    witness: &Witness,
+                                                                    rev_reg_deltas: I,
+                                                                    witness_store: &WS,
+                                                                    rev_tails_accessor: &RTA) -> Result<(), IndyCryptoError>
+        where I: IntoIterator<Item=&'a RevocationRegistryDelta>, WS: WitnessStore, RTA: RevocationTailsAccessor {
+        trace!("ProofBuilder::add_sub_proof_request_with_witness_store: >>> rev_reg_id: {:?}, max_cred_num: {:?}, rev_reg: {:?}",
+               rev_reg_id, max_cred_num, rev_reg);
+
+        let rev_idx = credential_signature.extract_index()
+            .ok_or(IndyCryptoError::InvalidStructure("Credential signature doesn't contain non revocation part".to_string()))?;
+
+        let rev_reg_deltas: Vec<&RevocationRegistryDelta> = rev_reg_deltas.into_iter().collect();
+        let accum = rev_reg_deltas.last().map(|rev_reg_delta| rev_reg_delta.accum).unwrap_or(rev_reg.accum);
+
+        let (rev_reg, witness) = match witness_store.get(rev_reg_id, rev_idx, &accum)? {
+            Some(witness) => (RevocationRegistry { accum }, witness),
+            None => {
+                let mut witness = witness.clone();
+                let rev_reg = witness.apply_delta_stream(rev_idx, max_cred_num, rev_reg, rev_reg_deltas, rev_tails_accessor)?;
+                witness_store.put(rev_reg_id, rev_idx, &accum, &witness)?;
+                (rev_reg, witness)
+            }
+        };
+
+        self.add_sub_proof_request(sub_proof_request,
+                                   credential_schema,
+                                   non_credential_schema,
+                                   credential_signature,
+                                   credential_values,
+                                   credential_pub_key,
+                                   Some(&rev_reg),
+                                   Some(&witness))?;
+
+        trace!("ProofBuilder::add_sub_proof_request_with_witness_store: <<<");
+
+        Ok(())
+    }
+
+    /// Adds sub proof request for extension credential of the credential of the previous sub proof request.
+    ///
+    /// Extension credential is issued with `Issuer::sign_extension_credential` and carries attributes added
+    /// to the schema after the base credential was issued. Sub proofs of base and extension credentials share
+    /// blinding of credential context `m2`, so Verifier checks that both credentials were issued
+    /// for the same credential context and treats them as one credential.
+    ///
+    /// Revocation of extension credential is covered by non-revocation proof of the base credential.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates of extension credential.
+    /// * `credential_schema` - Credential schema of extension credential.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_signature` - Extension credential signature.
+    /// * `credential_values` - Extension credential values.
+    /// * `credential_pub_key` - Credential public key of extension credential.
+    pub fn add_extension_sub_proof_request(&mut self,
+                                           sub_proof_request: &SubProofRequest,
+                                           credential_schema: &CredentialSchema,
+                                           non_credential_schema: &NonCredentialSchema,
+                                           credential_signature: &CredentialSignature,
+                                           credential_values: &CredentialValues,
+                                           credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        let m2_tilde = {
+            let base_eq_proof = &self.init_proofs.last()
+                .ok_or(IndyCryptoError::InvalidState("Sub proof request of base credential must be added before extension".to_string()))?
+                .primary_init_proof.eq_proof;
+
+            if base_eq_proof.m2 != credential_signature.p_credential.m_2 {
+                return Err(IndyCryptoError::InvalidStructure("Extension credential is not linked to credential of the previous sub proof request".to_string()));
+            }
+
+            base_eq_proof.m2_tilde.clone()?
+        };
+
+        self._add_sub_proof_request(sub_proof_request,
+                                    credential_schema,
+                                    non_credential_schema,
+                                    credential_signature,
+                                    credential_values,
+                                    credential_pub_key,
+                                    None,
+                                    None,
+                                    &HashMap::new(),
+                                    Some(m2_tilde))
+    }
+
+    /// Removes all sub proofs over credentials of the credential public key with `key_id`
+    /// (see `CredentialPublicKey::key_id`) together with their extension sub proofs.
+    ///
+    /// Values of removed sub proofs are removed from c_list and tau_list, so the rest sub proofs
+    /// are finalized as if removed ones were never added. Randomness of the rest sub proofs is kept,
+    /// so sub proofs can't be removed once the builder was finalized with `finalize_ref`.
+    /// Returns number of removed sub proofs.
+    ///
+    /// # Arguments
+    /// * `key_id` - Identifier of credential public key of sub proofs to remove.
+    pub fn remove_sub_proof_request(&mut self, key_id: &str) -> Result<usize, IndyCryptoError> {
+        trace!("ProofBuilder::remove_sub_proof_request: >>> key_id: {:?}", key_id);
+
+        if !self.snark_proofs.is_empty() {
+            return Err(IndyCryptoError::InvalidState("Sub proof requests can't be removed after snark proofs are added".to_string()));
+        }
+
+        if self.finalized_challenge.is_some() {
+            return Err(IndyCryptoError::InvalidState("Proof builder was finalized, reset it to remove sub proof requests".to_string()));
+        }
+
+        let mut removed = 0;
+        let mut c_list_offset = 0;
+        let mut tau_list_offset = 0;
+        let mut removing_base = false;
+        let mut idx = 0;
+
+        while idx < self.sub_proof_slices.len() {
+            let slice = &self.sub_proof_slices[idx];
+            let c_list_range = c_list_offset..c_list_offset + slice.c_list_len;
+            let tau_list_range = tau_list_offset..tau_list_offset + slice.tau_list_len;
+
+            // Extension sub proof shares m2 with its base, so it is removed together with the base
+            removing_base = slice.key_id == key_id || (slice.is_extension && removing_base);
+
+            if removing_base {
+                self.c_list.drain(c_list_range);
+                self.tau_list.drain(tau_list_range);
+                self.init_proofs.remove(idx);
+                self.sub_proof_slices.remove(idx);
+                removed += 1;
+            } else {
+                c_list_offset = c_list_range.end;
+                tau_list_offset = tau_list_range.end;
+                idx += 1;
+            }
+        }
+
+        trace!("ProofBuilder::remove_sub_proof_request: <<< removed: {:?}", removed);
+
+        Ok(removed)
+    }
+
+    /// Removes all added sub proofs and snark proofs, so the builder can be reused for other credentials.
+    ///
+    /// Blindings of common attributes are regenerated, while settings (guardianship, challenge reduction,
+    /// channel binding, master secret) are kept.
+    pub fn reset(&mut self) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::reset: >>>");
+
+        for m_tilde in self.common_attributes.values_mut() {
+            *m_tilde = bn_rand(LARGE_MVECT)?;
+        }
+
+        self.init_proofs.clear();
+        self.c_list.clear();
+        self.tau_list.clear();
+        self.sub_proof_slices.clear();
+        self.snark_proofs.clear();
+        self.finalized_challenge = None;
+
+        trace!("ProofBuilder::reset: <<<");
+
+        Ok(())
+    }
+
+    fn _add_sub_proof_request(&mut self,
+                              sub_proof_request: &SubProofRequest,
+                              credential_schema: &CredentialSchema,
+                              non_credential_schema: &NonCredentialSchema,
+                              credential_signature: &CredentialSignature,
+                              credential_values: &CredentialValues,
+                              credential_pub_key: &CredentialPublicKey,
+                              rev_reg: Option<&RevocationRegistry>,
+                              witness: Option<&Witness>,
+                              attr_rev_states: &HashMap<String, (&RevocationRegistry, &Witness)>,
+                              linked_m2_tilde: Option<BigNumber>) -> Result<(), IndyCryptoError> {
+        if self.finalized_challenge.is_some() {
+            return Err(IndyCryptoError::InvalidState("Proof builder was finalized, reset it to add sub proof requests".to_string()));
+        }
+
+        let key_id = credential_pub_key.key_id()?;
+        let is_extension = linked_m2_tilde.is_some();
+        let c_list_start = self.c_list.len();
+        let tau_list_start = self.tau_list.len();
+
+        let res = self._init_sub_proof_request(sub_proof_request,
+                                               credential_schema,
+                                               non_credential_schema,
+                                               credential_signature,
+                                               credential_values,
+                                               credential_pub_key,
+                                               rev_reg,
+                                               witness,
+                                               attr_rev_states,
+                                               linked_m2_tilde);
+
+        if res.is_err() {
+            // Values of partially initialized sub proof are dropped, so the builder stays usable
+            self.c_list.truncate(c_list_start);
+            self.tau_list.truncate(tau_list_start);
+            return res;
+        }
+
+        self.sub_proof_slices.push(SubProofSlice {
+            key_id,
+            c_list_len: self.c_list.len() - c_list_start,
+            tau_list_len: self.tau_list.len() - tau_list_start,
+            is_extension
+        });
+
+        Ok(())
+    }
+
+    fn _init_sub_proof_request(&mut self,
+                               sub_proof_request: &SubProofRequest,
+                               credential_schema: &CredentialSchema,
+                               non_credential_schema: &NonCredentialSchema,
+                               credential_signature: &CredentialSignature,
+                               credential_values: &CredentialValues,
+                               credential_pub_key: &CredentialPublicKey,
+                               rev_reg: Option<&RevocationRegistry>,
+                               witness: Option<&Witness>,
+                               attr_rev_states: &HashMap<String, (&RevocationRegistry, &Witness)>,
+                               linked_m2_tilde: Option<BigNumber>) -> Result<(), IndyCryptoError> {
+        let _arena = BigNumberArena::new();
+
+        let credential_values_with_ms = match self.master_secret {
+            Some(ref master_secret) => Some(credential_values._with_master_secret(master_secret)?),
+            None => None
+        };
+        let credential_values = credential_values_with_ms.as_ref().unwrap_or(credential_values);
+
         trace!("ProofBuilder::add_sub_proof_request: >>> sub_proof_request: {:?}, \
                                                          credential_schema: {:?}, \
                                                          non_credential_schema: {:?}, \
@@ -817,7 +1970,8 @@ impl ProofBuilder {
                                                          credential_values: {:?}, \
                                                          credential_pub_key: {:?}, \
                                                          rev_reg: {:?}, \
-                                                         witness: {:?}",
+                                                         witness: {:?}, \
+                                                         attr_rev_states: {:?}",
                sub_proof_request,
                credential_schema,
                non_credential_schema,
@@ -825,16 +1979,19 @@ impl ProofBuilder {
                credential_values,
                credential_pub_key,
                rev_reg,
-               witness);
+               witness,
+               attr_rev_states);
         ProofBuilder::_check_add_sub_proof_request_params_consistency(
             credential_values,
             sub_proof_request,
             credential_schema,
             non_credential_schema,
         )?;
+        ProofBuilder::_check_attr_revocation_params_consistency(sub_proof_request, credential_signature, credential_pub_key, attr_rev_states)?;
+        ProofBuilder::_check_common_attributes_consistency(&self.common_attributes, sub_proof_request, credential_schema, non_credential_schema)?;
 
         let mut non_revoc_init_proof = None;
-        let mut m2_tilde: Option<BigNumber> = None;
+        let mut m2_tilde: Option<BigNumber> = linked_m2_tilde;
 
         if let (&Some(ref r_cred), &Some(ref r_reg), &Some(ref r_pub_key), &Some(ref witness)) = (&credential_signature.r_credential,
                                                                                                   &rev_reg,
@@ -843,7 +2000,9 @@ impl ProofBuilder {
             let proof = ProofBuilder::_init_non_revocation_proof(&r_cred,
                                                                  &r_reg,
                                                                  &r_pub_key,
-                                                                 &witness)?;
+                                                                 &witness,
+                                                                 None,
+                                                                 &self.non_revoc_pairings)?;
 
             self.c_list.extend_from_slice(&proof.as_c_list()?);
             self.tau_list.extend_from_slice(&proof.as_tau_list()?);
@@ -851,6 +2010,30 @@ impl ProofBuilder {
             non_revoc_init_proof = Some(proof);
         }
 
+        // All non-revocation proofs of the sub proof share the same `m2` blinding
+        let mut attr_non_revoc_init_proofs: BTreeMap<String, NonRevocInitProof> = BTreeMap::new();
+
+        for attr in sub_proof_request.unrevoked_attrs.iter() {
+            let r_cred = &credential_signature.attr_r_credentials[attr];
+            let r_pub_key = credential_pub_key.r_key.as_ref()
+                .ok_or(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential revocation public key.")))?;
+            let &(r_reg, witness) = &attr_rev_states[attr];
+
+            let m2_t = match m2_tilde {
+                Some(ref m2_tilde) => Some(bignum_to_group_element(m2_tilde)?),
+                None => None
+            };
+
+            let proof = ProofBuilder::_init_non_revocation_proof(r_cred, r_reg, r_pub_key, witness, m2_t, &self.non_revoc_pairings)?;
+
+            self.c_list.extend_from_slice(&proof.as_c_list()?);
+            self.tau_list.extend_from_slice(&proof.as_tau_list()?);
+            if m2_tilde.is_none() {
+                m2_tilde = Some(group_element_to_bignum(&proof.tau_list_params.m2)?);
+            }
+            attr_non_revoc_init_proofs.insert(attr.clone(), proof);
+        }
+
         let primary_init_proof = ProofBuilder::_init_primary_proof(&self.common_attributes,
                                                                    &credential_pub_key.p_key,
                                                                    &credential_signature.p_credential,
@@ -858,18 +2041,35 @@ impl ProofBuilder {
                                                                    credential_schema,
                                                                    non_credential_schema,
                                                                    sub_proof_request,
-                                                                   m2_tilde)?;
+                                                                   m2_tilde,
+                                                                   self.mod_exp_threads)?;
 
         self.c_list.extend_from_slice(&primary_init_proof.as_c_list()?);
         self.tau_list.extend_from_slice(&primary_init_proof.as_tau_list()?);
 
+        let mut committed_attr_init_proofs: BTreeMap<String, CommittedAttributeInitProof> = BTreeMap::new();
+
+        for attr in sub_proof_request.committed_attrs.iter() {
+            let proof = ProofBuilder::_init_committed_attr_proof(&credential_pub_key.p_key,
+                                                                 &primary_init_proof.eq_proof.m_tilde,
+                                                                 credential_values,
+                                                                 attr)?;
+
+            self.c_list.push(proof.commitment.to_bytes()?);
+            self.tau_list.push(proof.t.to_bytes()?);
+            committed_attr_init_proofs.insert(attr.clone(), proof);
+        }
+
         let init_proof = InitProof {
             primary_init_proof,
             non_revoc_init_proof,
+            attr_non_revoc_init_proofs,
+            committed_attr_init_proofs,
             credential_values: credential_values.clone()?,
             sub_proof_request: sub_proof_request.clone(),
             credential_schema: credential_schema.clone(),
             non_credential_schema: non_credential_schema.clone(),
+            non_revoc_timestamp: None,
         };
         self.init_proofs.push(init_proof);
 
@@ -952,20 +2152,156 @@ impl ProofBuilder {
     /// let proof_request_nonce = new_nonce().unwrap();
     /// let _proof = proof_builder.finalize(&proof_request_nonce).unwrap();
     /// ```
-    pub fn finalize(&self, nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
+    pub fn finalize(self, nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
         trace!("ProofBuilder::finalize: >>> nonce: {:?}", nonce);
 
-        let mut values: Vec<Vec<u8>> = Vec::new();
-        values.extend_from_slice(&self.tau_list);
-        values.extend_from_slice(&self.c_list);
-        values.push(nonce.to_bytes()?);
+        let ProofBuilder { init_proofs, c_list, tau_list, guardianship, challenge_reduction, snark_proofs, channel_binding, finalized_challenge, .. } = self;
+
+        let session_values = session_values(nonce, channel_binding.as_ref().map(|v| v.as_slice()))?;
+        let proof = ProofBuilder::_finalize(&init_proofs, c_list, tau_list, guardianship, challenge_reduction, snark_proofs,
+                                            BTreeMap::new(), session_values, finalized_challenge.as_ref())?;
+
+        trace!("ProofBuilder::finalize: <<< proof: {:?}", proof);
+
+        Ok(proof)
+    }
+
+    /// Finalizes proof with values of self-attested attributes requested by verifier.
+    ///
+    /// Self-attested values aren't signed by any issuer, but they are hashed into the challenge,
+    /// so they can't be swapped in the proof after it was built.
+    ///
+    /// # Arguments
+    /// * `nonce` - Nonce.
+    /// * `self_attested_attrs` - Values of self-attested attributes by attribute name.
+    pub fn finalize_with_self_attested_attrs(self,
+                                             nonce: &Nonce,
+                                             self_attested_attrs: &BTreeMap<String, String>) -> Result<Proof, IndyCryptoError> {
+        trace!("ProofBuilder::finalize_with_self_attested_attrs: >>> nonce: {:?}, self_attested_attrs: {:?}", nonce, self_attested_attrs);
+
+        let ProofBuilder { init_proofs, c_list, tau_list, guardianship, challenge_reduction, snark_proofs, channel_binding, finalized_challenge, .. } = self;
+
+        let session_values = session_values(nonce, channel_binding.as_ref().map(|v| v.as_slice()))?;
+        let proof = ProofBuilder::_finalize(&init_proofs, c_list, tau_list, guardianship, challenge_reduction, snark_proofs,
+                                            self_attested_attrs.clone(), session_values, finalized_challenge.as_ref())?;
+
+        trace!("ProofBuilder::finalize_with_self_attested_attrs: <<< proof: {:?}", proof);
+
+        Ok(proof)
+    }
+
+    /// Finalizes proof keeping the builder, so the same proof can be finalized again (e.g. for retransmission).
+    /// Prefer `finalize` that consumes the builder and moves its buffers to the proof instead of copying them.
+    ///
+    /// Finalizing the same sub proofs with another challenge (other nonce, channel binding or changed sub proofs)
+    /// reveals hidden attributes from the two responses, so it's rejected until the builder is `reset`.
+    ///
+    /// # Arguments
+    /// * `nonce` - Nonce.
+    pub fn finalize_ref(&mut self, nonce: &Nonce) -> Result<Proof, IndyCryptoError> {
+        trace!("ProofBuilder::finalize_ref: >>> nonce: {:?}", nonce);
+
+        let proof = ProofBuilder::_finalize(&self.init_proofs,
+                                            self.c_list.clone(),
+                                            self.tau_list.clone(),
+                                            self.guardianship.clone(),
+                                            self.challenge_reduction,
+                                            self.snark_proofs.clone(),
+                                            BTreeMap::new(),
+                                            session_values(nonce, self.channel_binding.as_ref().map(|v| v.as_slice()))?,
+                                            self.finalized_challenge.as_ref())?;
+
+        self.finalized_challenge = Some(proof.aggregated_proof.c_hash.clone()?);
+
+        trace!("ProofBuilder::finalize_ref: <<< proof: {:?}", proof);
+
+        Ok(proof)
+    }
+
+    /// Returns tau list of added sub proofs labeled by sub proof and its part.
+    ///
+    /// Debugging aid for `ProofVerifier::diagnose`. Tau list together with the proof reveals
+    /// hidden attributes, so it must be used with test credentials only.
+    pub fn tau_list_trace(&self) -> Result<TauListTrace, IndyCryptoError> {
+        trace!("ProofBuilder::tau_list_trace: >>>");
+
+        let mut tau_list_trace = TauListTrace::default();
+
+        for (idx, init_proof) in self.init_proofs.iter().enumerate() {
+            if let Some(ref non_revoc_init_proof) = init_proof.non_revoc_init_proof {
+                tau_list_trace.push(idx, TauListPart::NonRevocation, non_revoc_init_proof.as_tau_list()?);
+            }
+            for (attr, attr_non_revoc_init_proof) in init_proof.attr_non_revoc_init_proofs.iter() {
+                tau_list_trace.push(idx, TauListPart::AttrNonRevocation(attr.clone()), attr_non_revoc_init_proof.as_tau_list()?);
+            }
+            tau_list_trace.push(idx, TauListPart::Primary, init_proof.primary_init_proof.as_tau_list()?);
+            for (attr, committed_attr_init_proof) in init_proof.committed_attr_init_proofs.iter() {
+                tau_list_trace.push(idx, TauListPart::CommittedAttr(attr.clone()), vec![committed_attr_init_proof.t.to_bytes()?]);
+            }
+        }
+
+        trace!("ProofBuilder::tau_list_trace: <<< tau_list_trace: {:?}", secret!(&tau_list_trace));
+
+        Ok(tau_list_trace)
+    }
+
+    fn _finalize(init_proofs: &[InitProof],
+                 c_list: Vec<Vec<u8>>,
+                 tau_list: Vec<Vec<u8>>,
+                 guardianship: Option<GuardianshipMarker>,
+                 challenge_reduction: ChallengeReduction,
+                 snark_proofs: Vec<SnarkProof>,
+                 self_attested_attrs: BTreeMap<String, String>,
+                 session_values: Vec<Vec<u8>>,
+                 finalized_challenge: Option<&BigNumber>) -> Result<Proof, IndyCryptoError> {
+        let _arena = BigNumberArena::new();
+
+        // c_list is moved through hash values and taken back, so its buffers are never copied
+        let c_list_range = tau_list.len()..tau_list.len() + c_list.len();
+
+        let mut values: Vec<Vec<u8>> = tau_list;
+        values.extend(c_list);
+        if let Some(ref guardianship) = guardianship {
+            for init_proof in init_proofs.iter() {
+                if !guardianship.allows(&init_proof.sub_proof_request) {
+                    return Err(IndyCryptoError::InvalidStructure("Sub proof request is out of delegation scope".to_string()));
+                }
+            }
+            values.push(guardianship.to_bytes()?);
+        }
+        for snark_proof in snark_proofs.iter() {
+            values.push(snark_proof.to_bytes()?);
+        }
+        let sub_proof_request_fingerprints = init_proofs.iter()
+            .map(|init_proof| init_proof.sub_proof_request.fingerprint())
+            .collect::<Result<Vec<Vec<u8>>, IndyCryptoError>>()?;
+        values.extend(sub_proof_request_fingerprints.iter().cloned());
+        for init_proof in init_proofs.iter() {
+            if init_proof.sub_proof_request.non_revoc_interval.is_some() && init_proof.non_revoc_init_proof.is_some() &&
+                init_proof.non_revoc_timestamp.is_none() {
+                return Err(IndyCryptoError::InvalidStructure(
+                    "Sub proof request requires non-revocation interval, add it with revocation registry state".to_string()));
+            }
+            if let Some(non_revoc_timestamp) = init_proof.non_revoc_timestamp {
+                values.push(u64_to_be_bytes(non_revoc_timestamp.as_secs()));
+            }
+        }
+        values.extend(self_attested_values(&self_attested_attrs)?);
+        values.extend(session_values);
 
         // In the anoncreds whitepaper, `challenge` is denoted by `c_h`
-        let challenge = get_hash_as_int(&values)?;
+        let challenge = challenge_reduction.reduce(get_hash_as_int(&values)?)?;
+
+        if finalized_challenge.map_or(false, |finalized_challenge| *finalized_challenge != challenge) {
+            return Err(IndyCryptoError::InvalidState(
+                "Proof builder was finalized with other challenge, reset it to finalize for other nonce".to_string()));
+        }
+
+        let c_list: Vec<Vec<u8>> = values.drain(c_list_range).collect();
 
         let mut proofs: Vec<SubProof> = Vec::new();
 
-        for init_proof in self.init_proofs.iter() {
+        for (init_proof, sub_proof_request_fingerprint) in init_proofs.iter().zip(sub_proof_request_fingerprints) {
             let mut non_revoc_proof: Option<NonRevocProof> = None;
             if let Some(ref non_revoc_init_proof) = init_proof.non_revoc_init_proof {
                 non_revoc_proof = Some(ProofBuilder::_finalize_non_revocation_proof(&non_revoc_init_proof, &challenge)?);
@@ -980,17 +2316,78 @@ impl ProofBuilder {
                 &init_proof.sub_proof_request,
             )?;
 
-            let proof = SubProof { primary_proof, non_revoc_proof };
+            let mut attr_non_revoc_proofs: BTreeMap<String, NonRevocProof> = BTreeMap::new();
+            for (attr, attr_non_revoc_init_proof) in init_proof.attr_non_revoc_init_proofs.iter() {
+                attr_non_revoc_proofs.insert(attr.clone(), ProofBuilder::_finalize_non_revocation_proof(&attr_non_revoc_init_proof, &challenge)?);
+            }
+
+            let mut committed_attr_proofs: BTreeMap<String, CommittedAttributeProof> = BTreeMap::new();
+            for (attr, committed_attr_init_proof) in init_proof.committed_attr_init_proofs.iter() {
+                committed_attr_proofs.insert(attr.clone(), ProofBuilder::_finalize_committed_attr_proof(committed_attr_init_proof, &challenge)?);
+            }
+
+            let proof = SubProof {
+                primary_proof,
+                non_revoc_proof,
+                attr_non_revoc_proofs,
+                committed_attr_proofs,
+                sub_proof_request_fingerprint,
+                non_revoc_timestamp: init_proof.non_revoc_timestamp
+            };
             proofs.push(proof);
         }
 
-        let aggregated_proof = AggregatedProof { c_hash: challenge, c_list: self.c_list.clone() };
+        let aggregated_proof = AggregatedProof { c_hash: challenge, c_list };
+
+        Ok(Proof { proofs, aggregated_proof, guardianship, challenge_reduction, snark_proofs, self_attested_attrs })
+    }
 
-        let proof = Proof { proofs, aggregated_proof };
+    fn _check_attr_revocation_params_consistency(sub_proof_request: &SubProofRequest,
+                                                 cred_signature: &CredentialSignature,
+                                                 cred_pub_key: &CredentialPublicKey,
+                                                 attr_rev_states: &HashMap<String, (&RevocationRegistry, &Witness)>) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::_check_attr_revocation_params_consistency: >>> sub_proof_request: {:?}, attr_rev_states: {:?}",
+               sub_proof_request, attr_rev_states);
 
-        trace!("ProofBuilder::finalize: <<< proof: {:?}", proof);
+        if !sub_proof_request.unrevoked_attrs.is_empty() && cred_pub_key.r_key.is_none() {
+            return Err(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential revocation public key.")));
+        }
 
-        Ok(proof)
+        for attr in sub_proof_request.unrevoked_attrs.iter() {
+            if !cred_signature.attr_r_credentials.contains_key(attr) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Attribute '{}' is not signed with revocation keys", attr)));
+            }
+
+            if !attr_rev_states.contains_key(attr) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Revocation state for attribute '{}' not found", attr)));
+            }
+        }
+
+        trace!("ProofBuilder::_check_attr_revocation_params_consistency: <<<");
+
+        Ok(())
+    }
+
+    fn _check_common_attributes_consistency(common_attributes: &HashMap<String, BigNumber>,
+                                            sub_proof_request: &SubProofRequest,
+                                            cred_schema: &CredentialSchema,
+                                            non_cred_schema: &NonCredentialSchema) -> Result<(), IndyCryptoError> {
+        trace!("ProofBuilder::_check_common_attributes_consistency: >>> common_attributes: {:?}, sub_proof_request: {:?}",
+               common_attributes.keys().collect::<Vec<&String>>(), sub_proof_request);
+
+        for attr in common_attributes.keys() {
+            if !cred_schema.attrs.contains(attr) && !non_cred_schema.attrs.contains(attr) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Common attribute '{}' is not present in credential schema", attr)));
+            }
+
+            if sub_proof_request.revealed_attrs.contains(attr) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Common attribute '{}' can't be revealed", attr)));
+            }
+        }
+
+        trace!("ProofBuilder::_check_common_attributes_consistency: <<<");
+
+        Ok(())
     }
 
     fn _check_add_sub_proof_request_params_consistency(
@@ -1014,19 +2411,23 @@ impl ProofBuilder {
 
         let cred_attrs = BTreeSet::from_iter(cred_values.attrs_values.keys().cloned());
 
-        if schema_attrs != cred_attrs {
-            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't correspond to credential schema")));
+        // All mismatches are reported at once, so a wallet can fix credential values or request in one go
+        let mut mismatches = Vec::new();
+
+        let missing_values = schema_attrs.difference(&cred_attrs).cloned().collect::<Vec<String>>();
+        if !missing_values.is_empty() {
+            mismatches.push(format!("credential values are missing schema attributes [{}]", missing_values.join(", ")));
         }
 
-        if sub_proof_request
-            .revealed_attrs
-            .difference(&cred_attrs)
-            .count() != 0
-            {
-                return Err(IndyCryptoError::InvalidStructure(
-                    format!("Credential doesn't contain requested attribute"),
-                ));
-            }
+        let unknown_values = cred_attrs.difference(&schema_attrs).cloned().collect::<Vec<String>>();
+        if !unknown_values.is_empty() {
+            mismatches.push(format!("credential values contain attributes not in schema [{}]", unknown_values.join(", ")));
+        }
+
+        let revealed_attrs = sub_proof_request.revealed_attrs.difference(&schema_attrs).cloned().collect::<Vec<String>>();
+        if !revealed_attrs.is_empty() {
+            mismatches.push(format!("requested revealed attributes are not in schema [{}]", revealed_attrs.join(", ")));
+        }
 
         let predicates_attrs = sub_proof_request
             .predicates
@@ -1034,8 +2435,23 @@ impl ProofBuilder {
             .map(|predicate| predicate.attr_name.clone())
             .collect::<BTreeSet<String>>();
 
-        if predicates_attrs.difference(&cred_attrs).count() != 0 {
-            return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute requested in predicate")));
+        let predicate_attrs = predicates_attrs.difference(&schema_attrs).cloned().collect::<Vec<String>>();
+        if !predicate_attrs.is_empty() {
+            mismatches.push(format!("attributes requested in predicates are not in schema [{}]", predicate_attrs.join(", ")));
+        }
+
+        let committed_attrs = sub_proof_request.committed_attrs.difference(&schema_attrs).cloned().collect::<Vec<String>>();
+        if !committed_attrs.is_empty() {
+            mismatches.push(format!("attributes requested as committed are not in schema [{}]", committed_attrs.join(", ")));
+        }
+
+        if !mismatches.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Credential doesn't correspond to sub proof request or credential schema: {}", mismatches.join("; "))));
+        }
+
+        for attr in predicates_attrs.iter() {
+            cred_schema.check_predicate_attr(attr)?;
         }
 
         trace!("ProofBuilder::_check_add_sub_proof_request_params_consistency: <<<");
@@ -1050,7 +2466,8 @@ impl ProofBuilder {
                            cred_schema: &CredentialSchema,
                            non_cred_schema_elems: &NonCredentialSchema,
                            sub_proof_request: &SubProofRequest,
-                           m2_t: Option<BigNumber>) -> Result<PrimaryInitProof, IndyCryptoError> {
+                           m2_t: Option<BigNumber>,
+                           mod_exp_threads: usize) -> Result<PrimaryInitProof, IndyCryptoError> {
         trace!("ProofBuilder::_init_primary_proof: >>> common_attributes: {:?}, \
                                                        issuer_pub_key: {:?}, \
                                                        c1: {:?}, \
@@ -1071,16 +2488,11 @@ impl ProofBuilder {
                                                     m2_t,
         )?;
 
-        let mut ge_proofs: Vec<PrimaryPredicateGEInitProof> = Vec::new();
-        for predicate in sub_proof_request.predicates.iter() {
-            let ge_proof = ProofBuilder::_init_ge_proof(
-                &issuer_pub_key,
-                &eq_proof.m_tilde,
-                cred_values,
-                predicate,
-            )?;
-            ge_proofs.push(ge_proof);
-        }
+        let ge_proofs = ProofBuilder::_init_ge_proofs(&issuer_pub_key,
+                                                      &eq_proof.m_tilde,
+                                                      cred_values,
+                                                      sub_proof_request.predicates.iter(),
+                                                      mod_exp_threads)?;
 
         let primary_init_proof = PrimaryInitProof { eq_proof, ge_proofs };
 
@@ -1092,18 +2504,27 @@ impl ProofBuilder {
     fn _init_non_revocation_proof(r_cred: &NonRevocationCredentialSignature,
                                   rev_reg: &RevocationRegistry,
                                   cred_rev_pub_key: &CredentialRevocationPublicKey,
-                                  witness: &Witness) -> Result<NonRevocInitProof, IndyCryptoError> {
-        trace!("ProofBuilder::_init_non_revocation_proof: >>> r_cred: {:?}, rev_reg: {:?}, cred_rev_pub_key: {:?}, witness: {:?}",
-               r_cred, rev_reg, cred_rev_pub_key, witness);
+                                  witness: &Witness,
+                                  m2_t: Option<GroupOrderElement>,
+                                  non_revoc_pairings: &[NonRevocProofPairings]) -> Result<NonRevocInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_non_revocation_proof: >>> r_cred: {:?}, rev_reg: {:?}, cred_rev_pub_key: {:?}, witness: {:?}, m2_t: {:?}",
+               r_cred, rev_reg, cred_rev_pub_key, witness, m2_t);
 
         let c_list_params = ProofBuilder::_gen_c_list_params(&r_cred)?;
         let c_list = ProofBuilder::_create_c_list_values(&r_cred, &c_list_params, &cred_rev_pub_key, witness)?;
 
-        let tau_list_params = ProofBuilder::_gen_tau_list_params()?;
-        let tau_list = create_tau_list_values(&cred_rev_pub_key,
-                                              &rev_reg,
-                                              &tau_list_params,
-                                              &c_list)?;
+        let mut tau_list_params = ProofBuilder::_gen_tau_list_params()?;
+        if let Some(m2_t) = m2_t {
+            tau_list_params.m2 = m2_t;
+        }
+        let pairings = match non_revoc_pairings.iter().find(|pairings| pairings.is_for_key(cred_rev_pub_key)) {
+            Some(pairings) => pairings.for_rev_reg(rev_reg)?,
+            None => NonRevocProofPairings::new(cred_rev_pub_key, rev_reg)?
+        };
+        let tau_list = create_tau_list_values_with_pairings(&cred_rev_pub_key,
+                                                            &pairings,
+                                                            &tau_list_params,
+                                                            &c_list)?;
 
         let r_init_proof = NonRevocInitProof {
             c_list_params,
@@ -1136,9 +2557,9 @@ impl ProofBuilder {
 
         let m2_tilde = m2_t.unwrap_or(bn_rand(LARGE_MVECT)?);
 
-        let r = bn_rand(LARGE_VPRIME)?;
-        let e_tilde = bn_rand(LARGE_ETILDE)?;
-        let v_tilde = bn_rand(LARGE_VTILDE)?;
+        let r = bn_rand(cred_pub_key.config.large_vprime)?;
+        let e_tilde = bn_rand(cred_pub_key.config.large_etilde())?;
+        let v_tilde = bn_rand(cred_pub_key.config.large_vtilde())?;
 
         let unrevealed_attrs = non_cred_schema_elems.attrs.union(&cred_schema.attrs)
             .cloned()
@@ -1154,7 +2575,7 @@ impl ProofBuilder {
             .mod_exp(&r, &cred_pub_key.n, Some(&mut ctx))?
             .mod_mul(&c1.a, &cred_pub_key.n, Some(&mut ctx))?;
 
-        let e_prime = c1.e.sub(&LARGE_E_START_VALUE)?;
+        let e_prime = c1.e.sub(&cred_pub_key.config.e_start_value()?)?;
 
         let v_prime = c1.v.sub(&c1.e.mul(&r, Some(&mut ctx))?)?;
 
@@ -1177,88 +2598,234 @@ impl ProofBuilder {
         Ok(primary_equal_init_proof)
     }
 
-    fn _init_ge_proof(p_pub_key: &CredentialPrimaryPublicKey,
-                      m_tilde: &HashMap<String, BigNumber>,
-                      cred_values: &CredentialValues,
-                      predicate: &Predicate) -> Result<PrimaryPredicateGEInitProof, IndyCryptoError> {
-        trace!("ProofBuilder::_init_ge_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, predicate: {:?}",
-               p_pub_key, m_tilde, cred_values, predicate);
+    /// Returns value of the attribute used in predicate.
+    /// Predicates are supported only for attributes with numeric encoding, i.e. encoded as non-negative 64-bit integers;
+    /// other attributes (e.g. strings encoded as hashes) can't be compared.
+    fn _get_numeric_attr_value(attr: &str, value: &BigNumber) -> Result<i64, IndyCryptoError> {
+        if value.is_negative() || value.num_bits()? > 63 {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Predicate on non-numeric attribute '{}': encoded value is not a 64-bit non-negative integer", attr)));
+        }
+
+        value.to_dec()?
+            .parse::<i64>()
+            .map_err(|_| IndyCryptoError::InvalidStructure(format!("Value by key '{}' has invalid format", attr)))
+    }
+
+    fn _init_ge_proofs<'a, I>(p_pub_key: &CredentialPrimaryPublicKey,
+                              m_tilde: &HashMap<String, BigNumber>,
+                              cred_values: &CredentialValues,
+                              predicates: I,
+                              threads: usize) -> Result<Vec<PrimaryPredicateGEInitProof>, IndyCryptoError>
+        where I: IntoIterator<Item=&'a Predicate> {
+        trace!("ProofBuilder::_init_ge_proofs: >>> p_pub_key: {:?}, m_tilde: {:?}, cred_values: {:?}, threads: {:?}",
+               p_pub_key, m_tilde, cred_values, threads);
 
         let mut ctx = BigNumber::new_context()?;
-        let (k, value) = (&predicate.attr_name, predicate.value);
 
-        let attr_value = cred_values.attrs_values.get(k.as_str())
-            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in cred_values", k)))?
-            .value()
-            .to_dec()?
-            .parse::<i32>()
-            .map_err(|_| IndyCryptoError::InvalidStructure(format!("Value by key '{}' has invalid format", k)))?;
+        // Random values of every predicate are generated first,
+        // so all exponentiations of the sub proof can be executed in one batch
+        let mut params: Vec<GEInitProofParams> = Vec::new();
+
+        for predicate in predicates {
+            let k = &predicate.attr_name;
+
+            let attr_value = cred_values.attrs_values.get(k.as_str())
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in cred_values", k)))?
+                .value();
 
-        let delta: i32 = attr_value - value;
+            let attr_value = ProofBuilder::_get_numeric_attr_value(k, attr_value)?;
 
-        if delta < 0 {
-            return Err(IndyCryptoError::InvalidStructure("Predicate is not satisfied".to_string()));
+            let delta = predicate.get_delta(attr_value)?;
+
+            if delta.is_negative() {
+                return Err(IndyCryptoError::InvalidStructure("Predicate is not satisfied".to_string()));
+            }
+
+            let delta = delta.to_dec()?
+                .parse::<u64>()
+                .map_err(|_| IndyCryptoError::InvalidStructure("Predicate delta doesn't fit u64".to_string()))?;
+
+            let u = four_squares(delta)?;
+
+            let mut r = HashMap::new();
+            for i in 0..ITERATION {
+                r.insert(i.to_string(), bn_rand(p_pub_key.config.large_vprime)?);
+            }
+            r.insert("DELTA".to_string(), bn_rand(p_pub_key.config.large_vprime)?);
+
+            let mut u_tilde = HashMap::new();
+            let mut r_tilde = HashMap::new();
+
+            for i in 0..ITERATION {
+                u_tilde.insert(i.to_string(), bn_rand(LARGE_UTILDE)?);
+                r_tilde.insert(i.to_string(), bn_rand(LARGE_RTILDE)?);
+            }
+
+            r_tilde.insert("DELTA".to_string(), bn_rand(LARGE_RTILDE)?);
+            let alpha_tilde = bn_rand(p_pub_key.config.large_alphatilde())?;
+
+            let mj = m_tilde.get(k.as_str())
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.mtilde", k)))?;
+
+            params.push(GEInitProofParams {
+                predicate: predicate.clone(),
+                delta: BigNumber::from_dec(&delta.to_string())?,
+                u,
+                r,
+                u_tilde,
+                r_tilde,
+                alpha_tilde,
+                mj
+            });
         }
 
-        let u = four_squares(delta)?;
+        // Commitments `T_i = Z^u_i * S^r_i` and all parts of tau list that don't depend on commitments
+        let results = {
+            let mut batch = ModExpBatch::new(&p_pub_key.n);
 
-        let mut r = HashMap::new();
-        let mut t = HashMap::new();
-        let mut c_list: Vec<BigNumber> = Vec::new();
+            for param in params.iter() {
+                for i in 0..ITERATION {
+                    batch.push(&p_pub_key.z, _get_ge_value("u", &param.u, &i.to_string())?);
+                    batch.push(&p_pub_key.s, _get_ge_value("r", &param.r, &i.to_string())?);
+                }
+                batch.push(&p_pub_key.z, &param.delta);
+                batch.push(&p_pub_key.s, _get_ge_value("r", &param.r, "DELTA")?);
 
-        for i in 0..ITERATION {
-            let cur_u = u.get(&i.to_string())
-                .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in u1", i)))?;
+                for i in 0..ITERATION {
+                    batch.push(&p_pub_key.z, _get_ge_value("u_tilde", &param.u_tilde, &i.to_string())?);
+                    batch.push(&p_pub_key.s, _get_ge_value("r_tilde", &param.r_tilde, &i.to_string())?);
+                }
+                batch.push(&p_pub_key.z, param.mj);
+                batch.push(&p_pub_key.s, _get_ge_value("r_tilde", &param.r_tilde, "DELTA")?);
+                batch.push(&p_pub_key.s, &param.alpha_tilde);
+            }
+
+            batch.execute(threads)?
+        };
+
+        let mut results = results.into_iter();
+        let mut ts: Vec<HashMap<String, BigNumber>> = Vec::new();
+        let mut tau_lists: Vec<Vec<BigNumber>> = Vec::new();
+        let mut s_alphas: Vec<BigNumber> = Vec::new();
+
+        for _ in params.iter() {
+            let mut t = HashMap::new();
+            for i in 0..ITERATION {
+                t.insert(i.to_string(), ProofBuilder::_next_mod_mul(&mut results, &p_pub_key.n, &mut ctx)?);
+            }
+            t.insert("DELTA".to_string(), ProofBuilder::_next_mod_mul(&mut results, &p_pub_key.n, &mut ctx)?);
 
-            let cur_r = bn_rand(LARGE_VPRIME)?;
-            let cut_t = get_pedersen_commitment(&p_pub_key.z, &cur_u, &p_pub_key.s,
-                                                &cur_r, &p_pub_key.n, &mut ctx)?;
+            let mut tau_list = Vec::new();
+            for _ in 0..ITERATION + 1 {
+                tau_list.push(ProofBuilder::_next_mod_mul(&mut results, &p_pub_key.n, &mut ctx)?);
+            }
 
-            r.insert(i.to_string(), cur_r);
-            t.insert(i.to_string(), cut_t.clone()?);
-            c_list.push(cut_t)
+            ts.push(t);
+            tau_lists.push(tau_list);
+            s_alphas.push(ProofBuilder::_next_batch_result(&mut results)?);
         }
 
-        let r_delta = bn_rand(LARGE_VPRIME)?;
+        // Last element of tau list `Q = S^alpha_tilde * prod(T_i^u_tilde_i)` depends on commitments
+        let results = {
+            let mut batch = ModExpBatch::new(&p_pub_key.n);
+
+            for (param, t) in params.iter().zip(ts.iter()) {
+                for i in 0..ITERATION {
+                    batch.push(_get_ge_value("t", t, &i.to_string())?,
+                               _get_ge_value("u_tilde", &param.u_tilde, &i.to_string())?);
+                }
+            }
 
-        let t_delta = get_pedersen_commitment(&p_pub_key.z, &BigNumber::from_dec(&delta.to_string())?,
-                                              &p_pub_key.s, &r_delta, &p_pub_key.n, &mut ctx)?;
+            batch.execute(threads)?
+        };
 
-        r.insert("DELTA".to_string(), r_delta);
-        t.insert("DELTA".to_string(), t_delta.clone()?);
-        c_list.push(t_delta);
+        let mut results = results.into_iter();
+        let mut ge_init_proofs: Vec<PrimaryPredicateGEInitProof> = Vec::new();
 
-        let mut u_tilde = HashMap::new();
-        let mut r_tilde = HashMap::new();
+        for ((param, t), (mut tau_list, s_alpha)) in params.into_iter().zip(ts.into_iter()).zip(tau_lists.into_iter().zip(s_alphas.into_iter())) {
+            let mut q = s_alpha;
+            for _ in 0..ITERATION {
+                q = ProofBuilder::_next_batch_result(&mut results)?.mod_mul(&q, &p_pub_key.n, Some(&mut ctx))?;
+            }
+            tau_list.push(q);
 
-        for i in 0..ITERATION {
-            u_tilde.insert(i.to_string(), bn_rand(LARGE_UTILDE)?);
-            r_tilde.insert(i.to_string(), bn_rand(LARGE_RTILDE)?);
+            let mut c_list: Vec<BigNumber> = Vec::new();
+            for i in 0..ITERATION {
+                c_list.push(_get_ge_value("t", &t, &i.to_string())?.clone()?);
+            }
+            c_list.push(_get_ge_value("t", &t, "DELTA")?.clone()?);
+
+            ge_init_proofs.push(PrimaryPredicateGEInitProof {
+                c_list,
+                tau_list,
+                u: param.u,
+                u_tilde: param.u_tilde,
+                r: param.r,
+                r_tilde: param.r_tilde,
+                alpha_tilde: param.alpha_tilde,
+                predicate: param.predicate,
+                t
+            });
         }
 
-        r_tilde.insert("DELTA".to_string(), bn_rand(LARGE_RTILDE)?);
-        let alpha_tilde = bn_rand(LARGE_ALPHATILDE)?;
+        trace!("ProofBuilder::_init_ge_proofs: <<< ge_init_proofs: {:?}", ge_init_proofs);
 
-        let mj = m_tilde.get(k.as_str())
-            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.mtilde", k)))?;
+        Ok(ge_init_proofs)
+    }
 
-        let tau_list = calc_tge(&p_pub_key, &u_tilde, &r_tilde, &mj, &alpha_tilde, &t)?;
+    fn _next_batch_result<I>(results: &mut I) -> Result<BigNumber, IndyCryptoError> where I: Iterator<Item=BigNumber> {
+        results.next()
+            .ok_or(IndyCryptoError::InvalidState("Result of modular exponentiation not found in batch".to_string()))
+    }
 
-        let primary_predicate_ge_init_proof = PrimaryPredicateGEInitProof {
-            c_list,
-            tau_list,
-            u,
-            u_tilde,
-            r,
-            r_tilde,
-            alpha_tilde,
-            predicate: predicate.clone(),
-            t
-        };
+    fn _next_mod_mul<I>(results: &mut I, n: &BigNumber, ctx: &mut BigNumberContext) -> Result<BigNumber, IndyCryptoError>
+        where I: Iterator<Item=BigNumber> {
+        let a = ProofBuilder::_next_batch_result(results)?;
+        let b = ProofBuilder::_next_batch_result(results)?;
+        a.mod_mul(&b, n, Some(ctx))
+    }
+
+    fn _init_committed_attr_proof(p_pub_key: &CredentialPrimaryPublicKey,
+                                  m_tilde: &HashMap<String, BigNumber>,
+                                  cred_values: &CredentialValues,
+                                  attr: &str) -> Result<CommittedAttributeInitProof, IndyCryptoError> {
+        trace!("ProofBuilder::_init_committed_attr_proof: >>> p_pub_key: {:?}, m_tilde: {:?}, attr: {:?}",
+               p_pub_key, secret!(m_tilde), attr);
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let m = cred_values.attrs_values.get(attr)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in credential values", attr)))?;
+
+        // Response for `m` is taken from the equality proof, so attribute must be hidden there
+        let mj_tilde = m_tilde.get(attr)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Value by key '{}' not found in eq_proof.mtilde", attr)))?;
+
+        let r = bn_rand(p_pub_key.config.large_vprime)?;
+        let r_tilde = bn_rand(LARGE_RTILDE)?;
+
+        let commitment = get_pedersen_commitment(&p_pub_key.z, m.value(), &p_pub_key.s, &r, &p_pub_key.n, &mut ctx)?;
+        let t = get_pedersen_commitment(&p_pub_key.z, mj_tilde, &p_pub_key.s, &r_tilde, &p_pub_key.n, &mut ctx)?;
+
+        let committed_attr_init_proof = CommittedAttributeInitProof { commitment, r, r_tilde, t };
+
+        trace!("ProofBuilder::_init_committed_attr_proof: <<< committed_attr_init_proof: {:?}", secret!(&committed_attr_init_proof));
+
+        Ok(committed_attr_init_proof)
+    }
+
+    fn _finalize_committed_attr_proof(init_proof: &CommittedAttributeInitProof,
+                                      c_h: &BigNumber) -> Result<CommittedAttributeProof, IndyCryptoError> {
+        trace!("ProofBuilder::_finalize_committed_attr_proof: >>> init_proof: {:?}, c_h: {:?}", secret!(init_proof), c_h);
+
+        let r_cap = c_h.mul(&init_proof.r, None)?.add(&init_proof.r_tilde)?;
 
-        trace!("ProofBuilder::_init_ge_proof: <<< primary_predicate_ge_init_proof: {:?}", primary_predicate_ge_init_proof);
+        let committed_attr_proof = CommittedAttributeProof { commitment: init_proof.commitment.clone()?, r_cap };
 
-        Ok(primary_predicate_ge_init_proof)
+        trace!("ProofBuilder::_finalize_committed_attr_proof: <<< committed_attr_proof: {:?}", committed_attr_proof);
+
+        Ok(committed_attr_proof)
     }
 
     fn _finalize_eq_proof(init_proof: &PrimaryEqualInitProof,
@@ -1329,8 +2896,13 @@ impl ProofBuilder {
             );
         }
 
+        let revealed_raw_attrs = sub_proof_request.revealed_attrs.iter()
+            .filter_map(|attr| cred_values.raw_values.get(attr).map(|raw| (attr.clone(), raw.clone())))
+            .collect();
+
         let primary_equal_proof = PrimaryEqualProof {
             revealed_attrs: revealed_attrs_with_values,
+            revealed_raw_attrs,
             a_prime: init_proof.a_prime.clone()?,
             e,
             v,
@@ -1375,8 +2947,15 @@ impl ProofBuilder {
 
             let cur_rtilde_delta = &init_proof.r_tilde["DELTA"];
 
+            // For LE/LT predicates verifier checks commitment to delta with the opposite sign
+            let r_delta = if init_proof.predicate.is_less() {
+                init_proof.r["DELTA"].set_negative(true)?
+            } else {
+                init_proof.r["DELTA"].clone()?
+            };
+
             let new_delta = c_h
-                .mul(&init_proof.r["DELTA"], Some(&mut ctx))?
+                .mul(&r_delta, Some(&mut ctx))?
                 .add(&cur_rtilde_delta)?;
 
             r.insert("DELTA".to_string(), new_delta);
@@ -1589,6 +3168,22 @@ mod tests {
     use cl::issuer;
     use serde_json;
 
+    #[test]
+    fn check_signature_correctness_proof_works_for_e_out_of_range() {
+        let mut p_cred_sig = mocks::primary_credential();
+        p_cred_sig.e = BigNumber::from_u32(65537).unwrap();
+
+        let res = Prover::_check_signature_correctness_proof(&p_cred_sig,
+                                                             &issuer::mocks::credential_values(),
+                                                             &issuer::mocks::signature_correctness_proof(),
+                                                             &issuer::mocks::credential_primary_public_key(),
+                                                             &issuer::mocks::credential_nonce());
+        match res {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
     #[test]
     fn key_correctness_proof_validation_works_for_deserialized_output_v0_4_1_crypto() {
         let kcp = r#"{"c":"37611675737093606611354469283892411880852495117565168932358663398963131397507","xz_cap":"81579130320284221659747319740108875652446580605626929564515869699158446225972801134098632494713496313081314380866687966418290227597750899002882970519534702423347828404017509366494708523530025686292969865053261834885716665417122559158656847219251019258307743208838075692695164262680850087806525721184647037789559371016575764323904037635266872661253754958239070844593676990703001641163014837607074604574439994741936613409912802229927895424755757352646030336597690950842465911939873272966620342405909930599727835739699655473154455657878429132861698360924836632047016333549106122684361100949241413364697739541658923119788014990949301155631757300624437448380216292364426202602100074188682993006187","xr_cap":[["sex","800280099800023684394221657855578281425593426428797438278634535803826854973287741112297002561462044581730457464290768546940348121889048006353304776646794823653560200707175243576534399257694825778643847023451169693956070462522652667711052051119060371846591706152099200381794609252833996514839617453462295422079364560725012355479350713908774407072059863925714626035129287654437915380442859411132043551952897474887960834654566958110046975477442837252851593858380406893298039998278146813948374557719947480415431505168848477644721410506100843223565186964968463081686726318431810101100839476456665117568759117498622946466335362502138675885007428245786030655866656241152568981953362753866546347245506"],["age","588088631461299425903748636894451597454180996508770107860820879608066278697726969676142820725979998876687628461524297952569445512912113947952863000770341397107329530774939533674792868680827566279577518607195225037390604727483704420911912238224219864823492245908348105557153285313698657725038609899106209002384198903035975551652419617009072704552236735717389754124395458798446740853188430442908535423980999434501037185906780341482928855355637070027953698599569975766436241558834373873737728336703980967063844033141464829186289408341005936078717542471679931243178369744750036706021440802187762189222523038598747576436835546143611288733061739572462869076736405341538116562816483588163276630145588"],["height","553220455491285418654889779078476533199565266037716057819253262456706086296310865820014979289644399892322745082334493480377902246036427120996737141182672228618720768916010742192428961333242647461723166430891725984061962166185290028781330840468287369467210902803713581463138002887245708126181113498506095878475477562185158200076760989353034954621747102865883089591566895303014875251551529870810800964290188402770835695975293408858132429212162793578010820152709965777440582153499339685425754384078776656170709303540365276228433474426237479107459583876421876578975913079855215398240111839997147164550277110095530104844265258104360762567118292063538492192083952712713837994596074547775217382719579"],["name","383325619072931698489524170594499308335325217367787209202882000237923187775119979058633557703022426956865524033530017842216102964924733310029537256438963746099184641563671420576298749176202668215626084998168583932862834827081323228031589641597768136343232183260789201414439414019145929237988915293970815065021922162304853953719973584719975042952713084160885042865916208477614187377876264496125987756268019899327470534991407455234648438185065303663808513544394761315253646500213994569448735987674657147571753166712102581100080484612181607406695322516789021386859985149430517261727189786324895636842320235453633433344220062995558348664785301570376489352431483740437508437906549673849465012384545"]]}"#;
@@ -1760,12 +3355,157 @@ mod tests {
         let predicate = mocks::predicate();
         let credential_values = issuer::mocks::credential_values();
 
-        let init_ge_proof = ProofBuilder::_init_ge_proof(&pk,
-                                                         &init_eq_proof.m_tilde,
-                                                         &credential_values,
-                                                         &predicate).unwrap();
+        let init_ge_proofs = ProofBuilder::_init_ge_proofs(&pk,
+                                                           &init_eq_proof.m_tilde,
+                                                           &credential_values,
+                                                           vec![&predicate],
+                                                           1).unwrap();
+
+        assert_eq!(vec![mocks::primary_ge_init_proof()], init_ge_proofs);
+    }
+
+    #[test]
+    fn init_ge_proofs_works_for_multiple_threads() {
+        MockHelper::inject();
+
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicates = vec![
+            mocks::predicate(),
+            Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LE, value: 60 },
+            Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GT, value: 3 }
+        ];
+        let credential_values = issuer::mocks::credential_values();
+
+        let init_ge_proofs = ProofBuilder::_init_ge_proofs(&pk, &init_eq_proof.m_tilde, &credential_values, predicates.iter(), 4).unwrap();
+
+        assert_eq!(3, init_ge_proofs.len());
+        assert_eq!(mocks::primary_ge_init_proof(), init_ge_proofs[0]);
+
+        for (predicate, init_ge_proof) in predicates.iter().zip(init_ge_proofs.iter()) {
+            let expected = ProofBuilder::_init_ge_proofs(&pk, &init_eq_proof.m_tilde, &credential_values, vec![predicate], 1).unwrap();
+            assert_eq!(expected[0], *init_ge_proof);
+        }
+    }
+
+    #[test]
+    fn init_ge_proof_works_for_non_numeric_attribute() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicate = Predicate { attr_name: "name".to_owned(), p_type: PredicateType::GE, value: 18 };
+        let credential_values = issuer::mocks::credential_values();
+
+        match ProofBuilder::_init_ge_proofs(&pk, &init_eq_proof.m_tilde, &credential_values, vec![&predicate], 1) {
+            Err(IndyCryptoError::InvalidStructure(err)) => assert!(err.contains("non-numeric attribute 'name'")),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn init_ge_proof_works_for_unsatisfied_lt_predicate() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::LT, value: 25 };
+        let credential_values = issuer::mocks::credential_values();
+
+        match ProofBuilder::_init_ge_proofs(&pk, &init_eq_proof.m_tilde, &credential_values, vec![&predicate], 1) {
+            Err(IndyCryptoError::InvalidStructure(err)) => assert!(err.contains("Predicate is not satisfied")),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn init_ge_proof_works_for_64_bit_delta() {
+        let pk = issuer::mocks::credential_primary_public_key();
+        let init_eq_proof = mocks::primary_equal_init_proof();
+        let predicate = Predicate { attr_name: "age".to_owned(), p_type: PredicateType::GE, value: ::std::i64::MIN };
+        let credential_values = issuer::mocks::credential_values();
+
+        let ge_proofs = ProofBuilder::_init_ge_proofs(&pk, &init_eq_proof.m_tilde, &credential_values, vec![&predicate], 1).unwrap();
+        assert_eq!(1, ge_proofs.len());
+    }
+
+    #[test]
+    fn check_add_sub_proof_request_params_consistency_works() {
+        let credential_schema = issuer::mocks::credential_schema();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let credential_values = issuer::mocks::credential_values();
+        let sub_proof_request = mocks::sub_proof_request();
+
+        ProofBuilder::_check_add_sub_proof_request_params_consistency(&credential_values,
+                                                                      &sub_proof_request,
+                                                                      &credential_schema,
+                                                                      &non_credential_schema).unwrap();
+    }
+
+    #[test]
+    fn check_add_sub_proof_request_params_consistency_works_for_all_mismatches() {
+        let credential_schema = issuer::mocks::credential_schema();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+
+        let mut credential_values = issuer::mocks::credential_values();
+        credential_values.attrs_values.remove("height");
+        credential_values.attrs_values.remove("sex");
+        credential_values.attrs_values.insert("zip".to_string(), CredentialValue::Known { value: BigNumber::from_u32(1).unwrap() });
+
+        let mut sub_proof_request_builder = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_revealed_attr("status").unwrap();
+        sub_proof_request_builder.add_predicate("period", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let err = ProofBuilder::_check_add_sub_proof_request_params_consistency(&credential_values,
+                                                                                &sub_proof_request,
+                                                                                &credential_schema,
+                                                                                &non_credential_schema).unwrap_err();
+        let message = format!("{}", err);
+        for attr in ["height", "sex", "zip", "status", "period"].iter() {
+            assert!(message.contains(attr), "{} is not reported in: {}", attr, message);
+        }
+    }
+
+    #[test]
+    fn check_common_attributes_consistency_works() {
+        let credential_schema = issuer::mocks::credential_schema();
+        let non_credential_schema = issuer::mocks::non_credential_schema();
+        let sub_proof_request = mocks::sub_proof_request();
+
+        let common_attributes = hashmap!["master_secret".to_string() => mocks::m1_t(), "age".to_string() => mocks::m1_t()];
+        ProofBuilder::_check_common_attributes_consistency(&common_attributes, &sub_proof_request, &credential_schema, &non_credential_schema).unwrap();
+
+        let common_attributes = hashmap!["name".to_string() => mocks::m1_t()];
+        assert!(ProofBuilder::_check_common_attributes_consistency(&common_attributes, &sub_proof_request, &credential_schema, &non_credential_schema).is_err());
+
+        let common_attributes = hashmap!["did".to_string() => mocks::m1_t()];
+        assert!(ProofBuilder::_check_common_attributes_consistency(&common_attributes, &sub_proof_request, &credential_schema, &non_credential_schema).is_err());
+    }
+
+    #[test]
+    fn add_common_attribute_works_for_added_sub_proof_request() {
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&mocks::sub_proof_request(),
+                                            &issuer::mocks::credential_schema(),
+                                            &issuer::mocks::non_credential_schema(),
+                                            &mocks::credential(),
+                                            &issuer::mocks::credential_values(),
+                                            &issuer::mocks::credential_public_key(),
+                                            None,
+                                            None).unwrap();
+
+        assert!(proof_builder.add_common_attribute("sex").is_err());
+    }
 
-        assert_eq!(mocks::primary_ge_init_proof(), init_ge_proof);
+    #[test]
+    fn add_extension_sub_proof_request_works_for_missed_base_sub_proof_request() {
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        let res = proof_builder.add_extension_sub_proof_request(&mocks::sub_proof_request(),
+                                                                &issuer::mocks::credential_schema(),
+                                                                &issuer::mocks::non_credential_schema(),
+                                                                &mocks::credential(),
+                                                                &issuer::mocks::credential_values(),
+                                                                &issuer::mocks::credential_public_key());
+        assert!(res.is_err());
     }
 
     #[test]
@@ -1788,7 +3528,8 @@ mod tests {
                                                            &credential_schema,
                                                            &non_credential_schema,
                                                            &sub_proof_request,
-                                                           Some(m2_tilde)).unwrap();
+                                                           Some(m2_tilde),
+                                                           1).unwrap();
         assert_eq!(mocks::primary_init_proof(), init_proof);
     }
 
@@ -2070,7 +3811,8 @@ pub mod mocks {
     pub fn credential() -> CredentialSignature {
         CredentialSignature {
             p_credential: primary_credential(),
-            r_credential: Some(issuer::mocks::revocation_credential())
+            r_credential: Some(issuer::mocks::revocation_credential()),
+            attr_r_credentials: BTreeMap::new()
         }
     }
 
@@ -2108,14 +3850,22 @@ pub mod mocks {
     pub fn proof() -> Proof {
         Proof {
             proofs: vec![subproof()],
-            aggregated_proof: aggregated_proof()
+            aggregated_proof: aggregated_proof(),
+            guardianship: None,
+            challenge_reduction: ChallengeReduction::V1Full,
+            snark_proofs: Vec::new(),
+            self_attested_attrs: BTreeMap::new()
         }
     }
 
     pub fn subproof() -> SubProof {
         SubProof {
             primary_proof: primary_proof(),
-            non_revoc_proof: Some(non_revoc_proof())
+            non_revoc_proof: Some(non_revoc_proof()),
+            attr_non_revoc_proofs: BTreeMap::new(),
+            committed_attr_proofs: BTreeMap::new(),
+            sub_proof_request_fingerprint: Vec::new(),
+            non_revoc_timestamp: None
         }
     }
 
@@ -2233,6 +3983,7 @@ pub mod mocks {
             revealed_attrs: btreemap![
                 "name".to_string() => BigNumber::from_dec("66682250590915135919393234675423675079281389286836524491448775067034910960723").unwrap()
             ],
+            revealed_raw_attrs: BTreeMap::new(),
             a_prime: BigNumber::from_dec("93850854506025106167175657367900738564840399460457583396522672546367771557204596986051012396385435450263898123125896474854176367786952154894815573554451004746144139656996044265545613968836176711502602815031392209790095794160045376494471161541029201092195175557986308757797292716881081775201092320235240062158880723682328272460090331253190919323449053508332270184449026105339413097644934519533429034485982687030017670766107427442501537423985935074367321676374406375566791092427955935956566771002472855738585522175250186544831364686282512410608147641314561395934098066750903464501612432084069923446054698174905994358631").unwrap(),
             e: BigNumber::from_dec("162083298053730499878539837415798033696428693449892281052193919207514842725975444071338657195491572547562439622393591965427898285748359108").unwrap(),
             v: BigNumber::from_dec("241132863422049783305938040060597331735278274539541049316128678268379301866997158072011728743321723078574060931449243960464715113938435991871547190135480379265493203441002211218757120311064385792274455797457074741542288420192538286547871288116110058144080647854995527978708188991483561739974917309498779192480418427060775726652318167442183177955447797995160859302520108340826199956754805286213211181508112097818654928169122460464135690611512133363376553662825967455495276836834812520601471833287810311342575033448652033691127511180098524259451386027266077398672694996373787324223860522678035901333613641370426224798680813171225438770578377781015860719028452471648107174226406996348525110692233661632116547069810544117288754524961349911209241835217711929316799411645465546281445291569655422683908113895340361971530636987203042713656548617543163562701947578529101436799250628979720035967402306966520999250819096598649121167").unwrap(),
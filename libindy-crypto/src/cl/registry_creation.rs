@@ -0,0 +1,430 @@
+//! Staged, resumable creation of revocation registry.
+//!
+//! For large `max_cred_num` creation takes hours: accumulator of issuance by default registry sums
+//! `max_cred_num` tails and tails file holds `2 * max_cred_num + 1` tails. `RevocationRegistryCreation`
+//! splits creation into steps (keys, accumulator, tails) that run in bounded batches. After every batch
+//! issuer persists produced artifacts and then the creation manifest (it is serializable), so failed
+//! creation is resumed from the last persisted manifest instead of restarted.
+//!
+//! Manifest records progress of every step and SHA-256 hashes of produced artifacts, so artifacts
+//! loaded from storage on resume are checked to be the ones the manifest was written for.
+//! Manifest holds no secrets: revocation private key is persisted by issuer separately.
+use cl::*;
+use cl::issuer::Issuer;
+use cl::tails::TAIL_SIZE;
+use errors::IndyCryptoError;
+use pair::GroupOrderElement;
+
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+const RESUME_BUFFER_TAILS: usize = 256;
+
+/// Step of revocation registry creation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RevocationRegistryCreationStep {
+    /// Revocation keys aren't created yet.
+    Keys,
+    /// Initial accumulator isn't summed up yet (issuance by default only).
+    Accumulator,
+    /// Tails file isn't written completely yet.
+    Tails,
+    /// All artifacts are created.
+    Complete
+}
+
+/// Manifest of staged revocation registry creation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RevocationRegistryCreation {
+    cred_pub_key_fingerprint: Vec<u8>,
+    max_cred_num: u32,
+    issuance_by_default: bool,
+    rev_key_pub_hash: Option<Vec<u8>>,
+    accum_count: u32,
+    accum: Accumulator,
+    tails_count: u32,
+    tails_hash: Vec<u8>
+}
+
+impl RevocationRegistryCreation {
+    /// Starts creation of revocation registry definition.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    /// * `max_cred_num` - Max credential number in generated registry.
+    /// * `issuance_by_default` - Type of issuance.
+    pub fn new(credential_pub_key: &CredentialPublicKey,
+               max_cred_num: u32,
+               issuance_by_default: bool) -> Result<RevocationRegistryCreation, IndyCryptoError> {
+        trace!("RevocationRegistryCreation::new: >>> credential_pub_key: {:?}, max_cred_num: {:?}, issuance_by_default: {:?}",
+               credential_pub_key, max_cred_num, issuance_by_default);
+
+        RevocationRegistryCreation::_cred_rev_pub_key(credential_pub_key)?;
+
+        if max_cred_num == 0 {
+            return Err(IndyCryptoError::InvalidParam2("Max credential number is 0".to_string()));
+        }
+
+        let creation = RevocationRegistryCreation {
+            cred_pub_key_fingerprint: credential_pub_key.fingerprint()?,
+            max_cred_num,
+            issuance_by_default,
+            rev_key_pub_hash: None,
+            accum_count: 0,
+            accum: Accumulator::new_inf()?,
+            tails_count: 0,
+            tails_hash: Sha256::default().result().to_vec()
+        };
+
+        trace!("RevocationRegistryCreation::new: <<< creation: {:?}", creation);
+
+        Ok(creation)
+    }
+
+    pub fn max_cred_num(&self) -> u32 {
+        self.max_cred_num
+    }
+
+    pub fn issuance_by_default(&self) -> bool {
+        self.issuance_by_default
+    }
+
+    /// Returns the first step that isn't completed.
+    pub fn step(&self) -> RevocationRegistryCreationStep {
+        if self.rev_key_pub_hash.is_none() {
+            RevocationRegistryCreationStep::Keys
+        } else if self.accum_count < self._accum_size() {
+            RevocationRegistryCreationStep::Accumulator
+        } else if self.tails_count < self._tails_size() {
+            RevocationRegistryCreationStep::Tails
+        } else {
+            RevocationRegistryCreationStep::Complete
+        }
+    }
+
+    /// Creates revocation keys and records hash of public key.
+    ///
+    /// Keys are random, so they are created only once: issuer has to persist both keys
+    /// before the manifest and load them on resume.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    pub fn create_keys(&mut self,
+                       credential_pub_key: &CredentialPublicKey) -> Result<(RevocationKeyPublic, RevocationKeyPrivate), IndyCryptoError> {
+        trace!("RevocationRegistryCreation::create_keys: >>> credential_pub_key: {:?}", credential_pub_key);
+
+        if self.rev_key_pub_hash.is_some() {
+            return Err(IndyCryptoError::InvalidState("Revocation keys are already created: load persisted keys".to_string()));
+        }
+
+        let cred_rev_pub_key = self._check_cred_pub_key(credential_pub_key)?;
+
+        let (rev_key_pub, rev_key_priv) = Issuer::_new_revocation_registry_keys(cred_rev_pub_key, self.max_cred_num, GroupOrderElement::new()?)?;
+
+        self.rev_key_pub_hash = Some(_hash_rev_key_pub(&rev_key_pub)?);
+
+        trace!("RevocationRegistryCreation::create_keys: <<< rev_key_pub: {:?}, rev_key_priv: {:?}", rev_key_pub, secret!(&rev_key_priv));
+
+        Ok((rev_key_pub, rev_key_priv))
+    }
+
+    /// Checks that keys loaded from storage are the keys the manifest was written for.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    /// * `rev_key_pub` - Revocation public key loaded from storage.
+    /// * `rev_key_priv` - Revocation private key loaded from storage.
+    pub fn check_keys(&self,
+                      credential_pub_key: &CredentialPublicKey,
+                      rev_key_pub: &RevocationKeyPublic,
+                      rev_key_priv: &RevocationKeyPrivate) -> Result<(), IndyCryptoError> {
+        trace!("RevocationRegistryCreation::check_keys: >>> credential_pub_key: {:?}, rev_key_pub: {:?}, rev_key_priv: {:?}",
+               credential_pub_key, rev_key_pub, secret!(rev_key_priv));
+
+        if self.rev_key_pub_hash != Some(_hash_rev_key_pub(rev_key_pub)?) {
+            return Err(IndyCryptoError::InvalidStructure("Revocation public key doesn't match creation manifest".to_string()));
+        }
+
+        self._check_rev_key_priv(credential_pub_key, rev_key_priv)?;
+
+        trace!("RevocationRegistryCreation::check_keys: <<<");
+
+        Ok(())
+    }
+
+    /// Sums at most `max_tails` next tails into initial accumulator.
+    /// Returns revocation registry once all the tails are summed (immediately for issuance on demand).
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    /// * `rev_key_priv` - Revocation private key created by `create_keys`.
+    /// * `max_tails` - Max number of tails summed by this call.
+    pub fn build_accumulator(&mut self,
+                             credential_pub_key: &CredentialPublicKey,
+                             rev_key_priv: &RevocationKeyPrivate,
+                             max_tails: u32) -> Result<Option<RevocationRegistry>, IndyCryptoError> {
+        trace!("RevocationRegistryCreation::build_accumulator: >>> credential_pub_key: {:?}, rev_key_priv: {:?}, max_tails: {:?}",
+               credential_pub_key, secret!(rev_key_priv), max_tails);
+
+        if max_tails == 0 {
+            return Err(IndyCryptoError::InvalidParam3("Max tails number is 0".to_string()));
+        }
+
+        let cred_rev_pub_key = self._check_rev_key_priv(credential_pub_key, rev_key_priv)?;
+
+        let end = self.accum_count.saturating_add(max_tails).min(self._accum_size());
+        let mut accum = self.accum;
+
+        for i in self.accum_count + 1..end + 1 {
+            let index = Issuer::_get_index(self.max_cred_num, i);
+            accum = accum.add(&Tail::new_tail(index, &cred_rev_pub_key.g_dash, &rev_key_priv.gamma)?)?;
+        }
+
+        self.accum = accum;
+        self.accum_count = end;
+
+        let rev_reg = self.rev_reg();
+
+        trace!("RevocationRegistryCreation::build_accumulator: <<< rev_reg: {:?}", rev_reg);
+
+        Ok(rev_reg)
+    }
+
+    /// Returns revocation registry if accumulator step is completed.
+    pub fn rev_reg(&self) -> Option<RevocationRegistry> {
+        if self.rev_key_pub_hash.is_some() && self.accum_count == self._accum_size() {
+            Some(RevocationRegistry { accum: self.accum })
+        } else {
+            None
+        }
+    }
+
+    /// Returns length of tails file part written so far: tails file has to be truncated to it on resume.
+    pub fn tails_offset(&self) -> u64 {
+        self.tails_count as u64 * TAIL_SIZE as u64
+    }
+
+    /// Returns tails file length and SHA-256 hash Issuer has to publish if tails step is completed.
+    pub fn tails_hash(&self) -> Option<(u64, Vec<u8>)> {
+        if self.tails_count == self._tails_size() {
+            Some((self.tails_offset(), self.tails_hash.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Starts or resumes writing of tails file.
+    ///
+    /// Hash state can't be persisted, so the already written part is hashed again and checked against the manifest.
+    ///
+    /// # Arguments
+    /// * `credential_pub_key` - Credential public key entity.
+    /// * `rev_key_priv` - Revocation private key created by `create_keys`.
+    /// * `written` - Already written part of tails file (`tails_offset` bytes).
+    pub fn resume_tails<R: Read>(&self,
+                                 credential_pub_key: &CredentialPublicKey,
+                                 rev_key_priv: &RevocationKeyPrivate,
+                                 written: &mut R) -> Result<RevocationTailsWriter, IndyCryptoError> {
+        trace!("RevocationRegistryCreation::resume_tails: >>> credential_pub_key: {:?}, rev_key_priv: {:?}",
+               credential_pub_key, secret!(rev_key_priv));
+
+        let cred_rev_pub_key = self._check_rev_key_priv(credential_pub_key, rev_key_priv)?;
+
+        let mut hasher = Sha256::default();
+        let mut buffer = vec![0u8; RESUME_BUFFER_TAILS * TAIL_SIZE];
+        let mut offset = 0u64;
+
+        while offset < self.tails_offset() {
+            let len = ::std::cmp::min(buffer.len() as u64, self.tails_offset() - offset) as usize;
+            written.read_exact(&mut buffer[..len]).map_err(IndyCryptoError::IOError)?;
+            hasher.input(&buffer[..len]);
+            offset += len as u64;
+        }
+
+        if hasher.clone().result().as_slice() != self.tails_hash.as_slice() {
+            return Err(IndyCryptoError::InvalidStructure("Written tails don't match creation manifest".to_string()));
+        }
+
+        let mut generator = RevocationTailsGenerator::new(self.max_cred_num,
+                                                          rev_key_priv.gamma.clone(),
+                                                          cred_rev_pub_key.g_dash.clone());
+        generator.current_index = self.tails_count;
+
+        let tails_writer = RevocationTailsWriter { generator, hasher };
+
+        trace!("RevocationRegistryCreation::resume_tails: <<< tails_count: {:?}", self.tails_count);
+
+        Ok(tails_writer)
+    }
+
+    fn _accum_size(&self) -> u32 {
+        if self.issuance_by_default { self.max_cred_num } else { 0 }
+    }
+
+    fn _tails_size(&self) -> u32 {
+        2 * self.max_cred_num + 1
+    }
+
+    fn _cred_rev_pub_key(credential_pub_key: &CredentialPublicKey) -> Result<&CredentialRevocationPublicKey, IndyCryptoError> {
+        credential_pub_key.r_key
+            .as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure("There are not revocation keys in the credential public key.".to_string()))
+    }
+
+    fn _check_cred_pub_key<'a>(&self, credential_pub_key: &'a CredentialPublicKey) -> Result<&'a CredentialRevocationPublicKey, IndyCryptoError> {
+        if credential_pub_key.fingerprint()? != self.cred_pub_key_fingerprint {
+            return Err(IndyCryptoError::InvalidStructure("Credential public key doesn't match creation manifest".to_string()));
+        }
+        RevocationRegistryCreation::_cred_rev_pub_key(credential_pub_key)
+    }
+
+    /// Checks private key by public key derived from it, so the manifest holds no hash of secret.
+    fn _check_rev_key_priv<'a>(&self,
+                               credential_pub_key: &'a CredentialPublicKey,
+                               rev_key_priv: &RevocationKeyPrivate) -> Result<&'a CredentialRevocationPublicKey, IndyCryptoError> {
+        let rev_key_pub_hash = self.rev_key_pub_hash.as_ref()
+            .ok_or(IndyCryptoError::InvalidState("Revocation keys aren't created yet".to_string()))?;
+
+        let cred_rev_pub_key = self._check_cred_pub_key(credential_pub_key)?;
+
+        let (rev_key_pub, _) = Issuer::_new_revocation_registry_keys(cred_rev_pub_key, self.max_cred_num, rev_key_priv.gamma.clone())?;
+
+        if _hash_rev_key_pub(&rev_key_pub)? != *rev_key_pub_hash {
+            return Err(IndyCryptoError::InvalidStructure("Revocation private key doesn't match creation manifest".to_string()));
+        }
+
+        Ok(cred_rev_pub_key)
+    }
+}
+
+/// Writer of tails file resumed by `RevocationRegistryCreation::resume_tails`.
+#[derive(Debug)]
+pub struct RevocationTailsWriter {
+    generator: RevocationTailsGenerator,
+    hasher: Sha256
+}
+
+impl RevocationTailsWriter {
+    /// Writes at most `max_tails` next tails to `writer` and records progress in `creation`.
+    /// Returns true once tails file is complete.
+    ///
+    /// Written tails must be durably stored (e.g. file synced) before updated manifest is persisted.
+    ///
+    /// # Arguments
+    /// * `creation` - Manifest the writer was resumed from.
+    /// * `writer` - Destination of tails file positioned at `tails_offset`.
+    /// * `max_tails` - Max number of tails written by this call.
+    pub fn write<W: Write>(&mut self,
+                           creation: &mut RevocationRegistryCreation,
+                           writer: &mut W,
+                           max_tails: u32) -> Result<bool, IndyCryptoError> {
+        trace!("RevocationTailsWriter::write: >>> creation: {:?}, max_tails: {:?}", creation, max_tails);
+
+        if max_tails == 0 {
+            return Err(IndyCryptoError::InvalidParam3("Max tails number is 0".to_string()));
+        }
+
+        if self.generator.current_index != creation.tails_count || self.generator.size != creation._tails_size() {
+            return Err(IndyCryptoError::InvalidState("Tails writer doesn't match creation manifest".to_string()));
+        }
+
+        for tail in self.generator.tails().take(max_tails as usize) {
+            let bytes = tail?.to_bytes()?;
+            self.hasher.input(&bytes);
+            writer.write_all(&bytes).map_err(IndyCryptoError::IOError)?;
+        }
+        writer.flush().map_err(IndyCryptoError::IOError)?;
+
+        creation.tails_count = self.generator.current_index;
+        creation.tails_hash = self.hasher.clone().result().to_vec();
+
+        let complete = creation.tails_count == creation._tails_size();
+
+        trace!("RevocationTailsWriter::write: <<< complete: {:?}", complete);
+
+        Ok(complete)
+    }
+}
+
+fn _hash_rev_key_pub(rev_key_pub: &RevocationKeyPublic) -> Result<Vec<u8>, IndyCryptoError> {
+    let mut hasher = Sha256::default();
+    hasher.input(::utils::json::to_canonical_json(rev_key_pub)?.as_bytes());
+    Ok(hasher.result().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cl::issuer::mocks;
+    use cl::tails::write_tails;
+
+    // Persists and loads manifest the way issuer does between batches
+    fn _reload(creation: &RevocationRegistryCreation) -> RevocationRegistryCreation {
+        ::serde_json::from_str(&::serde_json::to_string(creation).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn revocation_registry_creation_works_for_resume() {
+        let cred_pub_key = mocks::credential_public_key();
+        let g_dash = cred_pub_key.r_key.as_ref().unwrap().g_dash.clone();
+
+        let mut creation = RevocationRegistryCreation::new(&cred_pub_key, 5, true).unwrap();
+        assert_eq!(RevocationRegistryCreationStep::Keys, creation.step());
+
+        let (rev_key_pub, rev_key_priv) = creation.create_keys(&cred_pub_key).unwrap();
+        assert!(creation.create_keys(&cred_pub_key).is_err());
+
+        let mut creation = _reload(&creation);
+        creation.check_keys(&cred_pub_key, &rev_key_pub, &rev_key_priv).unwrap();
+        assert!(creation.check_keys(&cred_pub_key, &mocks::revocation_key_public(), &rev_key_priv).is_err());
+        assert_eq!(RevocationRegistryCreationStep::Accumulator, creation.step());
+
+        assert!(creation.build_accumulator(&cred_pub_key, &rev_key_priv, 2).unwrap().is_none());
+        let mut creation = _reload(&creation);
+        assert!(creation.build_accumulator(&cred_pub_key, &rev_key_priv, 2).unwrap().is_none());
+        let rev_reg = creation.build_accumulator(&cred_pub_key, &rev_key_priv, 2).unwrap().unwrap();
+
+        let mut expected_accum = Accumulator::new_inf().unwrap();
+        for i in 1..6 {
+            expected_accum = expected_accum.add(&Tail::new_tail(i, &g_dash, &rev_key_priv.gamma).unwrap()).unwrap();
+        }
+        assert_eq!(expected_accum, rev_reg.accum);
+        assert_eq!(RevocationRegistryCreationStep::Tails, creation.step());
+
+        let mut tails = Vec::new();
+        let mut tails_writer = creation.resume_tails(&cred_pub_key, &rev_key_priv, &mut &tails[..]).unwrap();
+        assert!(!tails_writer.write(&mut creation, &mut tails, 4).unwrap());
+
+        // Failure before the next manifest is persisted: tails written after the persisted one are truncated
+        let mut creation = _reload(&creation);
+        assert!(!tails_writer.write(&mut _reload(&creation), &mut tails, 4).unwrap());
+        tails.truncate(creation.tails_offset() as usize);
+
+        let mut tails_writer = creation.resume_tails(&cred_pub_key, &rev_key_priv, &mut &tails[..]).unwrap();
+        assert!(tails_writer.write(&mut creation, &mut tails, 10).unwrap());
+        assert_eq!(RevocationRegistryCreationStep::Complete, creation.step());
+        assert_eq!(rev_reg.accum, creation.rev_reg().unwrap().accum);
+
+        let mut generator = RevocationTailsGenerator::new(5, rev_key_priv.gamma.clone(), g_dash);
+        let mut expected_tails = Vec::new();
+        let expected_hash = write_tails(&mut generator, &mut expected_tails).unwrap();
+        assert_eq!(expected_tails, tails);
+        assert_eq!(expected_hash, creation.tails_hash().unwrap());
+    }
+
+    #[test]
+    fn resume_tails_works_for_corrupted_tails() {
+        let cred_pub_key = mocks::credential_public_key();
+
+        let mut creation = RevocationRegistryCreation::new(&cred_pub_key, 5, false).unwrap();
+        let (_, rev_key_priv) = creation.create_keys(&cred_pub_key).unwrap();
+        assert!(creation.rev_reg().is_some());
+
+        let mut tails = Vec::new();
+        let mut tails_writer = creation.resume_tails(&cred_pub_key, &rev_key_priv, &mut &tails[..]).unwrap();
+        tails_writer.write(&mut creation, &mut tails, 3).unwrap();
+
+        tails[0] ^= 1;
+        assert!(creation.resume_tails(&cred_pub_key, &rev_key_priv, &mut &tails[..]).is_err());
+    }
+}
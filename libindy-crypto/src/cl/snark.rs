@@ -0,0 +1,112 @@
+//! Experimental bridge to external zk-SNARK circuits for predicates beyond `GE`/`LE`/`GT`/`LT`.
+//!
+//! Verifier requests commitments to hidden attributes with `SubProofRequestBuilder::add_committed_attr`.
+//! CL proof contains commitment `Z^m * S^r mod n` (over the credential primary public key) for every
+//! such attribute and proves that it commits to the credential attribute value.
+//! External circuit proves a policy over committed values: it takes commitment openings on the prover side
+//! and commitments as public inputs on the verifier side. SNARK proofs are included in the challenge
+//! of the CL proof, so they can't be detached from it.
+use bn::BigNumber;
+use errors::IndyCryptoError;
+
+/// Commitment to an attribute of a sub proof. Public input of external circuit.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AttributeCommitment {
+    sub_proof_index: usize,
+    attr_name: String,
+    commitment: BigNumber
+}
+
+impl AttributeCommitment {
+    pub(crate) fn new(sub_proof_index: usize, attr_name: &str, commitment: BigNumber) -> AttributeCommitment {
+        AttributeCommitment { sub_proof_index, attr_name: attr_name.to_owned(), commitment }
+    }
+
+    /// Returns index of sub proof (in order of adding sub proof requests) the attribute belongs to.
+    pub fn sub_proof_index(&self) -> usize {
+        self.sub_proof_index
+    }
+
+    pub fn attr_name(&self) -> &str {
+        &self.attr_name
+    }
+
+    pub fn commitment(&self) -> &BigNumber {
+        &self.commitment
+    }
+}
+
+/// Opening of attribute commitment known to Prover only. Private input of external circuit.
+#[derive(Debug)]
+pub struct AttributeCommitmentOpening {
+    commitment: AttributeCommitment,
+    value: BigNumber,
+    blinding: BigNumber
+}
+
+impl AttributeCommitmentOpening {
+    pub(crate) fn new(commitment: AttributeCommitment, value: BigNumber, blinding: BigNumber) -> AttributeCommitmentOpening {
+        AttributeCommitmentOpening { commitment, value, blinding }
+    }
+
+    pub fn commitment(&self) -> &AttributeCommitment {
+        &self.commitment
+    }
+
+    /// Returns encoded attribute value `m`.
+    pub fn value(&self) -> &BigNumber {
+        &self.value
+    }
+
+    /// Returns commitment blinding factor `r`.
+    pub fn blinding(&self) -> &BigNumber {
+        &self.blinding
+    }
+}
+
+/// Proof created by external circuit over attribute commitments.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SnarkProof {
+    #[serde(alias = "circuitId")]
+    circuit_id: String,
+    proof: Vec<u8>
+}
+
+impl SnarkProof {
+    pub(crate) fn new(circuit_id: &str, proof: Vec<u8>) -> SnarkProof {
+        SnarkProof { circuit_id: circuit_id.to_owned(), proof }
+    }
+
+    pub fn circuit_id(&self) -> &str {
+        &self.circuit_id
+    }
+
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        Ok(::serde_json::to_vec(self)?)
+    }
+}
+
+/// Prover side of external circuit.
+pub trait SnarkProver {
+    /// Creates proof of the circuit over committed attribute values.
+    ///
+    /// # Arguments
+    /// * `circuit_id` - Identifier of the circuit (policy) to prove.
+    /// * `openings` - Openings of all attribute commitments of the proof.
+    fn prove(&self, circuit_id: &str, openings: &[AttributeCommitmentOpening]) -> Result<Vec<u8>, IndyCryptoError>;
+}
+
+/// Verifier side of external circuit.
+pub trait SnarkVerifier {
+    /// Verifies proof of the circuit and returns true - if proof valid or false otherwise.
+    ///
+    /// # Arguments
+    /// * `circuit_id` - Identifier of the circuit (policy) to verify.
+    /// * `commitments` - All attribute commitments of the proof.
+    /// * `proof` - Proof created by `SnarkProver`.
+    fn verify(&self, circuit_id: &str, commitments: &[AttributeCommitment], proof: &[u8]) -> Result<bool, IndyCryptoError>;
+}
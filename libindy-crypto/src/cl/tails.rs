@@ -0,0 +1,562 @@
+//! Tails file: concatenation of `Tail::to_bytes` of all tails in order they are produced by
+//! `RevocationTailsGenerator`. Issuer publishes SHA-256 hash of the file together with revocation registry.
+//!
+//! `TailsVerifier` checks downloaded file against published hash chunk by chunk while it is downloaded,
+//! so the file is never buffered and re-scanned. `FileTailsAccessor` reads tails from the file on demand,
+//! `MmapTailsAccessor` maps the file into memory read-only and lets OS page it in and out.
+use cl::{RevocationTailsAccessor, RevocationTailsGenerator, SimpleTailsAccessor, Tail};
+use errors::IndyCryptoError;
+use pair::PointG2;
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Size of one tail in the tails file.
+pub const TAIL_SIZE: usize = PointG2::BYTES_REPR_SIZE;
+
+const HASH_SIZE: usize = 32;
+const RESUME_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Number of tails `MmapTailsAccessor::open` keeps deserialized.
+pub const DEFAULT_TAILS_CACHE_SIZE: usize = 4096;
+
+/// Writes all the rest tails of generator to writer.
+/// Returns tails file length and SHA-256 hash Issuer has to publish.
+///
+/// # Arguments
+/// * `rev_tails_generator` - Revocation tails generator.
+/// * `writer` - Destination of tails file.
+pub fn write_tails<W: Write>(rev_tails_generator: &mut RevocationTailsGenerator,
+                             writer: &mut W) -> Result<(u64, Vec<u8>), IndyCryptoError> {
+    trace!("write_tails: >>> rev_tails_generator: {:?}", rev_tails_generator);
+
+    let mut hasher = Sha256::default();
+    let mut len = 0u64;
+
+    for tail in rev_tails_generator.tails() {
+        let bytes = tail?.to_bytes()?;
+        hasher.input(&bytes);
+        writer.write_all(&bytes).map_err(IndyCryptoError::IOError)?;
+        len += bytes.len() as u64;
+    }
+
+    let hash = hasher.result().to_vec();
+
+    trace!("write_tails: <<< len: {:?}, hash: {:?}", len, hash);
+
+    Ok((len, hash))
+}
+
+/// Reads whole tails file from reader tail by tail, so the file is never buffered as a whole.
+///
+/// # Arguments
+/// * `reader` - Source of tails file.
+pub fn read_tails<R: Read>(reader: &mut R) -> Result<SimpleTailsAccessor, IndyCryptoError> {
+    trace!("read_tails: >>>");
+
+    let mut tails = Vec::new();
+    let mut bytes = vec![0u8; TAIL_SIZE];
+
+    loop {
+        let mut read = 0;
+        while read < TAIL_SIZE {
+            match reader.read(&mut bytes[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(IndyCryptoError::IOError(err))
+            }
+        }
+
+        if read == 0 {
+            break;
+        }
+
+        if read < TAIL_SIZE {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tails file is truncated: last tail has {} of {} bytes", read, TAIL_SIZE)));
+        }
+
+        tails.push(Tail::from_bytes(&bytes)?);
+    }
+
+    trace!("read_tails: <<< tails count: {:?}", tails.len());
+
+    Ok(SimpleTailsAccessor { tails })
+}
+
+/// Progress of tails file verification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TailsProgress {
+    bytes_verified: u64,
+    total_bytes: u64
+}
+
+impl TailsProgress {
+    pub fn bytes_verified(&self) -> u64 {
+        self.bytes_verified
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Returns count of completely received tails.
+    pub fn tails_verified(&self) -> u64 {
+        self.bytes_verified / TAIL_SIZE as u64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.bytes_verified == self.total_bytes
+    }
+}
+
+/// Incremental verifier of downloaded tails file against published hash.
+#[derive(Debug)]
+pub struct TailsVerifier {
+    expected_hash: Vec<u8>,
+    total_bytes: u64,
+    offset: u64,
+    hasher: Sha256
+}
+
+impl TailsVerifier {
+    /// Creates verifier for download from the beginning of the file.
+    ///
+    /// # Arguments
+    /// * `expected_hash` - Published SHA-256 hash of tails file.
+    /// * `total_bytes` - Published length of tails file.
+    pub fn new(expected_hash: &[u8], total_bytes: u64) -> Result<TailsVerifier, IndyCryptoError> {
+        trace!("TailsVerifier::new: >>> expected_hash: {:?}, total_bytes: {:?}", expected_hash, total_bytes);
+
+        if expected_hash.len() != HASH_SIZE {
+            return Err(IndyCryptoError::InvalidParam1(format!("Invalid len of tails hash: expected {}, actual {}", HASH_SIZE, expected_hash.len())));
+        }
+
+        if total_bytes == 0 || total_bytes % TAIL_SIZE as u64 != 0 {
+            return Err(IndyCryptoError::InvalidParam2(format!("Tails file length {} is not multiple of tail size {}", total_bytes, TAIL_SIZE)));
+        }
+
+        let verifier = TailsVerifier {
+            expected_hash: expected_hash.to_vec(),
+            total_bytes,
+            offset: 0,
+            hasher: Sha256::default()
+        };
+
+        trace!("TailsVerifier::new: <<< verifier: {:?}", verifier);
+
+        Ok(verifier)
+    }
+
+    /// Creates verifier for download interrupted at `offset`.
+    ///
+    /// Hash state can't be persisted, so the already downloaded part is hashed again from local storage.
+    /// Download continues from `offset` (e.g. with HTTP Range request).
+    ///
+    /// # Arguments
+    /// * `expected_hash` - Published SHA-256 hash of tails file.
+    /// * `total_bytes` - Published length of tails file.
+    /// * `downloaded` - Already downloaded part of tails file.
+    /// * `offset` - Length of already downloaded part.
+    pub fn resume<R: Read>(expected_hash: &[u8],
+                           total_bytes: u64,
+                           downloaded: &mut R,
+                           offset: u64) -> Result<TailsVerifier, IndyCryptoError> {
+        trace!("TailsVerifier::resume: >>> expected_hash: {:?}, total_bytes: {:?}, offset: {:?}", expected_hash, total_bytes, offset);
+
+        let mut verifier = TailsVerifier::new(expected_hash, total_bytes)?;
+        let mut buffer = vec![0u8; RESUME_BUFFER_SIZE];
+
+        while verifier.offset < offset {
+            let len = ::std::cmp::min(buffer.len() as u64, offset - verifier.offset) as usize;
+            downloaded.read_exact(&mut buffer[..len]).map_err(IndyCryptoError::IOError)?;
+            verifier.update(&buffer[..len])?;
+        }
+
+        trace!("TailsVerifier::resume: <<< verifier: {:?}", verifier);
+
+        Ok(verifier)
+    }
+
+    /// Returns offset the download has to be continued from.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn progress(&self) -> TailsProgress {
+        TailsProgress { bytes_verified: self.offset, total_bytes: self.total_bytes }
+    }
+
+    /// Hashes next downloaded chunk.
+    ///
+    /// # Arguments
+    /// * `chunk` - Next part of tails file.
+    pub fn update(&mut self, chunk: &[u8]) -> Result<TailsProgress, IndyCryptoError> {
+        if self.offset + chunk.len() as u64 > self.total_bytes {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tails file is longer than {} bytes", self.total_bytes)));
+        }
+
+        self.hasher.input(chunk);
+        self.offset += chunk.len() as u64;
+
+        Ok(self.progress())
+    }
+
+    /// Checks that the whole file is received and matches published hash.
+    pub fn finalize(self) -> Result<(), IndyCryptoError> {
+        trace!("TailsVerifier::finalize: >>> offset: {:?}, total_bytes: {:?}", self.offset, self.total_bytes);
+
+        if self.offset != self.total_bytes {
+            return Err(IndyCryptoError::InvalidState(format!("Tails file is incomplete: {} of {} bytes received", self.offset, self.total_bytes)));
+        }
+
+        if self.hasher.result().as_slice() != self.expected_hash.as_slice() {
+            return Err(IndyCryptoError::InvalidStructure("Tails file doesn't match published hash".to_string()));
+        }
+
+        trace!("TailsVerifier::finalize: <<<");
+
+        Ok(())
+    }
+}
+
+/// Implementation of `RevocationTailsAccessor` that reads tails from tails file on demand,
+/// so only the accessed tails are held in memory.
+#[derive(Debug)]
+pub struct FileTailsAccessor {
+    file: Mutex<File>,
+    tails_count: u64
+}
+
+impl FileTailsAccessor {
+    /// Opens tails file written by `write_tails`.
+    /// File content has to be checked against published hash with `TailsVerifier` before.
+    ///
+    /// # Arguments
+    /// * `path` - Path to tails file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileTailsAccessor, IndyCryptoError> {
+        let file = File::open(path).map_err(IndyCryptoError::IOError)?;
+        let len = file.metadata().map_err(IndyCryptoError::IOError)?.len();
+
+        if len % TAIL_SIZE as u64 != 0 {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tails file length {} is not multiple of tail size {}", len, TAIL_SIZE)));
+        }
+
+        Ok(FileTailsAccessor { file: Mutex::new(file), tails_count: len / TAIL_SIZE as u64 })
+    }
+
+    pub fn tails_count(&self) -> u64 {
+        self.tails_count
+    }
+
+    fn _read_tail(&self, tail_id: u32) -> Result<Tail, IndyCryptoError> {
+        if tail_id as u64 >= self.tails_count {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tail {} is out of tails file", tail_id)));
+        }
+
+        let mut bytes = vec![0u8; TAIL_SIZE];
+        {
+            let mut file = self.file.lock()
+                .map_err(|_| IndyCryptoError::InvalidState("Tails file lock is poisoned".to_string()))?;
+            file.seek(SeekFrom::Start(tail_id as u64 * TAIL_SIZE as u64)).map_err(IndyCryptoError::IOError)?;
+            file.read_exact(&mut bytes).map_err(IndyCryptoError::IOError)?;
+        }
+
+        Tail::from_bytes(&bytes)
+    }
+}
+
+impl RevocationTailsAccessor for FileTailsAccessor {
+    fn access_tail(&self, tail_id: u32, accessor: &mut FnMut(&Tail)) -> Result<(), IndyCryptoError> {
+        let tail = self._read_tail(tail_id)?;
+        accessor(&tail);
+        Ok(())
+    }
+}
+
+/// Implementation of `RevocationTailsAccessor` backed by read-only memory mapping of tails file.
+///
+/// Tails are deserialized lazily on the first access and kept in a fixed size cache
+/// (a tail replaces the cached one with the same `tail_id % cache_size`), so repeated witness updates
+/// get near `SimpleTailsAccessor` lookup speed while memory use stays bounded.
+///
+/// Mapped file must not be truncated while the accessor is alive: reading pages past the new end
+/// of the file raises SIGBUS. Accessor checks file length before reading a tail, but it can't prevent
+/// truncation between the check and the read, so use `FileTailsAccessor` for files other processes may modify.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct MmapTailsAccessor {
+    file: File,
+    ptr: *const u8,
+    len: usize,
+    cache: Mutex<Vec<Option<(u32, Tail)>>>
+}
+
+// Mapping is read-only and never changed after creation, cache is guarded by mutex.
+#[cfg(unix)]
+unsafe impl Send for MmapTailsAccessor {}
+
+#[cfg(unix)]
+unsafe impl Sync for MmapTailsAccessor {}
+
+#[cfg(unix)]
+impl MmapTailsAccessor {
+    /// Maps tails file written by `write_tails` caching `DEFAULT_TAILS_CACHE_SIZE` tails.
+    /// File content has to be checked against published hash with `TailsVerifier` before.
+    ///
+    /// # Arguments
+    /// * `path` - Path to tails file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<MmapTailsAccessor, IndyCryptoError> {
+        MmapTailsAccessor::open_with_cache_size(path, DEFAULT_TAILS_CACHE_SIZE)
+    }
+
+    /// Maps tails file written by `write_tails`.
+    /// File content has to be checked against published hash with `TailsVerifier` before.
+    ///
+    /// # Arguments
+    /// * `path` - Path to tails file.
+    /// * `cache_size` - Max number of deserialized tails kept in memory.
+    pub fn open_with_cache_size<P: AsRef<Path>>(path: P, cache_size: usize) -> Result<MmapTailsAccessor, IndyCryptoError> {
+        if cache_size == 0 {
+            return Err(IndyCryptoError::InvalidParam2("Tails cache size must be positive".to_string()));
+        }
+
+        let file = File::open(path).map_err(IndyCryptoError::IOError)?;
+        let len = file.metadata().map_err(IndyCryptoError::IOError)?.len();
+
+        if len == 0 || len % TAIL_SIZE as u64 != 0 {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tails file length {} is not positive multiple of tail size {}", len, TAIL_SIZE)));
+        }
+
+        let len = len as usize;
+
+        let ptr = unsafe {
+            ::libc::mmap(::std::ptr::null_mut(), len, ::libc::PROT_READ, ::libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+        };
+
+        if ptr == ::libc::MAP_FAILED {
+            return Err(IndyCryptoError::IOError(::std::io::Error::last_os_error()));
+        }
+
+        Ok(MmapTailsAccessor { file, ptr: ptr as *const u8, len, cache: Mutex::new(vec![None; cache_size]) })
+    }
+
+    pub fn tails_count(&self) -> u64 {
+        (self.len / TAIL_SIZE) as u64
+    }
+
+    fn _get_tail(&self, tail_id: u32) -> Result<Tail, IndyCryptoError> {
+        if tail_id as u64 >= self.tails_count() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Tail {} is out of tails file", tail_id)));
+        }
+
+        let mut cache = self.cache.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Tails cache lock is poisoned".to_string()))?;
+
+        let slot = tail_id as usize % cache.len();
+
+        if let Some((cached_tail_id, tail)) = cache[slot] {
+            if cached_tail_id == tail_id {
+                return Ok(tail);
+            }
+        }
+
+        let offset = tail_id as usize * TAIL_SIZE;
+
+        // Pages past the end of truncated file raise SIGBUS on access
+        let file_len = self.file.metadata().map_err(IndyCryptoError::IOError)?.len();
+        if file_len < (offset + TAIL_SIZE) as u64 {
+            return Err(IndyCryptoError::InvalidState(format!("Tails file is truncated to {} bytes while mapped", file_len)));
+        }
+
+        let bytes = unsafe { ::std::slice::from_raw_parts(self.ptr.offset(offset as isize), TAIL_SIZE) };
+        let tail = Tail::from_bytes(bytes)?;
+
+        cache[slot] = Some((tail_id, tail));
+
+        Ok(tail)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MmapTailsAccessor {
+    fn drop(&mut self) {
+        unsafe { ::libc::munmap(self.ptr as *mut ::libc::c_void, self.len); }
+    }
+}
+
+#[cfg(unix)]
+impl RevocationTailsAccessor for MmapTailsAccessor {
+    fn access_tail(&self, tail_id: u32, accessor: &mut FnMut(&Tail)) -> Result<(), IndyCryptoError> {
+        let tail = self._get_tail(tail_id)?;
+        accessor(&tail);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use errors::ToErrorCode;
+    use ffi::ErrorCode;
+    use cl::SimpleTailsAccessor;
+    use pair::GroupOrderElement;
+
+    fn _tails_file() -> (Vec<u8>, Vec<u8>) {
+        let mut generator = RevocationTailsGenerator::new(3, GroupOrderElement::new().unwrap(), PointG2::new().unwrap());
+        let mut file = Vec::new();
+        let (len, hash) = write_tails(&mut generator, &mut file).unwrap();
+        assert_eq!(len, file.len() as u64);
+        (file, hash)
+    }
+
+    #[test]
+    fn read_tails_works() {
+        let (file, _) = _tails_file();
+        let rev_tails_accessor = read_tails(&mut file.as_slice()).unwrap();
+        assert_eq!(7, rev_tails_accessor.tails.len());
+        assert_eq!(file[TAIL_SIZE..2 * TAIL_SIZE].to_vec(), rev_tails_accessor.tails[1].to_bytes().unwrap());
+    }
+
+    #[test]
+    fn read_tails_works_for_truncated_file() {
+        let (file, _) = _tails_file();
+        match read_tails(&mut &file[..file.len() - 1]) {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn tails_verifier_works() {
+        let (file, hash) = _tails_file();
+        assert_eq!(7 * TAIL_SIZE, file.len());
+
+        let mut verifier = TailsVerifier::new(&hash, file.len() as u64).unwrap();
+        for chunk in file.chunks(100) {
+            let progress = verifier.update(chunk).unwrap();
+            assert_eq!(verifier.offset(), progress.bytes_verified());
+        }
+        assert!(verifier.progress().is_complete());
+        assert_eq!(7, verifier.progress().tails_verified());
+        verifier.finalize().unwrap();
+    }
+
+    #[test]
+    fn tails_verifier_works_for_resume() {
+        let (file, hash) = _tails_file();
+        let offset = 3 * TAIL_SIZE + 10;
+
+        let mut verifier = TailsVerifier::resume(&hash, file.len() as u64, &mut &file[..offset], offset as u64).unwrap();
+        assert_eq!(offset as u64, verifier.offset());
+        verifier.update(&file[offset..]).unwrap();
+        verifier.finalize().unwrap();
+    }
+
+    #[test]
+    fn tails_verifier_works_for_modified_file() {
+        let (mut file, hash) = _tails_file();
+        file[TAIL_SIZE] ^= 1;
+
+        let mut verifier = TailsVerifier::new(&hash, file.len() as u64).unwrap();
+        verifier.update(&file).unwrap();
+        assert_eq!(ErrorCode::CommonInvalidStructure, verifier.finalize().unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn tails_verifier_works_for_incomplete_and_oversized_file() {
+        let (file, hash) = _tails_file();
+
+        let mut verifier = TailsVerifier::new(&hash, file.len() as u64).unwrap();
+        verifier.update(&file[..TAIL_SIZE]).unwrap();
+        assert_eq!(ErrorCode::CommonInvalidState, verifier.finalize().unwrap_err().to_error_code());
+
+        let mut verifier = TailsVerifier::new(&hash, TAIL_SIZE as u64).unwrap();
+        assert_eq!(ErrorCode::CommonInvalidStructure, verifier.update(&file).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn file_tails_accessor_works() {
+        let mut generator = RevocationTailsGenerator::new(3, GroupOrderElement::new().unwrap(), PointG2::new().unwrap());
+        let path = ::std::env::temp_dir().join(format!("indy_crypto_tails_{}", GroupOrderElement::new().unwrap().to_string().unwrap()));
+        write_tails(&mut generator.clone(), &mut File::create(&path).unwrap()).unwrap();
+
+        let file_tails_accessor = FileTailsAccessor::open(&path).unwrap();
+        let simple_tails_accessor = SimpleTailsAccessor::new(&mut generator).unwrap();
+        assert_eq!(7, file_tails_accessor.tails_count());
+
+        for tail_id in (0..7).rev() {
+            let mut file_tail = None;
+            let mut simple_tail = None;
+            file_tails_accessor.access_tail(tail_id, &mut |tail| file_tail = Some(*tail)).unwrap();
+            simple_tails_accessor.access_tail(tail_id, &mut |tail| simple_tail = Some(*tail)).unwrap();
+            assert_eq!(simple_tail, file_tail);
+        }
+
+        let res = file_tails_accessor.access_tail(7, &mut |_| ());
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn mmap_tails_accessor_works() {
+        let mut generator = RevocationTailsGenerator::new(3, GroupOrderElement::new().unwrap(), PointG2::new().unwrap());
+        let path = ::std::env::temp_dir().join(format!("indy_crypto_tails_{}", GroupOrderElement::new().unwrap().to_string().unwrap()));
+        write_tails(&mut generator.clone(), &mut File::create(&path).unwrap()).unwrap();
+
+        let mmap_tails_accessor = MmapTailsAccessor::open(&path).unwrap();
+        let simple_tails_accessor = SimpleTailsAccessor::new(&mut generator).unwrap();
+        assert_eq!(7, mmap_tails_accessor.tails_count());
+
+        for tail_id in (0..7).chain(0..7) {
+            let mut mmap_tail = None;
+            let mut simple_tail = None;
+            mmap_tails_accessor.access_tail(tail_id, &mut |tail| mmap_tail = Some(*tail)).unwrap();
+            simple_tails_accessor.access_tail(tail_id, &mut |tail| simple_tail = Some(*tail)).unwrap();
+            assert_eq!(simple_tail, mmap_tail);
+        }
+
+        let res = mmap_tails_accessor.access_tail(7, &mut |_| ());
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn mmap_tails_accessor_works_for_small_cache_and_truncated_file() {
+        let mut generator = RevocationTailsGenerator::new(3, GroupOrderElement::new().unwrap(), PointG2::new().unwrap());
+        let path = ::std::env::temp_dir().join(format!("indy_crypto_tails_{}", GroupOrderElement::new().unwrap().to_string().unwrap()));
+        write_tails(&mut generator.clone(), &mut File::create(&path).unwrap()).unwrap();
+
+        assert!(MmapTailsAccessor::open_with_cache_size(&path, 0).is_err());
+
+        let mmap_tails_accessor = MmapTailsAccessor::open_with_cache_size(&path, 2).unwrap();
+        let simple_tails_accessor = SimpleTailsAccessor::new(&mut generator).unwrap();
+
+        for tail_id in (0..7).chain(0..7) {
+            let mut mmap_tail = None;
+            let mut simple_tail = None;
+            mmap_tails_accessor.access_tail(tail_id, &mut |tail| mmap_tail = Some(*tail)).unwrap();
+            simple_tails_accessor.access_tail(tail_id, &mut |tail| simple_tail = Some(*tail)).unwrap();
+            assert_eq!(simple_tail, mmap_tail);
+        }
+        assert_eq!(2, mmap_tails_accessor.cache.lock().unwrap().len());
+
+        ::std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(TAIL_SIZE as u64).unwrap();
+
+        // Tail 6 was the last one cached in its slot, tail 5 has to be read from the mapping
+        mmap_tails_accessor.access_tail(6, &mut |_| ()).unwrap();
+        let res = mmap_tails_accessor.access_tail(5, &mut |_| ());
+        assert_eq!(ErrorCode::CommonInvalidState, res.unwrap_err().to_error_code());
+
+        ::std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,12 +1,20 @@
-use bn::BigNumber;
+use bn::{BigNumber, BigNumberArena};
 use cl::*;
-use cl::constants::{LARGE_E_START_VALUE, ITERATION};
+use cl::constants::ITERATION;
 use cl::helpers::*;
+use cl::snark::SnarkVerifier;
 use errors::IndyCryptoError;
+use utils::commitment::get_pedersen_commitment;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
 use std::iter::FromIterator;
-use utils::get_hash_as_int;
+use std::mem;
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use utils::clock::{Clock, SystemClock, Timestamp};
+use utils::{get_hash_as_int, u64_to_be_bytes};
 
 /// Party that wants to check that prover has some credentials provided by issuer.
 pub struct Verifier {}
@@ -32,6 +40,22 @@ impl Verifier {
         Ok(res)
     }
 
+    /// Creates random nonce for proof request.
+    ///
+    /// # Arguments
+    /// * `bit_length` - Nonce length in bits (80 to 4096).
+    /// * `context` - (Optional) Context string mixed into the nonce, e.g. proof request id.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let _proof_request_nonce = Verifier::new_nonce(128, None).unwrap();
+    /// ```
+    pub fn new_nonce(bit_length: usize, context: Option<&str>) -> Result<Nonce, IndyCryptoError> {
+        new_nonce_with_params(bit_length, context)
+    }
+
     /// Creates and returns proof verifier.
     ///
     /// The purpose of `proof verifier` is check proof provided by Prover.
@@ -45,17 +69,762 @@ impl Verifier {
     pub fn new_proof_verifier() -> Result<ProofVerifier, IndyCryptoError> {
         Ok(ProofVerifier {
             credentials: Vec::new(),
+            limits: ProofLimits::default(),
+            channel_binding: None,
+            common_attributes: BTreeSet::new(),
+            disclosure_policy: DisclosurePolicy::default(),
+            self_attested_attrs: BTreeSet::new(),
+            legacy_policy: LegacyPolicy::default(),
+            protocol_version: None,
+            accepted_challenge_reductions: vec![ChallengeReduction::V1Full, ChallengeReduction::V2ReducedModOrder],
+            clock: VerifierClock(Box::new(SystemClock::new())),
+        })
+    }
+
+    /// Checks that the new accumulator of published `Revocation Registry Delta` corresponds exactly
+    /// to the declared issued and revoked indices: it must equal the previous accumulator
+    /// plus tails of issued indices minus tails of revoked ones.
+    ///
+    /// Accumulator doesn't reveal which indices it contains, so declared changes are also checked
+    /// against the indices issued before the delta: an index already in the accumulator can't be issued
+    /// (its tail would be added twice) and only such indices can be revoked.
+    ///
+    /// Tails are public, so ledgers and observers can detect bogus registry updates
+    /// without the private gamma of revocation registry.
+    ///
+    /// # Arguments
+    /// * `rev_reg_delta` - Revocation registry delta published by issuer.
+    /// * `prev_issued` - Indices contained in the previous accumulator (tracked by observer from previous deltas).
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    pub fn verify_revocation_registry_delta<RTA>(rev_reg_delta: &RevocationRegistryDelta,
+                                                 prev_issued: &HashSet<u32>,
+                                                 max_cred_num: u32,
+                                                 rev_tails_accessor: &RTA) -> Result<bool, IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Verifier::verify_revocation_registry_delta: >>> rev_reg_delta: {:?}, prev_issued: {:?}, max_cred_num: {:?}",
+               rev_reg_delta, prev_issued, max_cred_num);
+
+        let prev_accum = rev_reg_delta.prev_accum
+            .ok_or(IndyCryptoError::InvalidParam1("Revocation registry delta doesn't contain previous accumulator".to_string()))?;
+
+        let out_of_range = rev_reg_delta.issued.iter().chain(rev_reg_delta.revoked.iter())
+            .any(|rev_idx| *rev_idx == 0 || *rev_idx > max_cred_num);
+
+        if out_of_range ||
+            !rev_reg_delta.issued.is_disjoint(&rev_reg_delta.revoked) ||
+            !rev_reg_delta.issued.is_disjoint(prev_issued) ||
+            !rev_reg_delta.revoked.is_subset(prev_issued) {
+            trace!("Verifier::verify_revocation_registry_delta: <<< valid: false");
+            return Ok(false);
+        }
+
+        let mut accum = prev_accum;
+
+        for rev_idx in rev_reg_delta.issued.iter() {
+            access_tail_with(rev_tails_accessor, max_cred_num + 1 - rev_idx, |tail| {
+                accum = accum.add(tail)?;
+                Ok(())
+            })?;
+        }
+
+        for rev_idx in rev_reg_delta.revoked.iter() {
+            access_tail_with(rev_tails_accessor, max_cred_num + 1 - rev_idx, |tail| {
+                accum = accum.sub(tail)?;
+                Ok(())
+            })?;
+        }
+
+        let valid = accum == rev_reg_delta.accum;
+
+        trace!("Verifier::verify_revocation_registry_delta: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Checks that accumulator of the new epoch published in `Revocation Registry Epoch Transition`
+    /// is the sum of tails of carried over indices.
+    ///
+    /// # Arguments
+    /// * `transition` - Epoch transition published by issuer.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `rev_tails_accessor` - Revocation registry tails accessor.
+    pub fn verify_revocation_registry_epoch_transition<RTA>(transition: &RevocationRegistryEpochTransition,
+                                                            max_cred_num: u32,
+                                                            rev_tails_accessor: &RTA) -> Result<bool, IndyCryptoError> where RTA: RevocationTailsAccessor {
+        trace!("Verifier::verify_revocation_registry_epoch_transition: >>> transition: {:?}, max_cred_num: {:?}", transition, max_cred_num);
+
+        if transition.issued.iter().any(|rev_idx| *rev_idx == 0 || *rev_idx > max_cred_num) {
+            trace!("Verifier::verify_revocation_registry_epoch_transition: <<< valid: false");
+            return Ok(false);
+        }
+
+        let mut accum = Accumulator::new_inf()?;
+
+        for rev_idx in transition.issued.iter() {
+            access_tail_with(rev_tails_accessor, max_cred_num + 1 - rev_idx, |tail| {
+                accum = accum.add(tail)?;
+                Ok(())
+            })?;
+        }
+
+        let valid = accum == transition.accum;
+
+        trace!("Verifier::verify_revocation_registry_epoch_transition: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Verifies proof of credential issued with either key of rotation while the predecessor key is in grace period.
+    ///
+    /// Proof doesn't identify the key, so it is verified with every accepted key until it is accepted.
+    ///
+    /// # Arguments
+    /// * `key_rotation` - Key rotation.
+    /// * `clock` - Source of the current time.
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce.
+    /// * `new_proof_verifier` - Creates proof verifier with sub proof requests added for the given key.
+    pub fn verify_with_key_rotation<F, C>(key_rotation: &KeyRotation,
+                                          clock: &C,
+                                          proof: &Proof,
+                                          nonce: &Nonce,
+                                          new_proof_verifier: F) -> Result<bool, IndyCryptoError>
+        where F: Fn(&CredentialPublicKey) -> Result<ProofVerifier, IndyCryptoError>, C: Clock {
+        trace!("Verifier::verify_with_key_rotation: >>> key_rotation: {:?}, proof: {:?}, nonce: {:?}", key_rotation, proof, nonce);
+
+        let mut valid = false;
+        for pub_key in key_rotation.accepted_pub_keys(clock)? {
+            if new_proof_verifier(pub_key)?.verify(proof, nonce)? {
+                valid = true;
+                break;
+            }
+        }
+
+        trace!("Verifier::verify_with_key_rotation: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Verifies archived presentations grouped by issuer (e.g. for periodic compliance sweeps).
+    ///
+    /// Sub proof requests and keys of each group are prepared once in its `ProofVerifier`
+    /// and used for all presentations of the group. Groups are verified in parallel,
+    /// so they are moved to verification threads and consumed by the sweep.
+    /// Presentation that is rejected or fails to verify doesn't stop the sweep, it is reported in `AuditReport`.
+    ///
+    /// # Arguments
+    /// * `proofs_by_issuer` - Presentations grouped by issuer identifier.
+    /// * `threads` - Max number of threads.
+    pub fn verify_grouped(proofs_by_issuer: BTreeMap<String, ProofGroup>,
+                          threads: usize) -> Result<AuditReport, IndyCryptoError> {
+        trace!("Verifier::verify_grouped: >>> issuers: {:?}, threads: {:?}", proofs_by_issuer.len(), threads);
+
+        if threads == 0 {
+            return Err(IndyCryptoError::InvalidParam2("Number of threads must be positive".to_string()));
+        }
+
+        let mut groups = proofs_by_issuer.into_iter().collect::<Vec<(String, ProofGroup)>>();
+        let mut report = AuditReport::default();
+
+        if threads == 1 || groups.len() <= 1 {
+            for &(ref issuer_id, ref group) in groups.iter() {
+                group._verify(issuer_id, &mut report);
+            }
+        } else {
+            let chunk_size = (groups.len() + threads - 1) / threads;
+            let mut handles = Vec::new();
+
+            while !groups.is_empty() {
+                let rest = groups.split_off(::std::cmp::min(chunk_size, groups.len()));
+                let chunk = mem::replace(&mut groups, rest);
+
+                handles.push(thread::spawn(move || {
+                    let mut report = AuditReport::default();
+                    for &(ref issuer_id, ref group) in chunk.iter() {
+                        group._verify(issuer_id, &mut report);
+                    }
+                    report
+                }));
+            }
+
+            let reports = handles
+                .into_iter()
+                .map(|handle| handle.join()
+                    .map_err(|_| IndyCryptoError::InvalidState("Verification thread panicked".to_string())))
+                .collect::<Result<Vec<AuditReport>, IndyCryptoError>>()?;
+
+            for chunk_report in reports {
+                report.merge(chunk_report);
+            }
+        }
+
+        trace!("Verifier::verify_grouped: <<< report: {:?}", report);
+
+        Ok(report)
+    }
+
+    /// Checks credential public key against the key pinned for the issuer, pinning it on first use (TOFU).
+    ///
+    /// Protects deployments without ledger access against substitution of issuer key: once a key is pinned,
+    /// any other key presented for the issuer is rejected with `AnoncredsIssuerKeyChanged`.
+    /// Legitimate key rotations are accepted with `rotate_key_pin`.
+    ///
+    /// # Arguments
+    /// * `key_pin_store` - Store of pinned keys.
+    /// * `issuer_did` - DID of the issuer (or other identifier of the issuer's key, e.g. credential definition id).
+    /// * `credential_pub_key` - Credential public key presented for the issuer.
+    pub fn check_key_pin<KPS>(key_pin_store: &KPS,
+                              issuer_did: &str,
+                              credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> where KPS: KeyPinStore {
+        trace!("Verifier::check_key_pin: >>> issuer_did: {:?}", issuer_did);
+
+        let fingerprint = credential_pub_key.fingerprint()?;
+
+        match key_pin_store.get(issuer_did)? {
+            Some(ref pinned_fingerprint) if *pinned_fingerprint == fingerprint => (),
+            Some(_) => return Err(IndyCryptoError::AnoncredsIssuerKeyChanged(
+                format!("Key {} differs from the key pinned for issuer '{}'", credential_pub_key.key_id()?, issuer_did))),
+            None => key_pin_store.put(issuer_did, &fingerprint)?
+        }
+
+        trace!("Verifier::check_key_pin: <<<");
+
+        Ok(())
+    }
+
+    /// Moves pin of the issuer from predecessor to successor key of verified key rotation.
+    ///
+    /// # Arguments
+    /// * `key_pin_store` - Store of pinned keys.
+    /// * `issuer_did` - DID of the issuer.
+    /// * `key_rotation` - Key rotation checked against rotation statement published by the issuer.
+    pub fn rotate_key_pin<KPS>(key_pin_store: &KPS,
+                               issuer_did: &str,
+                               key_rotation: &KeyRotation) -> Result<(), IndyCryptoError> where KPS: KeyPinStore {
+        trace!("Verifier::rotate_key_pin: >>> issuer_did: {:?}", issuer_did);
+
+        let old_fingerprint = key_rotation.old_pub_key.fingerprint()?;
+        let new_fingerprint = key_rotation.new_pub_key.fingerprint()?;
+
+        match key_pin_store.get(issuer_did)? {
+            Some(ref pinned_fingerprint) if *pinned_fingerprint == old_fingerprint || *pinned_fingerprint == new_fingerprint => (),
+            Some(_) => return Err(IndyCryptoError::AnoncredsIssuerKeyChanged(
+                format!("Rotated key {} isn't the key pinned for issuer '{}'", key_rotation.old_pub_key.key_id()?, issuer_did))),
+            None => ()
+        }
+
+        key_pin_store.put(issuer_did, &new_fingerprint)?;
+
+        trace!("Verifier::rotate_key_pin: <<<");
+
+        Ok(())
+    }
+}
+
+/// Store of issuer key fingerprints pinned by verifier on first use, keyed by issuer DID.
+pub trait KeyPinStore {
+    fn get(&self, issuer_did: &str) -> Result<Option<Vec<u8>>, IndyCryptoError>;
+    fn put(&self, issuer_did: &str, key_fingerprint: &[u8]) -> Result<(), IndyCryptoError>;
+}
+
+/// Simple implementation of `KeyPinStore` that keeps pins in memory.
+#[derive(Debug, Default)]
+pub struct SimpleKeyPinStore {
+    pins: RwLock<HashMap<String, Vec<u8>>>
+}
+
+impl SimpleKeyPinStore {
+    pub fn new() -> SimpleKeyPinStore {
+        SimpleKeyPinStore::default()
+    }
+}
+
+impl KeyPinStore for SimpleKeyPinStore {
+    fn get(&self, issuer_did: &str) -> Result<Option<Vec<u8>>, IndyCryptoError> {
+        let pins = self.pins.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Key pin store lock is poisoned".to_string()))?;
+        Ok(pins.get(issuer_did).cloned())
+    }
+
+    fn put(&self, issuer_did: &str, key_fingerprint: &[u8]) -> Result<(), IndyCryptoError> {
+        let mut pins = self.pins.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Key pin store lock is poisoned".to_string()))?;
+        pins.insert(issuer_did.to_owned(), key_fingerprint.to_vec());
+        Ok(())
+    }
+}
+
+/// Rotation of issuer credential key checked by verifier.
+///
+/// Both keys are accepted for a grace period after rotation, so presentations of credentials
+/// issued with the predecessor key keep working until holders get re-issued credentials.
+#[derive(Debug)]
+pub struct KeyRotation {
+    old_pub_key: CredentialPublicKey,
+    new_pub_key: CredentialPublicKey,
+    rotated_at: Timestamp,
+    grace_period: u64
+}
+
+impl KeyRotation {
+    /// Checks rotation statement and creates key rotation.
+    ///
+    /// # Arguments
+    /// * `statement` - Rotation statement published by issuer.
+    /// * `old_pub_key` - Predecessor credential public key.
+    /// * `new_pub_key` - Successor credential public key.
+    /// * `grace_period` - Seconds after rotation predecessor key is still accepted for.
+    pub fn new(statement: &KeyRotationStatement,
+               old_pub_key: &CredentialPublicKey,
+               new_pub_key: &CredentialPublicKey,
+               grace_period: u64) -> Result<KeyRotation, IndyCryptoError> {
+        if !statement.verify(old_pub_key, new_pub_key)? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid key rotation statement".to_string()));
+        }
+
+        Ok(KeyRotation {
+            old_pub_key: old_pub_key.clone()?,
+            new_pub_key: new_pub_key.clone()?,
+            rotated_at: statement.rotated_at(),
+            grace_period
         })
     }
+
+    /// Returns keys presentations are accepted for at the current time of the clock: successor key first.
+    pub fn accepted_pub_keys<C>(&self, clock: &C) -> Result<Vec<&CredentialPublicKey>, IndyCryptoError> where C: Clock {
+        let mut pub_keys = vec![&self.new_pub_key];
+        if Timestamp::now(clock)?.as_secs() <= self.rotated_at.as_secs().saturating_add(self.grace_period) {
+            pub_keys.push(&self.old_pub_key);
+        }
+        Ok(pub_keys)
+    }
+}
+
+/// Presentations of one issuer verified against the same sub proof requests and keys.
+#[derive(Debug)]
+pub struct ProofGroup {
+    proof_verifier: ProofVerifier,
+    presentations: Vec<(Proof, Nonce)>
+}
+
+impl ProofGroup {
+    /// Creates group verified with the given proof verifier.
+    ///
+    /// # Arguments
+    /// * `proof_verifier` - Proof verifier with all sub proof requests added.
+    pub fn new(proof_verifier: ProofVerifier) -> ProofGroup {
+        ProofGroup { proof_verifier, presentations: Vec::new() }
+    }
+
+    /// Adds presentation to the group.
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce the proof was generated for.
+    pub fn add_presentation(&mut self, proof: Proof, nonce: Nonce) -> Result<(), IndyCryptoError> {
+        self.presentations.push((proof, nonce));
+        Ok(())
+    }
+
+    fn _verify(&self, issuer_id: &str, report: &mut AuditReport) {
+        for (idx, &(ref proof, ref nonce)) in self.presentations.iter().enumerate() {
+            report.total += 1;
+            let reason = match self.proof_verifier.verify(proof, nonce) {
+                Ok(true) => {
+                    report.accepted += 1;
+                    continue;
+                }
+                Ok(false) => {
+                    report.rejected += 1;
+                    "Proof is rejected".to_string()
+                }
+                Err(err) => {
+                    report.failed += 1;
+                    err.to_string()
+                }
+            };
+            report.failures.push(AuditFailure { issuer_id: issuer_id.to_string(), presentation_idx: idx, reason });
+        }
+    }
+}
+
+/// Presentation that didn't pass `Verifier::verify_grouped`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditFailure {
+    pub issuer_id: String,
+    /// Index of the presentation in its `ProofGroup`.
+    pub presentation_idx: usize,
+    pub reason: String
+}
+
+/// Summary of `Verifier::verify_grouped`.
+/// Presentations are either accepted, rejected (proof is invalid) or failed (proof can't be verified, e.g. it is malformed).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    pub total: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub failed: u64,
+    pub failures: Vec<AuditFailure>
+}
+
+impl AuditReport {
+    fn merge(&mut self, other: AuditReport) {
+        self.total += other.total;
+        self.accepted += other.accepted;
+        self.rejected += other.rejected;
+        self.failed += other.failed;
+        self.failures.extend(other.failures);
+    }
+}
+
+
+/// Limits of proof size and complexity enforced by `ProofVerifier` before verification math.
+///
+/// Protects verification services from resource exhaustion by pathological proofs.
+/// `None` means that the value isn't limited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofLimits {
+    pub max_sub_proofs: Option<usize>,
+    pub max_predicates: Option<usize>,
+    pub max_c_list_entries: Option<usize>,
+    pub max_size: Option<usize>
+}
+
+impl ProofLimits {
+    fn check(&self, proof: &Proof) -> Result<(), IndyCryptoError> {
+        ProofLimits::_check_limit("sub proofs", proof.proofs.len(), self.max_sub_proofs)?;
+
+        let predicates = proof.proofs.iter()
+            .map(|sub_proof| sub_proof.primary_proof.ge_proofs.len())
+            .sum();
+        ProofLimits::_check_limit("predicates", predicates, self.max_predicates)?;
+
+        let c_list_entries = if proof.is_compact() {
+            proof.restore_c_list()?.len()
+        } else {
+            proof.aggregated_proof.c_list.len()
+        };
+        ProofLimits::_check_limit("c_list entries", c_list_entries, self.max_c_list_entries)?;
+
+        Ok(())
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), IndyCryptoError> {
+        ProofLimits::_check_limit("proof bytes", size, self.max_size)
+    }
+
+    fn _check_limit(name: &str, value: usize, limit: Option<usize>) -> Result<(), IndyCryptoError> {
+        match limit {
+            Some(limit) if value > limit =>
+                Err(IndyCryptoError::AnoncredsProofLimitExceeded(format!("Proof contains {} {}, limit is {}", value, name, limit))),
+            _ => Ok(())
+        }
+    }
+}
+
+/// Data minimization policy enforced by `ProofVerifier` against revealed attributes of the proof.
+///
+/// Violations are reported as `AnoncredsProofPolicyViolated` errors, so they can be told apart
+/// from proofs failing verification math. `None` means that the value isn't limited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosurePolicy {
+    pub max_revealed_attrs: Option<usize>,
+    pub required_hidden_attrs: BTreeSet<String>
+}
+
+impl DisclosurePolicy {
+    fn check(&self, proof: &Proof) -> Result<(), IndyCryptoError> {
+        let revealed_attrs = proof.proofs.iter()
+            .map(|sub_proof| sub_proof.primary_proof.eq_proof.revealed_attrs.len())
+            .sum::<usize>();
+
+        match self.max_revealed_attrs {
+            Some(max_revealed_attrs) if revealed_attrs > max_revealed_attrs =>
+                return Err(IndyCryptoError::AnoncredsProofPolicyViolated(
+                    format!("Proof reveals {} attributes, limit is {}", revealed_attrs, max_revealed_attrs))),
+            _ => ()
+        }
+
+        for sub_proof in proof.proofs.iter() {
+            if let Some(attr) = sub_proof.primary_proof.eq_proof.revealed_attrs.keys()
+                .find(|attr| self.required_hidden_attrs.contains(*attr)) {
+                return Err(IndyCryptoError::AnoncredsProofPolicyViolated(format!("Attribute {} must be hidden", attr)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Relaxation of a check introduced by newer protocol versions that proofs of older provers don't pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LegacyRelaxation {
+    /// Sub proof doesn't contain fingerprint of the sub proof request it answers,
+    /// so the request isn't bound to the challenge.
+    MissingSubProofRequestFingerprint,
+    /// Sub proof doesn't report timestamp of the revocation registry state it's built against
+    /// although verifier knows the state timestamp.
+    MissingNonRevocTimestamp
+}
+
+/// Legacy relaxation applied while verifying a sub proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppliedRelaxation {
+    pub sub_proof_idx: usize,
+    pub relaxation: LegacyRelaxation
+}
+
+/// Policy of `ProofVerifier` on proofs of older provers lacking newer checks.
+///
+/// Relaxation is looked up in `accepted_by_issuer` by key id of credential public key (see `CredentialPublicKey::key_id`),
+/// then in `accepted_by_version` by protocol version verifier negotiated with prover (passed to `ProofVerifier::set_legacy_policy`),
+/// then in `accepted`. Version is never taken from the proof, so prover can't pick the entry accepting the relaxation. Proofs needing not accepted relaxations are rejected with `AnoncredsProofRejected` error.
+///
+/// Default policy accepts all relaxations, as verifier did before the policy was introduced;
+/// `LegacyPolicy::strict()` accepts none.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyPolicy {
+    pub accepted: BTreeSet<LegacyRelaxation>,
+    pub accepted_by_issuer: BTreeMap<String /* key id */, BTreeSet<LegacyRelaxation>>,
+    pub accepted_by_version: BTreeMap<u32 /* negotiated protocol version */, BTreeSet<LegacyRelaxation>>
+}
+
+impl Default for LegacyPolicy {
+    fn default() -> LegacyPolicy {
+        LegacyPolicy {
+            accepted: btreeset![LegacyRelaxation::MissingSubProofRequestFingerprint, LegacyRelaxation::MissingNonRevocTimestamp],
+            accepted_by_issuer: BTreeMap::new(),
+            accepted_by_version: BTreeMap::new()
+        }
+    }
+}
+
+impl LegacyPolicy {
+    /// Returns policy accepting no legacy relaxations.
+    pub fn strict() -> LegacyPolicy {
+        LegacyPolicy {
+            accepted: BTreeSet::new(),
+            accepted_by_issuer: BTreeMap::new(),
+            accepted_by_version: BTreeMap::new()
+        }
+    }
+
+    fn apply(&self,
+             relaxation: LegacyRelaxation,
+             sub_proof_idx: usize,
+             pub_key: &CredentialPublicKey,
+             protocol_version: Option<u32>,
+             applied: &mut Vec<AppliedRelaxation>) -> Result<(), IndyCryptoError> {
+        let accepted = if self.accepted_by_issuer.is_empty() { None } else { self.accepted_by_issuer.get(&pub_key.key_id()?) }
+            .or_else(|| protocol_version.and_then(|protocol_version| self.accepted_by_version.get(&protocol_version)))
+            .unwrap_or(&self.accepted);
+
+        if !accepted.contains(&relaxation) {
+            return Err(IndyCryptoError::AnoncredsProofRejected(
+                format!("Sub proof {} needs legacy relaxation {:?} not accepted by verifier", sub_proof_idx, relaxation)));
+        }
+
+        applied.push(AppliedRelaxation { sub_proof_idx, relaxation });
+
+        Ok(())
+    }
 }
 
+/// Result of `ProofVerifier::verify_with_report`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Whether the proof is valid.
+    pub valid: bool,
+    /// Legacy relaxations the proof was verified with.
+    pub relaxations: Vec<AppliedRelaxation>
+}
 
 #[derive(Debug)]
 pub struct ProofVerifier {
     credentials: Vec<VerifiableCredential>,
+    limits: ProofLimits,
+    channel_binding: Option<Vec<u8>>,
+    common_attributes: BTreeSet<String>,
+    disclosure_policy: DisclosurePolicy,
+    self_attested_attrs: BTreeSet<String>,
+    legacy_policy: LegacyPolicy,
+    protocol_version: Option<u32>,
+    accepted_challenge_reductions: Vec<ChallengeReduction>,
+    clock: VerifierClock,
+}
+
+/// Source of the current time usage constraints of credential keys are checked at.
+struct VerifierClock(Box<Clock + Send + Sync>);
+
+impl fmt::Debug for VerifierClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VerifierClock")
+    }
 }
 
-impl ProofVerifier {
+impl ProofVerifier {
+    /// Sets limits of proof size and complexity checked before verification.
+    ///
+    /// # Arguments
+    /// * `limits` - Proof limits.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::{ProofLimits, Verifier};
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.set_limits(ProofLimits { max_sub_proofs: Some(10), max_size: Some(1 << 20), ..ProofLimits::default() }).unwrap();
+    /// ```
+    pub fn set_limits(&mut self, limits: ProofLimits) -> Result<(), IndyCryptoError> {
+        self.limits = limits;
+        Ok(())
+    }
+
+    /// Sets data minimization policy checked against revealed attributes of the proof before verification.
+    ///
+    /// # Arguments
+    /// * `disclosure_policy` - Disclosure policy.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::{DisclosurePolicy, Verifier};
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.set_disclosure_policy(DisclosurePolicy { max_revealed_attrs: Some(1), ..DisclosurePolicy::default() }).unwrap();
+    /// ```
+    pub fn set_disclosure_policy(&mut self, disclosure_policy: DisclosurePolicy) -> Result<(), IndyCryptoError> {
+        self.disclosure_policy = disclosure_policy;
+        Ok(())
+    }
+
+    /// Sets clock validity period of credential keys (see `CredentialKeyUsageConstraints`)
+    /// is checked against when sub proof requests are added. System clock is used by default.
+    ///
+    /// # Arguments
+    /// * `clock` - Source of the current time.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::Verifier;
+    /// use indy_crypto::utils::clock::FixedClock;
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.set_clock(FixedClock::new(1_500_000_000)).unwrap();
+    /// ```
+    pub fn set_clock<C>(&mut self, clock: C) -> Result<(), IndyCryptoError> where C: Clock + Send + Sync + 'static {
+        self.clock = VerifierClock(Box::new(clock));
+        Ok(())
+    }
+
+    /// Sets challenge reduction policies (see `ChallengeReduction`) accepted in proofs. All of them are accepted by default,
+    /// so deployments that have migrated all provers can pin the current version and reject older ones.
+    ///
+    /// # Arguments
+    /// * `challenge_reductions` - Accepted challenge reduction policies.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::ChallengeReduction;
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.set_accepted_challenge_reductions(&[ChallengeReduction::V2ReducedModOrder]).unwrap();
+    /// ```
+    pub fn set_accepted_challenge_reductions(&mut self, challenge_reductions: &[ChallengeReduction]) -> Result<(), IndyCryptoError> {
+        if challenge_reductions.is_empty() {
+            return Err(IndyCryptoError::InvalidParam1("At least one challenge reduction must be accepted".to_string()));
+        }
+        self.accepted_challenge_reductions = challenge_reductions.to_vec();
+        Ok(())
+    }
+
+    /// Sets policy on legacy relaxations of checks accepted for proofs of older provers.
+    ///
+    /// # Arguments
+    /// * `legacy_policy` - Legacy policy.
+    /// * `protocol_version` - (Optional) Protocol version verifier negotiated with prover out of band,
+    ///   relaxations are looked up by it in `LegacyPolicy::accepted_by_version`.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::{LegacyPolicy, Verifier};
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.set_legacy_policy(LegacyPolicy::strict(), None).unwrap();
+    /// ```
+    pub fn set_legacy_policy(&mut self, legacy_policy: LegacyPolicy, protocol_version: Option<u32>) -> Result<(), IndyCryptoError> {
+        self.legacy_policy = legacy_policy;
+        self.protocol_version = protocol_version;
+        Ok(())
+    }
+
+    /// Requires proof to be bound to the transport session with the given channel binding value
+    /// (see `ProofBuilder::set_channel_binding`). Proofs built for other sessions are rejected.
+    ///
+    /// # Arguments
+    /// * `channel_binding` - Channel binding value of the current session.
+    pub fn set_channel_binding(&mut self, channel_binding: &[u8]) -> Result<(), IndyCryptoError> {
+        if channel_binding.is_empty() {
+            return Err(IndyCryptoError::InvalidParam1("Channel binding is empty".to_string()));
+        }
+        self.channel_binding = Some(channel_binding.to_vec());
+        Ok(())
+    }
+
+    /// Requests self-attested attribute: value provided by prover without any credential
+    /// (see `ProofBuilder::finalize_with_self_attested_attrs`).
+    /// Proofs that don't contain exactly the requested self-attested attributes are rejected.
+    ///
+    /// # Arguments
+    /// * `attr` - Name of self-attested attribute.
+    pub fn add_self_attested_attr(&mut self, attr: &str) -> Result<(), IndyCryptoError> {
+        self.self_attested_attrs.insert(attr.to_owned());
+        Ok(())
+    }
+
+    /// Requires hidden value of the attribute to be identical in all sub proofs
+    /// (Prover must mark it with `ProofBuilder::add_common_attribute`).
+    /// Proofs where the attribute is missing or differs in some sub proof are rejected.
+    ///
+    /// # Arguments
+    /// * `attr_name` - Name of the attribute.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// proof_verifier.add_common_attribute("did").unwrap();
+    /// ```
+    pub fn add_common_attribute(&mut self, attr_name: &str) -> Result<(), IndyCryptoError> {
+        if attr_name.is_empty() {
+            return Err(IndyCryptoError::InvalidParam1("Attribute name is empty".to_string()));
+        }
+        self.common_attributes.insert(attr_name.to_owned());
+        Ok(())
+    }
+
+    /// Converts proof verifier to long-lived verification session.
+    ///
+    /// Sub proof requests can't be added to the session, so all of them must be added
+    /// to proof verifier before conversion.
+    ///
+    /// # Example
+    /// ```
+    /// use indy_crypto::cl::verifier::Verifier;
+    ///
+    /// let proof_verifier = Verifier::new_proof_verifier().unwrap();
+    /// let session = proof_verifier.into_session();
+    /// assert_eq!(0, session.stats().unwrap().calls);
+    /// ```
+    pub fn into_session(self) -> ProofVerifierSession {
+        ProofVerifierSession {
+            proof_verifier: RwLock::new(self),
+            stats: Mutex::new(VerificationStats::default())
+        }
+    }
+
     /// Add sub proof request to proof verifier.
     /// The order of sub-proofs is important: both Prover and Verifier should use the same order.
     ///
@@ -103,7 +872,41 @@ impl ProofVerifier {
                                  credential_pub_key: &CredentialPublicKey,
                                  rev_key_pub: Option<&RevocationKeyPublic>,
                                  rev_reg: Option<&RevocationRegistry>) -> Result<(), IndyCryptoError> {
+        self.add_sub_proof_request_with_attr_revoc(sub_proof_request,
+                                                   credential_schema,
+                                                   non_credential_schema,
+                                                   credential_pub_key,
+                                                   rev_key_pub,
+                                                   rev_reg,
+                                                   &HashMap::new())
+    }
+
+    /// Add sub proof request to proof verifier with revocation registries
+    /// for attributes requested as unrevoked (`unrevoked_attrs` of sub proof request).
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates instance pointer.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_key_pub` - (Optional) Revocation registry public key of the credential.
+    /// * `rev_reg` - (Optional) Revocation registry of the credential.
+    /// * `attr_rev_regs` - Revocation registry public key and registry for every attribute requested as unrevoked.
+    pub fn add_sub_proof_request_with_attr_revoc(&mut self,
+                                                 sub_proof_request: &SubProofRequest,
+                                                 credential_schema: &CredentialSchema,
+                                                 non_credential_schema: &NonCredentialSchema,
+                                                 credential_pub_key: &CredentialPublicKey,
+                                                 rev_key_pub: Option<&RevocationKeyPublic>,
+                                                 rev_reg: Option<&RevocationRegistry>,
+                                                 attr_rev_regs: &HashMap<String, (&RevocationKeyPublic, &RevocationRegistry)>) -> Result<(), IndyCryptoError> {
         ProofVerifier::_check_add_sub_proof_request_params_consistency(sub_proof_request, credential_schema)?;
+        ProofVerifier::_check_attr_revocation_params_consistency(sub_proof_request, credential_pub_key, attr_rev_regs)?;
+        ProofVerifier::_check_key_usage_constraints(credential_pub_key,
+                                                    credential_schema,
+                                                    non_credential_schema,
+                                                    rev_reg,
+                                                    self.clock.0.as_ref())?;
 
         self.credentials.push(VerifiableCredential {
             pub_key: credential_pub_key.clone()?,
@@ -111,11 +914,92 @@ impl ProofVerifier {
             credential_schema: credential_schema.clone(),
             non_credential_schema: non_credential_schema.clone(),
             rev_key_pub: rev_key_pub.map(Clone::clone),
-            rev_reg: rev_reg.map(Clone::clone)
+            rev_reg: rev_reg.map(Clone::clone),
+            attr_rev_regs: sub_proof_request.unrevoked_attrs.iter()
+                .map(|attr| {
+                    let &(rev_key_pub, rev_reg) = &attr_rev_regs[attr];
+                    (attr.clone(), (rev_key_pub.clone(), rev_reg.clone()))
+                })
+                .collect(),
+            rev_reg_timestamp: None,
+            extends_previous: false
         });
         Ok(())
     }
 
+    /// Add sub proof request to proof verifier with revocation registry state published at `rev_reg_timestamp`.
+    ///
+    /// Proof is rejected if its sub proof was built against registry state with another timestamp
+    /// (see `Proof::non_revoc_timestamp`) or the timestamp is out of non-revocation interval of sub proof request.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes, predicates and non-revocation interval.
+    /// * `credential_schema` - Credential schema.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_pub_key` - Credential public key.
+    /// * `rev_key_pub` - Revocation registry public key of the credential.
+    /// * `rev_reg` - Revocation registry state of the credential.
+    /// * `rev_reg_timestamp` - Time revocation registry state was published at.
+    pub fn add_sub_proof_request_with_rev_reg_timestamp(&mut self,
+                                                        sub_proof_request: &SubProofRequest,
+                                                        credential_schema: &CredentialSchema,
+                                                        non_credential_schema: &NonCredentialSchema,
+                                                        credential_pub_key: &CredentialPublicKey,
+                                                        rev_key_pub: &RevocationKeyPublic,
+                                                        rev_reg: &RevocationRegistry,
+                                                        rev_reg_timestamp: Timestamp) -> Result<(), IndyCryptoError> {
+        if let Some(ref interval) = sub_proof_request.non_revoc_interval {
+            if !interval.contains(rev_reg_timestamp) {
+                return Err(IndyCryptoError::InvalidParam7(
+                    format!("Revocation registry state published at {} is out of the requested non-revocation interval", rev_reg_timestamp)));
+            }
+        }
+
+        self.add_sub_proof_request(sub_proof_request,
+                                   credential_schema,
+                                   non_credential_schema,
+                                   credential_pub_key,
+                                   Some(rev_key_pub),
+                                   Some(rev_reg))?;
+
+        if let Some(credential) = self.credentials.last_mut() {
+            credential.rev_reg_timestamp = Some(rev_reg_timestamp);
+        }
+
+        Ok(())
+    }
+
+    /// Add sub proof request for extension credential of the credential of the previous sub proof request
+    /// (see `ProofBuilder::add_extension_sub_proof_request`). Proof is rejected if extension credential
+    /// wasn't issued for the same credential context as the base one.
+    ///
+    /// # Arguments
+    /// * `sub_proof_request` - Requested attributes and predicates of extension credential.
+    /// * `credential_schema` - Credential schema of extension credential.
+    /// * `non_credential_schema` - Non credential schema.
+    /// * `credential_pub_key` - Credential public key of extension credential.
+    pub fn add_extension_sub_proof_request(&mut self,
+                                           sub_proof_request: &SubProofRequest,
+                                           credential_schema: &CredentialSchema,
+                                           non_credential_schema: &NonCredentialSchema,
+                                           credential_pub_key: &CredentialPublicKey) -> Result<(), IndyCryptoError> {
+        if self.credentials.is_empty() {
+            return Err(IndyCryptoError::InvalidState("Sub proof request of base credential must be added before extension".to_string()));
+        }
+
+        if !sub_proof_request.unrevoked_attrs.is_empty() {
+            return Err(IndyCryptoError::InvalidStructure("Attributes of extension credential can't be requested as unrevoked".to_string()));
+        }
+
+        self.add_sub_proof_request(sub_proof_request, credential_schema, non_credential_schema, credential_pub_key, None, None)?;
+
+        if let Some(credential) = self.credentials.last_mut() {
+            credential.extends_previous = true;
+        }
+
+        Ok(())
+    }
+
     /// Verifies proof.
     ///
     /// # Arguments
@@ -205,11 +1089,181 @@ impl ProofVerifier {
                   nonce: &Nonce) -> Result<bool, IndyCryptoError> {
         trace!("ProofVerifier::verify: >>> proof: {:?}, nonce: {:?}", proof, nonce);
 
+        let valid = self._verify(proof, nonce, &mut Vec::new())?;
+
+        trace!("ProofVerifier::verify: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Verifies proof and returns report listing legacy relaxations of checks the proof was verified with
+    /// (see `ProofVerifier::set_legacy_policy`).
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce.
+    pub fn verify_with_report(&self,
+                              proof: &Proof,
+                              nonce: &Nonce) -> Result<VerificationReport, IndyCryptoError> {
+        trace!("ProofVerifier::verify_with_report: >>> proof: {:?}, nonce: {:?}", proof, nonce);
+
+        let mut relaxations = Vec::new();
+        let valid = self._verify(proof, nonce, &mut relaxations)?;
+
+        let report = VerificationReport { valid, relaxations };
+
+        trace!("ProofVerifier::verify_with_report: <<< report: {:?}", report);
+
+        Ok(report)
+    }
+
+    fn _verify(&self,
+               proof: &Proof,
+               nonce: &Nonce,
+               relaxations: &mut Vec<AppliedRelaxation>) -> Result<bool, IndyCryptoError> {
+        let _arena = BigNumberArena::new();
+
+        self.limits.check(proof)?;
+
+        self.disclosure_policy.check(proof)?;
+
+        if !self.accepted_challenge_reductions.contains(&proof.challenge_reduction) {
+            return Err(IndyCryptoError::AnoncredsProofPolicyViolated(
+                format!("Challenge reduction version {} isn't accepted", proof.challenge_reduction.version())));
+        }
+
+        ProofVerifier::_check_verify_params_consistency(&self.credentials, proof)?;
+
+        if !self._check_common_attributes(proof)? {
+            trace!("ProofVerifier::_verify: common attributes differ");
+            return Ok(false);
+        }
+
+        if !self._check_extensions(proof) {
+            trace!("ProofVerifier::_verify: extension credential is not linked to base credential");
+            return Ok(false);
+        }
+
+        if let Some(ref guardianship) = proof.guardianship {
+            if self.credentials.iter().any(|credential| !guardianship.allows(&credential.sub_proof_request)) {
+                return Err(IndyCryptoError::AnoncredsProofRejected("Sub proof request is out of delegation scope".to_string()));
+            }
+        }
+
+        let sub_proof_request_fingerprints = self._check_sub_proof_request_fingerprints(proof, relaxations)?;
+
+        let non_revoc_timestamps = self._check_non_revoc_timestamps(proof, relaxations)?;
+
+        self._check_self_attested_attrs(proof)?;
+
+        let tau_list = self._tau_list_trace(proof)?.into_values();
+
+        let mut values: Vec<Vec<u8>> = tau_list;
+        if proof.is_compact() {
+            values.extend_from_slice(&proof.restore_c_list()?);
+        } else {
+            values.extend_from_slice(&proof.aggregated_proof.c_list);
+        }
+        if let Some(ref guardianship) = proof.guardianship {
+            values.push(guardianship.to_bytes()?);
+        }
+        for snark_proof in proof.snark_proofs.iter() {
+            values.push(snark_proof.to_bytes()?);
+        }
+        values.extend(sub_proof_request_fingerprints);
+        values.extend(non_revoc_timestamps);
+        values.extend(self_attested_values(&proof.self_attested_attrs)?);
+        values.extend(session_values(nonce, self.channel_binding.as_ref().map(|v| v.as_slice()))?);
+
+        let c_hver = proof.challenge_reduction.reduce(get_hash_as_int(&values)?)?;
+
+        info!(target: "anoncreds_service", "Verifier verify proof -> done");
+
+        Ok(c_hver == proof.aggregated_proof.c_hash)
+    }
+
+    /// Recomputes tau list of the proof and compares it with tau list dumped by Prover
+    /// with `ProofBuilder::tau_list_trace`.
+    ///
+    /// Debugging aid for proofs failing verification: returns location of the first diverging tau list value.
+    /// `None` means tau lists are equal and the proof is rejected because of other hashed values
+    /// (c list, sub proof request fingerprints, non-revocation timestamps, self-attested attributes, nonce, channel binding,
+    /// guardianship marker or snark proofs).
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `prover_tau_list_trace` - Tau list dumped by Prover while creating the proof.
+    pub fn diagnose(&self,
+                    proof: &Proof,
+                    prover_tau_list_trace: &TauListTrace) -> Result<Option<TauListMismatch>, IndyCryptoError> {
+        trace!("ProofVerifier::diagnose: >>> proof: {:?}", proof);
+
         ProofVerifier::_check_verify_params_consistency(&self.credentials, proof)?;
 
-        let mut tau_list: Vec<Vec<u8>> = Vec::new();
+        let mismatch = prover_tau_list_trace.find_mismatch(&self._tau_list_trace(proof)?);
+
+        trace!("ProofVerifier::diagnose: <<< mismatch: {:?}", mismatch);
+
+        Ok(mismatch)
+    }
+
+    /// Verifies proof serialized to json.
+    ///
+    /// Size limit is checked before the proof is parsed, so oversized proofs are rejected
+    /// without allocating their content.
+    ///
+    /// # Arguments
+    /// * `proof_json` - Proof generated by Prover serialized to json.
+    /// * `nonce` - Nonce.
+    pub fn verify_json(&self,
+                       proof_json: &str,
+                       nonce: &Nonce) -> Result<bool, IndyCryptoError> {
+        trace!("ProofVerifier::verify_json: >>> proof_json: {:?}, nonce: {:?}", proof_json, nonce);
+
+        self.limits.check_size(proof_json.len())?;
+
+        let proof: Proof = ::serde_json::from_str(proof_json)?;
+        let valid = self.verify(&proof, nonce)?;
+
+        trace!("ProofVerifier::verify_json: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    /// Verifies proof and proof of external circuit over attribute commitments (experimental).
+    ///
+    /// Returns false if proof doesn't contain proof of the circuit.
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce.
+    /// * `circuit_id` - Identifier of the circuit.
+    /// * `snark_verifier` - Verifier of the circuit.
+    pub fn verify_with_snark<V>(&self,
+                                proof: &Proof,
+                                nonce: &Nonce,
+                                circuit_id: &str,
+                                snark_verifier: &V) -> Result<bool, IndyCryptoError> where V: SnarkVerifier {
+        trace!("ProofVerifier::verify_with_snark: >>> proof: {:?}, nonce: {:?}, circuit_id: {:?}", proof, nonce, circuit_id);
+
+        let valid = self.verify(proof, nonce)? && match proof.snark_proofs.iter().find(|snark_proof| snark_proof.circuit_id() == circuit_id) {
+            Some(snark_proof) => snark_verifier.verify(circuit_id, &proof.attribute_commitments()?, snark_proof.proof())?,
+            None => false
+        };
+
+        trace!("ProofVerifier::verify_with_snark: <<< valid: {:?}", valid);
+
+        Ok(valid)
+    }
+
+    fn _tau_list_trace(&self, proof: &Proof) -> Result<TauListTrace, IndyCryptoError> {
+        let mut tau_list_trace = TauListTrace::default();
+
+        if proof.proofs.len() != self.credentials.len() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Proof contains {} sub proofs, {} sub proof requests added to verifier", proof.proofs.len(), self.credentials.len())));
+        }
 
-        assert_eq!(proof.proofs.len(), self.credentials.len()); //FIXME return error
         for idx in 0..proof.proofs.len() {
             let proof_item = &proof.proofs[idx];
             let credential = &self.credentials[idx];
@@ -217,16 +1271,32 @@ impl ProofVerifier {
                                                                                                              credential.pub_key.r_key.as_ref(),
                                                                                                              credential.rev_reg.as_ref(),
                                                                                                              credential.rev_key_pub.as_ref()) {
-                tau_list.extend_from_slice(
-                    &ProofVerifier::_verify_non_revocation_proof(&cred_rev_pub_key,
-                                                                 &rev_reg,
-                                                                 &rev_key_pub,
-                                                                 &proof.aggregated_proof.c_hash,
-                                                                 &non_revocation_proof)?.as_slice()?
+                tau_list_trace.push(idx, TauListPart::NonRevocation,
+                    ProofVerifier::_verify_non_revocation_proof(&cred_rev_pub_key,
+                                                                &rev_reg,
+                                                                &rev_key_pub,
+                                                                &proof.aggregated_proof.c_hash,
+                                                                &non_revocation_proof)?.as_slice()?
                 );
             };
 
-            tau_list.append_vec(
+            for (attr, &(ref rev_key_pub, ref rev_reg)) in credential.attr_rev_regs.iter() {
+                let cred_rev_pub_key = credential.pub_key.r_key.as_ref()
+                    .ok_or(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential revocation public key.")))?;
+                let non_revocation_proof = proof_item.attr_non_revoc_proofs.get(attr)
+                    .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Non-revocation proof for attribute '{}' not found", attr)))?;
+
+                tau_list_trace.push(idx, TauListPart::AttrNonRevocation(attr.clone()),
+                    ProofVerifier::_verify_non_revocation_proof(&cred_rev_pub_key,
+                                                                &rev_reg,
+                                                                &rev_key_pub,
+                                                                &proof.aggregated_proof.c_hash,
+                                                                &non_revocation_proof)?.as_slice()?
+                );
+            }
+
+            let mut primary_tau_list: Vec<Vec<u8>> = Vec::new();
+            primary_tau_list.append_vec(
                 &ProofVerifier::_verify_primary_proof(&credential.pub_key.p_key,
                                                       &proof.aggregated_proof.c_hash,
                                                       &proof_item.primary_proof,
@@ -234,22 +1304,124 @@ impl ProofVerifier {
                                                       &credential.non_credential_schema,
                                                       &credential.sub_proof_request)?
             )?;
+            tau_list_trace.push(idx, TauListPart::Primary, primary_tau_list);
+
+            for (attr, committed_attr_proof) in proof_item.committed_attr_proofs.iter() {
+                tau_list_trace.push(idx, TauListPart::CommittedAttr(attr.clone()), vec![
+                    ProofVerifier::_verify_committed_attr_proof(&credential.pub_key.p_key,
+                                                                &proof.aggregated_proof.c_hash,
+                                                                &proof_item.primary_proof.eq_proof,
+                                                                attr,
+                                                                committed_attr_proof)?.to_bytes()?
+                ]);
+            }
         }
 
-        let mut values: Vec<Vec<u8>> = Vec::new();
-        values.extend_from_slice(&tau_list);
-        values.extend_from_slice(&proof.aggregated_proof.c_list);
-        values.push(nonce.to_bytes()?);
+        Ok(tau_list_trace)
+    }
 
-        let c_hver = get_hash_as_int(&values)?;
+    /// Checks that sub proofs answer sub proof requests added to the verifier and
+    /// returns fingerprints of these requests to be hashed into the challenge.
+    /// Proofs created before fingerprints were introduced don't contain them and don't hash them:
+    /// they're accepted only if legacy policy allows it.
+    fn _check_sub_proof_request_fingerprints(&self,
+                                             proof: &Proof,
+                                             relaxations: &mut Vec<AppliedRelaxation>) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+        let mut fingerprints = Vec::new();
+
+        for (i, (sub_proof, credential)) in proof.proofs.iter().zip(self.credentials.iter()).enumerate() {
+            if sub_proof.sub_proof_request_fingerprint.is_empty() {
+                self.legacy_policy.apply(LegacyRelaxation::MissingSubProofRequestFingerprint, i, &credential.pub_key, self.protocol_version, relaxations)?;
+                continue;
+            }
 
-        info!(target: "anoncreds_service", "Verifier verify proof -> done");
+            let fingerprint = credential.sub_proof_request.fingerprint()?;
+            if sub_proof.sub_proof_request_fingerprint != fingerprint {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} answers another sub proof request", i)));
+            }
+            fingerprints.push(fingerprint);
+        }
 
-        let valid = c_hver == proof.aggregated_proof.c_hash;
+        Ok(fingerprints)
+    }
 
-        trace!("ProofVerifier::verify: <<< valid: {:?}", valid);
+    /// Checks that sub proofs were built against revocation registry states published within requested
+    /// non-revocation intervals and returns their timestamps to be hashed into the challenge.
+    fn _check_non_revoc_timestamps(&self,
+                                   proof: &Proof,
+                                   relaxations: &mut Vec<AppliedRelaxation>) -> Result<Vec<Vec<u8>>, IndyCryptoError> {
+        let mut timestamps = Vec::new();
+
+        for (i, (sub_proof, credential)) in proof.proofs.iter().zip(self.credentials.iter()).enumerate() {
+            let interval = match credential.rev_reg {
+                Some(_) => credential.sub_proof_request.non_revoc_interval,
+                None => None
+            };
 
-        Ok(valid)
+            match (sub_proof.non_revoc_timestamp, interval) {
+                (None, Some(_)) =>
+                    return Err(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} doesn't report revocation registry timestamp", i))),
+                (Some(timestamp), Some(interval)) if !interval.contains(timestamp) =>
+                    return Err(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} is built against revocation registry state published at {}, \
+                                                                                out of the requested non-revocation interval", i, timestamp))),
+                _ => ()
+            }
+
+            match (sub_proof.non_revoc_timestamp, credential.rev_reg_timestamp) {
+                (Some(timestamp), Some(rev_reg_timestamp)) if timestamp != rev_reg_timestamp =>
+                    return Err(IndyCryptoError::AnoncredsProofRejected(format!("Sub proof {} is built against revocation registry state published at {}, \
+                                                                                verifier has state published at {}", i, timestamp, rev_reg_timestamp))),
+                (None, Some(_)) if sub_proof.non_revoc_proof.is_some() =>
+                    self.legacy_policy.apply(LegacyRelaxation::MissingNonRevocTimestamp, i, &credential.pub_key, self.protocol_version, relaxations)?,
+                _ => ()
+            }
+
+            if let Some(timestamp) = sub_proof.non_revoc_timestamp {
+                timestamps.push(u64_to_be_bytes(timestamp.as_secs()));
+            }
+        }
+
+        Ok(timestamps)
+    }
+
+    fn _check_self_attested_attrs(&self, proof: &Proof) -> Result<(), IndyCryptoError> {
+        if let Some(attr) = self.self_attested_attrs.iter().find(|attr| !proof.self_attested_attrs.contains_key(*attr)) {
+            return Err(IndyCryptoError::AnoncredsProofRejected(format!("Self-attested attribute '{}' is missing in the proof", attr)));
+        }
+
+        if let Some(attr) = proof.self_attested_attrs.keys().find(|attr| !self.self_attested_attrs.contains(*attr)) {
+            return Err(IndyCryptoError::AnoncredsProofRejected(format!("Self-attested attribute '{}' is not requested", attr)));
+        }
+
+        Ok(())
+    }
+
+    fn _check_extensions(&self, proof: &Proof) -> bool {
+        (1..self.credentials.len().min(proof.proofs.len()))
+            .filter(|&idx| self.credentials[idx].extends_previous)
+            .all(|idx| proof.proofs[idx].primary_proof.eq_proof.m2 == proof.proofs[idx - 1].primary_proof.eq_proof.m2)
+    }
+
+    fn _check_common_attributes(&self, proof: &Proof) -> Result<bool, IndyCryptoError> {
+        for attr in self.common_attributes.iter() {
+            if self.credentials.iter().any(|credential| credential.sub_proof_request.revealed_attrs.contains(attr)) {
+                return Err(IndyCryptoError::InvalidStructure(format!("Common attribute '{}' is requested as revealed", attr)));
+            }
+
+            let mut m_hats = proof.proofs.iter().map(|proof_item| proof_item.primary_proof.eq_proof.m.get(attr));
+
+            let first = match m_hats.next() {
+                Some(Some(m_hat)) => m_hat,
+                Some(None) => return Ok(false),
+                None => continue
+            };
+
+            if !m_hats.all(|m_hat| m_hat == Some(first)) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
     }
 
     fn _check_add_sub_proof_request_params_consistency(sub_proof_request: &SubProofRequest,
@@ -269,16 +1441,68 @@ impl ProofVerifier {
             return Err(IndyCryptoError::InvalidStructure(format!("Credential doesn't contain attribute requested in predicate")));
         }
 
+        for attr in predicates_attrs.iter() {
+            cred_schema.check_predicate_attr(attr)?;
+        }
+
+        if sub_proof_request.committed_attrs.difference(&cred_schema.attrs).count() != 0 {
+            return Err(IndyCryptoError::InvalidStructure("Credential doesn't contain attribute requested as committed".to_string()));
+        }
+
         trace!("ProofVerifier::_check_add_sub_proof_request_params_consistency: <<<");
 
         Ok(())
     }
 
+    fn _check_key_usage_constraints<C>(credential_pub_key: &CredentialPublicKey,
+                                       credential_schema: &CredentialSchema,
+                                       non_credential_schema: &NonCredentialSchema,
+                                       rev_reg: Option<&RevocationRegistry>,
+                                       clock: &C) -> Result<(), IndyCryptoError> where C: Clock + ?Sized {
+        trace!("ProofVerifier::_check_key_usage_constraints: >>> credential_pub_key: {:?}, credential_schema: {:?}, non_credential_schema: {:?}, rev_reg: {:?}",
+               credential_pub_key, credential_schema, non_credential_schema, rev_reg);
+
+        if let Some(constraints) = credential_pub_key.usage_constraints()? {
+            if constraints.revocable_only() && rev_reg.is_none() {
+                return Err(IndyCryptoError::InvalidStructure("Credential keys allow revocable credentials only, revocation registry must be provided".to_string()));
+            }
+            constraints.check_attrs(credential_schema.attrs.len() + non_credential_schema.attrs.len())?;
+            constraints.check_validity(clock.now()?)?;
+        }
+
+        trace!("ProofVerifier::_check_key_usage_constraints: <<<");
+
+        Ok(())
+    }
+
+    fn _check_attr_revocation_params_consistency(sub_proof_request: &SubProofRequest,
+                                                 cred_pub_key: &CredentialPublicKey,
+                                                 attr_rev_regs: &HashMap<String, (&RevocationKeyPublic, &RevocationRegistry)>) -> Result<(), IndyCryptoError> {
+        trace!("ProofVerifier::_check_attr_revocation_params_consistency: >>> sub_proof_request: {:?}, attr_rev_regs: {:?}",
+               sub_proof_request, attr_rev_regs);
+
+        if !sub_proof_request.unrevoked_attrs.is_empty() && cred_pub_key.r_key.is_none() {
+            return Err(IndyCryptoError::InvalidStructure(format!("No revocation part present in credential revocation public key.")));
+        }
+
+        if let Some(attr) = sub_proof_request.unrevoked_attrs.iter().find(|attr| !attr_rev_regs.contains_key(attr.as_str())) {
+            return Err(IndyCryptoError::InvalidStructure(format!("Revocation registry for attribute '{}' not found", attr)));
+        }
+
+        trace!("ProofVerifier::_check_attr_revocation_params_consistency: <<<");
+
+        Ok(())
+    }
+
     fn _check_verify_params_consistency(credentials: &Vec<VerifiableCredential>,
                                         proof: &Proof) -> Result<(), IndyCryptoError> {
         trace!("ProofVerifier::_check_verify_params_consistency: >>> credentials: {:?}, proof: {:?}", credentials, proof);
 
-        assert_eq!(proof.proofs.len(), credentials.len()); //FIXME return error
+        if proof.proofs.len() != credentials.len() {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Proof contains {} sub proofs, {} sub proof requests added to verifier", proof.proofs.len(), credentials.len())));
+        }
+
         for idx in 0..proof.proofs.len() {
             let proof_for_credential = &proof.proofs[idx];
             let credential = &credentials[idx];
@@ -289,6 +1513,15 @@ impl ProofVerifier {
                 return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof revealed attributes not correspond to requested attributes")));
             }
 
+            let eq_proof = &proof_for_credential.primary_proof.eq_proof;
+
+            for (attr, raw_value) in eq_proof.revealed_raw_attrs.iter() {
+                match eq_proof.revealed_attrs.get(attr) {
+                    Some(value) if *value == encode_raw_value(raw_value)? => {}
+                    _ => return Err(IndyCryptoError::AnoncredsProofRejected(format!("Raw value of attribute '{}' not correspond to revealed value", attr)))
+                }
+            }
+
             let proof_predicates =
                 proof_for_credential.primary_proof.ge_proofs.iter()
                     .map(|ge_proof| ge_proof.predicate.clone())
@@ -297,6 +1530,18 @@ impl ProofVerifier {
             if proof_predicates != credential.sub_proof_request.predicates {
                 return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof predicates not correspond to requested predicates")));
             }
+
+            let proof_unrevoked_attrs = BTreeSet::from_iter(proof_for_credential.attr_non_revoc_proofs.keys().cloned());
+
+            if proof_unrevoked_attrs != credential.sub_proof_request.unrevoked_attrs {
+                return Err(IndyCryptoError::AnoncredsProofRejected(format!("Proof unrevoked attributes not correspond to requested attributes")));
+            }
+
+            let proof_committed_attrs = BTreeSet::from_iter(proof_for_credential.committed_attr_proofs.keys().cloned());
+
+            if proof_committed_attrs != credential.sub_proof_request.committed_attrs {
+                return Err(IndyCryptoError::AnoncredsProofRejected("Proof committed attributes not correspond to requested attributes".to_string()));
+            }
         }
 
         trace!("ProofVerifier::_check_verify_params_consistency: <<<");
@@ -352,7 +1597,7 @@ impl ProofVerifier {
 
         let mut ctx = BigNumber::new_context()?;
 
-        let mut rar = proof.a_prime.mod_exp(&LARGE_E_START_VALUE, &p_pub_key.n, Some(&mut ctx))?;
+        let mut rar = proof.a_prime.mod_exp(&p_pub_key.config.e_start_value()?, &p_pub_key.n, Some(&mut ctx))?;
 
         for (attr, encoded_value) in &proof.revealed_attrs {
             let cur_r = p_pub_key.r.get(attr)
@@ -375,6 +1620,30 @@ impl ProofVerifier {
         Ok(vec![t])
     }
 
+    fn _verify_committed_attr_proof(p_pub_key: &CredentialPrimaryPublicKey,
+                                    c_hash: &BigNumber,
+                                    eq_proof: &PrimaryEqualProof,
+                                    attr: &str,
+                                    proof: &CommittedAttributeProof) -> Result<BigNumber, IndyCryptoError> {
+        trace!("ProofVerifier::_verify_committed_attr_proof: >>> p_pub_key: {:?}, c_hash: {:?}, attr: {:?}, proof: {:?}",
+               p_pub_key, c_hash, attr, proof);
+
+        let mut ctx = BigNumber::new_context()?;
+
+        let m_cap = eq_proof.m.get(attr)
+            .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Value by key '{}' not found in eq_proof.m", attr)))?;
+
+        let t = proof.commitment
+            .inverse(&p_pub_key.n, Some(&mut ctx))?
+            .mod_exp(c_hash, &p_pub_key.n, Some(&mut ctx))?
+            .mod_mul(&get_pedersen_commitment(&p_pub_key.z, m_cap, &p_pub_key.s, &proof.r_cap, &p_pub_key.n, &mut ctx)?,
+                     &p_pub_key.n, Some(&mut ctx))?;
+
+        trace!("ProofVerifier::_verify_committed_attr_proof: <<< t: {:?}", t);
+
+        Ok(t)
+    }
+
     fn _verify_ge_predicate(p_pub_key: &CredentialPrimaryPublicKey,
                             proof: &PrimaryPredicateGEProof,
                             c_hash: &BigNumber) -> Result<Vec<BigNumber>, IndyCryptoError> {
@@ -397,10 +1666,16 @@ impl ProofVerifier {
         let delta = proof.t.get("DELTA")
             .ok_or(IndyCryptoError::AnoncredsProofRejected(format!("Value by key '{}' not found in proof.t", "DELTA")))?;
 
+        // For LE/LT predicates delta is committed as (bound - m), so Z^bound * T_delta^-1 = Z^m * S^-r_delta
+        let delta_predicate = if proof.predicate.is_less() {
+            delta.inverse(&p_pub_key.n, Some(&mut ctx))?
+        } else {
+            delta.clone()?
+        };
+
         tau_list[ITERATION] = p_pub_key.z
-            .mod_exp(&BigNumber::from_dec(&proof.predicate.value.to_string())?,
-                &p_pub_key.n, Some(&mut ctx))?
-            .mul(&delta, Some(&mut ctx))?
+            .mod_exp(&proof.predicate.get_bound()?, &p_pub_key.n, Some(&mut ctx))?
+            .mul(&delta_predicate, Some(&mut ctx))?
             .mod_exp(&c_hash, &p_pub_key.n, Some(&mut ctx))?
             .inverse(&p_pub_key.n, Some(&mut ctx))?
             .mod_mul(&tau_list[ITERATION], &p_pub_key.n, Some(&mut ctx))?;
@@ -445,6 +1720,112 @@ impl ProofVerifier {
     }
 }
 
+/// Statistics collected by `ProofVerifierSession` for all `verify` calls.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationStats {
+    pub calls: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub failed: u64,
+    pub elapsed: Duration
+}
+
+/// Statistics of a single `ProofVerifierSession::verify` call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationCallStats {
+    pub sub_proofs: usize,
+    pub non_revoc_proofs: usize,
+    pub elapsed: Duration
+}
+
+/// Long-lived wrapper around `ProofVerifier` for verification of many presentations
+/// against the same sub proof requests, keys and registries.
+///
+/// Session can be shared between threads: `verify` takes `&self`, revocation registries
+/// can be updated while other threads verify.
+#[derive(Debug)]
+pub struct ProofVerifierSession {
+    proof_verifier: RwLock<ProofVerifier>,
+    stats: Mutex<VerificationStats>
+}
+
+impl ProofVerifierSession {
+    /// Verifies proof and returns statistics of this call.
+    ///
+    /// # Arguments
+    /// * `proof` - Proof generated by Prover.
+    /// * `nonce` - Nonce.
+    pub fn verify(&self,
+                  proof: &Proof,
+                  nonce: &Nonce) -> Result<(bool, VerificationCallStats), IndyCryptoError> {
+        trace!("ProofVerifierSession::verify: >>> proof: {:?}, nonce: {:?}", proof, nonce);
+
+        let start = Instant::now();
+
+        let res = self.proof_verifier.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Proof verifier session is poisoned".to_string()))?
+            .verify(proof, nonce);
+
+        let call_stats = VerificationCallStats {
+            sub_proofs: proof.proofs.len(),
+            non_revoc_proofs: proof.proofs.iter().filter(|p| p.non_revoc_proof.is_some()).count(),
+            elapsed: start.elapsed()
+        };
+
+        {
+            let mut stats = self.stats.lock()
+                .map_err(|_| IndyCryptoError::InvalidState("Proof verifier session is poisoned".to_string()))?;
+            stats.calls += 1;
+            stats.elapsed += call_stats.elapsed;
+            match res {
+                Ok(true) => stats.accepted += 1,
+                Ok(false) => stats.rejected += 1,
+                Err(_) => stats.failed += 1
+            }
+        }
+
+        let res = (res?, call_stats);
+
+        trace!("ProofVerifierSession::verify: <<< res: {:?}", res);
+
+        Ok(res)
+    }
+
+    /// Replaces revocation registry used to verify sub proof with the given index.
+    ///
+    /// # Arguments
+    /// * `sub_proof_idx` - Index of sub proof request in order they were added to proof verifier.
+    /// * `rev_reg` - New state of revocation registry.
+    pub fn update_revocation_registry(&self,
+                                      sub_proof_idx: usize,
+                                      rev_reg: &RevocationRegistry) -> Result<(), IndyCryptoError> {
+        trace!("ProofVerifierSession::update_revocation_registry: >>> sub_proof_idx: {:?}, rev_reg: {:?}", sub_proof_idx, rev_reg);
+
+        let mut proof_verifier = self.proof_verifier.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Proof verifier session is poisoned".to_string()))?;
+
+        let credential = proof_verifier.credentials.get_mut(sub_proof_idx)
+            .ok_or(IndyCryptoError::InvalidParam1(format!("Sub proof request with index {} not found", sub_proof_idx)))?;
+
+        if credential.rev_reg.is_none() {
+            return Err(IndyCryptoError::InvalidStructure(format!("Sub proof request with index {} doesn't use revocation registry", sub_proof_idx)));
+        }
+
+        credential.rev_reg = Some(rev_reg.clone());
+
+        trace!("ProofVerifierSession::update_revocation_registry: <<<");
+
+        Ok(())
+    }
+
+    /// Returns statistics collected for all `verify` calls of this session.
+    pub fn stats(&self) -> Result<VerificationStats, IndyCryptoError> {
+        let stats = self.stats.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Proof verifier session is poisoned".to_string()))?;
+        Ok(stats.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -464,6 +1845,68 @@ mod tests {
         assert!(sub_proof_request.predicates.contains(&predicate()));
     }
 
+    #[test]
+    fn sub_proof_request_builder_works_for_range_predicate() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_range_predicate("age", 18, 65).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        assert_eq!(2, sub_proof_request.predicates.len());
+        assert!(sub_proof_request.predicates.contains(&predicate()));
+        assert!(sub_proof_request.predicates.contains(&Predicate { attr_name: "age".to_string(), p_type: PredicateType::LE, value: 65 }));
+    }
+
+    #[test]
+    fn sub_proof_request_builder_works_for_invalid_range_predicate() {
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        assert!(sub_proof_request_builder.add_range_predicate("age", 65, 18).is_err());
+    }
+
+    #[test]
+    fn proof_limits_check_size_works() {
+        let limits = ProofLimits { max_size: Some(10), ..ProofLimits::default() };
+        assert!(limits.check_size(10).is_ok());
+        match limits.check_size(11) {
+            Err(IndyCryptoError::AnoncredsProofLimitExceeded(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        assert!(ProofLimits::default().check_size(::std::usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn disclosure_policy_check_works() {
+        let proof = prover::mocks::proof();
+
+        assert!(DisclosurePolicy::default().check(&proof).is_ok());
+        assert!(DisclosurePolicy { max_revealed_attrs: Some(1), ..DisclosurePolicy::default() }.check(&proof).is_ok());
+        assert!(DisclosurePolicy { required_hidden_attrs: btreeset!["age".to_owned()], ..DisclosurePolicy::default() }.check(&proof).is_ok());
+
+        match (DisclosurePolicy { max_revealed_attrs: Some(0), ..DisclosurePolicy::default() }).check(&proof) {
+            Err(IndyCryptoError::AnoncredsProofPolicyViolated(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        match (DisclosurePolicy { required_hidden_attrs: btreeset!["name".to_owned()], ..DisclosurePolicy::default() }).check(&proof) {
+            Err(IndyCryptoError::AnoncredsProofPolicyViolated(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn check_common_attributes_works() {
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_common_attribute("master_secret").unwrap();
+
+        let mut proof = prover::mocks::proof();
+        proof.proofs.push(prover::mocks::subproof());
+        assert!(proof_verifier._check_common_attributes(&proof).unwrap());
+
+        proof.proofs[1].primary_proof.eq_proof.m.insert("master_secret".to_string(), BigNumber::from_u32(1).unwrap());
+        assert!(!proof_verifier._check_common_attributes(&proof).unwrap());
+
+        proof.proofs[1].primary_proof.eq_proof.m.remove("master_secret");
+        assert!(!proof_verifier._check_common_attributes(&proof).unwrap());
+    }
+
     #[test]
     fn verify_equality_works() {
         MockHelper::inject();
@@ -531,4 +1974,21 @@ mod tests {
         00403016403129020563799240705009712476150627783447048219852434435047969447195784507059403459\
         40533745092900800249667587825786217899894277583562804465078452786585349967293", res_data[5].to_dec().unwrap());
     }
+
+    #[test]
+    fn check_key_pin_works() {
+        let key_pin_store = SimpleKeyPinStore::new();
+        let credential_pub_key = issuer::mocks::credential_public_key();
+        let other_credential_pub_key = CredentialPublicKey { r_key: None, ..issuer::mocks::credential_public_key() };
+
+        Verifier::check_key_pin(&key_pin_store, "issuer_did", &credential_pub_key).unwrap();
+        Verifier::check_key_pin(&key_pin_store, "issuer_did", &credential_pub_key).unwrap();
+        Verifier::check_key_pin(&key_pin_store, "other_issuer_did", &other_credential_pub_key).unwrap();
+
+        match Verifier::check_key_pin(&key_pin_store, "issuer_did", &other_credential_pub_key) {
+            Err(IndyCryptoError::AnoncredsIssuerKeyChanged(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        assert_eq!(credential_pub_key.fingerprint().unwrap(), key_pin_store.get("issuer_did").unwrap().unwrap());
+    }
 }
@@ -0,0 +1,452 @@
+//! Client of remote witness services.
+//!
+//! Wallets that delegate witness updates to a service get witnesses through `WitnessServiceClient`
+//! instead of trusting service responses directly. Client batches requests per revocation registry,
+//! checks every returned witness against the returned accumulator (`e(g_i, accum) / e(g, omega) = z`),
+//! caches validated witnesses and backs off exponentially after failures of the service.
+//!
+//! All client state is behind locks that are never held while the service is called,
+//! so one client can be shared between threads and used again from the service implementation.
+use cl::*;
+use errors::IndyCryptoError;
+use pair::{Pair, PointG1};
+use utils::clock::{Clock, Timestamp};
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+
+/// Remote service computing witnesses for the current state of revocation registries.
+pub trait WitnessService {
+    /// Returns current revocation registry and witnesses of all requested indices for it.
+    ///
+    /// # Arguments
+    /// * `rev_reg_id` - Identifier of revocation registry.
+    /// * `rev_idxs` - Indices of credentials in revocation registry.
+    fn fetch_witnesses(&self, rev_reg_id: &str, rev_idxs: &[u32]) -> Result<WitnessBatch, IndyCryptoError>;
+}
+
+impl<'a, S> WitnessService for &'a S where S: WitnessService {
+    fn fetch_witnesses(&self, rev_reg_id: &str, rev_idxs: &[u32]) -> Result<WitnessBatch, IndyCryptoError> {
+        (*self).fetch_witnesses(rev_reg_id, rev_idxs)
+    }
+}
+
+/// Response of witness service: witnesses of requested indices for the same revocation registry state.
+#[derive(Debug, Clone)]
+pub struct WitnessBatch {
+    pub rev_reg: RevocationRegistry,
+    pub witnesses: BTreeMap<u32, Witness>
+}
+
+/// Request of the witness of a credential.
+#[derive(Debug, Clone, Copy)]
+pub struct WitnessRequest<'a> {
+    pub rev_reg_id: &'a str,
+    /// Non revocation signature of the credential (see `CredentialSignature::non_revocation_signature`).
+    pub r_cred: &'a NonRevocationCredentialSignature
+}
+
+/// Witness checked against revocation registry it was computed for.
+#[derive(Debug, Clone)]
+pub struct ValidatedWitness {
+    rev_reg: RevocationRegistry,
+    witness: Witness,
+    fetched_at: Timestamp
+}
+
+impl ValidatedWitness {
+    pub fn rev_reg(&self) -> &RevocationRegistry {
+        &self.rev_reg
+    }
+
+    pub fn witness(&self) -> &Witness {
+        &self.witness
+    }
+
+    /// Returns time the witness was received from the service.
+    pub fn fetched_at(&self) -> Timestamp {
+        self.fetched_at
+    }
+}
+
+/// Limits of witness service client. All periods are in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct WitnessServiceConfig {
+    /// Max number of indices requested from the service at once.
+    pub max_batch_size: usize,
+    /// Period cached witnesses are served without requesting the service.
+    pub cache_ttl: u64,
+    /// Min period between requests to the service.
+    pub min_request_interval: u64,
+    /// Backoff after the first failure, doubled after every next one.
+    pub backoff_base: u64,
+    pub backoff_max: u64
+}
+
+impl Default for WitnessServiceConfig {
+    fn default() -> WitnessServiceConfig {
+        WitnessServiceConfig {
+            max_batch_size: 100,
+            cache_ttl: 60,
+            min_request_interval: 0,
+            backoff_base: 1,
+            backoff_max: 300
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RequestState {
+    failures: u32,
+    next_request_at: u64
+}
+
+/// Client of witness service: batching, response validation, caching and backoff.
+pub struct WitnessServiceClient<S> where S: WitnessService {
+    service: S,
+    config: WitnessServiceConfig,
+    registries: RwLock<HashMap<String, (PointG1, Pair)>>,
+    cache: Mutex<HashMap<(String, u32), ValidatedWitness>>,
+    request_state: Mutex<RequestState>
+}
+
+impl<S> WitnessServiceClient<S> where S: WitnessService {
+    /// Creates client of the service.
+    ///
+    /// # Arguments
+    /// * `service` - Witness service.
+    /// * `config` - Client limits.
+    pub fn new(service: S, config: WitnessServiceConfig) -> Result<WitnessServiceClient<S>, IndyCryptoError> {
+        if config.max_batch_size == 0 {
+            return Err(IndyCryptoError::InvalidParam2("Max batch size of witness service client must be positive".to_string()));
+        }
+
+        Ok(WitnessServiceClient {
+            service,
+            config,
+            registries: RwLock::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+            request_state: Mutex::new(RequestState::default())
+        })
+    }
+
+    /// Registers public keys of revocation registry witnesses of which are validated against.
+    ///
+    /// # Arguments
+    /// * `rev_reg_id` - Identifier of revocation registry.
+    /// * `cred_pub_key` - Credential public key.
+    /// * `rev_key_pub` - Revocation registry public key.
+    pub fn add_registry(&self,
+                        rev_reg_id: &str,
+                        cred_pub_key: &CredentialPublicKey,
+                        rev_key_pub: &RevocationKeyPublic) -> Result<(), IndyCryptoError> {
+        let cred_rev_pub_key = cred_pub_key.r_key.as_ref()
+            .ok_or(IndyCryptoError::InvalidStructure("No revocation part present in credential revocation public key.".to_string()))?;
+
+        self.registries.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service client lock is poisoned".to_string()))?
+            .insert(rev_reg_id.to_owned(), (cred_rev_pub_key.g, rev_key_pub.z));
+
+        Ok(())
+    }
+
+    /// Returns witnesses of requested credentials in order of requests.
+    ///
+    /// Cached witnesses younger than `cache_ttl` are returned as is, the rest are requested
+    /// from the service in batches per revocation registry. While the service is rate limited
+    /// or backing off after failure, stale cached witnesses are returned instead;
+    /// requests without cached witness fail.
+    ///
+    /// # Arguments
+    /// * `requests` - Witness requests.
+    /// * `clock` - Source of the current time.
+    pub fn get_witnesses<C>(&self,
+                            requests: &[WitnessRequest],
+                            clock: &C) -> Result<Vec<ValidatedWitness>, IndyCryptoError> where C: Clock {
+        trace!("WitnessServiceClient::get_witnesses: >>> requests: {:?}", requests);
+
+        let now = Timestamp::now(clock)?;
+
+        let mut witnesses: Vec<Option<ValidatedWitness>> = Vec::with_capacity(requests.len());
+        let mut misses: BTreeMap<&str, BTreeMap<u32, &NonRevocationCredentialSignature>> = BTreeMap::new();
+
+        {
+            let cache = self._lock_cache()?;
+            for request in requests {
+                let cached = cache.get(&(request.rev_reg_id.to_owned(), request.r_cred.i))
+                    .and_then(|cached| {
+                        if now.as_secs() < cached.fetched_at.as_secs().saturating_add(self.config.cache_ttl) { Some(cached) } else { None }
+                    });
+
+                if cached.is_none() {
+                    misses.entry(request.rev_reg_id).or_insert_with(BTreeMap::new).insert(request.r_cred.i, request.r_cred);
+                }
+                witnesses.push(cached.cloned());
+            }
+        }
+
+        for (rev_reg_id, r_creds) in misses {
+            let r_creds = r_creds.into_iter().collect::<Vec<(u32, &NonRevocationCredentialSignature)>>();
+            for chunk in r_creds.chunks(self.config.max_batch_size) {
+                self._fetch(rev_reg_id, chunk, now)?;
+            }
+        }
+
+        let cache = self._lock_cache()?;
+        let witnesses = requests.iter().zip(witnesses.into_iter())
+            .map(|(request, witness)| match witness {
+                Some(witness) => Ok(witness),
+                None => cache.get(&(request.rev_reg_id.to_owned(), request.r_cred.i))
+                    .cloned()
+                    .ok_or(IndyCryptoError::InvalidState(
+                        format!("Witness service is unavailable: no witness for index {} of revocation registry {}", request.r_cred.i, request.rev_reg_id)))
+            })
+            .collect::<Result<Vec<ValidatedWitness>, IndyCryptoError>>()?;
+
+        trace!("WitnessServiceClient::get_witnesses: <<< witnesses: {:?}", witnesses);
+
+        Ok(witnesses)
+    }
+
+    /// Drops all cached witnesses (e.g. after revocation registry is rotated).
+    pub fn clear_cache(&self) -> Result<(), IndyCryptoError> {
+        self._lock_cache()?.clear();
+        Ok(())
+    }
+
+    fn _fetch(&self,
+              rev_reg_id: &str,
+              r_creds: &[(u32, &NonRevocationCredentialSignature)],
+              now: Timestamp) -> Result<(), IndyCryptoError> {
+        {
+            let mut request_state = self._lock_request_state()?;
+            if now.as_secs() < request_state.next_request_at {
+                // Rate limited or backing off: stale cached witnesses are used if any
+                return Ok(());
+            }
+            request_state.next_request_at = now.as_secs().saturating_add(self.config.min_request_interval);
+        }
+
+        let (g, z) = self.registries.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service client lock is poisoned".to_string()))?
+            .get(rev_reg_id)
+            .cloned()
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Revocation registry {} is not added to witness service client", rev_reg_id)))?;
+
+        let rev_idxs = r_creds.iter().map(|&(rev_idx, _)| rev_idx).collect::<Vec<u32>>();
+
+        let res = self.service.fetch_witnesses(rev_reg_id, &rev_idxs)
+            .and_then(|batch| WitnessServiceClient::<S>::_validate_batch(&batch, r_creds, &g, &z).map(|_| batch));
+
+        let mut request_state = self._lock_request_state()?;
+
+        let batch = match res {
+            Ok(batch) => {
+                request_state.failures = 0;
+                batch
+            }
+            Err(err) => {
+                request_state.failures = request_state.failures.saturating_add(1);
+                let backoff = match request_state.failures {
+                    failures if failures > 63 => self.config.backoff_max,
+                    failures => self.config.backoff_base.saturating_mul(1 << (failures - 1)).min(self.config.backoff_max)
+                };
+                request_state.next_request_at = request_state.next_request_at.max(now.as_secs().saturating_add(backoff));
+                return Err(err);
+            }
+        };
+
+        let mut cache = self._lock_cache()?;
+        for (rev_idx, witness) in batch.witnesses {
+            cache.insert((rev_reg_id.to_owned(), rev_idx), ValidatedWitness { rev_reg: batch.rev_reg.clone(), witness, fetched_at: now });
+        }
+
+        Ok(())
+    }
+
+    fn _validate_batch(batch: &WitnessBatch,
+                       r_creds: &[(u32, &NonRevocationCredentialSignature)],
+                       g: &PointG1,
+                       z: &Pair) -> Result<(), IndyCryptoError> {
+        for &(rev_idx, r_cred) in r_creds {
+            let witness = batch.witnesses.get(&rev_idx)
+                .ok_or(IndyCryptoError::InvalidStructure(format!("Witness service didn't return witness for index {}", rev_idx)))?;
+
+            let z_calc = Pair::pair(&r_cred.witness_signature.g_i, &batch.rev_reg.accum)?
+                .mul(&Pair::pair(g, &witness.omega)?.inverse()?)?;
+
+            if z_calc != *z {
+                return Err(IndyCryptoError::InvalidStructure(
+                    format!("Witness service returned witness for index {} that doesn't correspond to accumulator", rev_idx)));
+            }
+        }
+
+        if batch.witnesses.len() != r_creds.len() {
+            return Err(IndyCryptoError::InvalidStructure("Witness service returned witnesses that weren't requested".to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn _lock_cache(&self) -> Result<::std::sync::MutexGuard<HashMap<(String, u32), ValidatedWitness>>, IndyCryptoError> {
+        self.cache.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service client lock is poisoned".to_string()))
+    }
+
+    fn _lock_request_state(&self) -> Result<::std::sync::MutexGuard<RequestState>, IndyCryptoError> {
+        self.request_state.lock()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service client lock is poisoned".to_string()))
+    }
+}
+
+/// Reference witness service computing witnesses locally from full revocation registry deltas and tails.
+///
+/// Can be wrapped by a server to run witness service or used by wallets that have access to tails.
+pub struct LocalWitnessService<RTA> where RTA: RevocationTailsAccessor {
+    registries: RwLock<HashMap<String, LocalRegistry<RTA>>>
+}
+
+struct LocalRegistry<RTA> where RTA: RevocationTailsAccessor {
+    max_cred_num: u32,
+    issuance_by_default: bool,
+    rev_reg_delta: RevocationRegistryDelta,
+    rev_tails_accessor: RTA
+}
+
+impl<RTA> LocalWitnessService<RTA> where RTA: RevocationTailsAccessor {
+    pub fn new() -> LocalWitnessService<RTA> {
+        LocalWitnessService { registries: RwLock::new(HashMap::new()) }
+    }
+
+    /// Adds revocation registry to the service.
+    ///
+    /// # Arguments
+    /// * `rev_reg_id` - Identifier of revocation registry.
+    /// * `max_cred_num` - Max credential number in revocation registry.
+    /// * `issuance_by_default` - Type of issuance.
+    /// * `rev_reg_delta` - Delta from creation of revocation registry.
+    /// * `rev_tails_accessor` - Revocation tails accessor.
+    pub fn add_registry(&self,
+                        rev_reg_id: &str,
+                        max_cred_num: u32,
+                        issuance_by_default: bool,
+                        rev_reg_delta: &RevocationRegistryDelta,
+                        rev_tails_accessor: RTA) -> Result<(), IndyCryptoError> {
+        self._write_registries()?
+            .insert(rev_reg_id.to_owned(), LocalRegistry { max_cred_num, issuance_by_default, rev_reg_delta: rev_reg_delta.clone(), rev_tails_accessor });
+        Ok(())
+    }
+
+    /// Applies delta published by Issuer to revocation registry of the service.
+    ///
+    /// # Arguments
+    /// * `rev_reg_id` - Identifier of revocation registry.
+    /// * `rev_reg_delta` - Revocation registry delta.
+    pub fn update_registry(&self,
+                           rev_reg_id: &str,
+                           rev_reg_delta: &RevocationRegistryDelta) -> Result<(), IndyCryptoError> {
+        self._write_registries()?
+            .get_mut(rev_reg_id)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Revocation registry {} not found", rev_reg_id)))?
+            .rev_reg_delta
+            .merge(rev_reg_delta)
+    }
+
+    fn _write_registries(&self) -> Result<::std::sync::RwLockWriteGuard<HashMap<String, LocalRegistry<RTA>>>, IndyCryptoError> {
+        self.registries.write()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service lock is poisoned".to_string()))
+    }
+}
+
+impl<RTA> WitnessService for LocalWitnessService<RTA> where RTA: RevocationTailsAccessor {
+    fn fetch_witnesses(&self, rev_reg_id: &str, rev_idxs: &[u32]) -> Result<WitnessBatch, IndyCryptoError> {
+        let registries = self.registries.read()
+            .map_err(|_| IndyCryptoError::InvalidState("Witness service lock is poisoned".to_string()))?;
+
+        let registry = registries.get(rev_reg_id)
+            .ok_or(IndyCryptoError::InvalidStructure(format!("Revocation registry {} not found", rev_reg_id)))?;
+
+        let mut witnesses = BTreeMap::new();
+        for &rev_idx in rev_idxs {
+            if rev_idx == 0 || rev_idx > registry.max_cred_num {
+                return Err(IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(
+                    format!("Index {} is out of revocation registry {}", rev_idx, rev_reg_id)));
+            }
+            witnesses.insert(rev_idx, Witness::new(rev_idx,
+                                                   registry.max_cred_num,
+                                                   registry.issuance_by_default,
+                                                   &registry.rev_reg_delta,
+                                                   &registry.rev_tails_accessor)?);
+        }
+
+        Ok(WitnessBatch { rev_reg: RevocationRegistry::from(registry.rev_reg_delta.clone()), witnesses })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cl::issuer;
+    use cl::prover;
+    use pair::PointG2;
+    use utils::clock::FixedClock;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockWitnessService {
+        calls: AtomicUsize,
+        batch: Option<WitnessBatch>
+    }
+
+    impl WitnessService for MockWitnessService {
+        fn fetch_witnesses(&self, _rev_reg_id: &str, _rev_idxs: &[u32]) -> Result<WitnessBatch, IndyCryptoError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.batch.clone().ok_or(IndyCryptoError::InvalidState("Service is down".to_string()))
+        }
+    }
+
+    fn _client(batch: Option<WitnessBatch>) -> WitnessServiceClient<MockWitnessService> {
+        let client = WitnessServiceClient::new(MockWitnessService { calls: AtomicUsize::new(0), batch },
+                                               WitnessServiceConfig { backoff_base: 10, backoff_max: 25, ..WitnessServiceConfig::default() }).unwrap();
+        client.add_registry("rev_reg", &issuer::mocks::credential_public_key(), &issuer::mocks::revocation_key_public()).unwrap();
+        client
+    }
+
+    #[test]
+    fn get_witnesses_works_for_backoff() {
+        let client = _client(None);
+        let credential = prover::mocks::credential();
+        let requests = [WitnessRequest { rev_reg_id: "rev_reg", r_cred: credential.non_revocation_signature().unwrap() }];
+        let mut clock = FixedClock::new(1_500_000_000);
+
+        assert!(client.get_witnesses(&requests, &clock).is_err());
+        assert!(client.get_witnesses(&requests, &clock).is_err());
+        assert_eq!(1, client.service.calls.load(Ordering::SeqCst));
+
+        clock.advance(10);
+        assert!(client.get_witnesses(&requests, &clock).is_err());
+        assert_eq!(2, client.service.calls.load(Ordering::SeqCst));
+
+        clock.advance(19);
+        assert!(client.get_witnesses(&requests, &clock).is_err());
+        assert_eq!(2, client.service.calls.load(Ordering::SeqCst));
+
+        clock.advance(1);
+        assert!(client.get_witnesses(&requests, &clock).is_err());
+        assert_eq!(3, client.service.calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn get_witnesses_works_for_witness_not_corresponding_to_accumulator() {
+        let client = _client(Some(WitnessBatch {
+            rev_reg: issuer::mocks::revocation_registry(),
+            witnesses: btreemap![1 => Witness { omega: PointG2::new_inf().unwrap() }]
+        }));
+        let credential = prover::mocks::credential();
+        let requests = [WitnessRequest { rev_reg_id: "rev_reg", r_cred: credential.non_revocation_signature().unwrap() }];
+
+        let res = client.get_witnesses(&requests, &FixedClock::new(1_500_000_000));
+        assert!(res.is_err());
+        assert!(client._lock_cache().unwrap().is_empty());
+    }
+}
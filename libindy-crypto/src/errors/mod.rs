@@ -28,6 +28,9 @@ pub enum IndyCryptoError {
     AnoncredsInvalidRevocationAccumulatorIndex(String),
     AnoncredsCredentialRevoked(String),
     AnoncredsProofRejected(String),
+    AnoncredsProofLimitExceeded(String),
+    AnoncredsProofPolicyViolated(String),
+    AnoncredsIssuerKeyChanged(String),
 }
 
 impl fmt::Display for IndyCryptoError {
@@ -49,6 +52,9 @@ impl fmt::Display for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(ref description) => write!(f, "Invalid revocation accumulator index: {}", description),
             IndyCryptoError::AnoncredsCredentialRevoked(ref description) => write!(f, "Credential revoked: {}", description),
             IndyCryptoError::AnoncredsProofRejected(ref description) => write!(f, "Proof rejected: {}", description),
+            IndyCryptoError::AnoncredsProofLimitExceeded(ref description) => write!(f, "Proof limit exceeded: {}", description),
+            IndyCryptoError::AnoncredsProofPolicyViolated(ref description) => write!(f, "Proof policy violated: {}", description),
+            IndyCryptoError::AnoncredsIssuerKeyChanged(ref description) => write!(f, "Issuer key changed: {}", description),
         }
     }
 }
@@ -72,6 +78,9 @@ impl Error for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(ref description) => description,
             IndyCryptoError::AnoncredsCredentialRevoked(ref description) => description,
             IndyCryptoError::AnoncredsProofRejected(ref description) => description,
+            IndyCryptoError::AnoncredsProofLimitExceeded(ref description) => description,
+            IndyCryptoError::AnoncredsProofPolicyViolated(ref description) => description,
+            IndyCryptoError::AnoncredsIssuerKeyChanged(ref description) => description,
         }
     }
 
@@ -93,6 +102,9 @@ impl Error for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(_) => None,
             IndyCryptoError::AnoncredsCredentialRevoked(_) => None,
             IndyCryptoError::AnoncredsProofRejected(_) => None,
+            IndyCryptoError::AnoncredsProofLimitExceeded(_) => None,
+            IndyCryptoError::AnoncredsProofPolicyViolated(_) => None,
+            IndyCryptoError::AnoncredsIssuerKeyChanged(_) => None,
         }
     }
 }
@@ -116,6 +128,9 @@ impl ToErrorCode for IndyCryptoError {
             IndyCryptoError::AnoncredsInvalidRevocationAccumulatorIndex(_) => ErrorCode::AnoncredsInvalidRevocationAccumulatorIndex,
             IndyCryptoError::AnoncredsCredentialRevoked(_) => ErrorCode::AnoncredsCredentialRevoked,
             IndyCryptoError::AnoncredsProofRejected(_) => ErrorCode::AnoncredsProofRejected,
+            IndyCryptoError::AnoncredsProofLimitExceeded(_) => ErrorCode::AnoncredsProofLimitExceeded,
+            IndyCryptoError::AnoncredsProofPolicyViolated(_) => ErrorCode::AnoncredsProofPolicyViolated,
+            IndyCryptoError::AnoncredsIssuerKeyChanged(_) => ErrorCode::AnoncredsIssuerKeyChanged,
         }
     }
 }
@@ -0,0 +1,97 @@
+use errors::ToErrorCode;
+use ffi::ErrorCode;
+use utils::benchmark::{benchmark, BenchmarkOperation, BenchmarkParams};
+use utils::ctypes::CTypesUtils;
+
+use libc::c_char;
+
+use serde_json;
+
+/// Measures duration of a core crypto operation on the current device.
+///
+/// Allows wrappers to estimate duration of high level operations (e.g. witness update
+/// costs one "point_g2_add" per changed registry index) without shipping own micro-benchmarks.
+///
+/// # Arguments
+/// * `operation` - Operation name: "bn_mod_exp", "bn_mod_mul_batch", "bn_mod_mul_batch_arena", "safe_prime",
+///   "point_g1_mul", "point_g2_mul", "point_g2_add" or "pairing".
+/// * `params_json` - (optional) Parameters json: {"iterations": <number of runs, 10 by default>, "size": <bit size for big number operations, 2048 by default>}
+/// * `stats_json_p` - Reference that will contain statistics json:
+///   {"operation": <operation>, "iterations": <u32>, "total_us": <u64>, "min_us": <u64>, "max_us": <u64>, "mean_us": <u64>}
+#[no_mangle]
+pub extern fn indy_crypto_benchmark(operation: *const c_char,
+                                    params_json: *const c_char,
+                                    stats_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("indy_crypto_benchmark: >>> operation: {:?}, params_json: {:?}, stats_json_p: {:?}", operation, params_json, stats_json_p);
+
+    check_useful_c_str!(operation, ErrorCode::CommonInvalidParam1);
+    check_useful_opt_c_str!(params_json, ErrorCode::CommonInvalidParam2);
+    check_useful_c_ptr!(stats_json_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_crypto_benchmark: entities: operation: {:?}, params_json: {:?}", operation, params_json);
+
+    let operation = match BenchmarkOperation::from_name(&operation) {
+        Ok(operation) => operation,
+        Err(err) => return err.to_error_code()
+    };
+
+    let params: BenchmarkParams = match params_json {
+        Some(params_json) => match serde_json::from_str(&params_json) {
+            Ok(params) => params,
+            Err(_) => return ErrorCode::CommonInvalidParam2
+        },
+        None => BenchmarkParams::default()
+    };
+
+    let res = match benchmark(operation, &params) {
+        Ok(stats) => {
+            trace!("indy_crypto_benchmark: stats: {:?}", stats);
+            match serde_json::to_string(&stats) {
+                Ok(stats_json) => {
+                    unsafe {
+                        *stats_json_p = CTypesUtils::string_to_cstring(stats_json).into_raw();
+                        trace!("indy_crypto_benchmark: *stats_json_p: {:?}", *stats_json_p);
+                    }
+                    ErrorCode::Success
+                }
+                Err(_) => ErrorCode::CommonInvalidState
+            }
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_benchmark: <<< res: {:?}", res);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    #[test]
+    fn indy_crypto_benchmark_works() {
+        let operation = CString::new("point_g2_add").unwrap();
+        let params_json = CString::new(r#"{"iterations": 2}"#).unwrap();
+
+        let mut stats_json_p: *const c_char = ptr::null();
+        let err_code = indy_crypto_benchmark(operation.as_ptr(), params_json.as_ptr(), &mut stats_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let stats_json = unsafe { CStr::from_ptr(stats_json_p).to_str().unwrap() };
+        let stats: serde_json::Value = serde_json::from_str(stats_json).unwrap();
+        assert_eq!("point_g2_add", stats["operation"]);
+        assert_eq!(2, stats["iterations"]);
+    }
+
+    #[test]
+    fn indy_crypto_benchmark_works_for_unknown_operation() {
+        let operation = CString::new("unknown").unwrap();
+
+        let mut stats_json_p: *const c_char = ptr::null();
+        let err_code = indy_crypto_benchmark(operation.as_ptr(), ptr::null(), &mut stats_json_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidParam1);
+    }
+}
@@ -749,6 +749,53 @@ pub extern fn indy_crypto_cl_revocation_tails_generator_free(rev_tails_generator
     res
 }
 
+/// Verifies blinded credential secrets correctness proof created by Prover.
+///
+/// # Arguments
+/// * `blinded_credential_secrets` - Blinded master secret instance pointer generated by Prover.
+/// * `blinded_credential_secrets_correctness_proof` - Blinded master secret correctness proof instance pointer.
+/// * `credential_nonce` - Nonce instance pointer used for creation of blinded_credential_secrets_correctness_proof.
+/// * `credential_pub_key` - Credential public key instance pointer.
+/// * `valid_p` - Reference that will be filled with true - if proof valid or false otherwise.
+#[no_mangle]
+pub extern fn indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof(blinded_credential_secrets: *const c_void,
+                                                                                        blinded_credential_secrets_correctness_proof: *const c_void,
+                                                                                        credential_nonce: *const c_void,
+                                                                                        credential_pub_key: *const c_void,
+                                                                                        valid_p: *mut bool) -> ErrorCode {
+    trace!("indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof: >>> blinded_credential_secrets: {:?}, \
+        blinded_credential_secrets_correctness_proof: {:?}, credential_nonce: {:?}, credential_pub_key: {:?}, valid_p: {:?}",
+           blinded_credential_secrets, blinded_credential_secrets_correctness_proof, credential_nonce, credential_pub_key, valid_p);
+
+    check_useful_c_reference!(blinded_credential_secrets, BlindedCredentialSecrets, ErrorCode::CommonInvalidParam1);
+    check_useful_c_reference!(blinded_credential_secrets_correctness_proof, BlindedCredentialSecretsCorrectnessProof, ErrorCode::CommonInvalidParam2);
+    check_useful_c_reference!(credential_nonce, Nonce, ErrorCode::CommonInvalidParam3);
+    check_useful_c_reference!(credential_pub_key, CredentialPublicKey, ErrorCode::CommonInvalidParam4);
+    check_useful_c_ptr!(valid_p, ErrorCode::CommonInvalidParam5);
+
+    trace!("indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof: entities: blinded_credential_secrets: {:?}, \
+        blinded_credential_secrets_correctness_proof: {:?}, credential_nonce: {:?}, credential_pub_key: {:?}",
+           blinded_credential_secrets, blinded_credential_secrets_correctness_proof, credential_nonce, credential_pub_key);
+
+    let res = match Issuer::verify_blinded_credential_secrets_correctness_proof(&blinded_credential_secrets,
+                                                                                &blinded_credential_secrets_correctness_proof,
+                                                                                &credential_nonce,
+                                                                                &credential_pub_key) {
+        Ok(valid) => {
+            trace!("indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof: valid: {:?}", valid);
+            unsafe {
+                *valid_p = valid;
+                trace!("indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof: *valid_p: {:?}", *valid_p);
+            }
+            ErrorCode::Success
+        }
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof: <<< res: {:?}", res);
+    res
+}
+
 /// Signs credential values with primary keys only.
 ///
 /// Note that credential signature instances deallocation must be performed by
@@ -1680,6 +1727,42 @@ mod tests {
         _free_credential_signature_with_revoc(credential_signature_p, credential_signature_correctness_proof_p, revocation_registry_delta_p);
     }
 
+    #[test]
+    fn indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof_works() {
+        let credential_values = _credential_values();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
+        let credential_nonce = _nonce();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors,
+            blinded_credential_secrets_correctness_proof) = _blinded_credential_secrets(credential_pub_key,
+                                                                                   credential_key_correctness_proof,
+                                                                                   credential_values,
+                                                                                   credential_nonce);
+
+        let mut valid = false;
+        let err_code = indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
+                                                                                                 blinded_credential_secrets_correctness_proof,
+                                                                                                 credential_nonce,
+                                                                                                 credential_pub_key,
+                                                                                                 &mut valid);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(valid);
+
+        let other_nonce = _nonce();
+        let err_code = indy_crypto_cl_issuer_verify_blinded_credential_secrets_correctness_proof(blinded_credential_secrets,
+                                                                                                 blinded_credential_secrets_correctness_proof,
+                                                                                                 other_nonce,
+                                                                                                 credential_pub_key,
+                                                                                                 &mut valid);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!valid);
+
+        _free_credential_def(credential_pub_key, credential_priv_key, credential_key_correctness_proof);
+        _free_credential_values(credential_values);
+        _free_blinded_credential_secrets(blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof);
+        _free_nonce(credential_nonce);
+        _free_nonce(other_nonce);
+    }
+
     #[test]
     fn indy_crypto_cl_issuer_sign_credential_works() {
         let prover_id = _prover_did();
@@ -81,6 +81,19 @@ pub extern fn indy_crypto_cl_tail_free(tail: *const c_void) -> ErrorCode {
     res
 }
 
+/// Creates and returns witness of credential membership in revocation accumulator.
+///
+/// Note: Witness instance deallocation must be performed by calling indy_crypto_cl_witness_free.
+///
+/// # Arguments
+/// * `rev_idx` - Index of the credential in revocation registry.
+/// * `max_cred_num` - Max credential number in revocation registry.
+/// * `issuance_by_default` - Type of issuance of revocation registry.
+/// * `rev_reg_delta` - Reference that contains revocation registry delta instance pointer.
+/// * `ctx_tails` - Reference that contains tails context pointer.
+/// * `take_tail` - Callback to take tail by index.
+/// * `put_tail` - Callback to put taken tail back.
+/// * `witness_p` - Reference that will contain witness instance pointer.
 #[no_mangle]
 pub extern fn indy_crypto_cl_witness_new(rev_idx: u32,
                                          max_cred_num: u32,
@@ -111,6 +124,16 @@ pub extern fn indy_crypto_cl_witness_new(rev_idx: u32,
     res
 }
 
+/// Updates witness with revocation registry delta.
+///
+/// # Arguments
+/// * `rev_idx` - Index of the credential in revocation registry.
+/// * `max_cred_num` - Max credential number in revocation registry.
+/// * `rev_reg_delta` - Reference that contains revocation registry delta instance pointer.
+/// * `witness` - Reference that contains witness instance pointer.
+/// * `ctx_tails` - Reference that contains tails context pointer.
+/// * `take_tail` - Callback to take tail by index.
+/// * `put_tail` - Callback to put taken tail back.
 #[no_mangle]
 pub extern fn indy_crypto_cl_witness_update(rev_idx: u32,
                                             max_cred_num: u32,
@@ -135,6 +158,75 @@ pub extern fn indy_crypto_cl_witness_update(rev_idx: u32,
     res
 }
 
+/// Returns json representation of witness.
+///
+/// # Arguments
+/// * `witness` - Reference that contains witness instance pointer.
+/// * `witness_json_p` - Reference that will contain witness json.
+#[no_mangle]
+pub extern fn indy_crypto_cl_witness_to_json(witness: *const c_void,
+                                             witness_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("indy_crypto_cl_witness_to_json: >>> witness: {:?}, witness_json_p: {:?}", witness, witness_json_p);
+
+    check_useful_c_reference!(witness, Witness, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(witness_json_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("indy_crypto_cl_witness_to_json: entity >>> witness: {:?}", witness);
+
+    let res = match serde_json::to_string(witness) {
+        Ok(witness_json) => {
+            trace!("indy_crypto_cl_witness_to_json: witness_json: {:?}", witness_json);
+            unsafe {
+                let witness_json = CTypesUtils::string_to_cstring(witness_json);
+                *witness_json_p = witness_json.into_raw();
+                trace!("indy_crypto_cl_witness_to_json: witness_json_p: {:?}", *witness_json_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidState
+    };
+
+    trace!("indy_crypto_cl_witness_to_json: <<< res: {:?}", res);
+    res
+}
+
+/// Creates and returns witness from json.
+///
+/// Note: Witness instance deallocation must be performed by calling indy_crypto_cl_witness_free.
+///
+/// # Arguments
+/// * `witness_json` - Reference that contains witness json.
+/// * `witness_p` - Reference that will contain witness instance pointer.
+#[no_mangle]
+pub extern fn indy_crypto_cl_witness_from_json(witness_json: *const c_char,
+                                               witness_p: *mut *const c_void) -> ErrorCode {
+    trace!("indy_crypto_cl_witness_from_json: >>> witness_json: {:?}, witness_p: {:?}", witness_json, witness_p);
+
+    check_useful_c_str!(witness_json, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(witness_p, ErrorCode::CommonInvalidParam2);
+
+    trace!("indy_crypto_cl_witness_from_json: entity: witness_json: {:?}", witness_json);
+
+    let res = match serde_json::from_str::<Witness>(&witness_json) {
+        Ok(witness) => {
+            trace!("indy_crypto_cl_witness_from_json: witness: {:?}", witness);
+            unsafe {
+                *witness_p = Box::into_raw(Box::new(witness)) as *const c_void;
+                trace!("indy_crypto_cl_witness_from_json: *witness_p: {:?}", *witness_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidStructure
+    };
+
+    trace!("indy_crypto_cl_witness_from_json: <<< res: {:?}", res);
+    res
+}
+
+/// Deallocates witness instance.
+///
+/// # Arguments
+/// * `witness` - Reference that contains witness instance pointer.
 #[no_mangle]
 pub extern fn indy_crypto_cl_witness_free(witness: *const c_void) -> ErrorCode {
     trace!("indy_crypto_cl_witness_free: >>> witness: {:?}", witness);
@@ -609,7 +701,7 @@ pub extern fn indy_crypto_cl_sub_proof_request_builder_add_revealed_attr(sub_pro
 /// # Arguments
 /// * `sub_proof_request_builder` - Reference that contains sub proof request builder instance pointer.
 /// * `attr_name` - Related attribute
-/// * `p_type` - Predicate type (Currently `GE` only).
+/// * `p_type` - Predicate type (`GE`, `LE`, `GT` or `LT`).
 /// * `value` - Requested value.
 #[no_mangle]
 pub extern fn indy_crypto_cl_sub_proof_request_builder_add_predicate(sub_proof_request_builder: *const c_void,
@@ -626,7 +718,7 @@ pub extern fn indy_crypto_cl_sub_proof_request_builder_add_predicate(sub_proof_r
     trace!("indy_crypto_cl_sub_proof_request_builder_add_predicate: entities: >>> sub_proof_request_builder: {:?}, attr_name: {:?}, p_type: {:?}, value: {:?}",
            sub_proof_request_builder, attr_name, p_type, value);
 
-    let res = match sub_proof_request_builder.add_predicate(&attr_name, &p_type, value) {
+    let res = match sub_proof_request_builder.add_predicate(&attr_name, &p_type, i64::from(value)) {
         Ok(_) => ErrorCode::Success,
         Err(err) => err.to_error_code()
     };
@@ -1138,6 +1230,16 @@ mod tests {
         _free_nonce(nonce)
     }
 
+    #[test]
+    fn indy_crypto_cl_witness_from_json_works_for_invalid_json() {
+        let witness_json = CString::new(r#"{"omega":"invalid"}"#).unwrap();
+
+        let mut witness_p: *const c_void = ptr::null();
+        let err_code = indy_crypto_cl_witness_from_json(witness_json.as_ptr(), &mut witness_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+        assert!(witness_p.is_null());
+    }
+
     #[test]
     fn indy_crypto_cl_nonce_free_works() {
         let nonce = _nonce();
@@ -1,3 +1,4 @@
+use bn::{with_dec_parsing, DecParsing};
 use cl::prover::*;
 use cl::*;
 use errors::ToErrorCode;
@@ -616,6 +617,33 @@ pub extern fn indy_crypto_cl_prover_new_proof_builder(proof_builder_p: *mut *con
     res
 }
 
+/// Marks attribute as common for all sub proofs of the proof builder:
+/// its hidden value is proven to be the same in all sub proofs (e.g. master secret).
+///
+/// Common attributes must be added before sub proof requests.
+///
+/// # Arguments
+/// * `proof_builder` - Reference that contain proof builder instance pointer.
+/// * `attr` - Attribute name as null terminated string.
+#[no_mangle]
+pub extern fn indy_crypto_cl_proof_builder_add_common_attribute(proof_builder: *const c_void,
+                                                                attr: *const c_char) -> ErrorCode {
+    trace!("indy_crypto_cl_proof_builder_add_common_attribute: >>> proof_builder: {:?}, attr: {:?}", proof_builder, attr);
+
+    check_useful_mut_c_reference!(proof_builder, ProofBuilder, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+
+    trace!("indy_crypto_cl_proof_builder_add_common_attribute: entities: proof_builder: {:?}, attr: {:?}", proof_builder, attr);
+
+    let res = match proof_builder.add_common_attribute(&attr) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_cl_proof_builder_add_common_attribute: <<< res: {:?}", res);
+    res
+}
+
 /// Add a sub proof request to the proof builder
 ///
 /// # Arguments
@@ -809,6 +837,46 @@ pub extern fn indy_crypto_cl_proof_from_json(proof_json: *const c_char,
     res
 }
 
+/// Creates and returns proof json parsing big numbers with the given decimal parsing mode.
+///
+/// Lenient mode accepts leading zeros, `+` sign and surrounding whitespace produced by tolerant implementations,
+/// it applies to this call only.
+///
+/// Note: Proof instance deallocation must be performed by calling indy_crypto_cl_proof_free.
+///
+/// # Arguments
+/// * `proof_json` - Reference that contains proof json.
+/// * `lenient` - Parses big numbers in lenient mode if true, strictly otherwise.
+/// * `proof_p` - Reference that will contain proof instance pointer.
+#[no_mangle]
+pub extern fn indy_crypto_cl_proof_from_json_with_dec_parsing(proof_json: *const c_char,
+                                                              lenient: bool,
+                                                              proof_p: *mut *const c_void) -> ErrorCode {
+    trace!("indy_crypto_cl_proof_from_json_with_dec_parsing: >>> proof_json: {:?}, lenient: {:?}, proof_p: {:?}", proof_json, lenient, proof_p);
+
+    check_useful_c_str!(proof_json, ErrorCode::CommonInvalidParam1);
+    check_useful_c_ptr!(proof_p, ErrorCode::CommonInvalidParam3);
+
+    trace!("indy_crypto_cl_proof_from_json_with_dec_parsing: entity: proof_json: {:?}", proof_json);
+
+    let mode = if lenient { DecParsing::Lenient } else { DecParsing::Strict };
+
+    let res = match with_dec_parsing(mode, || Ok(serde_json::from_str::<Proof>(&proof_json)?)) {
+        Ok(proof) => {
+            trace!("indy_crypto_cl_proof_from_json_with_dec_parsing: proof: {:?}", proof);
+            unsafe {
+                *proof_p = Box::into_raw(Box::new(proof)) as *const c_void;
+                trace!("indy_crypto_cl_proof_from_json_with_dec_parsing: *proof_p: {:?}", *proof_p);
+            }
+            ErrorCode::Success
+        }
+        Err(_) => ErrorCode::CommonInvalidStructure
+    };
+
+    trace!("indy_crypto_cl_proof_from_json_with_dec_parsing: <<< res: {:?}", res);
+    res
+}
+
 /// Deallocates proof instance.
 ///
 /// # Arguments
@@ -833,6 +901,7 @@ pub extern fn indy_crypto_cl_proof_free(proof: *const c_void) -> ErrorCode {
 mod tests {
     use super::*;
 
+    use std::ffi::CString;
     use std::ptr;
     use ffi::cl::mocks::*;
     use ffi::cl::issuer::mocks::*;
@@ -1193,6 +1262,19 @@ mod tests {
         _free_proof_builder(proof_builder, nonce);
     }
 
+    #[test]
+    fn indy_crypto_cl_proof_builder_add_common_attribute_works() {
+        let proof_builder = _proof_builder();
+        let attr = CString::new("master_secret").unwrap();
+
+        let err_code = indy_crypto_cl_proof_builder_add_common_attribute(proof_builder, attr.as_ptr());
+        assert_eq!(err_code, ErrorCode::Success);
+
+        let nonce = _nonce();
+
+        _free_proof_builder(proof_builder, nonce);
+    }
+
     #[test]
     fn indy_crypto_cl_prover_proof_builder_finalize_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -1307,6 +1389,14 @@ mod tests {
         _free_proof(proof);
     }
 
+    #[test]
+    fn indy_crypto_cl_proof_from_json_with_dec_parsing_works_for_invalid_json() {
+        let proof_json = CString::new("{\"proofs\":\"+01\"}").unwrap();
+        let mut proof_p: *const c_void = ptr::null();
+        let err_code = indy_crypto_cl_proof_from_json_with_dec_parsing(proof_json.as_ptr(), true, &mut proof_p);
+        assert_eq!(err_code, ErrorCode::CommonInvalidStructure);
+    }
+
     #[test]
     fn indy_crypto_cl_proof_from_json_works() {
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = _credential_def();
@@ -2,8 +2,10 @@ use cl::verifier::*;
 use cl::*;
 use errors::ToErrorCode;
 use ffi::ErrorCode;
+use utils::ctypes::CTypesUtils;
 
 use std::os::raw::c_void;
+use libc::c_char;
 
 /// Creates and returns proof verifier.
 ///
@@ -78,6 +80,30 @@ pub extern fn indy_crypto_cl_proof_verifier_add_sub_proof_request(proof_verifier
 }
 
 
+/// Requires hidden value of the attribute to be identical in all sub proofs of verified proof.
+///
+/// # Arguments
+/// * `proof_verifier` - Reference that contain proof verifier instance pointer.
+/// * `attr` - Attribute name as null terminated string.
+#[no_mangle]
+pub extern fn indy_crypto_cl_proof_verifier_add_common_attribute(proof_verifier: *const c_void,
+                                                                 attr: *const c_char) -> ErrorCode {
+    trace!("indy_crypto_cl_proof_verifier_add_common_attribute: >>> proof_verifier: {:?}, attr: {:?}", proof_verifier, attr);
+
+    check_useful_mut_c_reference!(proof_verifier, ProofVerifier, ErrorCode::CommonInvalidParam1);
+    check_useful_c_str!(attr, ErrorCode::CommonInvalidParam2);
+
+    trace!("indy_crypto_cl_proof_verifier_add_common_attribute: entities: proof_verifier: {:?}, attr: {:?}", proof_verifier, attr);
+
+    let res = match proof_verifier.add_common_attribute(&attr) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_cl_proof_verifier_add_common_attribute: <<< res: {:?}", res);
+    res
+}
+
 /// Verifies proof and deallocates proof verifier.
 ///
 /// # Arguments
@@ -0,0 +1,78 @@
+use errors::ToErrorCode;
+use ffi::ErrorCode;
+use utils::entropy::{self, EntropyCB};
+
+use libc::c_void;
+
+#[cfg(feature = "fixed_entropy")]
+use std::slice;
+
+/// Sets host provided entropy source used by all randomness of the library
+/// (e.g. iOS SecRandomCopyBytes or Android Keystore backed RNG).
+///
+/// Prime candidates of credential keys are drawn from this source too, OpenSSL only tests their primality.
+///
+/// # Arguments
+/// * `context` - Pointer to some context that will be passed to the callback.
+/// * `entropy_cb` - (optional) Callback that fills `bytes_len` bytes at `bytes` and returns true on success.
+///   Null restores OS entropy.
+#[no_mangle]
+pub extern fn indy_crypto_set_entropy_callback(context: *const c_void,
+                                               entropy_cb: Option<EntropyCB>) -> ErrorCode {
+    trace!("indy_crypto_set_entropy_callback: >>> context: {:?}, entropy_cb: {:?}", context, entropy_cb.is_some());
+
+    let res = match entropy::set_entropy_callback(context, entropy_cb) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_set_entropy_callback: <<< res: {:?}", res);
+
+    res
+}
+
+/// Test mode: makes all randomness of the library deterministic bytes expanded from given seed.
+///
+/// Every random value (including secrets) becomes predictable, so it's exported only by builds
+/// with `fixed_entropy` feature intended for tests of wrappers.
+/// Call `indy_crypto_set_entropy_callback` with null callback to restore OS entropy.
+///
+/// # Arguments
+/// * `bytes` - Seed bytes.
+/// * `bytes_len` - Length of seed bytes.
+#[cfg(feature = "fixed_entropy")]
+#[no_mangle]
+pub extern fn indy_crypto_set_fixed_entropy(bytes: *const u8,
+                                            bytes_len: usize) -> ErrorCode {
+    trace!("indy_crypto_set_fixed_entropy: >>> bytes: {:?}, bytes_len: {:?}", bytes, bytes_len);
+
+    check_useful_c_byte_array!(bytes, bytes_len, ErrorCode::CommonInvalidParam1, ErrorCode::CommonInvalidParam2);
+
+    let res = match entropy::set_fixed_entropy(bytes) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_set_fixed_entropy: <<< res: {:?}", res);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ptr;
+
+    #[cfg(feature = "fixed_entropy")]
+    #[test]
+    fn indy_crypto_set_fixed_entropy_works_for_empty_bytes() {
+        assert_eq!(ErrorCode::CommonInvalidParam1, indy_crypto_set_fixed_entropy(ptr::null(), 0));
+        assert_eq!(ErrorCode::CommonInvalidParam2, indy_crypto_set_fixed_entropy([1u8].as_ptr(), 0));
+    }
+
+    #[test]
+    fn indy_crypto_set_entropy_callback_works_for_os_entropy() {
+        assert_eq!(ErrorCode::Success, indy_crypto_set_entropy_callback(ptr::null(), None));
+    }
+}
@@ -0,0 +1,55 @@
+use errors::ToErrorCode;
+use ffi::ErrorCode;
+use utils::ctypes::CTypesUtils;
+
+use libc::c_char;
+
+use serde_json;
+
+/// Performs thread-safe one-time initialization of crypto backends and returns their capabilities.
+///
+/// Calling it isn't required, every entry point initializes backend state lazily and safely.
+///
+/// # Arguments
+/// * `capabilities_json_p` - Reference that will contain capabilities json.
+#[no_mangle]
+pub extern fn indy_crypto_init(capabilities_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("indy_crypto_init: >>> capabilities_json_p: {:?}", capabilities_json_p);
+
+    check_useful_c_ptr!(capabilities_json_p, ErrorCode::CommonInvalidParam1);
+
+    let res = match ::init() {
+        Ok(capabilities) => match serde_json::to_string(&capabilities) {
+            Ok(capabilities_json) => {
+                trace!("indy_crypto_init: capabilities_json: {:?}", capabilities_json);
+                unsafe {
+                    let capabilities_json = CTypesUtils::string_to_cstring(capabilities_json);
+                    *capabilities_json_p = capabilities_json.into_raw();
+                    trace!("indy_crypto_init: capabilities_json_p: {:?}", *capabilities_json_p);
+                }
+                ErrorCode::Success
+            }
+            Err(_) => ErrorCode::CommonInvalidState
+        },
+        Err(err) => err.to_error_code()
+    };
+
+    trace!("indy_crypto_init: <<< res: {:?}", res);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ptr;
+
+    #[test]
+    fn indy_crypto_init_works() {
+        let mut capabilities_json_p: *const c_char = ptr::null();
+        let err_code = indy_crypto_init(&mut capabilities_json_p);
+        assert_eq!(err_code, ErrorCode::Success);
+        assert!(!capabilities_json_p.is_null());
+    }
+}
@@ -1,5 +1,8 @@
 pub mod cl;
+pub mod benchmark;
 pub mod bls;
+pub mod entropy;
+pub mod init;
 pub mod logger;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -66,4 +69,13 @@ pub enum ErrorCode
 
     // Proof rejected
     AnoncredsProofRejected = 118,
+
+    // Proof exceeds verifier limits
+    AnoncredsProofLimitExceeded = 119,
+
+    // Proof violates verifier disclosure policy
+    AnoncredsProofPolicyViolated = 120,
+
+    // Issuer key differs from the key pinned on first use
+    AnoncredsIssuerKeyChanged = 121,
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use errors::IndyCryptoError;
+
+use rand::os::OsRng;
+
+/// Capabilities of crypto backends the library is built with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub bn_backend: &'static str,
+    pub bn_backend_version: &'static str,
+    pub pair_backend: &'static str,
+    /// Whether OS entropy is available (host provided entropy callback can be set otherwise).
+    pub os_entropy: bool
+}
+
+lazy_static! {
+    static ref CAPABILITIES: Capabilities = _init();
+}
+
+/// Performs one-time initialization of crypto backends: OpenSSL state and constants of the library.
+///
+/// Initialization is thread-safe and is performed only once, later calls return the same capabilities.
+/// Calling it isn't required: every entry point initializes state it uses lazily and safely,
+/// but explicit call moves initialization cost out of the first operation
+/// and lets host check capabilities before use.
+///
+/// # Example
+/// ```
+/// let capabilities = indy_crypto::init().unwrap();
+/// assert_eq!("openssl", capabilities.bn_backend);
+/// ```
+pub fn init() -> Result<Capabilities, IndyCryptoError> {
+    trace!("init: >>>");
+
+    let capabilities = CAPABILITIES.clone();
+
+    trace!("init: <<< capabilities: {:?}", capabilities);
+
+    Ok(capabilities)
+}
+
+fn _init() -> Capabilities {
+    let bn_backend_version = ::bn::init();
+    ::cl::init_constants();
+
+    // AMCL has no global state: its RNG is seeded on every use from `utils::entropy`
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        bn_backend: "openssl",
+        bn_backend_version,
+        pair_backend: "amcl BN254",
+        os_entropy: OsRng::new().is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn init_works_for_concurrent_calls() {
+        let handles: Vec<_> = (0..8).map(|_| thread::spawn(|| init().unwrap())).collect();
+
+        let capabilities = init().unwrap();
+        for handle in handles {
+            assert_eq!(capabilities, handle.join().unwrap());
+        }
+    }
+}
@@ -1,3 +1,14 @@
+//! Shared crypto library for Hyperledger Indy components.
+//!
+//! # Panics
+//!
+//! Public APIs never panic on any input: malformed keys, proofs, json, hex or decimal representations
+//! and out of range indexes are reported as `IndyCryptoError`. This is a contract of FFI layer as well,
+//! where panic unwinding into host application aborts it. Parsers of untrusted input are fuzzed
+//! by `tests/panic_free.rs`.
+//!
+//! Remaining `unwrap` calls outside of tests operate on constant values only.
+
 extern crate amcl;
 extern crate env_logger;
 #[macro_use]
@@ -39,6 +50,7 @@ extern crate time;
 
 pub mod cl;
 pub mod bls;
+pub mod vrf;
 
 #[cfg(feature = "bn_openssl")]
 #[path = "bn/openssl.rs"]
@@ -47,6 +59,10 @@ pub mod bn;
 pub mod errors;
 pub mod ffi;
 
+mod init;
+
+pub use init::{init, Capabilities};
+
 #[cfg(feature = "pair_amcl")]
 #[path = "pair/amcl.rs"]
 pub mod pair;
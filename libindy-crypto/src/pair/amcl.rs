@@ -20,8 +20,9 @@ use amcl::fp2::FP2;
 use amcl::pair::{ate, g1mul, g2mul, gtpow, fexp};
 use amcl::rand::RAND;
 
-use rand::os::OsRng;
-use rand::Rng;
+use sha2::{Sha256, Digest};
+use utils::entropy;
+use utils::u64_to_be_bytes;
 use std::fmt::{Debug, Formatter, Error};
 
 #[cfg(feature = "serialization")]
@@ -33,6 +34,49 @@ use std::fmt;
 
 #[cfg(test)]
 use std::cell::RefCell;
+use std::ptr;
+
+/// Hex digits of the longest limb accepted by `_from_hex`: limbs of normalized values have 56 bits
+/// and any value of 15 digits fits `i64` amcl parses limbs to.
+const MAX_LIMB_HEX_DIGITS: usize = 15;
+
+/// Returns number of limbs of amcl `BIG` in its hex representation.
+fn _big_limbs() -> usize {
+    BIG::new().to_hex().split_whitespace().count()
+}
+
+/// Parses amcl hex representation after checking its structure, as amcl parser panics on malformed input
+/// and panics can't be caught if the library is built with `panic = "abort"`.
+///
+/// Representation must consist of `true`/`false` infinity flag (if `with_flag` is set)
+/// followed by exactly `limbs` hex limbs.
+fn _from_hex<T, F>(str: &str, with_flag: bool, limbs: usize, from_hex: F) -> Result<T, IndyCryptoError> where F: FnOnce(String) -> T {
+    let invalid = || IndyCryptoError::InvalidStructure("Invalid hex representation".to_string());
+
+    let mut tokens = str.split_whitespace();
+
+    if with_flag {
+        match tokens.next() {
+            Some("true") | Some("false") => (),
+            _ => return Err(invalid())
+        }
+    }
+
+    let mut count = 0;
+    for token in tokens {
+        let digits = if token.starts_with('-') { &token[1..] } else { token };
+        if digits.is_empty() || digits.len() > MAX_LIMB_HEX_DIGITS || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        count += 1;
+    }
+
+    if count != limbs {
+        return Err(invalid());
+    }
+
+    Ok(from_hex(str.to_string()))
+}
 
 #[cfg(test)]
 thread_local! {
@@ -75,8 +119,7 @@ fn random_mod_order() -> Result<BIG, IndyCryptoError> {
 fn _random_mod_order() -> Result<BIG, IndyCryptoError> {
     let entropy_bytes = 128;
     let mut seed = vec![0; entropy_bytes];
-    let mut os_rng = OsRng::new().unwrap();
-    os_rng.fill_bytes(&mut seed.as_mut_slice());
+    entropy::fill_bytes(&mut seed)?;
     let mut rng = RAND::new();
     rng.clean();
     // AMCL recommends to initialise from at least 128 bytes, check doc for `RAND.seed`
@@ -84,6 +127,76 @@ fn _random_mod_order() -> Result<BIG, IndyCryptoError> {
     Ok(BIG::randomnum(&BIG::new_ints(&CURVE_ORDER), &mut rng))
 }
 
+/// Domain tag for deterministic derivation of group elements.
+///
+/// Every purpose must use its own tag: the same seed used under two different tags
+/// yields unrelated values, while reusing a tag for two purposes makes them identical.
+///
+/// Tag is public, so domains can be declared as constants: `DerivationDomain(b"purpose")`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DerivationDomain(pub &'static [u8]);
+
+impl DerivationDomain {
+    pub fn new(tag: &'static [u8]) -> DerivationDomain {
+        DerivationDomain(tag)
+    }
+
+    pub fn tag(&self) -> &'static [u8] {
+        self.0
+    }
+
+    fn hash(&self, parts: &[&[u8]]) -> Vec<u8> {
+        let mut hasher = Sha256::default();
+        _hash_with_len(&mut hasher, self.0);
+        for part in parts {
+            _hash_with_len(&mut hasher, part);
+        }
+        hasher.result().to_vec()
+    }
+}
+
+fn _hash_with_len(hasher: &mut Sha256, data: &[u8]) {
+    hasher.input(&u64_to_be_bytes(data.len() as u64));
+    hasher.input(data);
+}
+
+/// Secret input of deterministic derivation.
+///
+/// Security note: every value derived from a seed is exactly as secret as the seed itself.
+/// Anyone who learns the seed can recompute all derived scalars, so the seed must come from
+/// a cryptographically secure source and be stored like a private key.
+pub struct SecretSeed(Vec<u8>);
+
+impl SecretSeed {
+    /// Minimal seed length in bytes.
+    pub const MIN_LEN: usize = MODBYTES;
+
+    pub fn new(seed: &[u8]) -> Result<SecretSeed, IndyCryptoError> {
+        if seed.len() < Self::MIN_LEN {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Invalid len of seed: expected at least {}, actual {}", Self::MIN_LEN, seed.len())));
+        }
+        Ok(SecretSeed(seed.to_vec()))
+    }
+
+    /// Creates seed from entropy of `utils::entropy` (OS entropy by default).
+    pub fn generate() -> Result<SecretSeed, IndyCryptoError> {
+        let mut seed = vec![0; Self::MIN_LEN];
+        entropy::fill_bytes(&mut seed)?;
+        Ok(SecretSeed(seed))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for SecretSeed {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "SecretSeed {{ len: {} }}", self.0.len())
+    }
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub struct PointG1 {
     point: ECP
@@ -121,6 +234,22 @@ impl PointG1 {
         Ok(r.is_infinity())
     }
 
+    /// Checks that point isn't infinity, lies on the curve and belongs to the subgroup of the group order
+    pub fn is_valid(&self) -> Result<bool, IndyCryptoError> {
+        let mut r = self.point;
+        if r.is_infinity() {
+            return Ok(false);
+        }
+
+        let (x, y) = (r.getx(), r.gety());
+        if ECP::new_bigs(&x, &y).is_infinity() {
+            return Ok(false);
+        }
+
+        let mut order = BIG::new_ints(&CURVE_ORDER);
+        Ok(r.mul(&mut order).is_infinity())
+    }
+
     /// PointG1 ^ GroupOrderElement
     pub fn mul(&self, e: &GroupOrderElement) -> Result<PointG1, IndyCryptoError> {
         let mut r = self.point;
@@ -165,7 +294,7 @@ impl PointG1 {
 
     pub fn from_string(str: &str) -> Result<PointG1, IndyCryptoError> {
         Ok(PointG1 {
-            point: ECP::from_hex(str.to_string())
+            point: _from_hex(str, true, 3 * _big_limbs(), ECP::from_hex)?
         })
     }
 
@@ -188,6 +317,14 @@ impl PointG1 {
         )
     }
 
+    /// Derives public point from domain tag and public input.
+    ///
+    /// Discrete log of the result to any other point is unknown, so it is suitable
+    /// as an independent base (e.g. per-session pseudonym base). Input is not secret.
+    pub fn derive_base(domain: &DerivationDomain, input: &[u8]) -> Result<PointG1, IndyCryptoError> {
+        PointG1::from_hash(&domain.hash(&[input]))
+    }
+
     pub fn from_hash(hash: &[u8]) -> Result<PointG1, IndyCryptoError> {
         let mut el = GroupOrderElement::from_bytes(hash)?;
         let mut point = ECP::new_big(&el.bn);
@@ -239,7 +376,7 @@ impl<'a> Deserialize<'a> for PointG1 {
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone)]
 pub struct PointG2 {
     point: ECP2
 }
@@ -276,6 +413,12 @@ impl PointG2 {
         })
     }
 
+    /// Checks infinity
+    pub fn is_inf(&self) -> Result<bool, IndyCryptoError> {
+        let mut r = self.point;
+        Ok(r.is_infinity())
+    }
+
     /// PointG2 * PointG2
     pub fn add(&self, q: &PointG2) -> Result<PointG2, IndyCryptoError> {
         let mut r = self.point;
@@ -313,7 +456,7 @@ impl PointG2 {
 
     pub fn from_string(str: &str) -> Result<PointG2, IndyCryptoError> {
         Ok(PointG2 {
-            point: ECP2::from_hex(str.to_string())
+            point: _from_hex(str, true, 6 * _big_limbs(), ECP2::from_hex)?
         })
     }
 
@@ -337,6 +480,15 @@ impl PointG2 {
     }
 }
 
+impl PartialEq for PointG2 {
+    fn eq(&self, other: &PointG2) -> bool {
+        // points are stored in projective coordinates, so the same point can have different representations
+        let mut point = self.point;
+        let mut other_point = other.point;
+        point.equals(&mut other_point)
+    }
+}
+
 impl Debug for PointG2 {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(f, "PointG2 {{ point: {} }}", self.point.to_hex())
@@ -403,6 +555,21 @@ impl GroupOrderElement {
         })
     }
 
+    /// Derives element in 0, ..., GroupOrder-1 from secret seed, domain tag and public info.
+    ///
+    /// Result is reproducible and exactly as secret as `seed`.
+    pub fn new_from_domain_seed(domain: &DerivationDomain, seed: &SecretSeed, info: &[u8]) -> Result<GroupOrderElement, IndyCryptoError> {
+        GroupOrderElement::new_from_seed(&domain.hash(&[seed.as_bytes(), info]))
+    }
+
+    /// Returns bytes representation of the group order.
+    pub fn order_bytes() -> Result<Vec<u8>, IndyCryptoError> {
+        let mut order = BIG::new_ints(&CURVE_ORDER);
+        let mut vec = vec![0u8; Self::BYTES_REPR_SIZE];
+        order.tobytes(&mut vec);
+        Ok(vec)
+    }
+
     /// (GroupOrderElement ^ GroupOrderElement) mod GroupOrder
     pub fn pow_mod(&self, e: &GroupOrderElement) -> Result<GroupOrderElement, IndyCryptoError> {
         let mut base = self.bn;
@@ -460,6 +627,37 @@ impl GroupOrderElement {
         })
     }
 
+    /// Inverts all elements with a single field inversion using Montgomery's trick.
+    ///
+    /// Returns inverses in the order of `elements`. Fails if any element is zero, as it has no inverse.
+    pub fn batch_inverse(elements: &[GroupOrderElement]) -> Result<Vec<GroupOrderElement>, IndyCryptoError> {
+        if elements.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let zero = GroupOrderElement { bn: BIG::new() };
+
+        // prefix_products[i] = elements[0] * ... * elements[i - 1]
+        let mut prefix_products = Vec::with_capacity(elements.len());
+        let mut product = GroupOrderElement::from_bytes(&[1])?;
+        for element in elements.iter() {
+            if element.add_mod(&zero)? == zero {
+                return Err(IndyCryptoError::InvalidStructure("Zero group order element can't be inverted".to_string()));
+            }
+            prefix_products.push(product);
+            product = product.mul_mod(element)?;
+        }
+
+        let mut inverse = product.inverse()?;
+        let mut inverses = vec![zero; elements.len()];
+        for (i, element) in elements.iter().enumerate().rev() {
+            inverses[i] = inverse.mul_mod(&prefix_products[i])?;
+            inverse = inverse.mul_mod(element)?;
+        }
+
+        Ok(inverses)
+    }
+
     /// - GroupOrderElement mod GroupOrder
     pub fn mod_neg(&self) -> Result<GroupOrderElement, IndyCryptoError> {
         let mut r = self.bn;
@@ -475,7 +673,7 @@ impl GroupOrderElement {
 
     pub fn from_string(str: &str) -> Result<GroupOrderElement, IndyCryptoError> {
         Ok(GroupOrderElement {
-            bn: BIG::from_hex(str.to_string())
+            bn: _from_hex(str, false, _big_limbs(), BIG::from_hex)?
         })
     }
 
@@ -486,6 +684,14 @@ impl GroupOrderElement {
         Ok(vec)
     }
 
+    /// Overwrites the element with zero in place, so secret values don't stay in memory.
+    ///
+    /// Note: the element is `Copy`, so copies made before aren't affected.
+    pub fn clear(&mut self) {
+        // Volatile write isn't optimized out even if the element is never read again
+        unsafe { ptr::write_volatile(&mut self.bn, BIG::new()); }
+    }
+
     pub fn from_bytes(b: &[u8]) -> Result<GroupOrderElement, IndyCryptoError> {
         if b.len() > Self::BYTES_REPR_SIZE {
             return Err(IndyCryptoError::InvalidStructure(
@@ -602,7 +808,7 @@ impl Pair {
 
     pub fn from_string(str: &str) -> Result<Pair, IndyCryptoError> {
         Ok(Pair {
-            pair: FP12::from_hex(str.to_string())
+            pair: _from_hex(str, false, 12 * _big_limbs(), FP12::from_hex)?
         })
     }
 
@@ -656,12 +862,79 @@ mod tests {
     use errors::ToErrorCode;
     use ffi::ErrorCode;
 
+    #[test]
+    fn from_string_works_for_malformed_hex() {
+        let point = PointG1::new().unwrap().to_string().unwrap();
+        assert_eq!(point, PointG1::from_string(&point).unwrap().to_string().unwrap());
+
+        assert!(PointG1::from_string(&point.replacen("false", "0", 1)).is_err());
+        assert!(PointG1::from_string(&format!("{} 1", point)).is_err());
+        assert!(PointG1::from_string(&point.replacen(" ", " FFFFFFFFFFFFFFFF", 1)).is_err());
+        assert!(GroupOrderElement::from_string("1 2 3 4 xyz").is_err());
+        assert!(Pair::from_string("").is_err());
+    }
+
     #[test]
     fn group_order_element_new_from_seed_works_for_invalid_seed_len() {
         let err = GroupOrderElement::new_from_seed(&[0, 1, 2]).unwrap_err();
         assert_eq!(err.to_error_code(), ErrorCode::CommonInvalidStructure);
     }
 
+    #[test]
+    fn group_order_element_new_from_domain_seed_works() {
+        const DOMAIN: DerivationDomain = DerivationDomain(b"TEST_DOMAIN");
+        const OTHER_DOMAIN: DerivationDomain = DerivationDomain(b"TEST_OTHER_DOMAIN");
+        let seed = SecretSeed::new(&[7u8; 32]).unwrap();
+
+        let el = GroupOrderElement::new_from_domain_seed(&DOMAIN, &seed, b"1").unwrap();
+        assert_eq!(el, GroupOrderElement::new_from_domain_seed(&DOMAIN, &seed, b"1").unwrap());
+        assert_ne!(el, GroupOrderElement::new_from_domain_seed(&DOMAIN, &seed, b"2").unwrap());
+        assert_ne!(el, GroupOrderElement::new_from_domain_seed(&OTHER_DOMAIN, &seed, b"1").unwrap());
+        assert_ne!(el, GroupOrderElement::new_from_domain_seed(&DOMAIN, &SecretSeed::generate().unwrap(), b"1").unwrap());
+    }
+
+    #[test]
+    fn group_order_element_batch_inverse_works() {
+        let elements = (0..5).map(|_| GroupOrderElement::new().unwrap()).collect::<Vec<GroupOrderElement>>();
+        let inverses = GroupOrderElement::batch_inverse(&elements).unwrap();
+        assert_eq!(elements.len(), inverses.len());
+        for (element, inverse) in elements.iter().zip(inverses.iter()) {
+            assert_eq!(element.inverse().unwrap(), *inverse);
+        }
+
+        assert!(GroupOrderElement::batch_inverse(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn group_order_element_batch_inverse_works_for_zero() {
+        let elements = vec![GroupOrderElement::new().unwrap(), GroupOrderElement::from_bytes(&[0]).unwrap()];
+        let err = GroupOrderElement::batch_inverse(&elements).unwrap_err();
+        assert_eq!(err.to_error_code(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn secret_seed_new_works_for_short_seed() {
+        let err = SecretSeed::new(&[0u8; 16]).unwrap_err();
+        assert_eq!(err.to_error_code(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn point_g1_derive_base_works() {
+        const DOMAIN: DerivationDomain = DerivationDomain(b"TEST_DOMAIN");
+        let base = PointG1::derive_base(&DOMAIN, b"session").unwrap();
+        assert_eq!(base, PointG1::derive_base(&DOMAIN, b"session").unwrap());
+        assert_ne!(base, PointG1::derive_base(&DOMAIN, b"other session").unwrap());
+    }
+
+    #[test]
+    fn point_g2_eq_works_for_different_representations() {
+        let a = PointG2::new().unwrap();
+        let b = PointG2::new().unwrap();
+        let c = PointG2::new().unwrap();
+        assert_eq!(a.add(&b).unwrap().add(&c).unwrap(), a.add(&b.add(&c).unwrap()).unwrap());
+        assert_ne!(a, b);
+    }
+
     #[test]
     fn pairing_definition_bilinearity() {
         let a = GroupOrderElement::new().unwrap();
@@ -710,6 +983,17 @@ mod tests {
         let pair3 = pair_result.mul(&pair1.inverse().unwrap()).unwrap();
         assert_eq!(pair2, pair3);
     }
+
+    #[test]
+    fn point_g1_is_valid_works() {
+        let point = PointG1::new().unwrap();
+        assert!(point.is_valid().unwrap());
+        assert!(!PointG1::new_inf().unwrap().is_valid().unwrap());
+
+        let mut bytes = point.to_bytes().unwrap();
+        bytes[2 * MODBYTES] ^= 1; // last byte of y
+        assert!(!PointG1::from_bytes(&bytes).unwrap().is_valid().unwrap());
+    }
 }
 
 #[cfg(feature = "serialization")]
@@ -747,6 +1031,13 @@ mod serialization_tests {
         assert_eq!(vec, result);
     }
 
+    #[test]
+    fn clear_works_for_group_order_element() {
+        let mut element = GroupOrderElement::new().unwrap();
+        element.clear();
+        assert_eq!(vec![0u8; GroupOrderElement::BYTES_REPR_SIZE], element.to_bytes().unwrap());
+    }
+
     #[test]
     fn serialize_deserialize_works_for_group_order_element() {
         let structure = TestGroupOrderElementStructure {
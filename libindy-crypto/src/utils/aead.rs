@@ -0,0 +1,96 @@
+use errors::IndyCryptoError;
+
+use openssl::hash::MessageDigest;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::symm::{Cipher, encrypt_aead, decrypt_aead};
+use utils::entropy;
+
+pub const IV_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+pub const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const PBKDF2_ITERATIONS: usize = 100_000;
+
+/// Generates random iv to be used for encryption
+pub fn gen_iv() -> Result<Vec<u8>, IndyCryptoError> {
+    let mut iv = vec![0; IV_LEN];
+    entropy::fill_bytes(&mut iv)?;
+    Ok(iv)
+}
+
+/// Generates random salt to be used for key derivation
+pub fn gen_salt() -> Result<Vec<u8>, IndyCryptoError> {
+    let mut salt = vec![0; SALT_LEN];
+    entropy::fill_bytes(&mut salt)?;
+    Ok(salt)
+}
+
+/// Derives 32 bytes encryption key from password with PBKDF2-HMAC-SHA256
+///
+/// # Arguments
+/// * `password` - password
+/// * `salt` - salt generated by `gen_salt`
+pub fn derive_key(password: &[u8], salt: &[u8]) -> Result<Vec<u8>, IndyCryptoError> {
+    let mut key = vec![0; KEY_LEN];
+    pbkdf2_hmac(password, salt, PBKDF2_ITERATIONS, MessageDigest::sha256(), &mut key)?;
+    Ok(key)
+}
+
+/// Encrypts data with AES-256-GCM
+///
+/// # Arguments
+/// * `key` - 32 bytes encryption key
+/// * `iv` - iv generated by `gen_iv`, must never be reused with the same key
+/// * `plaintext` - data to encrypt
+///
+/// # Result
+/// Return the ciphertext and the authentication tag
+pub fn encrypt(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), IndyCryptoError> {
+    let mut tag = vec![0; TAG_LEN];
+    let ciphertext = encrypt_aead(Cipher::aes_256_gcm(), key, Some(iv), &[], plaintext, &mut tag)?;
+    Ok((ciphertext, tag))
+}
+
+/// Decrypts data encrypted with AES-256-GCM and checks its authenticity
+///
+/// # Arguments
+/// * `key` - 32 bytes encryption key
+/// * `iv` - iv used for encryption
+/// * `ciphertext` - encrypted data
+/// * `tag` - authentication tag
+///
+/// # Result
+/// Return the decrypted data
+pub fn decrypt(key: &[u8], iv: &[u8], ciphertext: &[u8], tag: &[u8]) -> Result<Vec<u8>, IndyCryptoError> {
+    decrypt_aead(Cipher::aes_256_gcm(), key, Some(iv), &[], ciphertext, tag)
+        .map_err(|_| IndyCryptoError::InvalidStructure("Unable to decrypt data".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_works() {
+        let key = [7u8; 32];
+        let iv = gen_iv().unwrap();
+        let (ciphertext, tag) = encrypt(&key, &iv, b"data").unwrap();
+        assert_eq!(b"data".to_vec(), decrypt(&key, &iv, &ciphertext, &tag).unwrap());
+    }
+
+    #[test]
+    fn derive_key_works() {
+        let salt = gen_salt().unwrap();
+        let key = derive_key(b"password", &salt).unwrap();
+        assert_eq!(32, key.len());
+        assert_eq!(key, derive_key(b"password", &salt).unwrap());
+        assert_ne!(key, derive_key(b"other", &salt).unwrap());
+    }
+
+    #[test]
+    fn decrypt_works_for_other_key() {
+        let iv = gen_iv().unwrap();
+        let (ciphertext, tag) = encrypt(&[7u8; 32], &iv, b"data").unwrap();
+        assert!(decrypt(&[8u8; 32], &iv, &ciphertext, &tag).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use bn::{BigNumber, BigNumberArena};
+use errors::IndyCryptoError;
+use pair::{GroupOrderElement, Pair, PointG1, PointG2};
+
+use std::time::{Duration, Instant};
+
+const MAX_ITERATIONS: u32 = 10000;
+const MIN_SIZE: usize = 64;
+const MAX_SIZE: usize = 4096;
+const BATCH_SIZE: usize = 1000;
+
+/// Core operation which performance can be measured on the current device.
+///
+/// Costs of high level operations can be estimated from these ones, e.g. witness update
+/// costs one `PointG2Add` per changed registry index and tails generation costs one `PointG2Mul` per tail.
+///
+/// `BnModMulBatch` and `BnModMulBatchArena` run the same batch of modular multiplications without explicit context,
+/// as proof hot paths do, with and without `BigNumberArena`: their difference is the cost of number allocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkOperation {
+    BnModExp,
+    BnModMulBatch,
+    BnModMulBatchArena,
+    SafePrime,
+    PointG1Mul,
+    PointG2Mul,
+    PointG2Add,
+    Pairing
+}
+
+impl BenchmarkOperation {
+    pub fn from_name(name: &str) -> Result<BenchmarkOperation, IndyCryptoError> {
+        match name {
+            "bn_mod_exp" => Ok(BenchmarkOperation::BnModExp),
+            "bn_mod_mul_batch" => Ok(BenchmarkOperation::BnModMulBatch),
+            "bn_mod_mul_batch_arena" => Ok(BenchmarkOperation::BnModMulBatchArena),
+            "safe_prime" => Ok(BenchmarkOperation::SafePrime),
+            "point_g1_mul" => Ok(BenchmarkOperation::PointG1Mul),
+            "point_g2_mul" => Ok(BenchmarkOperation::PointG2Mul),
+            "point_g2_add" => Ok(BenchmarkOperation::PointG2Add),
+            "pairing" => Ok(BenchmarkOperation::Pairing),
+            _ => Err(IndyCryptoError::InvalidParam1(format!("Unknown benchmark operation: {}", name)))
+        }
+    }
+}
+
+/// Parameters of benchmark.
+/// `size` is bit size of numbers used by `BnModExp`, `BnModMulBatch`, `BnModMulBatchArena` and `SafePrime` operations.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BenchmarkParams {
+    #[serde(default = "BenchmarkParams::default_iterations")]
+    iterations: u32,
+    #[serde(default = "BenchmarkParams::default_size")]
+    size: usize
+}
+
+impl BenchmarkParams {
+    pub fn new(iterations: u32, size: usize) -> Result<BenchmarkParams, IndyCryptoError> {
+        let params = BenchmarkParams { iterations, size };
+        params.check()?;
+        Ok(params)
+    }
+
+    fn default_iterations() -> u32 {
+        10
+    }
+
+    fn default_size() -> usize {
+        2048
+    }
+
+    fn check(&self) -> Result<(), IndyCryptoError> {
+        if self.iterations == 0 || self.iterations > MAX_ITERATIONS {
+            return Err(IndyCryptoError::InvalidParam2(format!("Iterations must be in range 1..{}", MAX_ITERATIONS)));
+        }
+
+        if self.size < MIN_SIZE || self.size > MAX_SIZE {
+            return Err(IndyCryptoError::InvalidParam2(format!("Size must be in range {}..{}", MIN_SIZE, MAX_SIZE)));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for BenchmarkParams {
+    fn default() -> BenchmarkParams {
+        BenchmarkParams { iterations: BenchmarkParams::default_iterations(), size: BenchmarkParams::default_size() }
+    }
+}
+
+/// Timing statistics of benchmarked operation in microseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BenchmarkStats {
+    pub operation: BenchmarkOperation,
+    pub iterations: u32,
+    pub total_us: u64,
+    pub min_us: u64,
+    pub max_us: u64,
+    pub mean_us: u64
+}
+
+/// Measures duration of the operation on the current device.
+///
+/// # Arguments
+/// * `operation` - Operation to measure.
+/// * `params` - Benchmark parameters.
+///
+/// # Example
+/// ```
+/// use indy_crypto::utils::benchmark::{benchmark, BenchmarkOperation, BenchmarkParams};
+///
+/// let stats = benchmark(BenchmarkOperation::PointG2Add, &BenchmarkParams::new(3, 2048).unwrap()).unwrap();
+/// assert_eq!(3, stats.iterations);
+/// ```
+pub fn benchmark(operation: BenchmarkOperation, params: &BenchmarkParams) -> Result<BenchmarkStats, IndyCryptoError> {
+    trace!("benchmark: >>> operation: {:?}, params: {:?}", operation, params);
+
+    params.check()?;
+
+    let mut durations: Vec<u64> = Vec::with_capacity(params.iterations as usize);
+
+    for _ in 0..params.iterations {
+        durations.push(_as_micros(_measure(operation, params.size)?));
+    }
+
+    let total_us: u64 = durations.iter().sum();
+
+    let stats = BenchmarkStats {
+        operation,
+        iterations: params.iterations,
+        total_us,
+        min_us: durations.iter().cloned().min().unwrap_or(0),
+        max_us: durations.iter().cloned().max().unwrap_or(0),
+        mean_us: total_us / params.iterations as u64
+    };
+
+    trace!("benchmark: <<< stats: {:?}", stats);
+
+    Ok(stats)
+}
+
+fn _measure(operation: BenchmarkOperation, size: usize) -> Result<Duration, IndyCryptoError> {
+    Ok(match operation {
+        BenchmarkOperation::BnModExp => {
+            let mut ctx = BigNumber::new_context()?;
+            let modulus = BigNumber::rand(size)?;
+            let base = BigNumber::rand(size)?;
+            let exp = BigNumber::rand(size)?;
+
+            let start = Instant::now();
+            base.mod_exp(&exp, &modulus, Some(&mut ctx))?;
+            start.elapsed()
+        }
+        BenchmarkOperation::BnModMulBatch => {
+            let (a, n) = (BigNumber::rand(size)?, BigNumber::rand(size)?);
+
+            let start = Instant::now();
+            _mod_mul_batch(&a, &n)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::BnModMulBatchArena => {
+            let (a, n) = (BigNumber::rand(size)?, BigNumber::rand(size)?);
+
+            let start = Instant::now();
+            let _arena = BigNumberArena::new();
+            _mod_mul_batch(&a, &n)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::SafePrime => {
+            let start = Instant::now();
+            BigNumber::generate_safe_prime(size)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::PointG1Mul => {
+            let point = PointG1::new()?;
+            let e = GroupOrderElement::new()?;
+
+            let start = Instant::now();
+            point.mul(&e)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::PointG2Mul => {
+            let point = PointG2::new()?;
+            let e = GroupOrderElement::new()?;
+
+            let start = Instant::now();
+            point.mul(&e)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::PointG2Add => {
+            let p = PointG2::new()?;
+            let q = PointG2::new()?;
+
+            let start = Instant::now();
+            p.add(&q)?;
+            start.elapsed()
+        }
+        BenchmarkOperation::Pairing => {
+            let p = PointG1::new()?;
+            let q = PointG2::new()?;
+
+            let start = Instant::now();
+            Pair::pair(&p, &q)?;
+            start.elapsed()
+        }
+    })
+}
+
+fn _mod_mul_batch(a: &BigNumber, n: &BigNumber) -> Result<BigNumber, IndyCryptoError> {
+    let mut res = a.clone()?;
+    for _ in 0..BATCH_SIZE {
+        res = res.mod_mul(a, n, None)?;
+    }
+    Ok(res)
+}
+
+fn _as_micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + duration.subsec_nanos() as u64 / 1_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn benchmark_works() {
+        let stats = benchmark(BenchmarkOperation::Pairing, &BenchmarkParams::new(2, 2048).unwrap()).unwrap();
+        assert_eq!(BenchmarkOperation::Pairing, stats.operation);
+        assert_eq!(2, stats.iterations);
+        assert!(stats.min_us <= stats.mean_us && stats.mean_us <= stats.max_us);
+    }
+
+    #[test]
+    fn benchmark_works_for_mod_mul_batch_arena() {
+        let stats = benchmark(BenchmarkOperation::BnModMulBatchArena, &BenchmarkParams::new(2, 256).unwrap()).unwrap();
+        assert_eq!(BenchmarkOperation::BnModMulBatchArena, stats.operation);
+        assert_eq!(BenchmarkOperation::BnModMulBatchArena, BenchmarkOperation::from_name("bn_mod_mul_batch_arena").unwrap());
+    }
+
+    #[test]
+    fn benchmark_params_works_for_invalid_iterations() {
+        assert!(BenchmarkParams::new(0, 2048).is_err());
+        assert!(BenchmarkParams::new(MAX_ITERATIONS + 1, 2048).is_err());
+    }
+}
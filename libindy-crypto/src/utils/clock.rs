@@ -0,0 +1,170 @@
+use errors::IndyCryptoError;
+
+use time;
+
+#[cfg(feature = "serialization")]
+use serde::ser::{Serialize, Serializer};
+#[cfg(feature = "serialization")]
+use serde::de::{Deserialize, Deserializer, Error as DError};
+
+use std::fmt;
+
+/// Max valid timestamp: 9999-12-31T23:59:59Z.
+/// Any time in milliseconds since the epoch after 1978 is greater, so milliseconds are never taken for seconds.
+const MAX_TIMESTAMP_SECS: u64 = 253_402_300_799;
+
+/// Point in time as seconds since Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// Creates timestamp from seconds since the epoch.
+    /// Returns error for values that can't be seconds (i.e. milliseconds passed by mistake).
+    pub fn from_secs(secs: u64) -> Result<Timestamp, IndyCryptoError> {
+        if secs > MAX_TIMESTAMP_SECS {
+            return Err(IndyCryptoError::InvalidStructure(
+                format!("Invalid timestamp: {} is out of range of seconds since the epoch (milliseconds passed?)", secs)));
+        }
+        Ok(Timestamp(secs))
+    }
+
+    /// Creates timestamp from milliseconds since the epoch truncating it to seconds.
+    pub fn from_millis(millis: u64) -> Result<Timestamp, IndyCryptoError> {
+        Timestamp::from_secs(millis / 1000)
+    }
+
+    /// Returns the current time of the clock.
+    pub fn now<C>(clock: &C) -> Result<Timestamp, IndyCryptoError> where C: Clock {
+        Timestamp::from_secs(clock.now()?)
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<'a> Deserialize<'a> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Timestamp, D::Error> where D: Deserializer<'a> {
+        let secs = u64::deserialize(deserializer)?;
+        Timestamp::from_secs(secs).map_err(|err| DError::custom(format!("{:?}", err)))
+    }
+}
+
+/// Source of the current time.
+///
+/// Every operation that depends on the current time (non-revocation intervals, freshness checks etc.)
+/// takes a clock instead of reading the system time directly,
+/// so embedded platforms and tests can supply their own time source.
+pub trait Clock {
+    /// Returns the current time as seconds since Unix epoch.
+    fn now(&self) -> Result<u64, IndyCryptoError>;
+}
+
+/// Clock backed by the system time.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock {}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {}
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Result<u64, IndyCryptoError> {
+        let sec = time::get_time().sec;
+
+        if sec < 0 {
+            return Err(IndyCryptoError::InvalidState(format!("System time is before Unix epoch: {}", sec)));
+        }
+
+        Ok(sec as u64)
+    }
+}
+
+/// Clock that always returns the time it was set to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    time: u64
+}
+
+impl FixedClock {
+    pub fn new(time: u64) -> FixedClock {
+        FixedClock { time }
+    }
+
+    pub fn set(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    pub fn advance(&mut self, seconds: u64) {
+        self.time += seconds;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Result<u64, IndyCryptoError> {
+        Ok(self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_clock_works() {
+        let now = SystemClock::new().now().unwrap();
+        assert!(now > 1_500_000_000);
+    }
+
+    #[test]
+    fn fixed_clock_works() {
+        let mut clock = FixedClock::new(1000);
+        assert_eq!(1000, clock.now().unwrap());
+
+        clock.advance(10);
+        assert_eq!(1010, clock.now().unwrap());
+
+        clock.set(5);
+        assert_eq!(5, clock.now().unwrap());
+    }
+
+    #[test]
+    fn timestamp_works() {
+        let timestamp = Timestamp::from_secs(1_500_000_000).unwrap();
+        assert_eq!(1_500_000_000, timestamp.as_secs());
+        assert_eq!(timestamp, Timestamp::from_millis(1_500_000_000_123).unwrap());
+        assert_eq!(timestamp, Timestamp::now(&FixedClock::new(1_500_000_000)).unwrap());
+        assert!(timestamp < Timestamp::from_secs(1_500_000_001).unwrap());
+    }
+
+    #[test]
+    fn timestamp_from_secs_fails_for_millis() {
+        let res = Timestamp::from_secs(1_500_000_000_000);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serialization")]
+    fn timestamp_serialization_works() {
+        let timestamp = Timestamp::from_secs(1_500_000_000).unwrap();
+        assert_eq!("1500000000", ::serde_json::to_string(&timestamp).unwrap());
+        assert_eq!(timestamp, ::serde_json::from_str::<Timestamp>("1500000000").unwrap());
+        assert!(::serde_json::from_str::<Timestamp>("1500000000000").is_err());
+    }
+}
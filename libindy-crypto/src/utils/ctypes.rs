@@ -20,8 +20,9 @@ impl CTypesUtils {
         }
     }
 
+    /// Converts string to C string dropping interior nul bytes, so arbitrary input never panics.
     pub fn string_to_cstring(s: String) -> CString {
-        CString::new(s).unwrap()
+        CString::new(s.replace('\0', "")).unwrap_or_default()
     }
 }
 
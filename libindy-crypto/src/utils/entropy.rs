@@ -0,0 +1,295 @@
+use errors::IndyCryptoError;
+
+use libc::c_void;
+use rand::os::OsRng;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use utils::u64_to_be_bytes;
+
+use std::cell::Cell;
+use std::mem;
+#[cfg(any(test, feature = "fixed_entropy"))]
+use std::sync::Mutex;
+use std::sync::RwLock;
+
+/// Fills `bytes_len` bytes at `bytes` with entropy. Returns false if entropy can't be provided.
+pub type EntropyCB = extern fn(context: *const c_void,
+                               bytes: *mut u8,
+                               bytes_len: usize) -> bool;
+
+/// Source of randomness of all random values generated by the library:
+/// big numbers, primes (e.g. safe primes of credential keys), group elements, IVs and salts.
+enum EntropySource {
+    Os,
+    Callback {
+        context: *const c_void,
+        cb: EntropyCB
+    },
+    /// Test mode: deterministic bytes expanded from fixed seed. Never use outside of tests.
+    #[cfg(any(test, feature = "fixed_entropy"))]
+    Fixed(Mutex<SeededRng>)
+}
+
+unsafe impl Sync for EntropySource {}
+
+unsafe impl Send for EntropySource {}
+
+lazy_static! {
+    static ref ENTROPY_SOURCE: RwLock<EntropySource> = RwLock::new(EntropySource::Os);
+}
+
+/// Random number generator that can be passed to a single operation instead of the global entropy source,
+/// e.g. to produce reproducible transcripts for test vectors and audits.
+pub trait IndyRng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) -> Result<(), IndyCryptoError>;
+}
+
+/// Deterministic `IndyRng` expanding seed with SHA-256 in counter mode.
+///
+/// The same seed always produces the same bytes: never use it for real credentials and proofs.
+pub struct SeededRng {
+    seed: Vec<u8>,
+    counter: u64,
+    block: Vec<u8>,
+    offset: usize
+}
+
+impl SeededRng {
+    pub fn new(seed: &[u8]) -> Result<SeededRng, IndyCryptoError> {
+        if seed.is_empty() {
+            return Err(IndyCryptoError::InvalidParam1("Seed is empty".to_string()));
+        }
+        Ok(SeededRng { seed: seed.to_vec(), counter: 0, block: Vec::new(), offset: 0 })
+    }
+
+    fn _next_block(&mut self) {
+        let mut hasher = Sha256::default();
+        hasher.input(&self.seed);
+        hasher.input(&u64_to_be_bytes(self.counter));
+        self.block = hasher.result().to_vec();
+        self.counter += 1;
+        self.offset = 0;
+    }
+}
+
+impl IndyRng for SeededRng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) -> Result<(), IndyCryptoError> {
+        for byte in bytes.iter_mut() {
+            if self.offset == self.block.len() {
+                self._next_block();
+            }
+            *byte = self.block[self.offset];
+            self.offset += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Source of randomness of the current thread.
+#[derive(Clone, Copy)]
+enum ThreadRng {
+    /// Randomness is taken from the global source.
+    Global,
+    /// Randomness is taken from rng passed to `with_rng`.
+    Active(*mut (IndyRng + 'static)),
+    /// Rng passed to `with_rng` is generating randomness and can't be borrowed again.
+    Busy
+}
+
+thread_local! {
+    static THREAD_RNG: Cell<ThreadRng> = Cell::new(ThreadRng::Global);
+}
+
+/// Restores previous rng state of the thread when `with_rng` or rng call returns or panics.
+struct ThreadRngGuard {
+    prev: ThreadRng
+}
+
+impl Drop for ThreadRngGuard {
+    fn drop(&mut self) {
+        THREAD_RNG.with(|thread_rng| thread_rng.set(self.prev));
+    }
+}
+
+/// Runs `f` taking all randomness generated by the current thread from `rng` instead of the global entropy source.
+///
+/// Randomness generated by other threads isn't affected, so concurrent operations keep using the global source.
+/// `rng` must not request randomness from the library while generating it: such call returns `InvalidState` error.
+pub fn with_rng<R, F, T>(rng: &mut R, f: F) -> Result<T, IndyCryptoError>
+    where R: IndyRng, F: FnOnce() -> Result<T, IndyCryptoError> {
+    let rng: &mut IndyRng = rng;
+    // Pointer never outlives `rng`: guard resets it before `with_rng` returns
+    let rng = unsafe { mem::transmute::<&mut IndyRng, *mut (IndyRng + 'static)>(rng) };
+
+    let prev = THREAD_RNG.with(|thread_rng| thread_rng.replace(ThreadRng::Active(rng)));
+    let _guard = ThreadRngGuard { prev };
+
+    f()
+}
+
+/// Sets host provided entropy callback (e.g. iOS SecRandomCopyBytes or Android Keystore backed RNG).
+/// `None` restores OS entropy.
+pub fn set_entropy_callback(context: *const c_void, cb: Option<EntropyCB>) -> Result<(), IndyCryptoError> {
+    let source = match cb {
+        Some(cb) => EntropySource::Callback { context, cb },
+        None => EntropySource::Os
+    };
+    _set_entropy_source(source)
+}
+
+/// Makes all randomness of the library deterministic bytes expanded from `seed` with `SeededRng` (test mode).
+///
+/// Every random value becomes predictable: it's intended only for reproducible tests of wrappers,
+/// so it's available only with `fixed_entropy` feature.
+#[cfg(any(test, feature = "fixed_entropy"))]
+pub fn set_fixed_entropy(seed: &[u8]) -> Result<(), IndyCryptoError> {
+    _set_entropy_source(EntropySource::Fixed(Mutex::new(SeededRng::new(seed)?)))
+}
+
+fn _set_entropy_source(source: EntropySource) -> Result<(), IndyCryptoError> {
+    let mut entropy_source = ENTROPY_SOURCE.write()
+        .map_err(|_| IndyCryptoError::InvalidState("Entropy source lock is poisoned".to_string()))?;
+    *entropy_source = source;
+    Ok(())
+}
+
+/// Fills `bytes` with entropy of the current source: rng passed to `with_rng` or the global source.
+pub fn fill_bytes(bytes: &mut [u8]) -> Result<(), IndyCryptoError> {
+    match THREAD_RNG.with(|thread_rng| thread_rng.get()) {
+        ThreadRng::Active(rng) => {
+            // Rng is marked busy while it runs, so it is never borrowed twice by reentrant calls
+            let prev = THREAD_RNG.with(|thread_rng| thread_rng.replace(ThreadRng::Busy));
+            let _guard = ThreadRngGuard { prev };
+            return unsafe { (*rng).fill_bytes(bytes) };
+        }
+        ThreadRng::Busy => {
+            return Err(IndyCryptoError::InvalidState("Rng passed to with_rng requested randomness while generating it".to_string()));
+        }
+        ThreadRng::Global => ()
+    }
+
+    let entropy_source = ENTROPY_SOURCE.read()
+        .map_err(|_| IndyCryptoError::InvalidState("Entropy source lock is poisoned".to_string()))?;
+
+    _fill_bytes(&entropy_source, bytes)
+}
+
+fn _fill_bytes(entropy_source: &EntropySource, bytes: &mut [u8]) -> Result<(), IndyCryptoError> {
+    match *entropy_source {
+        EntropySource::Os => {
+            let mut os_rng = OsRng::new()
+                .map_err(|err| IndyCryptoError::InvalidState(format!("Unable to get OS rng: {}", err)))?;
+            os_rng.fill_bytes(bytes);
+        }
+        EntropySource::Callback { context, cb } => {
+            if !cb(context, bytes.as_mut_ptr(), bytes.len()) {
+                return Err(IndyCryptoError::InvalidState("Entropy callback failed".to_string()));
+            }
+        }
+        #[cfg(any(test, feature = "fixed_entropy"))]
+        EntropySource::Fixed(ref rng) => {
+            rng.lock()
+                .map_err(|_| IndyCryptoError::InvalidState("Fixed entropy lock is poisoned".to_string()))?
+                .fill_bytes(bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern fn _failing_entropy_cb(_context: *const c_void, _bytes: *mut u8, _bytes_len: usize) -> bool {
+        false
+    }
+
+    // Global source is shared by all tests running in parallel, so sources are tested locally
+
+    #[test]
+    fn fill_bytes_works_for_fixed_entropy() {
+        let fixed_bytes = || {
+            let source = EntropySource::Fixed(Mutex::new(SeededRng::new(&[1, 2]).unwrap()));
+            let (mut first, mut second) = ([0u8; 5], [0u8; 5]);
+            _fill_bytes(&source, &mut first).unwrap();
+            _fill_bytes(&source, &mut second).unwrap();
+            (first, second)
+        };
+
+        let (first, second) = fixed_bytes();
+        // Consecutive draws differ, so rejection sampling terminates
+        assert_ne!(first, second);
+        assert_eq!((first, second), fixed_bytes());
+    }
+
+    #[test]
+    fn fill_bytes_works_for_failing_callback() {
+        let mut bytes = [0u8; 5];
+        let source = EntropySource::Callback { context: ::std::ptr::null(), cb: _failing_entropy_cb };
+        assert!(_fill_bytes(&source, &mut bytes).is_err());
+    }
+
+    #[test]
+    fn with_rng_works() {
+        let bytes_with_seed = |seed: &[u8]| {
+            let mut rng = SeededRng::new(seed).unwrap();
+            with_rng(&mut rng, || {
+                let mut bytes = [0u8; 40];
+                fill_bytes(&mut bytes)?;
+                Ok(bytes.to_vec())
+            }).unwrap()
+        };
+
+        assert_eq!(bytes_with_seed(b"seed"), bytes_with_seed(b"seed"));
+        assert_ne!(bytes_with_seed(b"seed"), bytes_with_seed(b"other seed"));
+        assert!(_is_global_rng());
+    }
+
+    struct ReentrantRng;
+
+    impl IndyRng for ReentrantRng {
+        fn fill_bytes(&mut self, bytes: &mut [u8]) -> Result<(), IndyCryptoError> {
+            fill_bytes(bytes)
+        }
+    }
+
+    #[test]
+    fn with_rng_works_for_reentrant_rng() {
+        let res = with_rng(&mut ReentrantRng, || {
+            let mut bytes = [0u8; 5];
+            fill_bytes(&mut bytes)
+        });
+
+        match res {
+            Err(IndyCryptoError::InvalidState(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        assert!(_is_global_rng());
+    }
+
+    fn _is_global_rng() -> bool {
+        match THREAD_RNG.with(|thread_rng| thread_rng.get()) {
+            ThreadRng::Global => true,
+            _ => false
+        }
+    }
+
+    #[test]
+    fn seeded_rng_works_for_split_reads() {
+        let mut bytes = [0u8; 70];
+        SeededRng::new(b"seed").unwrap().fill_bytes(&mut bytes).unwrap();
+
+        let mut rng = SeededRng::new(b"seed").unwrap();
+        let mut split_bytes = [0u8; 70];
+        rng.fill_bytes(&mut split_bytes[..5]).unwrap();
+        rng.fill_bytes(&mut split_bytes[5..]).unwrap();
+
+        assert_eq!(bytes.to_vec(), split_bytes.to_vec());
+    }
+
+    #[test]
+    fn set_fixed_entropy_works_for_empty_bytes() {
+        assert!(set_fixed_entropy(&[]).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Live object counters used to detect native handle leaks in stress and soak tests.
+//!
+//! Enabled with the `leak_detection` feature. Counters are global, so leak assertions
+//! are only reliable when no other thread creates or drops tracked objects meanwhile.
+//! Pairing types (`PointG1`, `PointG2`, `GroupOrderElement`, `Pair`) are `Copy` values
+//! that don't own native handles and aren't tracked.
+use std::fmt;
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+static BIG_NUMBERS: AtomicIsize = AtomicIsize::new(0);
+static BIG_NUMBER_CONTEXTS: AtomicIsize = AtomicIsize::new(0);
+
+/// Kind of object that owns a native handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackedObject {
+    BigNumber,
+    BigNumberContext
+}
+
+impl TrackedObject {
+    fn counter(&self) -> &'static AtomicIsize {
+        match *self {
+            TrackedObject::BigNumber => &BIG_NUMBERS,
+            TrackedObject::BigNumberContext => &BIG_NUMBER_CONTEXTS
+        }
+    }
+}
+
+/// Live counts of tracked objects at some moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    pub big_numbers: isize,
+    pub big_number_contexts: isize
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BigNumber: {}, BigNumberContext: {}", self.big_numbers, self.big_number_contexts)
+    }
+}
+
+pub(crate) fn track_new(object: TrackedObject) {
+    object.counter().fetch_add(1, Ordering::SeqCst);
+}
+
+pub(crate) fn track_drop(object: TrackedObject) {
+    object.counter().fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Returns the number of live objects of the given kind.
+pub fn live_count(object: TrackedObject) -> isize {
+    object.counter().load(Ordering::SeqCst)
+}
+
+/// Returns live counts of all tracked objects.
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        big_numbers: live_count(TrackedObject::BigNumber),
+        big_number_contexts: live_count(TrackedObject::BigNumberContext)
+    }
+}
+
+/// Forces lazily initialized constants, so they aren't reported as leaks of the first operation using them.
+pub fn warm_up() {
+    ::bn::init_constants();
+    ::cl::init_constants();
+}
+
+/// Runs `op`, drops its result and panics if live counts didn't return to the baseline.
+///
+/// # Arguments
+/// * `name` - Operation name used in the panic message.
+/// * `op` - Operation to check.
+///
+/// # Example
+/// ```
+/// use indy_crypto::bn::BigNumber;
+/// use indy_crypto::utils::instrumentation::assert_no_leaks;
+///
+/// assert_no_leaks("BigNumber::rand", || BigNumber::rand(256).unwrap());
+/// ```
+pub fn assert_no_leaks<F, T>(name: &str, op: F) where F: FnOnce() -> T {
+    warm_up();
+
+    let baseline = snapshot();
+    drop(op());
+    let current = snapshot();
+
+    if current != baseline {
+        panic!("{} leaked objects: baseline {{ {} }}, after {{ {} }}", name, baseline, current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bn::BigNumber;
+
+    #[test]
+    fn snapshot_tracks_big_numbers() {
+        warm_up();
+
+        let bn = BigNumber::rand(256).unwrap();
+        assert!(live_count(TrackedObject::BigNumber) > 0);
+        drop(bn);
+    }
+}
@@ -0,0 +1,153 @@
+use errors::IndyCryptoError;
+
+use serde::{Serialize, Serializer};
+use serde::de::DeserializeOwned;
+use serde_json;
+use serde_json::Value;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+
+/// Serializes entity to canonical json: field names as declared by the entity and object keys sorted,
+/// so serialization of the same entity always produces the same string.
+pub fn to_canonical_json<T: Serialize>(entity: &T) -> Result<String, IndyCryptoError> {
+    // serde_json::Value keeps object keys in sorted map
+    Ok(serde_json::to_string(&serde_json::to_value(entity)?)?)
+}
+
+/// Serializes map with keys in sorted order, so serialization does not depend on `HashMap` iteration order.
+pub fn serialize_sorted_map<S: Serializer, V: Serialize>(map: &HashMap<String, V>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Deserializes entity accepting both snake_case and camelCase field names,
+/// as different Indy components serialize the same entities with different field casing.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, IndyCryptoError> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Deserializes entity from canonical json only:
+/// rejects field names with non-canonical casing and fields unknown to the entity.
+pub fn from_canonical_json<T: DeserializeOwned + Serialize>(json: &str) -> Result<T, IndyCryptoError> {
+    let value: Value = serde_json::from_str(json)?;
+    let entity: T = serde_json::from_value(value.clone())?;
+
+    _check_canonical_keys(&value, &serde_json::to_value(&entity)?, "")?;
+
+    Ok(entity)
+}
+
+/// Serializes entity directly to writer, so no intermediate string is built.
+pub fn to_writer<T: Serialize, W: Write>(entity: &T, writer: &mut W) -> Result<(), IndyCryptoError> {
+    serde_json::to_writer(writer, entity).map_err(_map_stream_err)
+}
+
+/// Deserializes entity directly from reader accepting the same field casing as `from_json`.
+///
+/// Reader is consumed byte by byte: wrap sockets and files into `BufReader`.
+pub fn from_reader<T: DeserializeOwned, R: Read>(reader: &mut R) -> Result<T, IndyCryptoError> {
+    serde_json::from_reader(reader).map_err(_map_stream_err)
+}
+
+fn _map_stream_err(err: serde_json::Error) -> IndyCryptoError {
+    if err.is_io() {
+        IndyCryptoError::IOError(err.into())
+    } else {
+        err.into()
+    }
+}
+
+fn _check_canonical_keys(value: &Value, canonical: &Value, path: &str) -> Result<(), IndyCryptoError> {
+    match (value, canonical) {
+        (Value::Object(map), Value::Object(canonical_map)) => {
+            for (key, item) in map.iter() {
+                let item_path = format!("{}/{}", path, key);
+                let canonical_item = canonical_map.get(key)
+                    .ok_or(IndyCryptoError::InvalidStructure(format!("Non-canonical field: {}", item_path)))?;
+                _check_canonical_keys(item, canonical_item, &item_path)?;
+            }
+        }
+        (Value::Array(items), Value::Array(canonical_items)) if items.len() == canonical_items.len() => {
+            for (idx, (item, canonical_item)) in items.iter().zip(canonical_items.iter()).enumerate() {
+                _check_canonical_keys(item, canonical_item, &format!("{}/{}", path, idx))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Entity {
+        #[serde(alias = "attrName")]
+        attr_name: String,
+        #[serde(alias = "innerValues")]
+        inner_values: Vec<Inner>
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Inner {
+        #[serde(alias = "prev_value")]
+        prev_value: u32
+    }
+
+    fn entity() -> Entity {
+        Entity { attr_name: "age".to_string(), inner_values: vec![Inner { prev_value: 1 }] }
+    }
+
+    #[derive(Serialize)]
+    struct MapEntity {
+        #[serde(serialize_with = "serialize_sorted_map")]
+        values: HashMap<String, u32>
+    }
+
+    #[test]
+    fn serialize_sorted_map_works() {
+        let values: HashMap<String, u32> = (0..20).map(|i| (format!("attr{}", i), i)).collect();
+        let expected = format!("{{\"values\":{{{}}}}}",
+                               values.iter().collect::<BTreeMap<_, _>>().iter().map(|(k, v)| format!("\"{}\":{}", k, v)).collect::<Vec<_>>().join(","));
+        assert_eq!(expected, serde_json::to_string(&MapEntity { values }).unwrap());
+    }
+
+    #[test]
+    fn from_json_works_for_mixed_casing() {
+        assert_eq!(entity(), from_json::<Entity>(r#"{"attrName":"age","inner_values":[{"prev_value":1}]}"#).unwrap());
+    }
+
+    #[test]
+    fn to_canonical_json_works() {
+        assert_eq!(r#"{"attr_name":"age","inner_values":[{"prevValue":1}]}"#, to_canonical_json(&entity()).unwrap());
+    }
+
+    #[test]
+    fn from_canonical_json_works() {
+        assert_eq!(entity(), from_canonical_json::<Entity>(r#"{"attr_name":"age","inner_values":[{"prevValue":1}]}"#).unwrap());
+    }
+
+    #[test]
+    fn to_writer_from_reader_works() {
+        let mut bytes = Vec::new();
+        to_writer(&entity(), &mut bytes).unwrap();
+        assert_eq!(entity(), from_reader::<Entity, _>(&mut bytes.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn from_reader_works_for_truncated_input() {
+        let json = r#"{"attr_name":"age","inner_values":["#;
+        match from_reader::<Entity, _>(&mut json.as_bytes()) {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn from_canonical_json_works_for_non_canonical_casing() {
+        assert!(from_canonical_json::<Entity>(r#"{"attrName":"age","inner_values":[{"prevValue":1}]}"#).is_err());
+        assert!(from_canonical_json::<Entity>(r#"{"attr_name":"age","inner_values":[{"prev_value":1}]}"#).is_err());
+    }
+}
@@ -9,9 +9,9 @@ use std::io::Write;
 use log::{Record, Metadata};
 
 use errors::IndyCryptoError;
+use utils::ctypes::CTypesUtils;
 
 use self::libc::{c_void, c_char};
-use std::ffi::CString;
 use std::ptr;
 
 pub type EnabledCB = extern fn(context: *const c_void,
@@ -45,7 +45,7 @@ impl log::Log for IndyCryptoLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
         if let Some(enabled_cb) = self.enabled {
             let level = metadata.level() as u32;
-            let target = CString::new(metadata.target()).unwrap();
+            let target = CTypesUtils::string_to_cstring(metadata.target().to_string());
 
             enabled_cb(self.context,
                        level,
@@ -58,11 +58,12 @@ impl log::Log for IndyCryptoLogger {
         let log_cb = self.log;
 
         let level = record.level() as u32;
-        let target = CString::new(record.target()).unwrap();
-        let message = CString::new(record.args().to_string()).unwrap();
+        // Messages may contain user supplied values with nul bytes: they are dropped instead of panicking in host callback
+        let target = CTypesUtils::string_to_cstring(record.target().to_string());
+        let message = CTypesUtils::string_to_cstring(record.args().to_string());
 
-        let module_path = record.module_path().map(|a| CString::new(a).unwrap());
-        let file = record.file().map(|a| CString::new(a).unwrap());
+        let module_path = record.module_path().map(|a| CTypesUtils::string_to_cstring(a.to_string()));
+        let file = record.file().map(|a| CTypesUtils::string_to_cstring(a.to_string()));
         let line = record.line().unwrap_or(0);
 
         log_cb(self.context,
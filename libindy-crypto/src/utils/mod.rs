@@ -1,6 +1,15 @@
 #[macro_use]
 pub mod ctypes;
+#[cfg(feature = "bn_openssl")]
+pub mod aead;
+pub mod benchmark;
+pub mod clock;
 pub mod commitment;
+pub mod entropy;
+#[cfg(feature = "leak_detection")]
+pub mod instrumentation;
+#[cfg(feature = "serialization")]
+pub mod json;
 pub mod rsa;
 #[macro_use]
 pub mod logger;
@@ -18,6 +27,11 @@ pub fn get_hash_as_int(nums: &Vec<Vec<u8>>) -> Result<BigNumber, IndyCryptoError
     hash
 }
 
+/// Returns big-endian bytes of `x` (length prefixes and counters of hashed values).
+pub fn u64_to_be_bytes(x: u64) -> Vec<u8> {
+    (0..8).rev().map(|i| (x >> (i * 8)) as u8).collect()
+}
+
 pub fn clone_option_bignum(b: &Option<BigNumber>) -> Result<Option<BigNumber>, IndyCryptoError> {
     match *b {
         Some(ref bn) => Ok(Some(bn.clone()?)),
@@ -88,4 +102,10 @@ mod tests {
         assert!(res.is_ok());
         assert_eq!("2C2566C22E04AB3F18B3BA693823175002F10F400811363D26BBB33633AC8BAD", res.unwrap().to_hex().unwrap());
     }
+
+    #[test]
+    fn u64_to_be_bytes_works() {
+        assert_eq!(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF], u64_to_be_bytes(0x0123456789ABCDEF));
+        assert_eq!(vec![0u8; 8], u64_to_be_bytes(0));
+    }
 }
@@ -0,0 +1,283 @@
+use bls::Generator;
+use errors::IndyCryptoError;
+use pair::{GroupOrderElement, PointG2, PointG1, Pair};
+
+use sha2::{Sha256, Digest};
+
+const VRF_HASH_DOMAIN: &[u8] = b"INDY_CRYPTO_VRF_HASH";
+const VRF_OUTPUT_DOMAIN: &[u8] = b"INDY_CRYPTO_VRF_OUTPUT";
+
+/// VRF secret key.
+#[derive(Debug)]
+pub struct VrfSecretKey {
+    group_order_element: GroupOrderElement,
+    bytes: Vec<u8>
+}
+
+impl VrfSecretKey {
+    /// Creates and returns random (or seeded from seed) VRF secret key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use indy_crypto::vrf::VrfSecretKey;
+    /// VrfSecretKey::new(None).unwrap();
+    /// ```
+    pub fn new(seed: Option<&[u8]>) -> Result<VrfSecretKey, IndyCryptoError> {
+        let group_order_element = match seed {
+            Some(seed) => GroupOrderElement::new_from_seed(seed)?,
+            _ => GroupOrderElement::new()?
+        };
+
+        Ok(VrfSecretKey {
+            group_order_element,
+            bytes: group_order_element.to_bytes()?
+        })
+    }
+
+    /// Returns VRF secret key bytes representation.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Creates and returns VRF secret key from bytes representation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VrfSecretKey, IndyCryptoError> {
+        Ok(VrfSecretKey {
+            group_order_element: GroupOrderElement::from_bytes(bytes)?,
+            bytes: bytes.to_vec()
+        })
+    }
+}
+
+/// VRF public key.
+#[derive(Debug, Clone)]
+pub struct VrfPublicKey {
+    point: PointG2,
+    bytes: Vec<u8>
+}
+
+impl VrfPublicKey {
+    /// Creates and returns VRF public key that corresponds to secret key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use indy_crypto::bls::Generator;
+    /// use indy_crypto::vrf::{VrfSecretKey, VrfPublicKey};
+    /// let gen = Generator::new().unwrap();
+    /// let secret_key = VrfSecretKey::new(None).unwrap();
+    /// VrfPublicKey::new(&gen, &secret_key).unwrap();
+    /// ```
+    pub fn new(gen: &Generator, secret_key: &VrfSecretKey) -> Result<VrfPublicKey, IndyCryptoError> {
+        let point = PointG2::from_bytes(gen.as_bytes())?.mul(&secret_key.group_order_element)?;
+        VrfPublicKey::_check_point(&point)?;
+
+        Ok(VrfPublicKey {
+            point,
+            bytes: point.to_bytes()?
+        })
+    }
+
+    /// Returns VRF public key bytes representation.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Creates and returns VRF public key from bytes representation.
+    /// Identity point is rejected: any proof would verify with it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VrfPublicKey, IndyCryptoError> {
+        let point = PointG2::from_bytes(bytes)?;
+        VrfPublicKey::_check_point(&point)?;
+
+        Ok(VrfPublicKey {
+            point,
+            bytes: bytes.to_vec()
+        })
+    }
+
+    fn _check_point(point: &PointG2) -> Result<(), IndyCryptoError> {
+        if point.is_inf()? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid VRF public key: identity point".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// VRF proof. Proof is unique for the public key and input, so is the output derived from it.
+#[derive(Debug, Clone)]
+pub struct VrfProof {
+    point: PointG1,
+    bytes: Vec<u8>
+}
+
+impl VrfProof {
+    /// Returns pseudorandom output (32 bytes) of the proof.
+    ///
+    /// Output must be trusted only after the proof is verified with `Vrf::verify`.
+    pub fn output(&self) -> Result<Vec<u8>, IndyCryptoError> {
+        // Canonical encoding of the point: bytes the proof was parsed from may encode it differently
+        let mut hasher = Sha256::default();
+        hasher.input(VRF_OUTPUT_DOMAIN);
+        hasher.input(&self.point.to_bytes()?);
+        Ok(hasher.result().to_vec())
+    }
+
+    /// Returns VRF proof bytes representation.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Creates and returns VRF proof from bytes representation.
+    /// Identity point and points out of the group are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VrfProof, IndyCryptoError> {
+        let point = PointG1::from_bytes(bytes)?;
+
+        if !point.is_valid()? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid VRF proof: identity point or point out of the group".to_string()));
+        }
+
+        Ok(VrfProof {
+            point,
+            bytes: bytes.to_vec()
+        })
+    }
+}
+
+/// Verifiable random function over BN254 pairing.
+///
+/// Proof is the BLS signature `H(input)^sk` that is unique for the key,
+/// the output is a hash of the proof.
+/// Can be used to derive verifiable per-epoch pseudonyms or lottery values (e.g. input is `context || epoch`).
+///
+/// VRF keys are a standalone key type: they aren't derived from or bound to credential keys
+/// or master secret, so outputs of different keys can't be linked to a credential holder.
+pub struct Vrf {}
+
+impl Vrf {
+    /// Evaluates VRF on the input and returns proof containing the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - VRF input
+    /// * `secret_key` - VRF secret key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use indy_crypto::vrf::{Vrf, VrfSecretKey};
+    /// let secret_key = VrfSecretKey::new(None).unwrap();
+    /// let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+    /// assert_eq!(32, proof.output().unwrap().len());
+    /// ```
+    pub fn prove(input: &[u8], secret_key: &VrfSecretKey) -> Result<VrfProof, IndyCryptoError> {
+        let point = Vrf::_hash(input)?.mul(&secret_key.group_order_element)?;
+
+        if point.is_inf()? {
+            return Err(IndyCryptoError::InvalidStructure("Invalid VRF secret key: zero".to_string()));
+        }
+
+        Ok(VrfProof {
+            point,
+            bytes: point.to_bytes()?
+        })
+    }
+
+    /// Verifies VRF proof for the input and returns true - if proof valid or false otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - VRF proof
+    /// * `input` - VRF input
+    /// * `public_key` - VRF public key
+    /// * `gen` - Generator point
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use indy_crypto::bls::Generator;
+    /// use indy_crypto::vrf::{Vrf, VrfSecretKey, VrfPublicKey};
+    /// let gen = Generator::new().unwrap();
+    /// let secret_key = VrfSecretKey::new(None).unwrap();
+    /// let public_key = VrfPublicKey::new(&gen, &secret_key).unwrap();
+    /// let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+    ///
+    /// let valid = Vrf::verify(&proof, b"epoch-1", &public_key, &gen).unwrap();
+    /// assert!(valid);
+    /// ```
+    pub fn verify(proof: &VrfProof, input: &[u8], public_key: &VrfPublicKey, gen: &Generator) -> Result<bool, IndyCryptoError> {
+        let h = Vrf::_hash(input)?;
+        let gen = PointG2::from_bytes(gen.as_bytes())?;
+        Ok(Pair::pair(&proof.point, &gen)?.eq(&Pair::pair(&h, &public_key.point)?))
+    }
+
+    fn _hash(input: &[u8]) -> Result<PointG1, IndyCryptoError> {
+        let mut hasher = Sha256::default();
+        hasher.input(VRF_HASH_DOMAIN);
+        hasher.input(input);
+        PointG1::from_hash(hasher.result().as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vrf_works() {
+        let gen = Generator::new().unwrap();
+        let secret_key = VrfSecretKey::new(None).unwrap();
+        let public_key = VrfPublicKey::new(&gen, &secret_key).unwrap();
+
+        let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+        assert!(Vrf::verify(&proof, b"epoch-1", &public_key, &gen).unwrap());
+    }
+
+    #[test]
+    fn vrf_output_is_unique_for_key_and_input() {
+        let seed = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 2, 3, 4, 5, 6, 7, 8, 9, 10, 21, 2, 3, 4, 5, 6, 7, 8, 9, 10, 31, 32];
+        let secret_key = VrfSecretKey::new(Some(&seed)).unwrap();
+        let other_secret_key = VrfSecretKey::from_bytes(secret_key.as_bytes()).unwrap();
+
+        let output = Vrf::prove(b"epoch-1", &secret_key).unwrap().output().unwrap();
+
+        assert_eq!(output, Vrf::prove(b"epoch-1", &other_secret_key).unwrap().output().unwrap());
+        assert_ne!(output, Vrf::prove(b"epoch-2", &secret_key).unwrap().output().unwrap());
+    }
+
+    #[test]
+    fn vrf_verify_works_for_other_input() {
+        let gen = Generator::new().unwrap();
+        let secret_key = VrfSecretKey::new(None).unwrap();
+        let public_key = VrfPublicKey::new(&gen, &secret_key).unwrap();
+
+        let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+        assert!(!Vrf::verify(&proof, b"epoch-2", &public_key, &gen).unwrap());
+    }
+
+    #[test]
+    fn vrf_verify_works_for_other_key() {
+        let gen = Generator::new().unwrap();
+        let secret_key = VrfSecretKey::new(None).unwrap();
+        let other_public_key = VrfPublicKey::new(&gen, &VrfSecretKey::new(None).unwrap()).unwrap();
+
+        let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+        assert!(!Vrf::verify(&proof, b"epoch-1", &other_public_key, &gen).unwrap());
+    }
+
+    #[test]
+    fn vrf_proof_from_bytes_works() {
+        let gen = Generator::new().unwrap();
+        let secret_key = VrfSecretKey::new(None).unwrap();
+        let public_key = VrfPublicKey::from_bytes(VrfPublicKey::new(&gen, &secret_key).unwrap().as_bytes()).unwrap();
+
+        let proof = Vrf::prove(b"epoch-1", &secret_key).unwrap();
+        let proof = VrfProof::from_bytes(proof.as_bytes()).unwrap();
+        assert!(Vrf::verify(&proof, b"epoch-1", &public_key, &gen).unwrap());
+    }
+
+    #[test]
+    fn vrf_from_bytes_works_for_identity() {
+        assert!(VrfPublicKey::from_bytes(&PointG2::new_inf().unwrap().to_bytes().unwrap()).is_err());
+        assert!(VrfProof::from_bytes(&PointG1::new_inf().unwrap().to_bytes().unwrap()).is_err());
+    }
+}
@@ -3,13 +3,26 @@ extern crate serde_derive;
 extern crate serde_json;
 extern crate indy_crypto;
 
-use indy_crypto::cl::{new_nonce, Witness, RevocationRegistry, RevocationRegistryDelta, SimpleTailsAccessor};
+use indy_crypto::cl::{encode_raw_value, namespaced_attr, new_nonce, CancellationToken, ChallengeReduction, CredentialIssuanceNonces, CredentialKeyConfig, CredentialPublicKey, CredentialSchema, CredentialSignature, CredentialValues, IssuerKeyPair, KeyGenProgress, MasterSecret, NonCredentialSchema, Nonce, NonRevocationInterval, Proof, Witness, RevocationRegistry, RevocationRegistryDelta,
+                      RevocationRegistryHistoryEntry, RevocationTailsAccessor, SimpleMasterSecretStore, SimpleTailsAccessor, SimpleWitnessStore, Tail, TauListPart, TauListTrace};
+use indy_crypto::cl::actors::{HolderActor, IssuerActor, VerifierActor};
 use indy_crypto::cl::issuer::Issuer;
 use indy_crypto::cl::prover::Prover;
-use indy_crypto::cl::verifier::Verifier;
-use indy_crypto::pair::PointG2;
+use indy_crypto::cl::tails::{read_tails, write_tails};
+use indy_crypto::cl::snark::{AttributeCommitment, AttributeCommitmentOpening, SnarkProver, SnarkVerifier};
+use indy_crypto::cl::verifier::{AppliedRelaxation, AuditFailure, DisclosurePolicy, KeyRotation, LegacyPolicy, LegacyRelaxation, ProofGroup, ProofLimits, Verifier};
+use indy_crypto::cl::witness_service::{LocalWitnessService, WitnessRequest, WitnessServiceClient, WitnessServiceConfig};
+use indy_crypto::errors::IndyCryptoError;
+use indy_crypto::bls::{Generator, SignKey, VerKey};
+use indy_crypto::bn::BigNumber;
+use indy_crypto::pair::{PointG1, PointG2};
+use indy_crypto::utils::clock::{FixedClock, SystemClock, Timestamp};
+use indy_crypto::utils::entropy::SeededRng;
 use self::indy_crypto::utils::logger::IndyCryptoDefaultLogger;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::thread;
 
 pub const PROVER_ID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
 
@@ -321,6 +334,2493 @@ mod test {
         assert!(proof_verifier.verify(&proof, &nonce).unwrap());
     }
 
+    #[test]
+    fn anoncreds_works_for_named_master_secrets() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Prover creates master secrets for two personas
+        let master_secret_store = SimpleMasterSecretStore::new();
+        Prover::new_master_secret_with_id(&master_secret_store, "work").unwrap();
+        Prover::new_master_secret_with_id(&master_secret_store, "personal").unwrap();
+
+        match Prover::new_master_secret_with_id(&master_secret_store, "work") {
+            Err(IndyCryptoError::InvalidParam2(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // 2. Issuer creates credential schema and credential definition
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values without master secret
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "28").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds master secret of "work" persona
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets_with_master_secret_id(&credential_pub_key,
+                                                                   &credential_key_correctness_proof,
+                                                                   &credential_values,
+                                                                   &master_secret_store,
+                                                                   "work",
+                                                                   &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values.with_master_secret(&master_secret_store, "work").unwrap(),
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof request and nonce
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+
+        // 10. Prover creates proof with master secret of "work" persona
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.set_master_secret_id(&master_secret_store, "work").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 11. Verifier verifies proof
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 12. Proof with master secret of "personal" persona isn't valid for the credential
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.set_master_secret_id(&master_secret_store, "personal").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      &credential_pub_key,
+                                                      None,
+                                                      None)
+            .and_then(|_| proof_builder.finalize(&nonce));
+
+        if let Ok(proof) = res {
+            assert!(!proof_verifier.verify(&proof, &nonce).unwrap_or(false));
+        }
+
+        // 13. Prover can't select unknown persona
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        match proof_builder.set_master_secret_id(&master_secret_store, "unknown") {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_holder_binding_across_issuers() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Prover creates master secret and holder binding commitment to biometric template hash
+        let master_secret = Prover::new_master_secret().unwrap();
+        let biometric = encode_raw_value("biometric template").unwrap();
+        let (holder_binding_commitment, holder_binding_opening) = Prover::new_holder_binding(&biometric).unwrap();
+
+        // 2. Two issuers create credential definitions
+        let non_credential_schema = helpers::non_credential_schema();
+        let (gvt_credential_pub_key, _, gvt_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&helpers::gvt_credential_schema(), &non_credential_schema, false).unwrap();
+        let (xyz_credential_pub_key, _, xyz_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&helpers::xyz_credential_schema(), &non_credential_schema, false).unwrap();
+
+        for &(credential_pub_key, credential_key_correctness_proof) in
+            [(&gvt_credential_pub_key, &gvt_credential_key_correctness_proof),
+             (&xyz_credential_pub_key, &xyz_credential_key_correctness_proof)].iter() {
+            // 3. Prover commits to the same biometric with new blinding factor for every issuer
+            let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+            credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+            credential_values_builder.add_value_commitment("biometric", &biometric, &BigNumber::rand(256).unwrap()).unwrap();
+            let credential_values = credential_values_builder.finalize().unwrap();
+
+            let credential_nonce = new_nonce().unwrap();
+            let (blinded_credential_secrets, _, blinded_credential_secrets_correctness_proof) =
+                Prover::blind_credential_secrets(credential_pub_key,
+                                                 credential_key_correctness_proof,
+                                                 &credential_values,
+                                                 &credential_nonce).unwrap();
+
+            // 4. Prover proves that committed biometric is the value of holder binding commitment
+            let holder_binding_proof = Prover::new_holder_binding_proof(credential_pub_key,
+                                                                        &credential_values,
+                                                                        "biometric",
+                                                                        &holder_binding_commitment,
+                                                                        &holder_binding_opening,
+                                                                        &credential_nonce).unwrap();
+
+            // 5. Issuer verifies holder binding proof and blinded credential secrets
+            assert!(Issuer::verify_holder_binding_proof(&blinded_credential_secrets,
+                                                        "biometric",
+                                                        &holder_binding_commitment,
+                                                        &holder_binding_proof,
+                                                        &credential_nonce,
+                                                        credential_pub_key).unwrap());
+
+            assert!(!Issuer::verify_holder_binding_proof(&blinded_credential_secrets,
+                                                         "biometric",
+                                                         &holder_binding_commitment,
+                                                         &holder_binding_proof,
+                                                         &new_nonce().unwrap(),
+                                                         credential_pub_key).unwrap());
+
+            assert!(Issuer::verify_blinded_credential_secrets_correctness_proof(&blinded_credential_secrets,
+                                                                               &blinded_credential_secrets_correctness_proof,
+                                                                               &credential_nonce,
+                                                                               credential_pub_key).unwrap());
+        }
+
+        // 6. Proof for another value doesn't verify against the holder binding commitment
+        let (other_commitment, other_opening) = Prover::new_holder_binding(&encode_raw_value("other template").unwrap()).unwrap();
+
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_value_commitment("biometric", &other_opening.value().unwrap(), &BigNumber::rand(256).unwrap()).unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, _, _) =
+            Prover::blind_credential_secrets(&gvt_credential_pub_key,
+                                             &gvt_credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let holder_binding_proof = Prover::new_holder_binding_proof(&gvt_credential_pub_key,
+                                                                    &credential_values,
+                                                                    "biometric",
+                                                                    &other_commitment,
+                                                                    &other_opening,
+                                                                    &credential_nonce).unwrap();
+
+        assert!(!Issuer::verify_holder_binding_proof(&blinded_credential_secrets,
+                                                     "biometric",
+                                                     &holder_binding_commitment,
+                                                     &holder_binding_proof,
+                                                     &credential_nonce,
+                                                     &gvt_credential_pub_key).unwrap());
+
+        // 7. Prover can't create proof for a value different from the opening
+        match Prover::new_holder_binding_proof(&gvt_credential_pub_key,
+                                               &credential_values,
+                                               "biometric",
+                                               &holder_binding_commitment,
+                                               &holder_binding_opening,
+                                               &credential_nonce) {
+            Err(IndyCryptoError::InvalidParam5(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_reproducible_proof_with_rng() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema and credential definition
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 2. Prover creates master secret from seed
+        let master_secret = Prover::new_master_secret_with_rng(&mut SeededRng::new(b"master secret").unwrap()).unwrap();
+        let credential_values = helpers::gvt_credential_values(&master_secret);
+
+        // 3. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 4. Prover blinds hidden attributes with seeded rng
+        let blind = || Prover::blind_credential_secrets_with_rng(&credential_pub_key,
+                                                                 &credential_key_correctness_proof,
+                                                                 &credential_values,
+                                                                 &credential_nonce,
+                                                                 &mut SeededRng::new(b"blinding").unwrap()).unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) = blind();
+        let (same_blinded_credential_secrets, _, _) = blind();
+        assert_eq!(serde_json::to_value(&blinded_credential_secrets).unwrap(), serde_json::to_value(&same_blinded_credential_secrets).unwrap());
+
+        // 5. Issuer signs credential values
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 6. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 7. Verifier creates sub proof request and nonce
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        // 8. Prover creates the same proof twice with the same seed
+        let build_proof = |seed: &[u8]| {
+            let mut rng = SeededRng::new(seed).unwrap();
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.add_common_attribute_with_rng("master_secret", &mut rng).unwrap();
+            proof_builder.add_sub_proof_request_with_rng(&sub_proof_request,
+                                                         &credential_schema,
+                                                         &non_credential_schema,
+                                                         &credential_signature,
+                                                         &credential_values,
+                                                         &credential_pub_key,
+                                                         None,
+                                                         None,
+                                                         &mut rng).unwrap();
+            proof_builder.finalize(&nonce).unwrap()
+        };
+
+        let proof = build_proof(b"proof");
+        assert_eq!(serde_json::to_value(&proof).unwrap(), serde_json::to_value(&build_proof(b"proof")).unwrap());
+        assert_ne!(serde_json::to_value(&proof).unwrap(), serde_json::to_value(&build_proof(b"other proof")).unwrap());
+
+        // 9. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_key_rotation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Issuer rotates credential keys at time 1000
+        let (new_credential_pub_key, _, _, statement) =
+            Issuer::new_successor_credential_def(&credential_schema,
+                                                 &non_credential_schema,
+                                                 false,
+                                                 &credential_pub_key,
+                                                 &credential_priv_key,
+                                                 Timestamp::from_secs(1000).unwrap()).unwrap();
+
+        assert!(statement.verify(&credential_pub_key, &new_credential_pub_key).unwrap());
+        assert!(!statement.verify(&new_credential_pub_key, &credential_pub_key).unwrap());
+
+        // 13. Verifier accepts proof over credential issued with predecessor key during grace period only
+        let key_rotation = KeyRotation::new(&statement, &credential_pub_key, &new_credential_pub_key, 100).unwrap();
+
+        let new_proof_verifier = |pub_key: &CredentialPublicKey| {
+            let mut proof_verifier = Verifier::new_proof_verifier()?;
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 pub_key,
+                                                 None,
+                                                 None)?;
+            Ok(proof_verifier)
+        };
+
+        assert!(Verifier::verify_with_key_rotation(&key_rotation, &FixedClock::new(1050), &proof, &nonce, &new_proof_verifier).unwrap());
+        assert!(!Verifier::verify_with_key_rotation(&key_rotation, &FixedClock::new(1200), &proof, &nonce, &new_proof_verifier).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_grouped_verification() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proofs
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+
+        // 12. Auditor groups archived presentations by issuer
+        let new_proof_verifier = || {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier
+        };
+
+        let mut new_proofs_by_issuer = || {
+            let mut gvt_group = ProofGroup::new(new_proof_verifier());
+            gvt_group.add_presentation(proof_builder.finalize_ref(&nonce).unwrap(), nonce.clone().unwrap()).unwrap();
+            gvt_group.add_presentation(proof_builder.finalize_ref(&nonce).unwrap(), new_nonce().unwrap()).unwrap();
+
+            let mut limited_proof_verifier = new_proof_verifier();
+            limited_proof_verifier.set_limits(ProofLimits { max_sub_proofs: Some(0), ..ProofLimits::default() }).unwrap();
+            let mut limited_group = ProofGroup::new(limited_proof_verifier);
+            limited_group.add_presentation(proof_builder.finalize_ref(&nonce).unwrap(), nonce.clone().unwrap()).unwrap();
+
+            let mut proofs_by_issuer = BTreeMap::new();
+            proofs_by_issuer.insert("issuer1".to_string(), gvt_group);
+            proofs_by_issuer.insert("issuer2".to_string(), limited_group);
+            proofs_by_issuer
+        };
+
+        // 13. Auditor verifies all groups
+        for threads in 1..3 {
+            let report = Verifier::verify_grouped(new_proofs_by_issuer(), threads).unwrap();
+            assert_eq!(3, report.total);
+            assert_eq!(1, report.accepted);
+            assert_eq!(1, report.rejected);
+            assert_eq!(1, report.failed);
+            assert_eq!(AuditFailure { issuer_id: "issuer1".to_string(), presentation_idx: 1, reason: "Proof is rejected".to_string() },
+                       report.failures[0]);
+            assert_eq!("issuer2", report.failures[1].issuer_id);
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_channel_bound_proof() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof bound to the session channel
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.set_channel_binding(b"session-1").unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof in the same session
+        let new_proof_verifier = |channel_binding: Option<&[u8]>| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            if let Some(channel_binding) = channel_binding {
+                proof_verifier.set_channel_binding(channel_binding).unwrap();
+            }
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier
+        };
+        assert!(new_proof_verifier(Some(b"session-1")).verify(&proof, &nonce).unwrap());
+
+        // 13. Proof relayed to other session or verifier not requiring channel binding is rejected
+        assert!(!new_proof_verifier(Some(b"session-2")).verify(&proof, &nonce).unwrap());
+        assert!(!new_proof_verifier(None).verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_self_attested_attrs() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request and requests self-attested phone number
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof with self-attested phone number
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let mut self_attested_attrs = BTreeMap::new();
+        self_attested_attrs.insert("phone".to_string(), "+1-555-0100".to_string());
+        let proof = proof_builder.finalize_with_self_attested_attrs(&nonce, &self_attested_attrs).unwrap();
+        assert_eq!(&self_attested_attrs, proof.self_attested_attrs());
+
+        // 12. Verifier verifies proof with requested self-attested attribute
+        let new_proof_verifier = |self_attested_attrs: &[&str]| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            for attr in self_attested_attrs.iter() {
+                proof_verifier.add_self_attested_attr(attr).unwrap();
+            }
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier
+        };
+        assert!(new_proof_verifier(&["phone"]).verify(&proof, &nonce).unwrap());
+
+        // 13. Proof with missing or unrequested self-attested attributes is rejected
+        for self_attested_attrs in [&[][..], &["phone", "email"][..]].iter() {
+            match new_proof_verifier(self_attested_attrs).verify(&proof, &nonce) {
+                Err(IndyCryptoError::AnoncredsProofRejected(_)) => (),
+                res => panic!("Unexpected result: {:?}", res)
+            }
+        }
+
+        // 14. Self-attested value swapped after the proof was built is detected
+        let proof_json = serde_json::to_string(&proof).unwrap().replace("+1-555-0100", "+1-555-0199");
+        let swapped_proof: Proof = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!("+1-555-0199", swapped_proof.self_attested_attrs()["phone"]);
+        assert!(!new_proof_verifier(&["phone"]).verify(&swapped_proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_tau_list_diagnostics() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof and dumps tau list
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let tau_list_trace = proof_builder.tau_list_trace().unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+
+        // 12. Verifier rejects proof for other nonce, but tau lists are equal
+        assert!(!proof_verifier.verify(&proof, &new_nonce().unwrap()).unwrap());
+        assert_eq!(None, proof_verifier.diagnose(&proof, &tau_list_trace).unwrap());
+
+        // 13. Verifier finds diverging tau list value
+        let mut tau_list_trace_json: serde_json::Value = serde_json::to_value(&tau_list_trace).unwrap();
+        tau_list_trace_json["entries"][0]["values"][1][0] = serde_json::Value::from(0);
+        let modified_tau_list_trace: TauListTrace = serde_json::from_value(tau_list_trace_json).unwrap();
+
+        let mismatch = proof_verifier.diagnose(&proof, &modified_tau_list_trace).unwrap().unwrap();
+        assert_eq!(0, mismatch.sub_proof_idx());
+        assert_eq!(&TauListPart::Primary, mismatch.part());
+        assert_eq!(Some(1), mismatch.index());
+    }
+
+    #[test]
+    fn anoncreds_works_for_hidden_attribute_committed_at_issuance() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Prover creates credential values with "age" hidden from issuer
+        let master_secret = Prover::new_master_secret().unwrap();
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_hidden("age", "28").unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        // Issuer knows only values that are not hidden
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let issuer_credential_values = credential_values_builder.finalize().unwrap();
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &issuer_credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_builder_finalize_ref() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonces for two sessions
+        let nonce_1 = new_nonce().unwrap();
+        let nonce_2 = new_nonce().unwrap();
+
+        // 11. Prover creates proof for the first nonce and finalizes it again for retransmission
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof_1 = proof_builder.finalize_ref(&nonce_1).unwrap();
+        assert_eq!(serde_json::to_string(&proof_1).unwrap(), serde_json::to_string(&proof_builder.finalize_ref(&nonce_1).unwrap()).unwrap());
+
+        // 12. Builder can't be finalized with other nonce: the same randomness with two challenges reveals hidden attributes
+        match proof_builder.finalize_ref(&nonce_2) {
+            Err(IndyCryptoError::InvalidState(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        match proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                  &credential_schema,
+                                                  &non_credential_schema,
+                                                  &credential_signature,
+                                                  &credential_values,
+                                                  &credential_pub_key,
+                                                  None,
+                                                  None) {
+            Err(IndyCryptoError::InvalidState(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+        match proof_builder.remove_sub_proof_request(&credential_pub_key.key_id().unwrap()) {
+            Err(IndyCryptoError::InvalidState(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // 13. Prover resets builder and creates proof for the second nonce with new randomness
+        proof_builder.reset().unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof_2 = proof_builder.finalize(&nonce_2).unwrap();
+
+        // 14. Verifier verifies proofs
+        for &(proof, nonce) in [(&proof_1, &nonce_1), (&proof_2, &nonce_2)].iter() {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            assert!(proof_verifier.verify(proof, nonce).unwrap());
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_verifier_limits() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof request and nonce
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        // 10. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let compact_proof = serde_json::from_str::<Proof>(&proof_json).unwrap().compact();
+
+        let new_proof_verifier = |limits: ProofLimits| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier.set_limits(limits).unwrap();
+            proof_verifier
+        };
+
+        // 11. Verifier accepts proof within limits
+        let c_list_entries = 1 + 4 + 1; // a_prime, t for each of 4 iterations and t for delta
+        let proof_verifier = new_proof_verifier(ProofLimits {
+            max_sub_proofs: Some(1),
+            max_predicates: Some(1),
+            max_c_list_entries: Some(c_list_entries),
+            max_size: Some(proof_json.len())
+        });
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(proof_verifier.verify(&compact_proof, &nonce).unwrap());
+        assert!(proof_verifier.verify_json(&proof_json, &nonce).unwrap());
+
+        // 12. Verifier rejects proof exceeding limits
+        let exceeded = |res: Result<bool, IndyCryptoError>| match res {
+            Err(IndyCryptoError::AnoncredsProofLimitExceeded(_)) => true,
+            _ => false
+        };
+
+        let proof_verifier = new_proof_verifier(ProofLimits { max_sub_proofs: Some(0), ..ProofLimits::default() });
+        assert!(exceeded(proof_verifier.verify(&proof, &nonce)));
+
+        let proof_verifier = new_proof_verifier(ProofLimits { max_predicates: Some(0), ..ProofLimits::default() });
+        assert!(exceeded(proof_verifier.verify(&proof, &nonce)));
+
+        let proof_verifier = new_proof_verifier(ProofLimits { max_c_list_entries: Some(c_list_entries - 1), ..ProofLimits::default() });
+        assert!(exceeded(proof_verifier.verify(&proof, &nonce)));
+        assert!(exceeded(proof_verifier.verify(&compact_proof, &nonce)));
+
+        let proof_verifier = new_proof_verifier(ProofLimits { max_size: Some(proof_json.len() - 1), ..ProofLimits::default() });
+        assert!(exceeded(proof_verifier.verify_json(&proof_json, &nonce)));
+    }
+
+    #[test]
+    fn anoncreds_works_for_disclosure_policy() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof request and nonce
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        // 10. Prover creates proof revealing name
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let new_proof_verifier = |disclosure_policy: DisclosurePolicy| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier.set_disclosure_policy(disclosure_policy).unwrap();
+            proof_verifier
+        };
+
+        // 11. Verifier accepts proof satisfying policy
+        let proof_verifier = new_proof_verifier(DisclosurePolicy {
+            max_revealed_attrs: Some(1),
+            required_hidden_attrs: vec!["sex".to_owned(), "height".to_owned()].into_iter().collect()
+        });
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 12. Verifier rejects proof violating policy with policy error
+        let violated = |res: Result<bool, IndyCryptoError>| match res {
+            Err(IndyCryptoError::AnoncredsProofPolicyViolated(_)) => true,
+            _ => false
+        };
+
+        let proof_verifier = new_proof_verifier(DisclosurePolicy { max_revealed_attrs: Some(0), ..DisclosurePolicy::default() });
+        assert!(violated(proof_verifier.verify(&proof, &nonce)));
+
+        let proof_verifier = new_proof_verifier(DisclosurePolicy {
+            required_hidden_attrs: vec!["name".to_owned()].into_iter().collect(),
+            ..DisclosurePolicy::default()
+        });
+        assert!(violated(proof_verifier.verify(&proof, &nonce)));
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_answering_other_sub_proof_request() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof request and nonce
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 21).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+        let nonce = new_nonce().unwrap();
+
+        // 10. Prover creates proof answering weaker sub proof request
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 11. Verifier rejects proof answering other sub proof request
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        match proof_verifier.verify(&proof, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofRejected(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // 12. Sub proof request fingerprints can't be stripped from proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let mut proof_json = serde_json::to_value(&proof).unwrap();
+        for sub_proof in proof_json["proofs"].as_array_mut().unwrap() {
+            sub_proof.as_object_mut().unwrap().remove("sub_proof_request_fingerprint");
+        }
+        let stripped_proof: Proof = serde_json::from_value(proof_json).unwrap();
+        assert!(!proof_verifier.verify(&stripped_proof, &nonce).unwrap());
+
+        // 13. Verifier reports legacy relaxations and rejects ones not accepted by its legacy policy
+        let report = proof_verifier.verify_with_report(&stripped_proof, &nonce).unwrap();
+        assert!(!report.valid);
+        assert_eq!(vec![AppliedRelaxation { sub_proof_idx: 0, relaxation: LegacyRelaxation::MissingSubProofRequestFingerprint }],
+                   report.relaxations);
+
+        let report = proof_verifier.verify_with_report(&proof, &nonce).unwrap();
+        assert!(report.valid);
+        assert!(report.relaxations.is_empty());
+
+        proof_verifier.set_legacy_policy(LegacyPolicy::strict(), None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        match proof_verifier.verify(&stripped_proof, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofRejected(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        let mut legacy_policy = LegacyPolicy::strict();
+        legacy_policy.accepted_by_issuer.insert(credential_pub_key.key_id().unwrap(),
+                                                vec![LegacyRelaxation::MissingSubProofRequestFingerprint].into_iter().collect());
+        proof_verifier.set_legacy_policy(legacy_policy, None).unwrap();
+        assert_eq!(1, proof_verifier.verify_with_report(&stripped_proof, &nonce).unwrap().relaxations.len());
+
+        // 14. Relaxations accepted for protocol version apply only if verifier negotiated this version
+        let mut legacy_policy = LegacyPolicy::strict();
+        legacy_policy.accepted_by_version.insert(1, vec![LegacyRelaxation::MissingSubProofRequestFingerprint].into_iter().collect());
+        proof_verifier.set_legacy_policy(legacy_policy.clone(), Some(2)).unwrap();
+        assert!(proof_verifier.verify_with_report(&stripped_proof, &nonce).is_err());
+        proof_verifier.set_legacy_policy(legacy_policy, Some(1)).unwrap();
+        assert_eq!(1, proof_verifier.verify_with_report(&stripped_proof, &nonce).unwrap().relaxations.len());
+    }
+
+    #[test]
+    fn anoncreds_works_for_malformed_blinded_credential_secrets() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates revocable credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Prover blinds hidden attributes
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, _, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 4. Issuer accepts well-formed blinded credential secrets
+        Issuer::validate_blinded_credential_secrets(&blinded_credential_secrets, &credential_pub_key).unwrap();
+
+        // 5. Issuer rejects malformed blinded credential secrets
+        let blinded_credential_secrets_json = serde_json::to_value(&blinded_credential_secrets).unwrap();
+        let malformed = |field: &str, value: String| {
+            let mut json = blinded_credential_secrets_json.clone();
+            json[field] = serde_json::Value::String(value);
+            serde_json::from_value(json).unwrap()
+        };
+
+        let out_of_range = format!("1{}", "0".repeat(700));
+        for malformed_blinded_credential_secrets in vec![malformed("u", "0".to_string()),
+                                                         malformed("u", out_of_range),
+                                                         malformed("ur", PointG1::new_inf().unwrap().to_string().unwrap())] {
+            let res = Issuer::validate_blinded_credential_secrets(&malformed_blinded_credential_secrets, &credential_pub_key);
+            assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+            let res = Issuer::sign_credential(PROVER_ID,
+                                              &malformed_blinded_credential_secrets,
+                                              &blinded_credential_secrets_correctness_proof,
+                                              &credential_nonce,
+                                              &new_nonce().unwrap(),
+                                              &credential_values,
+                                              &credential_pub_key,
+                                              &credential_priv_key);
+            assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_master_secret_rotation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Prover creates master secret and Issuer issues credential bound to it
+        let old_master_secret = Prover::new_master_secret().unwrap();
+        let old_credential_values = helpers::gvt_credential_values(&old_master_secret);
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &old_credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &old_credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &old_credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 4. Prover creates new master secret and blinds it
+        let new_master_secret = Prover::new_master_secret().unwrap();
+        let new_credential_values = helpers::gvt_credential_values(&new_master_secret);
+
+        let new_credential_nonce = new_nonce().unwrap();
+        let (new_blinded_credential_secrets, new_credential_secrets_blinding_factors, new_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &new_credential_values,
+                                             &new_credential_nonce).unwrap();
+
+        // 5. Issuer creates nonce and sub proof request for rotation
+        let rotation_nonce = new_nonce().unwrap();
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 6. Prover creates rotation proof over the old credential bound to new blinded secrets
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &old_credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let rotation_proof = Prover::new_master_secret_rotation_proof(proof_builder,
+                                                                      &new_blinded_credential_secrets,
+                                                                      &rotation_nonce).unwrap();
+
+        // 7. Issuer verifies rotation proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(Issuer::verify_master_secret_rotation_proof(&proof_verifier,
+                                                            &rotation_proof,
+                                                            &new_blinded_credential_secrets,
+                                                            &rotation_nonce).unwrap());
+
+        // 8. Rotation proof is rejected for other nonce and other blinded secrets
+        assert!(!Issuer::verify_master_secret_rotation_proof(&proof_verifier,
+                                                             &rotation_proof,
+                                                             &new_blinded_credential_secrets,
+                                                             &new_nonce().unwrap()).unwrap());
+        assert!(!Issuer::verify_master_secret_rotation_proof(&proof_verifier,
+                                                             &rotation_proof,
+                                                             &blinded_credential_secrets,
+                                                             &rotation_nonce).unwrap());
+
+        // 9. Issuer re-issues credential bound to the new master secret
+        let new_credential_issuance_nonce = new_nonce().unwrap();
+        let (mut new_credential_signature, new_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &new_blinded_credential_secrets,
+                                                                                                      &new_blinded_credential_secrets_correctness_proof,
+                                                                                                      &new_credential_nonce,
+                                                                                                      &new_credential_issuance_nonce,
+                                                                                                      &new_credential_values,
+                                                                                                      &credential_pub_key,
+                                                                                                      &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut new_credential_signature,
+                                             &new_credential_values,
+                                             &new_signature_correctness_proof,
+                                             &new_credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &new_credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 10. Prover proves with the re-issued credential
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &new_credential_signature,
+                                            &new_credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    // Toy circuit for "height > 6 * age": "proof" is the opened values, so it is not zero-knowledge
+    struct ToySnark {}
+
+    impl SnarkProver for ToySnark {
+        fn prove(&self, _circuit_id: &str, openings: &[AttributeCommitmentOpening]) -> Result<Vec<u8>, IndyCryptoError> {
+            let values: BTreeMap<String, u64> = openings.iter()
+                .map(|opening| (opening.commitment().attr_name().to_owned(), opening.value().to_dec().unwrap().parse().unwrap()))
+                .collect();
+            Ok(serde_json::to_vec(&values).unwrap())
+        }
+    }
+
+    impl SnarkVerifier for ToySnark {
+        fn verify(&self, _circuit_id: &str, commitments: &[AttributeCommitment], proof: &[u8]) -> Result<bool, IndyCryptoError> {
+            let values: BTreeMap<String, u64> = serde_json::from_slice(proof).unwrap();
+            let attrs: BTreeSet<String> = commitments.iter().map(|commitment| commitment.attr_name().to_owned()).collect();
+            Ok(attrs == values.keys().cloned().collect() && values["height"] > 6 * values["age"])
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_snark_over_committed_attrs() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer issues credential
+        let master_secret = Prover::new_master_secret().unwrap();
+        let credential_values = helpers::gvt_credential_values(&master_secret);
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 4. Verifier requests commitments to hidden attributes
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_committed_attr("age").unwrap();
+        sub_proof_request_builder.add_committed_attr("height").unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 5. Prover creates proof with proof of the circuit over committed attributes
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        assert_eq!(2, proof_builder.attribute_commitment_openings().unwrap().len());
+        proof_builder.add_snark_proof("height_gt_6_age", &ToySnark {}).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        assert_eq!(2, proof.attribute_commitments().unwrap().len());
+
+        // 6. Verifier verifies proof and proof of the circuit
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(proof_verifier.verify_with_snark(&proof, &nonce, "height_gt_6_age", &ToySnark {}).unwrap());
+        assert!(!proof_verifier.verify_with_snark(&proof, &nonce, "other_circuit", &ToySnark {}).unwrap());
+
+        // 7. Proof of the circuit is bound to the challenge
+        let mut proof_json: serde_json::Value = serde_json::to_value(&proof).unwrap();
+        proof_json["snark_proofs"][0]["proof"] = serde_json::to_value(br#"{"age":1,"height":175}"#.to_vec()).unwrap();
+        let tampered_proof: Proof = serde_json::from_value(proof_json).unwrap();
+        assert!(!proof_verifier.verify(&tampered_proof, &nonce).unwrap());
+
+        // 8. Compact proof restores commitments to c_list
+        let compact_proof = proof.compact();
+        assert!(proof_verifier.verify_with_snark(&compact_proof, &nonce, "height_gt_6_age", &ToySnark {}).unwrap());
+
+        // 9. Attribute can't be both revealed and committed
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("age").unwrap();
+        sub_proof_request_builder.add_committed_attr("age").unwrap();
+        assert!(sub_proof_request_builder.finalize().is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_nonce_bound_credential_request() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema and credential definition
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 2. Issuer sends offer nonce, Prover creates request nonce
+        let offer_nonce = new_nonce().unwrap();
+        let request_nonce = new_nonce().unwrap();
+        let nonces = CredentialIssuanceNonces::new(&offer_nonce, &request_nonce).unwrap();
+
+        // 3. Prover blinds hidden attributes bound to both nonces
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets_with_nonces(&credential_pub_key,
+                                                         &credential_key_correctness_proof,
+                                                         &credential_values,
+                                                         &nonces).unwrap();
+
+        // 4. Blinded secrets are rejected in other session with the same offer nonce
+        let other_nonces = CredentialIssuanceNonces::new(&offer_nonce, &new_nonce().unwrap()).unwrap();
+        let res = Issuer::sign_credential_with_nonces(PROVER_ID,
+                                                      &blinded_credential_secrets,
+                                                      &blinded_credential_secrets_correctness_proof,
+                                                      &other_nonces,
+                                                      &credential_values,
+                                                      &credential_pub_key,
+                                                      &credential_priv_key);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // 5. Issuer signs credential
+        let (mut credential_signature, signature_correctness_proof) =
+            Issuer::sign_credential_with_nonces(PROVER_ID,
+                                                &blinded_credential_secrets,
+                                                &blinded_credential_secrets_correctness_proof,
+                                                &nonces,
+                                                &credential_values,
+                                                &credential_pub_key,
+                                                &credential_priv_key).unwrap();
+
+        // 6. Signature is rejected for other session and accepted for own one
+        let res = Prover::process_credential_signature_with_nonces(&mut credential_signature.clone().unwrap(),
+                                                                   &credential_values,
+                                                                   &signature_correctness_proof,
+                                                                   &credential_secrets_blinding_factors,
+                                                                   &credential_pub_key,
+                                                                   &other_nonces,
+                                                                   None, None, None);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        Prover::process_credential_signature_with_nonces(&mut credential_signature,
+                                                         &credential_values,
+                                                         &signature_correctness_proof,
+                                                         &credential_secrets_blinding_factors,
+                                                         &credential_pub_key,
+                                                         &nonces,
+                                                         None, None, None).unwrap();
+
+        // 7. Offer and request nonces must differ
+        assert!(CredentialIssuanceNonces::new(&offer_nonce, &offer_nonce).is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_cancelled_credential_def_generation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // Issuer starts credential definition generation and cancels it after 3 tested candidates
+        let cancellation = CancellationToken::new();
+        let mut last_progress = KeyGenProgress::default();
+
+        let res = Issuer::new_credential_def_with_progress(&credential_schema,
+                                                           &non_credential_schema,
+                                                           false,
+                                                           &CredentialKeyConfig::default(),
+                                                           |progress| {
+                                                               last_progress = *progress;
+                                                               if progress.candidates_tested == 3 {
+                                                                   cancellation.cancel();
+                                                               }
+                                                           },
+                                                           &cancellation);
+        assert_eq!(ErrorCode::CommonInvalidState, res.unwrap_err().to_error_code());
+        assert_eq!(3, last_progress.candidates_tested);
+        assert!(last_progress.primes_found <= 2);
+    }
+
+    #[test]
+    fn anoncreds_works_for_exported_issuer_key_pair() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential definition with revocation keys
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 2. Issuer exports key pair as encrypted backup and imports it back
+        let key_pair = IssuerKeyPair::new(credential_pub_key.clone().unwrap(), credential_priv_key).unwrap();
+        let backup = key_pair.export("issuer password").unwrap();
+
+        assert!(IssuerKeyPair::import(&backup, "wrong password").is_err());
+
+        let (restored_pub_key, restored_priv_key) = IssuerKeyPair::import(&backup, "issuer password").unwrap().into_parts();
+        assert!(restored_pub_key.is_same_key(&credential_pub_key).unwrap());
+
+        // 3. Issuer signs credential with restored private key
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &restored_pub_key,
+                                                                                              &restored_priv_key).unwrap();
+
+        // 4. Prover accepts signature created with restored key
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+    }
+
+    #[test]
+    fn anoncreds_works_for_actors() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer, Holder and Verifier are created
+        let issuer = IssuerActor::new(&helpers::gvt_credential_schema()).unwrap();
+        let mut holder = HolderActor::new(PROVER_ID).unwrap();
+        let verifier = VerifierActor::new(issuer.pub_key(), issuer.credential_schema()).unwrap();
+
+        // 2. Issuer offers credential with known values only
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+        credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+        credential_values_builder.add_dec_known("age", "28").unwrap();
+        credential_values_builder.add_dec_known("height", "175").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let mut hidden_values_builder = Issuer::new_credential_values_builder().unwrap();
+        hidden_values_builder.add_dec_hidden("age", "28").unwrap();
+        assert!(issuer.create_offer(hidden_values_builder.finalize().unwrap()).is_err());
+
+        let offer = issuer.create_offer(credential_values).unwrap();
+
+        // 3. Holder accepts offer and stores issued credential
+        let request = holder.accept_offer(&offer).unwrap();
+        let credential_index = holder.store_credential(issuer.issue(&offer, &request).unwrap()).unwrap();
+
+        // 4. Credential can't be stored twice for the same request
+        let res = holder.store_credential(issuer.issue(&offer, &request).unwrap());
+        assert_eq!(ErrorCode::CommonInvalidState, res.unwrap_err().to_error_code());
+
+        // 5. Holder presents proof requested by Verifier
+        let presentation_request = verifier.request(helpers::gvt_sub_proof_request()).unwrap();
+        let proof = holder.present(credential_index, &presentation_request).unwrap();
+        assert!(verifier.verify(&presentation_request, &proof).unwrap());
+
+        // 6. Proof can't be replayed for other presentation request
+        let other_presentation_request = verifier.request(helpers::gvt_sub_proof_request()).unwrap();
+        assert!(!verifier.verify(&other_presentation_request, &proof).unwrap());
+
+        assert!(holder.present(credential_index + 1, &presentation_request).is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_3072_bit_modulus() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition with 3072-bit modulus
+        let config = CredentialKeyConfig::new(3072).unwrap();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def_with_config(&credential_schema, &non_credential_schema, false, &config).unwrap();
+        assert_eq!(&config, credential_pub_key.get_primary_key().unwrap().config());
+
+        // 3. Configuration is kept in serialized public key
+        let credential_pub_key_json = serde_json::to_string(&credential_pub_key).unwrap();
+        let credential_pub_key: CredentialPublicKey = serde_json::from_str(&credential_pub_key_json).unwrap();
+        assert_eq!(&config, credential_pub_key.get_primary_key().unwrap().config());
+
+        // 4. Issuer issues credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 5. Prover creates proof and Verifier verifies it
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_multiple_predicates_with_mod_exp_threads() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer issues credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 4. Verifier creates sub proof request with several predicates
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        sub_proof_request_builder.add_predicate("age", "LT", 29).unwrap();
+        sub_proof_request_builder.add_predicate("height", "LE", 175).unwrap();
+        sub_proof_request_builder.add_predicate("height", "GT", 150).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let nonce = new_nonce().unwrap();
+
+        // 5. Prover creates proof executing exponentiations of predicates in 4 threads
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        assert!(proof_builder.set_mod_exp_threads(0).is_err());
+        proof_builder.set_mod_exp_threads(4).unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 6. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_lt_le_gt_predicates() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof requests with "age < 29", "height <= 175" and "age > 27", "height < 200"
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "LT", 29).unwrap();
+        sub_proof_request_builder.add_predicate("height", "LE", 175).unwrap();
+        let sub_proof_request_1 = sub_proof_request_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GT", 27).unwrap();
+        sub_proof_request_builder.add_predicate("height", "LT", 200).unwrap();
+        let sub_proof_request_2 = sub_proof_request_builder.finalize().unwrap();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        for sub_proof_request in [&sub_proof_request_1, &sub_proof_request_2].iter() {
+            proof_builder.add_sub_proof_request(sub_proof_request,
+                                                &credential_schema,
+                                                &non_credential_schema,
+                                                &credential_signature,
+                                                &credential_values,
+                                                &credential_pub_key,
+                                                None,
+                                                None).unwrap();
+        }
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        for sub_proof_request in [&sub_proof_request_1, &sub_proof_request_2].iter() {
+            proof_verifier.add_sub_proof_request(sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+        }
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 13. Prover can't satisfy "age < 28"
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "LT", 28).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      &credential_pub_key,
+                                                      None,
+                                                      None);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_range_predicate() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier creates sub proof request with "18 <= age <= 65"
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_range_predicate("age", 18, 65).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 13. Prover can't satisfy "30 <= age <= 65"
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_range_predicate("age", 30, 65).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      &credential_pub_key,
+                                                      None,
+                                                      None);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_key_usage_constraints() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (mut credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, _, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let sign = |credential_pub_key: &CredentialPublicKey, clock: &FixedClock| Issuer::sign_credential_at(PROVER_ID,
+                                                                                                     &blinded_credential_secrets,
+                                                                                                     &blinded_credential_secrets_correctness_proof,
+                                                                                                     &credential_nonce,
+                                                                                                     &credential_issuance_nonce,
+                                                                                                     &credential_values,
+                                                                                                     credential_pub_key,
+                                                                                                     &credential_priv_key,
+                                                                                                     clock);
+
+        let add_sub_proof_request = |credential_pub_key: &CredentialPublicKey, clock: FixedClock| {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.set_clock(clock).unwrap();
+            proof_verifier.add_sub_proof_request(&helpers::gvt_sub_proof_request(),
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 credential_pub_key,
+                                                 None,
+                                                 None)
+        };
+
+        // 7. Issuer embeds constraints satisfied by credential into keys and signs credential values
+        let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+        constraints_builder.set_max_attrs(5).unwrap();
+        constraints_builder.set_validity_period(Some(1000), None).unwrap();
+        let constraints = constraints_builder.finalize().unwrap();
+
+        Issuer::add_key_usage_constraints(&mut credential_pub_key, &credential_priv_key, &constraints).unwrap();
+        assert_eq!(Some(&constraints), credential_pub_key.usage_constraints().unwrap());
+        assert!(sign(&credential_pub_key, &FixedClock::new(1000)).is_ok());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(1000)).is_ok());
+
+        // 8. Issuer can't sign more attributes than allowed and verifier rejects such keys
+        let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+        constraints_builder.set_max_attrs(4).unwrap();
+        Issuer::add_key_usage_constraints(&mut credential_pub_key, &credential_priv_key, &constraints_builder.finalize().unwrap()).unwrap();
+        assert!(sign(&credential_pub_key, &FixedClock::new(1000)).is_err());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(1000)).is_err());
+
+        // 9. Issuer can't sign with keys not valid yet or expired and verifier rejects them
+        let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+        constraints_builder.set_validity_period(Some(1000), Some(2000)).unwrap();
+        Issuer::add_key_usage_constraints(&mut credential_pub_key, &credential_priv_key, &constraints_builder.finalize().unwrap()).unwrap();
+        assert!(sign(&credential_pub_key, &FixedClock::new(2000)).is_ok());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(2000)).is_ok());
+
+        assert!(sign(&credential_pub_key, &FixedClock::new(999)).is_err());
+        assert!(sign(&credential_pub_key, &FixedClock::new(2001)).is_err());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(999)).is_err());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(2001)).is_err());
+
+        // 10. Issuer can't sign non revocable credential with revocable only keys and verifier rejects such keys
+        let mut constraints_builder = Issuer::new_key_usage_constraints_builder().unwrap();
+        constraints_builder.set_revocable_only(true).unwrap();
+        Issuer::add_key_usage_constraints(&mut credential_pub_key, &credential_priv_key, &constraints_builder.finalize().unwrap()).unwrap();
+        assert!(sign(&credential_pub_key, &FixedClock::new(1000)).is_err());
+        assert!(add_sub_proof_request(&credential_pub_key, FixedClock::new(1000)).is_err());
+
+        // 11. Constraints can't be altered without issuer private key
+        let mut credential_pub_key_json = serde_json::to_value(&credential_pub_key).unwrap();
+        credential_pub_key_json["usage_constraints"]["constraints"]["revocable_only"] = serde_json::Value::Bool(false);
+        let credential_pub_key: CredentialPublicKey = serde_json::from_value(credential_pub_key_json).unwrap();
+        assert!(credential_pub_key.usage_constraints().is_err());
+        assert!(sign(&credential_pub_key, &FixedClock::new(1000)).is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_guardian_delegation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values over master secret dedicated to the delegation
+        let link_secret = Prover::new_master_secret().unwrap();
+        let guardian_master_secret = Prover::new_master_secret().unwrap();
+        let credential_values = helpers::gvt_credential_values(&guardian_master_secret);
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Prover delegates credential to guardian
+        let gen = Generator::new().unwrap();
+        let guardian_sign_key = SignKey::new(None).unwrap();
+        let guardian_ver_key = VerKey::new(&gen, &guardian_sign_key).unwrap();
+
+        let mut scope = BTreeSet::new();
+        scope.insert("name".to_string());
+        scope.insert("age".to_string());
+
+        let delegation_bundle = Prover::new_delegation_bundle(&credential_signature, &credential_values, None, &scope, 2000, &link_secret).unwrap();
+        assert!(Prover::new_delegation_bundle(&credential_signature, &credential_values, None, &scope, 2000, &guardian_master_secret).is_err());
+        let sealed_delegation_bundle = Prover::seal_delegation_bundle(&delegation_bundle, &guardian_ver_key, &gen).unwrap();
+
+        // 10. Guardian opens delegation bundle
+        let mut clock = FixedClock::new(1000);
+        assert!(Prover::open_delegation_bundle(&sealed_delegation_bundle, &SignKey::new(None).unwrap(), &clock).is_err());
+        let delegation_bundle = Prover::open_delegation_bundle(&sealed_delegation_bundle, &guardian_sign_key, &clock).unwrap();
+
+        // 11. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 12. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 13. Guardian creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_guardianship(&delegation_bundle).unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            delegation_bundle.cred_signature(),
+                                            delegation_bundle.cred_values(),
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 14. Verifier verifies proof and guardianship marker
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let guardianship = proof.guardianship().unwrap();
+        assert_eq!(&scope, guardianship.scope());
+        assert!(!guardianship.is_expired(&clock).unwrap());
+
+        // 15. Guardianship marker can't be stripped from proof
+        let mut proof_json = serde_json::to_value(&proof).unwrap();
+        proof_json.as_object_mut().unwrap().remove("guardianship");
+        let stripped_proof: Proof = serde_json::from_value(proof_json).unwrap();
+        assert!(!proof_verifier.verify(&stripped_proof, &nonce).unwrap());
+
+        // 16. Guardian can't reveal attributes out of delegation scope
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("sex").unwrap();
+        let out_of_scope_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_guardianship(&delegation_bundle).unwrap();
+        proof_builder.add_sub_proof_request(&out_of_scope_sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            delegation_bundle.cred_signature(),
+                                            delegation_bundle.cred_values(),
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        assert!(proof_builder.finalize(&nonce).is_err());
+
+        // 17. Delegation bundle can't be opened after expiration
+        clock.set(2000);
+        assert!(guardianship.is_expired(&clock).unwrap());
+        assert!(Prover::open_delegation_bundle(&sealed_delegation_bundle, &guardian_sign_key, &clock).is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_proof_verifier_session() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 4. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 5. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 6. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        // 7. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 8. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 9. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 12. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        let session = Arc::new(proof_verifier.into_session());
+        let proof = Arc::new(proof);
+        let nonce = Arc::new(nonce);
+
+        let handles: Vec<_> = (0..2).map(|_| {
+            let (session, proof, nonce) = (session.clone(), proof.clone(), nonce.clone());
+            thread::spawn(move || session.verify(&proof, &nonce).unwrap())
+        }).collect();
+
+        for handle in handles {
+            let (valid, call_stats) = handle.join().unwrap();
+            assert!(valid);
+            assert_eq!(1, call_stats.sub_proofs);
+            assert_eq!(0, call_stats.non_revoc_proofs);
+        }
+
+        let (valid, _) = session.verify(&proof, &new_nonce().unwrap()).unwrap();
+        assert!(!valid);
+
+        let stats = session.stats().unwrap();
+        assert_eq!(3, stats.calls);
+        assert_eq!(2, stats.accepted);
+        assert_eq!(1, stats.rejected);
+        assert_eq!(0, stats.failed);
+    }
+
     #[test]
     fn anoncreds_works_for_revocation_proof_issuance_on_demand() {
         IndyCryptoDefaultLogger::init(None).ok();
@@ -332,21 +2832,1432 @@ mod test {
         // 2. Issuer creates credential definition(with revocation keys)
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
 
-        // 3. Issuer creates revocation registry with IssuanceOnDemand type
+        // 3. Issuer creates revocation registry with IssuanceOnDemand type
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 8. Prover creates witness
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.unwrap(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 9. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 12. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 13. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_challenge_reduced_mod_order() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry with IssuanceOnDemand type
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 8. Prover creates witness
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.unwrap(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 9. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 12. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.set_challenge_reduction(ChallengeReduction::V2ReducedModOrder).unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        assert_eq!(ChallengeReduction::V2ReducedModOrder, proof.challenge_reduction());
+
+        // Policy is preserved by serialization
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let proof: Proof = serde_json::from_str(&proof_json).unwrap();
+
+        // 13. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 14. Verifier that pinned version 1 rejects the proof
+        proof_verifier.set_accepted_challenge_reductions(&[ChallengeReduction::V1Full]).unwrap();
+        match proof_verifier.verify(&proof, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofPolicyViolated(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        proof_verifier.set_accepted_challenge_reductions(&[ChallengeReduction::V2ReducedModOrder]).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        assert!(proof_verifier.set_accepted_challenge_reductions(&[]).is_err());
+    }
+
+    #[test]
+    fn anoncreds_works_for_revocation_registry_delta_verification() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential definition and revocation registry with IssuanceByDefault type
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, _, _) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        let max_cred_num = 5;
+        let (_, _, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, true).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 2. Issuer revokes credentials and publishes registry delta
+        let rev_reg_delta = Issuer::revoke_credentials(&mut rev_reg, max_cred_num, &[1, 2].iter().cloned().collect(), &simple_tail_accessor).unwrap();
+
+        // 3. Observer checks published delta without the private gamma against indices issued by default
+        let issued: HashSet<u32> = (1..max_cred_num + 1).collect();
+        assert!(Verifier::verify_revocation_registry_delta(&rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+        let issued: HashSet<u32> = [3, 4, 5].iter().cloned().collect();
+
+        // 4. Observer detects delta that doesn't match declared changes
+        let prev_rev_reg = rev_reg.clone();
+        let rev_reg_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, 3, &simple_tail_accessor).unwrap();
+        let bogus_rev_reg_delta = RevocationRegistryDelta::from_parts(Some(&prev_rev_reg), &rev_reg, &HashSet::new(), &[4].iter().cloned().collect());
+
+        assert!(Verifier::verify_revocation_registry_delta(&rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+        assert!(!Verifier::verify_revocation_registry_delta(&bogus_rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+        let issued: HashSet<u32> = [4, 5].iter().cloned().collect();
+
+        // 5. Observer detects issuance of index already contained in accumulator and revocation of not issued one
+        let bogus_rev_reg_delta = Issuer::recovery_credential(&mut rev_reg.clone(), max_cred_num, 4, &simple_tail_accessor).unwrap();
+        assert!(!Verifier::verify_revocation_registry_delta(&bogus_rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+
+        let bogus_rev_reg_delta = Issuer::revoke_credential(&mut rev_reg.clone(), max_cred_num, 1, &simple_tail_accessor).unwrap();
+        assert!(!Verifier::verify_revocation_registry_delta(&bogus_rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+
+        let rev_reg_delta = Issuer::recovery_credential(&mut rev_reg.clone(), max_cred_num, 3, &simple_tail_accessor).unwrap();
+        assert!(Verifier::verify_revocation_registry_delta(&rev_reg_delta, &issued, max_cred_num, &simple_tail_accessor).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_registry_state_selected_by_interval() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry with IssuanceOnDemand type and publishes it at time 100
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        let mut history = vec![
+            RevocationRegistryHistoryEntry::new(Timestamp::from_secs(100).unwrap(), RevocationRegistryDelta::from_parts(None, &rev_reg, &HashSet::new(), &HashSet::new()))
+        ];
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 8. Issuer signs credential values and publishes registry delta at time 200
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        history.push(RevocationRegistryHistoryEntry::new(Timestamp::from_secs(200).unwrap(), rev_reg_delta.unwrap()));
+
+        // 9. Issuer revokes credential and publishes registry delta at time 300
+        let rev_reg_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, rev_idx, &simple_tail_accessor).unwrap();
+        history.push(RevocationRegistryHistoryEntry::new(Timestamp::from_secs(300).unwrap(), rev_reg_delta));
+
+        // 10. Prover can't select state before credential issuance or after its revocation
+        let interval = NonRevocationInterval::new(None, Some(Timestamp::from_secs(150).unwrap())).unwrap();
+        assert!(Prover::select_registry_state(&history, &interval, rev_idx, max_cred_num, issuance_by_default, &simple_tail_accessor).is_err());
+
+        let interval = NonRevocationInterval::new(Some(Timestamp::from_secs(250).unwrap()), None).unwrap();
+        assert!(Prover::select_registry_state(&history, &interval, rev_idx, max_cred_num, issuance_by_default, &simple_tail_accessor).is_err());
+
+        // 11. Prover selects registry state for verifier's interval
+        let interval = NonRevocationInterval::new(Some(Timestamp::from_secs(150).unwrap()), Some(Timestamp::from_secs(250).unwrap())).unwrap();
+        let rev_reg_state =
+            Prover::select_registry_state(&history, &interval, rev_idx, max_cred_num, issuance_by_default, &simple_tail_accessor).unwrap();
+        assert_eq!(Timestamp::from_secs(200).unwrap(), rev_reg_state.timestamp());
+
+        // 12. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(rev_reg_state.rev_reg()),
+                                             Some(rev_reg_state.witness())).unwrap();
+
+        // 13. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 14. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 15. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(rev_reg_state.rev_reg()),
+                                            Some(rev_reg_state.witness())).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 16. Verifier verifies proof against registry state at reported timestamp
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(rev_reg_state.rev_reg())).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_non_revocation_interval_bound_to_proof() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry with IssuanceOnDemand type and publishes it at time 100
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        let mut history = vec![
+            RevocationRegistryHistoryEntry::new(Timestamp::from_secs(100).unwrap(), RevocationRegistryDelta::from_parts(None, &rev_reg, &HashSet::new(), &HashSet::new()))
+        ];
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 8. Issuer signs credential values and publishes registry delta at time 200
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        history.push(RevocationRegistryHistoryEntry::new(Timestamp::from_secs(200).unwrap(), rev_reg_delta.unwrap()));
+
+        // 9. Issuer revokes credential and publishes registry delta at time 300
+        let rev_reg_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, rev_idx, &simple_tail_accessor).unwrap();
+        history.push(RevocationRegistryHistoryEntry::new(Timestamp::from_secs(300).unwrap(), rev_reg_delta));
+
+        // 10. Prover selects registry state for verifier's interval
+        let interval = NonRevocationInterval::new(Some(Timestamp::from_secs(150).unwrap()), Some(Timestamp::from_secs(250).unwrap())).unwrap();
+        let rev_reg_state =
+            Prover::select_registry_state(&history, &interval, rev_idx, max_cred_num, issuance_by_default, &simple_tail_accessor).unwrap();
+        assert_eq!(Timestamp::from_secs(200).unwrap(), rev_reg_state.timestamp());
+
+        // 11. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(rev_reg_state.rev_reg()),
+                                             Some(rev_reg_state.witness())).unwrap();
+
+        // 12. Verifier creates nonce and sub proof request with non-revocation interval
+        let nonce = new_nonce().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.set_non_revoc_interval(&interval).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 13. Prover can't create proof without timestamp of registry state
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(rev_reg_state.rev_reg()),
+                                            Some(rev_reg_state.witness())).unwrap();
+        match proof_builder.finalize(&nonce) {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // 14. Prover creates proof against selected registry state
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request_with_rev_reg_state(&sub_proof_request,
+                                                               &credential_schema,
+                                                               &non_credential_schema,
+                                                               &credential_signature,
+                                                               &credential_values,
+                                                               &credential_pub_key,
+                                                               &rev_reg_state).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        assert_eq!(Some(Timestamp::from_secs(200).unwrap()), proof.non_revoc_timestamp(0));
+
+        // 15. Verifier takes registry state published at reported timestamp and verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request_with_rev_reg_timestamp(&sub_proof_request,
+                                                                    &credential_schema,
+                                                                    &non_credential_schema,
+                                                                    &credential_pub_key,
+                                                                    &rev_key_pub,
+                                                                    rev_reg_state.rev_reg(),
+                                                                    proof.non_revoc_timestamp(0).unwrap()).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 16. Verifier rejects proof reporting another timestamp
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request_with_rev_reg_timestamp(&sub_proof_request,
+                                                                    &credential_schema,
+                                                                    &non_credential_schema,
+                                                                    &credential_pub_key,
+                                                                    &rev_key_pub,
+                                                                    rev_reg_state.rev_reg(),
+                                                                    Timestamp::from_secs(210).unwrap()).unwrap();
+        match proof_verifier.verify(&proof, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofRejected(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+
+        // 17. Timestamp is hashed into the challenge, so it can't be changed in the proof
+        let proof_json = serde_json::to_string(&proof).unwrap()
+            .replace("\"non_revoc_timestamp\":200", "\"non_revoc_timestamp\":210");
+        let tampered_proof: Proof = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(Some(Timestamp::from_secs(210).unwrap()), tampered_proof.non_revoc_timestamp(0));
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(rev_reg_state.rev_reg())).unwrap();
+        assert!(!proof_verifier.verify(&tampered_proof, &nonce).unwrap());
+
+        // 18. Verifier rejects proof against registry state out of the interval
+        let late_interval = NonRevocationInterval::new(Some(Timestamp::from_secs(250).unwrap()), None).unwrap();
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.set_non_revoc_interval(&late_interval).unwrap();
+        let late_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        match proof_builder.add_sub_proof_request_with_rev_reg_state(&late_sub_proof_request,
+                                                                     &credential_schema,
+                                                                     &non_credential_schema,
+                                                                     &credential_signature,
+                                                                     &credential_values,
+                                                                     &credential_pub_key,
+                                                                     &rev_reg_state) {
+            Err(IndyCryptoError::InvalidParam7(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_compact_proof() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry with IssuanceOnDemand type
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 8. Prover creates witness
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.unwrap(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 9. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 12. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 13. Prover compacts proof
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let compact_proof = proof.compact();
+        assert!(compact_proof.is_compact());
+
+        let compact_proof_json = serde_json::to_string(&compact_proof).unwrap();
+        assert!(compact_proof_json.len() < proof_json.len());
+        let compact_proof: Proof = serde_json::from_str(&compact_proof_json).unwrap();
+
+        // 14. Verifier verifies compact proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&compact_proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_streamed_serialization() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys) and writes public key
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+        let mut credential_pub_key_stream = Vec::new();
+        credential_pub_key.write_to(&mut credential_pub_key_stream).unwrap();
+
+        // 3. Issuer creates revocation registry and writes it together with tails file
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let mut tails_stream = Vec::new();
+        write_tails(&mut rev_tails_generator, &mut tails_stream).unwrap();
+
+        // 4. Prover reads credential public key and tails file
+        let credential_pub_key = CredentialPublicKey::read_from(&mut credential_pub_key_stream.as_slice()).unwrap();
+        let simple_tail_accessor = read_tails(&mut tails_stream.as_slice()).unwrap();
+
+        // 5. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 6. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 7. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 8. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 9. Issuer signs credential values and writes registry delta
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        let mut rev_reg_delta_stream = Vec::new();
+        rev_reg_delta.unwrap().write_to(&mut rev_reg_delta_stream).unwrap();
+
+        let mut rev_reg_stream = Vec::new();
+        rev_reg.write_to(&mut rev_reg_stream).unwrap();
+
+        // 10. Prover reads registry delta and registry and creates witness
+        let rev_reg_delta = RevocationRegistryDelta::read_from(&mut rev_reg_delta_stream.as_slice()).unwrap();
+        let rev_reg = RevocationRegistry::read_from(&mut rev_reg_stream.as_slice()).unwrap();
+
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta,
+                                   &simple_tail_accessor).unwrap();
+
+        // 11. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 12. Verifier creates nonce and sub proof request
+        let nonce = new_nonce().unwrap();
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 13. Prover creates proof and writes it
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_stream = Vec::new();
+        proof.write_to(&mut proof_stream).unwrap();
+        assert_eq!(serde_json::to_string(&proof).unwrap().into_bytes(), proof_stream);
+
+        // 14. Verifier reads and verifies proof
+        let proof = Proof::read_from(&mut proof_stream.as_slice()).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 15. Verifier rejects truncated proof stream
+        match Proof::read_from(&mut &proof_stream[..proof_stream.len() / 2]) {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_canonical_proof() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Issuer creates and sign credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 8. Prover creates witness
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.unwrap(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 9. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 10. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 11. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 12. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 13. Prover converts proof to the canonical form
+        let proof_json = serde_json::to_string(&proof.canonicalize().unwrap()).unwrap();
+
+        // 14. Serialization of canonical proof is stable
+        let proof: Proof = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(proof_json, serde_json::to_string(&proof).unwrap());
+        let proof = proof.canonicalize().unwrap();
+        assert_eq!(proof_json, serde_json::to_string(&proof).unwrap());
+
+        // 15. Verifier verifies canonical proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_revocation_proof_issuance_by_default() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 4. Issuer creates GVT revocation registry with IssuanceByDefault type
+        let max_cred_num = 5;
+        let issuance_by_default = true;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Prover creates master secret with credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds master secret
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 8. Issuer creates and sign credential values
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+        assert!(rev_reg_delta.is_none());
+
+        let rev_reg_delta = RegistryDelta::from_rev_reg(&rev_reg);
+
+        // 9. Prover creates witness
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.to_delta(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 10. Prover processes credential signature
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 11. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 12. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 13. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 14. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_offline_proof_with_accumulator() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential definition and revocation registry
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        let max_cred_num = 5;
+        let issuance_by_default = true;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 2. Issuer issues credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, _) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &RegistryDelta::from_rev_reg(&rev_reg).to_delta(),
+                                   &simple_tail_accessor).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 3. Online device syncs accumulator and witness to offline device
+        let accum_json = serde_json::to_string(rev_reg.accum()).unwrap();
+        let witness_json = serde_json::to_string(&witness).unwrap();
+
+        let accum: PointG2 = serde_json::from_str(&accum_json).unwrap();
+        let witness: Witness = serde_json::from_str(&witness_json).unwrap();
+
+        // 4. Offline device creates proof without tails and revocation registry
+        let nonce = new_nonce().unwrap();
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request_with_accumulator(&sub_proof_request,
+                                                             &credential_schema,
+                                                             &non_credential_schema,
+                                                             &credential_signature,
+                                                             &credential_values,
+                                                             &credential_pub_key,
+                                                             &accum,
+                                                             &witness).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 5. Verifier verifies proof against revocation registry
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_witness_service_client() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential definition and revocation registry
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        let max_cred_num = 5;
+        let issuance_by_default = true;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 2. Issuer issues credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, _) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 3. Witness service is set up with full revocation registry delta and tails
+        let witness_service = LocalWitnessService::new();
+        witness_service.add_registry("rev_reg", max_cred_num, issuance_by_default,
+                                     &RegistryDelta::from_rev_reg(&rev_reg).to_delta(), simple_tail_accessor.clone()).unwrap();
+
+        let witness_service_client = WitnessServiceClient::new(&witness_service, WitnessServiceConfig::default()).unwrap();
+        witness_service_client.add_registry("rev_reg", &credential_pub_key, &rev_key_pub).unwrap();
+
+        // 4. Prover gets witness from the service and processes credential signature
+        let mut clock = FixedClock::new(1_500_000_000);
+        let witness = Witness::new(rev_idx, max_cred_num, issuance_by_default, &RegistryDelta::from_rev_reg(&rev_reg).to_delta(), &simple_tail_accessor).unwrap();
+        let validated_witness = {
+            let r_cred = credential_signature.non_revocation_signature().unwrap();
+            witness_service_client.get_witnesses(&[WitnessRequest { rev_reg_id: "rev_reg", r_cred }], &clock).unwrap().remove(0)
+        };
+        assert_eq!(rev_reg, *validated_witness.rev_reg());
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(validated_witness.rev_reg()),
+                                             Some(validated_witness.witness())).unwrap();
+        assert_eq!(serde_json::to_string(&witness).unwrap(), serde_json::to_string(validated_witness.witness()).unwrap());
+
+        // 5. Issuer revokes another credential and the service applies the delta
+        let rev_reg_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap();
+        witness_service.update_registry("rev_reg", &rev_reg_delta).unwrap();
+
+        // 6. Cached witness is returned until cache expires, then witness for the new accumulator
+        let r_cred = credential_signature.non_revocation_signature().unwrap();
+        let requests = [WitnessRequest { rev_reg_id: "rev_reg", r_cred }];
+        assert_ne!(rev_reg, *witness_service_client.get_witnesses(&requests, &clock).unwrap()[0].rev_reg());
+
+        clock.advance(WitnessServiceConfig::default().cache_ttl);
+        let validated_witness = witness_service_client.get_witnesses(&requests, &clock).unwrap().remove(0);
+        assert_eq!(rev_reg, *validated_witness.rev_reg());
+
+        // 7. Prover creates proof with the new witness and Verifier verifies it
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let nonce = new_nonce().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(validated_witness.rev_reg()),
+                                            Some(validated_witness.witness())).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_witness_store() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        struct CountingTailsAccessor {
+            tails: SimpleTailsAccessor,
+            accessed: Cell<usize>
+        }
+
+        impl RevocationTailsAccessor for CountingTailsAccessor {
+            fn access_tail(&self, tail_id: u32, accessor: &mut FnMut(&Tail)) -> Result<(), IndyCryptoError> {
+                self.accessed.set(self.accessed.get() + 1);
+                self.tails.access_tail(tail_id, accessor)
+            }
+        }
+
+        // 1. Issuer creates credential definition and revocation registry
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        let max_cred_num = 5;
+        let issuance_by_default = true;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 2. Issuer issues credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, _) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        // 3. Prover creates witness and processes credential signature
+        let witness = Witness::new(rev_idx, max_cred_num, issuance_by_default, &RegistryDelta::from_rev_reg(&rev_reg).to_delta(), &simple_tail_accessor).unwrap();
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 4. Issuer revokes other credentials
+        let synced_rev_reg = rev_reg.clone();
+        let rev_reg_deltas = vec![
+            Issuer::revoke_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap(),
+            Issuer::revoke_credential(&mut rev_reg, max_cred_num, 3, &simple_tail_accessor).unwrap()
+        ];
+
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let witness_store = SimpleWitnessStore::new();
+        let counting_tail_accessor = CountingTailsAccessor { tails: simple_tail_accessor.clone(), accessed: Cell::new(0) };
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+
+        // 5. Prover creates proofs: the first one updates witness and puts it to the store,
+        // the second one takes it from the store without accessing tails
+        let build_proof = |nonce: &Nonce| -> Proof {
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.add_common_attribute("master_secret").unwrap();
+            proof_builder.add_sub_proof_request_with_witness_store(&sub_proof_request,
+                                                                   &credential_schema,
+                                                                   &non_credential_schema,
+                                                                   &credential_signature,
+                                                                   &credential_values,
+                                                                   &credential_pub_key,
+                                                                   "rev_reg",
+                                                                   max_cred_num,
+                                                                   &synced_rev_reg,
+                                                                   &witness,
+                                                                   rev_reg_deltas.iter(),
+                                                                   &witness_store,
+                                                                   &counting_tail_accessor).unwrap();
+            proof_builder.finalize(nonce).unwrap()
+        };
+
+        let nonce = new_nonce().unwrap();
+        let proof = build_proof(&nonce);
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let accessed = counting_tail_accessor.accessed.get();
+        assert!(accessed > 0);
+
+        let nonce = new_nonce().unwrap();
+        let proof = build_proof(&nonce);
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        assert_eq!(accessed, counting_tail_accessor.accessed.get());
+    }
+
+    #[test]
+    fn anoncreds_works_for_non_revocation_signature_validation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential definition and revocation registry
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        let max_cred_num = 5;
+        let issuance_by_default = true;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 2. Issuer issues revocable credential
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let rev_idx = 1;
+        let (mut credential_signature, signature_correctness_proof, _) =
+            Issuer::sign_credential_with_revoc(PROVER_ID,
+                                               &blinded_credential_secrets,
+                                               &blinded_credential_secrets_correctness_proof,
+                                               &credential_nonce,
+                                               &credential_issuance_nonce,
+                                               &credential_values,
+                                               &credential_pub_key,
+                                               &credential_priv_key,
+                                               rev_idx,
+                                               max_cred_num,
+                                               issuance_by_default,
+                                               &mut rev_reg,
+                                               &rev_key_priv,
+                                               &simple_tail_accessor).unwrap();
+
+        let rev_reg_delta = RegistryDelta::from_rev_reg(&rev_reg);
+
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.to_delta(),
+                                   &simple_tail_accessor).unwrap();
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg),
+                                             Some(&witness)).unwrap();
+
+        // 3. Prover restores credential signature from wallet and checks it
+        let credential_signature_json = serde_json::to_string(&credential_signature).unwrap();
+        let credential_signature: CredentialSignature = serde_json::from_str(&credential_signature_json).unwrap();
+
+        let r_credential = credential_signature.non_revocation_signature().unwrap();
+        r_credential.validate(&credential_pub_key, &rev_key_pub, &rev_reg, &witness).unwrap();
+
+        // 4. Signature is invalid for other credential public key
+        let (other_credential_pub_key, _, _) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+        assert!(r_credential.validate(&other_credential_pub_key, &rev_key_pub, &rev_reg, &witness).is_err());
+
+        // 5. Witness isn't valid after credential is revoked
+        Issuer::revoke_credential(&mut rev_reg, max_cred_num, rev_idx, &simple_tail_accessor).unwrap();
+        let res = r_credential.validate(&credential_pub_key, &rev_key_pub, &rev_reg, &witness);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_witness_updated_from_delta_stream() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 4. Issuer creates GVT revocation registry with IssuanceByDefault type
         let max_cred_num = 5;
-        let issuance_by_default = false;
+        let issuance_by_default = true;
         let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
             Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
 
         let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
 
-        // 4. Issuer creates and sign credential values
+        // 4. Prover creates master secret with credential values
         let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
 
         // 5. Issuer creates nonce used Prover to blind master secret
         let credential_nonce = new_nonce().unwrap();
 
-        // 6. Prover blinds hidden attributes
+        // 6. Prover blinds master secret
         let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
             Prover::blind_credential_secrets(&credential_pub_key,
                                              &credential_key_correctness_proof,
@@ -356,7 +4267,7 @@ mod test {
         // 7. Prover creates nonce used Issuer to credential issue
         let credential_issuance_nonce = new_nonce().unwrap();
 
-
+        // 8. Issuer creates and sign credential values
         let rev_idx = 1;
         let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
             Issuer::sign_credential_with_revoc(PROVER_ID,
@@ -373,15 +4284,18 @@ mod test {
                                                &mut rev_reg,
                                                &rev_key_priv,
                                                &simple_tail_accessor).unwrap();
+        assert!(rev_reg_delta.is_none());
 
-        // 8. Prover creates witness
-        let witness = Witness::new(rev_idx,
+        let rev_reg_delta = RegistryDelta::from_rev_reg(&rev_reg);
+
+        // 9. Prover creates witness
+        let mut witness = Witness::new(rev_idx,
                                    max_cred_num,
                                    issuance_by_default,
-                                   &rev_reg_delta.unwrap(),
+                                   &rev_reg_delta.to_delta(),
                                    &simple_tail_accessor).unwrap();
 
-        // 9. Prover processes credential signature
+        // 10. Prover processes credential signature
         Prover::process_credential_signature(&mut credential_signature,
                                              &credential_values,
                                              &signature_correctness_proof,
@@ -392,14 +4306,38 @@ mod test {
                                              Some(&rev_reg),
                                              Some(&witness)).unwrap();
 
+        // 11. Issuer revokes and recovers other credentials while prover is offline
+        let synced_rev_reg = rev_reg.clone();
+        let rev_reg_deltas = vec![
+            Issuer::revoke_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap(),
+            Issuer::revoke_credential(&mut rev_reg, max_cred_num, 3, &simple_tail_accessor).unwrap(),
+            Issuer::recovery_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap(),
+            Issuer::revoke_credential(&mut rev_reg, max_cred_num, 4, &simple_tail_accessor).unwrap()
+        ];
+
+        // 12. Prover can't apply deltas out of order
+        let res = witness.clone().apply_delta_stream(rev_idx,
+                                                     max_cred_num,
+                                                     &synced_rev_reg,
+                                                     rev_reg_deltas.iter().rev(),
+                                                     &simple_tail_accessor);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // 13. Prover applies deltas to witness
+        let updated_rev_reg = witness.apply_delta_stream(rev_idx,
+                                                         max_cred_num,
+                                                         &synced_rev_reg,
+                                                         rev_reg_deltas.iter(),
+                                                         &simple_tail_accessor).unwrap();
+        assert_eq!(serde_json::to_string(&rev_reg).unwrap(), serde_json::to_string(&updated_rev_reg).unwrap());
 
-        // 10. Verifier creates nonce
+        // 14. Verifier creates nonce
         let nonce = new_nonce().unwrap();
 
-        // 11. Verifier create sub proof request
+        // 15. Verifier create sub proof request
         let sub_proof_request = helpers::gvt_sub_proof_request();
 
-        // 12. Prover creates proof
+        // 16. Prover creates proof
         let mut proof_builder = Prover::new_proof_builder().unwrap();
         proof_builder.add_common_attribute("master_secret").unwrap();
         proof_builder.add_sub_proof_request(&sub_proof_request,
@@ -408,11 +4346,11 @@ mod test {
                                             &credential_signature,
                                             &credential_values,
                                             &credential_pub_key,
-                                            Some(&rev_reg),
+                                            Some(&updated_rev_reg),
                                             Some(&witness)).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
-        // 13. Verifier verifies proof
+        // 17. Verifier verifies proof
         let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
@@ -424,7 +4362,7 @@ mod test {
     }
 
     #[test]
-    fn anoncreds_works_for_revocation_proof_issuance_by_default() {
+    fn anoncreds_works_for_revocation_registry_epoch_rotation() {
         IndyCryptoDefaultLogger::init(None).ok();
 
         // 1. Issuer creates credential schema
@@ -434,7 +4372,7 @@ mod test {
         // 2. Issuer creates credential definition(with revocation keys)
         let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
 
-        // 4. Issuer creates GVT revocation registry with IssuanceByDefault type
+        // 3. Issuer creates GVT revocation registry with IssuanceByDefault type
         let max_cred_num = 5;
         let issuance_by_default = true;
         let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
@@ -460,7 +4398,7 @@ mod test {
 
         // 8. Issuer creates and sign credential values
         let rev_idx = 1;
-        let (mut credential_signature, signature_correctness_proof, rev_reg_delta) =
+        let (mut credential_signature, signature_correctness_proof, _) =
             Issuer::sign_credential_with_revoc(PROVER_ID,
                                                &blinded_credential_secrets,
                                                &blinded_credential_secrets_correctness_proof,
@@ -475,16 +4413,15 @@ mod test {
                                                &mut rev_reg,
                                                &rev_key_priv,
                                                &simple_tail_accessor).unwrap();
-        assert!(rev_reg_delta.is_none());
 
         let rev_reg_delta = RegistryDelta::from_rev_reg(&rev_reg);
 
         // 9. Prover creates witness
-        let witness = Witness::new(rev_idx,
-                                   max_cred_num,
-                                   issuance_by_default,
-                                   &rev_reg_delta.to_delta(),
-                                   &simple_tail_accessor).unwrap();
+        let mut witness = Witness::new(rev_idx,
+                                       max_cred_num,
+                                       issuance_by_default,
+                                       &rev_reg_delta.to_delta(),
+                                       &simple_tail_accessor).unwrap();
 
         // 10. Prover processes credential signature
         Prover::process_credential_signature(&mut credential_signature,
@@ -497,13 +4434,36 @@ mod test {
                                              Some(&rev_reg),
                                              Some(&witness)).unwrap();
 
-        // 11. Verifier creates nonce
-        let nonce = new_nonce().unwrap();
+        // 11. Issuer revokes credentials and rotates registry into a new epoch without revoked indices
+        let mut verifier_rev_reg = rev_reg.clone();
+        Issuer::revoke_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap();
+        Issuer::revoke_credential(&mut rev_reg, max_cred_num, 3, &simple_tail_accessor).unwrap();
 
-        // 12. Verifier create sub proof request
+        let issued: HashSet<u32> = [1, 4].iter().cloned().collect();
+        let transition = Issuer::new_revocation_registry_epoch(&mut rev_reg, 1, max_cred_num, &issued, &simple_tail_accessor).unwrap();
+        assert_eq!(1, transition.epoch());
+        assert_eq!(serde_json::to_string(&rev_reg).unwrap(), serde_json::to_string(&transition.rev_reg()).unwrap());
+
+        // 12. Verifier checks transition and applies it to the registry state it knows
+        assert!(Verifier::verify_revocation_registry_epoch_transition(&transition, max_cred_num, &simple_tail_accessor).unwrap());
+
+        let res = verifier_rev_reg.apply_epoch_transition(&transition);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        verifier_rev_reg = transition.rev_reg();
+
+        // 13. Prover of credential that isn't carried over can't migrate witness
+        let res = witness.clone().migrate_to_epoch(5, max_cred_num, &transition, &simple_tail_accessor);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // 14. Prover migrates witness
+        witness.migrate_to_epoch(rev_idx, max_cred_num, &transition, &simple_tail_accessor).unwrap();
+
+        // 15. Verifier creates nonce and sub proof request
+        let nonce = new_nonce().unwrap();
         let sub_proof_request = helpers::gvt_sub_proof_request();
 
-        // 13. Prover creates proof
+        // 16. Prover creates proof
         let mut proof_builder = Prover::new_proof_builder().unwrap();
         proof_builder.add_common_attribute("master_secret").unwrap();
         proof_builder.add_sub_proof_request(&sub_proof_request,
@@ -516,17 +4476,352 @@ mod test {
                                             Some(&witness)).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
-        // 14. Verifier verifies proof
+        // 17. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&verifier_rev_reg)).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_common_attribute_across_sub_proofs() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        fn issue_credential(credential_schema: &CredentialSchema,
+                            non_credential_schema: &NonCredentialSchema,
+                            credential_values: &CredentialValues) -> (CredentialPublicKey, CredentialSignature) {
+            let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+                Issuer::new_credential_def(credential_schema, non_credential_schema, false).unwrap();
+            let credential_nonce = new_nonce().unwrap();
+            let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+                Prover::blind_credential_secrets(&credential_pub_key, &credential_key_correctness_proof, credential_values, &credential_nonce).unwrap();
+            let credential_issuance_nonce = new_nonce().unwrap();
+            let (mut credential_signature, signature_correctness_proof) =
+                Issuer::sign_credential(PROVER_ID,
+                                        &blinded_credential_secrets,
+                                        &blinded_credential_secrets_correctness_proof,
+                                        &credential_nonce,
+                                        &credential_issuance_nonce,
+                                        credential_values,
+                                        &credential_pub_key,
+                                        &credential_priv_key).unwrap();
+            Prover::process_credential_signature(&mut credential_signature,
+                                                 credential_values,
+                                                 &signature_correctness_proof,
+                                                 &credential_secrets_blinding_factors,
+                                                 &credential_pub_key,
+                                                 &credential_issuance_nonce,
+                                                 None, None, None).unwrap();
+            (credential_pub_key, credential_signature)
+        }
+
+        fn credential_values(master_secret: &MasterSecret, attr: &str, did: &str) -> CredentialValues {
+            let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+            credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+            credential_values_builder.add_dec_known(attr, "28").unwrap();
+            credential_values_builder.add_dec_known("did", did).unwrap();
+            credential_values_builder.finalize().unwrap()
+        }
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 1. Issuers create credential schemas both containing "did" attribute
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        credential_schema_builder.add_attr("did").unwrap();
+        let gvt_credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("period").unwrap();
+        credential_schema_builder.add_attr("did").unwrap();
+        let xyz_credential_schema = credential_schema_builder.finalize().unwrap();
+
+        // 2. Issuers issue credentials with the same "did" and one more XYZ credential with another "did"
+        let gvt_credential_values = credential_values(&master_secret, "age", "1139481716457488690172217916278103335");
+        let (gvt_credential_pub_key, gvt_credential_signature) = issue_credential(&gvt_credential_schema, &non_credential_schema, &gvt_credential_values);
+
+        let xyz_credential_values = credential_values(&master_secret, "period", "1139481716457488690172217916278103335");
+        let (xyz_credential_pub_key, xyz_credential_signature) = issue_credential(&xyz_credential_schema, &non_credential_schema, &xyz_credential_values);
+
+        let other_xyz_credential_values = credential_values(&master_secret, "period", "5944657099558967239210949258394887428692050081607692519917050011144233115103");
+        let (other_xyz_credential_pub_key, other_xyz_credential_signature) = issue_credential(&xyz_credential_schema, &non_credential_schema, &other_xyz_credential_values);
+
+        // 3. Verifier creates sub proof requests and requires "did" to be the same in all sub proofs
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let gvt_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("period").unwrap();
+        let xyz_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 4. Prover creates proofs for credentials with the same and with different "did" values
+        let mut proofs = Vec::new();
+        for &(xyz_credential_pub_key, xyz_credential_signature, xyz_credential_values) in [
+            (&xyz_credential_pub_key, &xyz_credential_signature, &xyz_credential_values),
+            (&other_xyz_credential_pub_key, &other_xyz_credential_signature, &other_xyz_credential_values)].iter() {
+            let nonce = new_nonce().unwrap();
+
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.add_common_attribute("master_secret").unwrap();
+            proof_builder.add_common_attribute("did").unwrap();
+            proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                                &gvt_credential_schema,
+                                                &non_credential_schema,
+                                                &gvt_credential_signature,
+                                                &gvt_credential_values,
+                                                &gvt_credential_pub_key,
+                                                None,
+                                                None).unwrap();
+            proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                                &xyz_credential_schema,
+                                                &non_credential_schema,
+                                                xyz_credential_signature,
+                                                xyz_credential_values,
+                                                xyz_credential_pub_key,
+                                                None,
+                                                None).unwrap();
+            proofs.push((proof_builder.finalize(&nonce).unwrap(), nonce, xyz_credential_pub_key));
+        }
+
+        // 5. Verifier accepts only proof with the same "did" in both credentials
+        for (&(ref proof, ref nonce, xyz_credential_pub_key), expected) in proofs.iter().zip([true, false].iter()) {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_common_attribute("did").unwrap();
+            proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                                 &gvt_credential_schema,
+                                                 &non_credential_schema,
+                                                 &gvt_credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                                 &xyz_credential_schema,
+                                                 &non_credential_schema,
+                                                 xyz_credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            assert_eq!(*expected, proof_verifier.verify(proof, nonce).unwrap());
+        }
+
+        // 6. Verifier can't require revealed attribute to be common
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_common_attribute("period").unwrap();
+        proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                             &xyz_credential_schema,
+                                             &non_credential_schema,
+                                             &xyz_credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            &xyz_credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let nonce = new_nonce().unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+        assert_eq!(ErrorCode::CommonInvalidStructure, proof_verifier.verify(&proof, &nonce).unwrap_err().to_error_code());
+    }
+
+    #[test]
+    fn anoncreds_works_for_extension_credential() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        fn issue_credential(prover_id: &str,
+                            base_rev_idx: Option<Option<u32>>,
+                            credential_schema: &CredentialSchema,
+                            non_credential_schema: &NonCredentialSchema,
+                            credential_values: &CredentialValues) -> (CredentialPublicKey, CredentialSignature) {
+            let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+                Issuer::new_credential_def(credential_schema, non_credential_schema, false).unwrap();
+            let credential_nonce = new_nonce().unwrap();
+            let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+                Prover::blind_credential_secrets(&credential_pub_key, &credential_key_correctness_proof, credential_values, &credential_nonce).unwrap();
+            let credential_issuance_nonce = new_nonce().unwrap();
+            let (mut credential_signature, signature_correctness_proof) = match base_rev_idx {
+                Some(base_rev_idx) => Issuer::sign_extension_credential(prover_id,
+                                                                        base_rev_idx,
+                                                                        &blinded_credential_secrets,
+                                                                        &blinded_credential_secrets_correctness_proof,
+                                                                        &credential_nonce,
+                                                                        &credential_issuance_nonce,
+                                                                        credential_values,
+                                                                        &credential_pub_key,
+                                                                        &credential_priv_key,
+                                                                        &SystemClock::new()).unwrap(),
+                None => Issuer::sign_credential(prover_id,
+                                                &blinded_credential_secrets,
+                                                &blinded_credential_secrets_correctness_proof,
+                                                &credential_nonce,
+                                                &credential_issuance_nonce,
+                                                credential_values,
+                                                &credential_pub_key,
+                                                &credential_priv_key).unwrap()
+            };
+            Prover::process_credential_signature(&mut credential_signature,
+                                                 credential_values,
+                                                 &signature_correctness_proof,
+                                                 &credential_secrets_blinding_factors,
+                                                 &credential_pub_key,
+                                                 &credential_issuance_nonce,
+                                                 None, None, None).unwrap();
+            (credential_pub_key, credential_signature)
+        }
+
+        let master_secret = Prover::new_master_secret().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 1. Issuer issues base GVT credential
+        let credential_schema = helpers::gvt_credential_schema();
+        let credential_values = helpers::gvt_credential_values(&master_secret);
+        let (credential_pub_key, credential_signature) =
+            issue_credential(PROVER_ID, None, &credential_schema, &non_credential_schema, &credential_values);
+
+        // 2. Schema grows with "zip" attribute, Issuer issues extension credential linked to the base one
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("zip").unwrap();
+        let ext_credential_schema = credential_schema_builder.finalize().unwrap();
+
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_dec_known("zip", "10115").unwrap();
+        let ext_credential_values = credential_values_builder.finalize().unwrap();
+
+        let (ext_credential_pub_key, ext_credential_signature) =
+            issue_credential(PROVER_ID, Some(None), &ext_credential_schema, &non_credential_schema, &ext_credential_values);
+
+        // 3. Extension credential issued for another prover isn't linked to the base credential
+        let (other_ext_credential_pub_key, other_ext_credential_signature) =
+            issue_credential("VsKV7grR1BUE29mG2Fm2kX", Some(None), &ext_credential_schema, &non_credential_schema, &ext_credential_values);
+
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("zip").unwrap();
+        let ext_sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let res = proof_builder.add_extension_sub_proof_request(&ext_sub_proof_request,
+                                                                &ext_credential_schema,
+                                                                &non_credential_schema,
+                                                                &other_ext_credential_signature,
+                                                                &ext_credential_values,
+                                                                &other_ext_credential_pub_key);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // 4. Prover presents base and extension credentials as one credential
+        proof_builder.add_extension_sub_proof_request(&ext_sub_proof_request,
+                                                      &ext_credential_schema,
+                                                      &non_credential_schema,
+                                                      &ext_credential_signature,
+                                                      &ext_credential_values,
+                                                      &ext_credential_pub_key).unwrap();
+        let nonce = new_nonce().unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 5. Verifier verifies proof
         let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
                                              &credential_pub_key,
-                                             Some(&rev_key_pub),
-                                             Some(&rev_reg)).unwrap();
+                                             None,
+                                             None).unwrap();
+        proof_verifier.add_extension_sub_proof_request(&ext_sub_proof_request,
+                                                       &ext_credential_schema,
+                                                       &non_credential_schema,
+                                                       &ext_credential_pub_key).unwrap();
         assert!(proof_verifier.verify(&proof, &nonce).unwrap());
     }
 
+    #[test]
+    fn anoncreds_works_for_revocation_registry_delta_apply() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, _, _) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry
+        let max_cred_num = 5;
+        let (_, _, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, true).unwrap();
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Verifier keeps copy of revocation registry
+        let mut verifier_rev_reg = rev_reg.clone();
+
+        // 5. Issuer revokes and recovers credentials
+        let first_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, 1, &simple_tail_accessor).unwrap();
+        let mut merged_delta = Issuer::revoke_credential(&mut rev_reg, max_cred_num, 2, &simple_tail_accessor).unwrap();
+        merged_delta.merge(&Issuer::recovery_credential(&mut rev_reg, max_cred_num, 1, &simple_tail_accessor).unwrap()).unwrap();
+        assert_eq!(&[2].iter().cloned().collect::<HashSet<u32>>(), merged_delta.revoked());
+        assert_eq!(&[1].iter().cloned().collect::<HashSet<u32>>(), merged_delta.issued());
+
+        // 6. Verifier can't apply deltas out of order
+        let res = verifier_rev_reg.apply_delta(&merged_delta);
+        assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
+
+        // 7. Verifier applies deltas
+        verifier_rev_reg.apply_delta(&first_delta).unwrap();
+        verifier_rev_reg.apply_delta(&merged_delta).unwrap();
+        assert_eq!(serde_json::to_string(&rev_reg).unwrap(), serde_json::to_string(&verifier_rev_reg).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_batch_revocation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, _, _) = Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry
+        let max_cred_num = 5;
+        let (_, _, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, true).unwrap();
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+        let mut sequential_rev_reg = rev_reg.clone();
+
+        // 4. Issuer can't revoke index out of registry range
+        let res = Issuer::revoke_credentials(&mut rev_reg.clone(), max_cred_num, &[1, 6].iter().cloned().collect(), &simple_tail_accessor);
+        assert_eq!(ErrorCode::CommonInvalidParam3, res.unwrap_err().to_error_code());
+
+        // 5. Issuer revokes set of credentials at once
+        let rev_idxs: HashSet<u32> = [1, 3, 4].iter().cloned().collect();
+        let rev_reg_delta = Issuer::revoke_credentials(&mut rev_reg, max_cred_num, &rev_idxs, &simple_tail_accessor).unwrap();
+        assert_eq!(&rev_idxs, rev_reg_delta.revoked());
+
+        // 6. Result is the same as for sequential revocation
+        for rev_idx in rev_idxs.iter() {
+            Issuer::revoke_credential(&mut sequential_rev_reg, max_cred_num, *rev_idx, &simple_tail_accessor).unwrap();
+        }
+        assert_eq!(rev_reg, sequential_rev_reg);
+    }
+
     #[test]
     fn anoncreds_works_for_multiple_credentials_used_for_proof() {
         IndyCryptoDefaultLogger::init(None).ok();
@@ -651,6 +4946,140 @@ mod test {
         assert!(proof_verifier.verify(&proof, &nonce).unwrap());
     }
 
+    #[test]
+    fn anoncreds_works_for_proof_builder_sub_proof_removal() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Prover creates master secret
+        let master_secret = Prover::new_master_secret().unwrap();
+
+        let gvt_credential_values = helpers::gvt_credential_values(&master_secret);
+
+        // 2. Issuer creates and signs GVT credential for Prover
+        let gvt_credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+        let (gvt_credential_pub_key, gvt_credential_priv_key, gvt_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&gvt_credential_schema, &non_credential_schema, false).unwrap();
+
+        let gvt_credential_nonce = new_nonce().unwrap();
+
+        let (gvt_blinded_credential_secrets, gvt_credential_secrets_blinding_factors, gvt_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&gvt_credential_pub_key,
+                                             &gvt_credential_key_correctness_proof,
+                                             &gvt_credential_values,
+                                             &gvt_credential_nonce).unwrap();
+
+        let gvt_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut gvt_credential_signature, gvt_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &gvt_blinded_credential_secrets,
+                                                                                                      &gvt_blinded_credential_secrets_correctness_proof,
+                                                                                                      &gvt_credential_nonce,
+                                                                                                      &gvt_credential_issuance_nonce,
+                                                                                                      &gvt_credential_values,
+                                                                                                      &gvt_credential_pub_key,
+                                                                                                      &gvt_credential_priv_key).unwrap();
+
+        // 3. Prover processes GVT credential
+        Prover::process_credential_signature(&mut gvt_credential_signature,
+                                             &gvt_credential_values,
+                                             &gvt_signature_correctness_proof,
+                                             &gvt_credential_secrets_blinding_factors,
+                                             &gvt_credential_pub_key,
+                                             &gvt_credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 4. Issuer creates and signs XYZ credential for Prover
+        let xyz_credential_schema = helpers::xyz_credential_schema();
+        let (xyz_credential_pub_key, xyz_credential_priv_key, xyz_credential_key_correctness_proof) =
+            Issuer::new_credential_def(&xyz_credential_schema, &non_credential_schema, false).unwrap();
+
+        let xyz_credential_nonce = new_nonce().unwrap();
+        let xyz_credential_values = helpers::xyz_credential_values(&master_secret);
+
+        let (xyz_blinded_credential_secrets, xyz_credential_secrets_blinding_factors, xyz_blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&xyz_credential_pub_key,
+                                             &xyz_credential_key_correctness_proof,
+                                             &xyz_credential_values,
+                                             &xyz_credential_nonce).unwrap();
+
+        let xyz_credential_issuance_nonce = new_nonce().unwrap();
+
+        let (mut xyz_credential_signature, xyz_signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                                      &xyz_blinded_credential_secrets,
+                                                                                                      &xyz_blinded_credential_secrets_correctness_proof,
+                                                                                                      &xyz_credential_nonce,
+                                                                                                      &xyz_credential_issuance_nonce,
+                                                                                                      &xyz_credential_values,
+                                                                                                      &xyz_credential_pub_key,
+                                                                                                      &xyz_credential_priv_key).unwrap();
+
+        // 5. Prover processes XYZ credential
+        Prover::process_credential_signature(&mut xyz_credential_signature,
+                                             &xyz_credential_values,
+                                             &xyz_signature_correctness_proof,
+                                             &xyz_credential_secrets_blinding_factors,
+                                             &xyz_credential_pub_key,
+                                             &xyz_credential_issuance_nonce,
+                                             None, None, None).unwrap();
+        // 6. Verifier creates nonce and sub proof requests
+        let nonce = new_nonce().unwrap();
+        let gvt_sub_proof_request = helpers::gvt_sub_proof_request();
+        let xyz_sub_proof_request = helpers::xyz_sub_proof_request();
+
+        // 7. Prover adds GVT and XYZ sub proof requests
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&gvt_sub_proof_request,
+                                            &gvt_credential_schema,
+                                            &non_credential_schema,
+                                            &gvt_credential_signature,
+                                            &gvt_credential_values,
+                                            &gvt_credential_pub_key,
+                                            None, None).unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            &xyz_credential_pub_key,
+                                            None, None).unwrap();
+
+        // 8. User deselects XYZ credential
+        assert_eq!(1, proof_builder.remove_sub_proof_request(&xyz_credential_pub_key.key_id().unwrap()).unwrap());
+        assert_eq!(0, proof_builder.remove_sub_proof_request(&xyz_credential_pub_key.key_id().unwrap()).unwrap());
+
+        // 9. Verifier verifies proof for GVT sub proof request only
+        let mut gvt_proof_verifier = Verifier::new_proof_verifier().unwrap();
+        gvt_proof_verifier.add_sub_proof_request(&gvt_sub_proof_request,
+                                                 &gvt_credential_schema,
+                                                 &non_credential_schema,
+                                                 &gvt_credential_pub_key,
+                                                 None, None).unwrap();
+        assert!(gvt_proof_verifier.verify(&proof_builder.finalize_ref(&nonce).unwrap(), &nonce).unwrap());
+
+        // 10. Prover resets builder and adds XYZ sub proof request instead
+        proof_builder.reset().unwrap();
+        proof_builder.add_sub_proof_request(&xyz_sub_proof_request,
+                                            &xyz_credential_schema,
+                                            &non_credential_schema,
+                                            &xyz_credential_signature,
+                                            &xyz_credential_values,
+                                            &xyz_credential_pub_key,
+                                            None, None).unwrap();
+        let other_nonce = new_nonce().unwrap();
+        let proof = proof_builder.finalize(&other_nonce).unwrap();
+
+        // 11. Verifier verifies proof for XYZ sub proof request only
+        let mut xyz_proof_verifier = Verifier::new_proof_verifier().unwrap();
+        xyz_proof_verifier.add_sub_proof_request(&xyz_sub_proof_request,
+                                                 &xyz_credential_schema,
+                                                 &non_credential_schema,
+                                                 &xyz_credential_pub_key,
+                                                 None, None).unwrap();
+        assert!(xyz_proof_verifier.verify(&proof, &other_nonce).unwrap());
+    }
+
     #[test]
     fn anoncreds_works_for_revocation_proof_for_three_credentials_proving_first() {
         IndyCryptoDefaultLogger::init(None).ok();
@@ -1735,16 +6164,200 @@ mod test {
                                              Some(&rev_reg),
                                              Some(&witness)).unwrap();
 
-        // 11. Verifier creates nonce
-        let nonce = new_nonce().unwrap();
+        // 11. Verifier creates nonce
+        let nonce = new_nonce().unwrap();
+
+        // 12. Verifier create sub proof request
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
+        // 13. Issuer revokes credential
+        Issuer::revoke_credential(&mut rev_reg, max_cred_num, rev_idx, &simple_tail_accessor).unwrap();
+
+        // 14. Prover creates proof
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            Some(&rev_reg),
+                                            Some(&witness)).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 15. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             Some(&rev_key_pub),
+                                             Some(&rev_reg)).unwrap();
+        assert_eq!(false, proof_verifier.verify(&proof, &nonce).unwrap());
+    }
+
+    #[test]
+    fn anoncreds_works_for_attribute_level_revocation() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema
+        let credential_schema = helpers::gvt_credential_schema();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition(with revocation keys)
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, true).unwrap();
+
+        // 3. Issuer creates revocation registry for attribute "height"
+        let max_cred_num = 5;
+        let issuance_by_default = false;
+        let (rev_key_pub, rev_key_priv, mut rev_reg, mut rev_tails_generator) =
+            Issuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, issuance_by_default).unwrap();
+
+        let simple_tail_accessor = SimpleTailsAccessor::new(&mut rev_tails_generator).unwrap();
+
+        // 4. Issuer creates credential values
+        let credential_values = helpers::gvt_credential_values(&Prover::new_master_secret().unwrap());
+
+        // 5. Issuer creates nonce used Prover to blind master secret
+        let credential_nonce = new_nonce().unwrap();
+
+        // 6. Prover blinds hidden attributes
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        // 7. Prover creates nonce used Issuer to credential issue
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        // 8. Issuer signs credential values
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+
+        // 9. Issuer signs attribute "height" with revocation keys
+        let rev_idx = 1;
+        let rev_reg_delta = Issuer::sign_attribute_with_revoc("height",
+                                                              &mut credential_signature,
+                                                              &blinded_credential_secrets,
+                                                              &credential_values,
+                                                              &credential_pub_key,
+                                                              &credential_priv_key,
+                                                              rev_idx,
+                                                              max_cred_num,
+                                                              issuance_by_default,
+                                                              &mut rev_reg,
+                                                              &rev_key_priv,
+                                                              &simple_tail_accessor).unwrap();
+
+        // 10. Prover creates witness for attribute
+        let witness = Witness::new(rev_idx,
+                                   max_cred_num,
+                                   issuance_by_default,
+                                   &rev_reg_delta.unwrap(),
+                                   &simple_tail_accessor).unwrap();
+
+        // 11. Prover processes credential and attribute signatures
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        Prover::process_attribute_signature(&mut credential_signature,
+                                            "height",
+                                            &credential_secrets_blinding_factors,
+                                            &credential_pub_key,
+                                            &rev_key_pub,
+                                            &rev_reg,
+                                            &witness).unwrap();
+
+        // 12. Verifier creates sub proof request requiring "height" to be unrevoked
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_unrevoked_attr("height").unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 13. Prover creates proof
+        let nonce = new_nonce().unwrap();
+
+        let mut attr_rev_states = HashMap::new();
+        attr_rev_states.insert("height".to_string(), (&rev_reg, &witness));
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request_with_attr_revoc(&sub_proof_request,
+                                                            &credential_schema,
+                                                            &non_credential_schema,
+                                                            &credential_signature,
+                                                            &credential_values,
+                                                            &credential_pub_key,
+                                                            None,
+                                                            None,
+                                                            &attr_rev_states).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 14. Verifier verifies proof
+        let mut attr_rev_regs = HashMap::new();
+        attr_rev_regs.insert("height".to_string(), (&rev_key_pub, &rev_reg));
 
-        // 12. Verifier create sub proof request
-        let sub_proof_request = helpers::gvt_sub_proof_request();
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request_with_attr_revoc(&sub_proof_request,
+                                                             &credential_schema,
+                                                             &non_credential_schema,
+                                                             &credential_pub_key,
+                                                             None,
+                                                             None,
+                                                             &attr_rev_regs).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
 
-        // 13. Issuer revokes credential
+        // 15. Issuer revokes attribute "height"
         Issuer::revoke_credential(&mut rev_reg, max_cred_num, rev_idx, &simple_tail_accessor).unwrap();
 
-        // 14. Prover creates proof
+        let mut attr_rev_states = HashMap::new();
+        attr_rev_states.insert("height".to_string(), (&rev_reg, &witness));
+
+        let mut attr_rev_regs = HashMap::new();
+        attr_rev_regs.insert("height".to_string(), (&rev_key_pub, &rev_reg));
+
+        // 16. Prover creates proof against new registry state
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request_with_attr_revoc(&sub_proof_request,
+                                                            &credential_schema,
+                                                            &non_credential_schema,
+                                                            &credential_signature,
+                                                            &credential_values,
+                                                            &credential_pub_key,
+                                                            None,
+                                                            None,
+                                                            &attr_rev_states).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 17. Verifier rejects proof for revoked attribute
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request_with_attr_revoc(&sub_proof_request,
+                                                             &credential_schema,
+                                                             &non_credential_schema,
+                                                             &credential_pub_key,
+                                                             None,
+                                                             None,
+                                                             &attr_rev_regs).unwrap();
+        assert_eq!(false, proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 18. Credential without revocation requirement for attribute is still valid
+        let sub_proof_request = helpers::gvt_sub_proof_request();
+
         let mut proof_builder = Prover::new_proof_builder().unwrap();
         proof_builder.add_common_attribute("master_secret").unwrap();
         proof_builder.add_sub_proof_request(&sub_proof_request,
@@ -1753,19 +6366,18 @@ mod test {
                                             &credential_signature,
                                             &credential_values,
                                             &credential_pub_key,
-                                            Some(&rev_reg),
-                                            Some(&witness)).unwrap();
+                                            None,
+                                            None).unwrap();
         let proof = proof_builder.finalize(&nonce).unwrap();
 
-        // 15. Verifier verifies proof
         let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
         proof_verifier.add_sub_proof_request(&sub_proof_request,
                                              &credential_schema,
                                              &non_credential_schema,
                                              &credential_pub_key,
-                                             Some(&rev_key_pub),
-                                             Some(&rev_reg)).unwrap();
-        assert_eq!(false, proof_verifier.verify(&proof, &nonce).unwrap());
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
     }
 
     #[test]
@@ -2145,7 +6757,7 @@ mod test {
                                              Some(&witness)).unwrap();
         // 21. Prover creates proof using new credential
         let mut new_proof_builder = Prover::new_proof_builder().unwrap();
-        proof_builder.add_common_attribute("master_secret").unwrap();
+        new_proof_builder.add_common_attribute("master_secret").unwrap();
         new_proof_builder.add_sub_proof_request(&sub_proof_request,
                                                 &credential_schema,
                                                 &non_credential_schema,
@@ -2155,7 +6767,7 @@ mod test {
                                                 Some(&rev_reg),
                                                 Some(&witness)).unwrap();
 
-        let new_proof = proof_builder.finalize(&nonce).unwrap();
+        let new_proof = new_proof_builder.finalize(&nonce).unwrap();
 
         // 22. Verifier verifies proof created by new credential
         let mut new_proof_verifier = Verifier::new_proof_verifier().unwrap();
@@ -3230,6 +7842,286 @@ mod test {
                                                        None, None, None);
         assert_eq!(ErrorCode::CommonInvalidStructure, res.unwrap_err().to_error_code());
     }
+
+    #[test]
+    fn anoncreds_works_for_namespaced_attrs() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema with attributes of two logical schemas
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_namespaced_attr("core", "name").unwrap();
+        credential_schema_builder.add_namespaced_attr("core", "age").unwrap();
+        credential_schema_builder.add_namespaced_attr("address", "city").unwrap();
+        credential_schema_builder.add_namespaced_attr("address", "street").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        // 2. Issuer creates credential definition
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 3. Issuer creates credential values
+        let master_secret = Prover::new_master_secret().unwrap();
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_raw_known(&namespaced_attr("core", "name").unwrap(), "Alex").unwrap();
+        credential_values_builder.add_raw_known(&namespaced_attr("core", "age").unwrap(), "28").unwrap();
+        credential_values_builder.add_raw_known(&namespaced_attr("address", "city").unwrap(), "Berlin").unwrap();
+        credential_values_builder.add_raw_known(&namespaced_attr("address", "street").unwrap(), "Main Street").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        // 4. Issuer issues credential
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 5. Verifier requests the whole address and age predicate
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_namespace(&credential_schema, "address").unwrap();
+        sub_proof_request_builder.add_predicate(&namespaced_attr("core", "age").unwrap(), "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 6. Prover creates proof
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 7. Verifier verifies proof and groups revealed attributes by namespace
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let revealed_attrs = proof.revealed_attrs_by_namespace(0).unwrap();
+        assert_eq!(vec!["address".to_string()], revealed_attrs.keys().cloned().collect::<Vec<String>>());
+        assert_eq!(encode_raw_value("Berlin").unwrap(), *revealed_attrs["address"]["city"]);
+        assert_eq!(encode_raw_value("Main Street").unwrap(), *revealed_attrs["address"]["street"]);
+    }
+
+    #[test]
+    fn anoncreds_works_for_64_bit_predicate() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema with timestamp attribute
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("issued_at_ms").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 2. Issuer issues credential with millisecond timestamp that doesn't fit i32
+        let master_secret = Prover::new_master_secret().unwrap();
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_raw_known("name", "Alex").unwrap();
+        credential_values_builder.add_dec_known("issued_at_ms", "1700000000000").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 3. Verifier requests credential issued within 64-bit range
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_range_predicate("issued_at_ms", 1600000000000, 1800000000000).unwrap();
+        sub_proof_request_builder.add_predicate("issued_at_ms", "GT", ::std::i64::MIN).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        // 4. Prover creates proof
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 5. Verifier verifies proof
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        // 6. Prover can't satisfy predicate out of 64-bit attribute value
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_predicate("issued_at_ms", "GE", 1800000000000).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        let res = proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                      &credential_schema,
+                                                      &non_credential_schema,
+                                                      &credential_signature,
+                                                      &credential_values,
+                                                      &credential_pub_key,
+                                                      None,
+                                                      None);
+        match res {
+            Err(IndyCryptoError::InvalidStructure(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+
+    #[test]
+    fn anoncreds_works_for_revealed_raw_values() {
+        IndyCryptoDefaultLogger::init(None).ok();
+
+        // 1. Issuer creates credential schema and credential definition
+        let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+        credential_schema_builder.add_attr("name").unwrap();
+        credential_schema_builder.add_attr("age").unwrap();
+        let credential_schema = credential_schema_builder.finalize().unwrap();
+        let non_credential_schema = helpers::non_credential_schema();
+
+        let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+            Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+        // 2. Issuer creates credential values from raw values
+        let master_secret = Prover::new_master_secret().unwrap();
+        let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+        credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+        credential_values_builder.add_raw_value("name", "Alex").unwrap();
+        credential_values_builder.add_raw_value("age", "28").unwrap();
+        let credential_values = credential_values_builder.finalize().unwrap();
+
+        // 3. Issuer issues credential
+        let credential_nonce = new_nonce().unwrap();
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+        let credential_issuance_nonce = new_nonce().unwrap();
+        let (mut credential_signature, signature_correctness_proof) = Issuer::sign_credential(PROVER_ID,
+                                                                                              &blinded_credential_secrets,
+                                                                                              &blinded_credential_secrets_correctness_proof,
+                                                                                              &credential_nonce,
+                                                                                              &credential_issuance_nonce,
+                                                                                              &credential_values,
+                                                                                              &credential_pub_key,
+                                                                                              &credential_priv_key).unwrap();
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        // 4. Prover creates proof revealing name
+        let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+        sub_proof_request_builder.add_revealed_attr("name").unwrap();
+        sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+        let nonce = new_nonce().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder.add_sub_proof_request(&sub_proof_request,
+                                            &credential_schema,
+                                            &non_credential_schema,
+                                            &credential_signature,
+                                            &credential_values,
+                                            &credential_pub_key,
+                                            None,
+                                            None).unwrap();
+        let proof = proof_builder.finalize(&nonce).unwrap();
+
+        // 5. Verifier verifies proof and reads raw value of revealed attribute
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+
+        let revealed_raw_attrs = proof.revealed_raw_attrs(0).unwrap();
+        assert_eq!(vec!["name".to_string()], revealed_raw_attrs.keys().cloned().collect::<Vec<String>>());
+        assert_eq!("Alex", revealed_raw_attrs["name"]);
+        assert_eq!(encode_raw_value("Alex").unwrap(), proof.revealed_attrs(0).unwrap()["name"]);
+
+        // 6. Verifier rejects proof with raw value not matching revealed value
+        let proof_json = serde_json::to_string(&proof).unwrap().replace(r#""Alex""#, r#""Bob""#);
+        let tampered_proof: Proof = serde_json::from_str(&proof_json).unwrap();
+
+        let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+        proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                             &credential_schema,
+                                             &non_credential_schema,
+                                             &credential_pub_key,
+                                             None,
+                                             None).unwrap();
+        match proof_verifier.verify(&tampered_proof, &nonce) {
+            Err(IndyCryptoError::AnoncredsProofRejected(_)) => (),
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
 }
 
 mod helpers {
@@ -0,0 +1,156 @@
+#![cfg(feature = "leak_detection")]
+
+extern crate indy_crypto;
+
+use indy_crypto::cl::{new_nonce, CredentialSchema, NonCredentialSchema, CredentialValues, SubProofRequest};
+use indy_crypto::cl::issuer::Issuer;
+use indy_crypto::cl::prover::Prover;
+use indy_crypto::cl::verifier::Verifier;
+use indy_crypto::utils::instrumentation::assert_no_leaks;
+use std::env;
+
+pub const PROVER_ID: &'static str = "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW";
+
+// Run with `cargo test --features leak_detection --test leak_detection`.
+// Number of soak iterations can be raised with INDY_CRYPTO_SOAK_ITERATIONS.
+const DEFAULT_SOAK_ITERATIONS: usize = 3;
+
+#[test]
+fn anoncreds_soak_works_without_leaks() {
+    let credential_schema = gvt_credential_schema();
+    let non_credential_schema = non_credential_schema();
+
+    assert_no_leaks("Issuer::new_credential_def", || {
+        Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap()
+    });
+
+    let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+        Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+    let master_secret = Prover::new_master_secret().unwrap();
+    let sub_proof_request = gvt_sub_proof_request();
+
+    let iterations = env::var("INDY_CRYPTO_SOAK_ITERATIONS").ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(DEFAULT_SOAK_ITERATIONS);
+
+    for _ in 0..iterations {
+        assert_no_leaks("Prover::new_master_secret", || Prover::new_master_secret().unwrap());
+        assert_no_leaks("new_nonce", || new_nonce().unwrap());
+
+        let credential_values = gvt_credential_values(&master_secret);
+        let credential_nonce = new_nonce().unwrap();
+
+        assert_no_leaks("Prover::blind_credential_secrets", || {
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap()
+        });
+
+        let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+            Prover::blind_credential_secrets(&credential_pub_key,
+                                             &credential_key_correctness_proof,
+                                             &credential_values,
+                                             &credential_nonce).unwrap();
+
+        let credential_issuance_nonce = new_nonce().unwrap();
+
+        let sign_credential = || {
+            Issuer::sign_credential(PROVER_ID,
+                                    &blinded_credential_secrets,
+                                    &blinded_credential_secrets_correctness_proof,
+                                    &credential_nonce,
+                                    &credential_issuance_nonce,
+                                    &credential_values,
+                                    &credential_pub_key,
+                                    &credential_priv_key).unwrap()
+        };
+
+        assert_no_leaks("Issuer::sign_credential", &sign_credential);
+
+        let (mut credential_signature, signature_correctness_proof) = sign_credential();
+
+        assert_no_leaks("Prover::process_credential_signature", || {
+            let (mut credential_signature, signature_correctness_proof) = sign_credential();
+            Prover::process_credential_signature(&mut credential_signature,
+                                                 &credential_values,
+                                                 &signature_correctness_proof,
+                                                 &credential_secrets_blinding_factors,
+                                                 &credential_pub_key,
+                                                 &credential_issuance_nonce,
+                                                 None, None, None).unwrap();
+        });
+
+        Prover::process_credential_signature(&mut credential_signature,
+                                             &credential_values,
+                                             &signature_correctness_proof,
+                                             &credential_secrets_blinding_factors,
+                                             &credential_pub_key,
+                                             &credential_issuance_nonce,
+                                             None, None, None).unwrap();
+
+        let nonce = new_nonce().unwrap();
+
+        let create_proof = || {
+            let mut proof_builder = Prover::new_proof_builder().unwrap();
+            proof_builder.add_common_attribute("master_secret").unwrap();
+            proof_builder.add_sub_proof_request(&sub_proof_request,
+                                                &credential_schema,
+                                                &non_credential_schema,
+                                                &credential_signature,
+                                                &credential_values,
+                                                &credential_pub_key,
+                                                None,
+                                                None).unwrap();
+            proof_builder.finalize(&nonce).unwrap()
+        };
+
+        assert_no_leaks("ProofBuilder::finalize", &create_proof);
+
+        let proof = create_proof();
+
+        assert_no_leaks("ProofVerifier::verify", || {
+            let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+            proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                                 &credential_schema,
+                                                 &non_credential_schema,
+                                                 &credential_pub_key,
+                                                 None,
+                                                 None).unwrap();
+            assert!(proof_verifier.verify(&proof, &nonce).unwrap());
+        });
+    }
+}
+
+fn gvt_credential_schema() -> CredentialSchema {
+    let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    credential_schema_builder.add_attr("name").unwrap();
+    credential_schema_builder.add_attr("sex").unwrap();
+    credential_schema_builder.add_attr("age").unwrap();
+    credential_schema_builder.add_attr("height").unwrap();
+    credential_schema_builder.finalize().unwrap()
+}
+
+fn non_credential_schema() -> NonCredentialSchema {
+    let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    non_credential_schema_builder.add_attr("master_secret").unwrap();
+    non_credential_schema_builder.finalize().unwrap()
+}
+
+fn gvt_credential_values(master_secret: &indy_crypto::cl::MasterSecret) -> CredentialValues {
+    let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    credential_values_builder.add_value_known("master_secret", &master_secret.value().unwrap()).unwrap();
+    credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+    credential_values_builder.add_dec_known("sex", "5944657099558967239210949258394887428692050081607692519917050011144233115103").unwrap();
+    credential_values_builder.add_dec_known("age", "28").unwrap();
+    credential_values_builder.add_dec_known("height", "175").unwrap();
+    credential_values_builder.finalize().unwrap()
+}
+
+fn gvt_sub_proof_request() -> SubProofRequest {
+    let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+    sub_proof_request_builder.add_revealed_attr("name").unwrap();
+    sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+    sub_proof_request_builder.finalize().unwrap()
+}
@@ -0,0 +1,203 @@
+extern crate indy_crypto;
+extern crate serde_json;
+
+use indy_crypto::bn::BigNumber;
+use indy_crypto::cl::{new_nonce, NonCredentialSchema, Nonce, RevocationRegistry, Witness};
+use indy_crypto::cl::issuer::Issuer;
+use indy_crypto::cl::prover::Prover;
+use indy_crypto::cl::verifier::{ProofVerifier, Verifier};
+use indy_crypto::pair::{GroupOrderElement, Pair, PointG1, PointG2};
+use indy_crypto::utils::ctypes::CTypesUtils;
+use indy_crypto::utils::entropy::{IndyRng, SeededRng};
+use std::env;
+use std::panic;
+
+// Fuzzes parsers of untrusted input with mutations of valid representations: public APIs must return errors, never panic.
+// Run with `cargo test --test panic_free`.
+// Number of mutations per input can be raised with INDY_CRYPTO_FUZZ_ITERATIONS.
+const DEFAULT_FUZZ_ITERATIONS: usize = 200;
+
+fn fuzz_iterations() -> usize {
+    env::var("INDY_CRYPTO_FUZZ_ITERATIONS").ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(DEFAULT_FUZZ_ITERATIONS)
+}
+
+fn next_usize(rng: &mut SeededRng, bound: usize) -> usize {
+    let mut bytes = [0u8; 4];
+    rng.fill_bytes(&mut bytes).unwrap();
+    let value = bytes.iter().fold(0usize, |value, byte| (value << 8) | *byte as usize);
+    value % bound
+}
+
+/// Replaces, inserts or removes a few bytes of `valid` (printable ASCII, whitespace and nul bytes).
+fn mutate(rng: &mut SeededRng, valid: &str) -> String {
+    const ALPHABET: &'static [u8] = b"0123456789abcdefABCDEF -+\"{}[]:,\n\0xyz\\";
+
+    let mut bytes = valid.as_bytes().to_vec();
+
+    for _ in 0..1 + next_usize(rng, 4) {
+        let byte = ALPHABET[next_usize(rng, ALPHABET.len())];
+        let pos = next_usize(rng, bytes.len() + 1);
+
+        match next_usize(rng, 3) {
+            0 if pos < bytes.len() => bytes[pos] = byte,
+            1 if pos < bytes.len() => { bytes.remove(pos); }
+            _ => bytes.insert(pos, byte)
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn assert_panic_free<F>(name: &str, seed: &[u8], valid: &str, target: F) where F: Fn(&str) + panic::RefUnwindSafe {
+    let mut rng = SeededRng::new(seed).unwrap();
+
+    for input in vec![String::new(), valid.to_string()].into_iter()
+        .chain((0..fuzz_iterations()).map(|_| mutate(&mut rng, valid))) {
+        if panic::catch_unwind(|| target(&input)).is_err() {
+            panic!("{} panicked on input: {:?}", name, input);
+        }
+    }
+}
+
+#[test]
+fn point_parsers_are_panic_free() {
+    assert_panic_free("PointG1::from_string", b"point_g1", &PointG1::new().unwrap().to_string().unwrap(), |input| {
+        let _ = PointG1::from_string(input);
+    });
+    assert_panic_free("PointG2::from_string", b"point_g2", &PointG2::new().unwrap().to_string().unwrap(), |input| {
+        let _ = PointG2::from_string(input);
+    });
+    assert_panic_free("GroupOrderElement::from_string", b"group_order_element", &GroupOrderElement::new().unwrap().to_string().unwrap(), |input| {
+        let _ = GroupOrderElement::from_string(input);
+    });
+
+    let pair = Pair::pair(&PointG1::new().unwrap(), &PointG2::new().unwrap()).unwrap();
+    assert_panic_free("Pair::from_string", b"pair", &pair.to_string().unwrap(), |input| {
+        let _ = Pair::from_string(input);
+    });
+}
+
+#[test]
+fn big_number_parsers_are_panic_free() {
+    let nonce = new_nonce().unwrap();
+
+    assert_panic_free("BigNumber::from_dec", b"from_dec", &nonce.to_dec().unwrap(), |input| {
+        let _ = BigNumber::from_dec(input);
+    });
+    assert_panic_free("BigNumber::from_hex", b"from_hex", &nonce.to_hex().unwrap(), |input| {
+        let _ = BigNumber::from_hex(input);
+    });
+}
+
+#[test]
+fn json_parsers_are_panic_free() {
+    let point_g2 = PointG2::new().unwrap().to_string().unwrap();
+
+    assert_panic_free("RevocationRegistry", b"rev_reg", &format!(r#"{{"accum":"{}"}}"#, point_g2), |input| {
+        let _ = serde_json::from_str::<RevocationRegistry>(input);
+    });
+    assert_panic_free("Witness", b"witness", &format!(r#"{{"omega":"{}"}}"#, point_g2), |input| {
+        let _ = serde_json::from_str::<Witness>(input);
+    });
+}
+
+/// Returns verifier of one sub proof request, valid proof json and its nonce.
+fn proof_verifier_with_proof() -> (ProofVerifier, String, Nonce) {
+    let mut credential_schema_builder = Issuer::new_credential_schema_builder().unwrap();
+    credential_schema_builder.add_attr("name").unwrap();
+    credential_schema_builder.add_attr("age").unwrap();
+    let credential_schema = credential_schema_builder.finalize().unwrap();
+
+    let mut non_credential_schema_builder = Issuer::new_non_credential_schema_builder().unwrap();
+    non_credential_schema_builder.add_attr("master_secret").unwrap();
+    let non_credential_schema: NonCredentialSchema = non_credential_schema_builder.finalize().unwrap();
+
+    let (credential_pub_key, credential_priv_key, credential_key_correctness_proof) =
+        Issuer::new_credential_def(&credential_schema, &non_credential_schema, false).unwrap();
+
+    let master_secret = Prover::new_master_secret().unwrap();
+    let mut credential_values_builder = Issuer::new_credential_values_builder().unwrap();
+    credential_values_builder.add_value_hidden("master_secret", &master_secret.value().unwrap()).unwrap();
+    credential_values_builder.add_dec_known("name", "1139481716457488690172217916278103335").unwrap();
+    credential_values_builder.add_dec_known("age", "28").unwrap();
+    let credential_values = credential_values_builder.finalize().unwrap();
+
+    let credential_nonce = new_nonce().unwrap();
+    let (blinded_credential_secrets, credential_secrets_blinding_factors, blinded_credential_secrets_correctness_proof) =
+        Prover::blind_credential_secrets(&credential_pub_key,
+                                         &credential_key_correctness_proof,
+                                         &credential_values,
+                                         &credential_nonce).unwrap();
+
+    let credential_issuance_nonce = new_nonce().unwrap();
+    let (mut credential_signature, signature_correctness_proof) =
+        Issuer::sign_credential("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW",
+                                &blinded_credential_secrets,
+                                &blinded_credential_secrets_correctness_proof,
+                                &credential_nonce,
+                                &credential_issuance_nonce,
+                                &credential_values,
+                                &credential_pub_key,
+                                &credential_priv_key).unwrap();
+
+    Prover::process_credential_signature(&mut credential_signature,
+                                         &credential_values,
+                                         &signature_correctness_proof,
+                                         &credential_secrets_blinding_factors,
+                                         &credential_pub_key,
+                                         &credential_issuance_nonce,
+                                         None, None, None).unwrap();
+
+    let mut sub_proof_request_builder = Verifier::new_sub_proof_request_builder().unwrap();
+    sub_proof_request_builder.add_revealed_attr("name").unwrap();
+    sub_proof_request_builder.add_predicate("age", "GE", 18).unwrap();
+    let sub_proof_request = sub_proof_request_builder.finalize().unwrap();
+
+    let mut proof_builder = Prover::new_proof_builder().unwrap();
+    proof_builder.add_common_attribute("master_secret").unwrap();
+    proof_builder.add_sub_proof_request(&sub_proof_request,
+                                        &credential_schema,
+                                        &non_credential_schema,
+                                        &credential_signature,
+                                        &credential_values,
+                                        &credential_pub_key,
+                                        None,
+                                        None).unwrap();
+    let nonce = new_nonce().unwrap();
+    let proof = proof_builder.finalize(&nonce).unwrap();
+
+    let mut proof_verifier = Verifier::new_proof_verifier().unwrap();
+    proof_verifier.add_sub_proof_request(&sub_proof_request,
+                                         &credential_schema,
+                                         &non_credential_schema,
+                                         &credential_pub_key,
+                                         None,
+                                         None).unwrap();
+
+    (proof_verifier, serde_json::to_string(&proof).unwrap(), nonce)
+}
+
+#[test]
+fn proof_verifier_is_panic_free() {
+    let (proof_verifier, proof_json, nonce) = proof_verifier_with_proof();
+    assert!(proof_verifier.verify_json(&proof_json, &nonce).unwrap());
+
+    // Proof with other number of sub proofs than sub proof requests added to verifier
+    let mut proof = serde_json::from_str::<serde_json::Value>(&proof_json).unwrap();
+    proof["proofs"] = serde_json::Value::Array(Vec::new());
+    assert!(proof_verifier.verify_json(&proof.to_string(), &nonce).is_err());
+
+    let (proof_verifier, nonce) = (panic::AssertUnwindSafe(proof_verifier), panic::AssertUnwindSafe(nonce));
+    assert_panic_free("ProofVerifier::verify_json", b"verify_json", &proof_json, |input| {
+        let _ = proof_verifier.verify_json(input, &nonce.0);
+    });
+}
+
+#[test]
+fn c_string_conversion_is_panic_free() {
+    assert_panic_free("CTypesUtils::string_to_cstring", b"cstring", "value\0with nul", |input| {
+        let _ = CTypesUtils::string_to_cstring(input.to_string());
+    });
+}